@@ -3,8 +3,13 @@
 use ::u128::mul_div_u64;
 use num_traits::ToPrimitive;
 use spl_math::checked_ceil_div::CheckedCeilDiv;
+use spl_math::uint::U256;
+use std::convert::TryFrom;
 
+pub mod rounding;
+pub mod stable;
 mod types;
+pub mod weighted;
 
 pub use types::*;
 
@@ -14,8 +19,20 @@ pub fn calculate_initial_swap_pool_amount(amount_a: u64, amount_b: u64) -> Optio
     spl_math::approximations::sqrt((amount_a as u128).checked_mul(amount_b as u128)?)?.to_u64()
 }
 
-// Minimum liquidity owned by the swap so that balances cannot be completely withdrawn
-// unless the pool dissolves
+/// Splits the initial amount of pool tokens into the amount received by the pool creator
+/// and the [MINIMUM_LIQUIDITY] which is locked in the swap forever.
+pub fn split_initial_swap_pool_amount(initial_pool_amount: u64) -> Option<(u64, u64)> {
+    Some((
+        initial_pool_amount.checked_sub(MINIMUM_LIQUIDITY)?,
+        MINIMUM_LIQUIDITY,
+    ))
+}
+
+/// BPS in 100%.
+pub const BPS_PER_WHOLE: u64 = 10_000;
+
+/// Minimum liquidity owned by the swap so that balances cannot be completely withdrawn.
+/// It is locked in the swap when the swap is created.
 pub const MINIMUM_LIQUIDITY: u64 = 1_000;
 
 /// Constant product swap ensures x * y = constant
@@ -57,6 +74,331 @@ pub fn swap(
     })
 }
 
+/// Constant product swap which computes the source amount required to
+/// receive an exact destination amount, ensuring x * y >= constant.
+///
+/// The source amount is rounded up so the invariant never decreases.
+/// Returns [None] if the destination amount would empty the pool.
+pub fn swap_exact_out(
+    destination_amount: u64,
+    swap_source_amount: u64,
+    swap_destination_amount: u64,
+) -> Option<SwapResult> {
+    // zero swap should not execute
+    if destination_amount == 0 {
+        return None;
+    }
+
+    let invariant = (swap_source_amount as u128).checked_mul(swap_destination_amount.into())?;
+
+    let new_swap_destination_amount: u128 = swap_destination_amount
+        .checked_sub(destination_amount)?
+        .into();
+    if new_swap_destination_amount == 0 {
+        return None;
+    }
+
+    let new_swap_source_amount =
+        rounding::div(invariant, new_swap_destination_amount, rounding::POOL_INPUT)?;
+
+    let source_amount_swapped = new_swap_source_amount
+        .checked_sub(swap_source_amount.into())?
+        .to_u64()?;
+
+    // zero swap should not execute
+    if source_amount_swapped == 0 {
+        return None;
+    }
+
+    Some(SwapResult {
+        source_amount_swapped,
+        destination_amount_swapped: destination_amount,
+    })
+}
+
+/// Price impact of a swap in bps: how much worse the price received by the swap
+/// is than the spot price of the pool before the swap.
+///
+/// The result is rounded up. Returns [None] if the source amount or either reserve is zero.
+pub fn price_impact_bps(
+    source_amount: u64,
+    destination_amount: u64,
+    swap_source_amount: u64,
+    swap_destination_amount: u64,
+) -> Option<u64> {
+    if swap_source_amount == 0 {
+        return None;
+    }
+
+    // both values are scaled by the source reserve
+    let spot_value = U256::from(source_amount).checked_mul(swap_destination_amount.into())?;
+    if spot_value.is_zero() {
+        return None;
+    }
+    let received_value = U256::from(destination_amount).checked_mul(swap_source_amount.into())?;
+
+    let shortfall = spot_value.saturating_sub(received_value);
+    let numerator = shortfall.checked_mul(BPS_PER_WHOLE.into())?;
+    let mut impact_bps = numerator.checked_div(spot_value)?;
+    if !numerator.checked_rem(spot_value)?.is_zero() {
+        impact_bps = impact_bps.checked_add(1.into())?;
+    }
+    u64::try_from(impact_bps).ok()
+}
+
+/// Computes the largest amount of the source token, up to `source_amount`, which [swap]s
+/// for at least `destination_amount` per `source_amount` of the source token.
+///
+/// Rounded down, so a [swap] of the result receives at least the proportional
+/// destination amount, rounded down.
+pub fn max_source_amount_at_price(
+    source_amount: u64,
+    destination_amount: u64,
+    swap_source_amount: u64,
+    swap_destination_amount: u64,
+) -> Option<u64> {
+    if destination_amount == 0 {
+        return Some(source_amount);
+    }
+
+    // destination(x) = swap_destination_amount * x / (swap_source_amount + x)
+    // destination(x) >= x * destination_amount / source_amount
+    //   <=> x <= swap_destination_amount * source_amount / destination_amount - swap_source_amount
+    let max_new_swap_source_amount = (swap_destination_amount as u128)
+        .checked_mul(source_amount.into())?
+        .checked_div(destination_amount.into())?;
+    let max_source_amount = max_new_swap_source_amount.saturating_sub(swap_source_amount.into());
+    max_source_amount.min(source_amount.into()).to_u64()
+}
+
+/// Returns true if the product of the new reserves is at least the product of the old reserves.
+pub fn is_invariant_preserved(
+    old_amount_a: u64,
+    old_amount_b: u64,
+    new_amount_a: u64,
+    new_amount_b: u64,
+) -> Option<bool> {
+    let old_invariant = U256::from(old_amount_a).checked_mul(old_amount_b.into())?;
+    let new_invariant = U256::from(new_amount_a).checked_mul(new_amount_b.into())?;
+    Some(new_invariant >= old_invariant)
+}
+
+/// Computes the relative change of the spot price of a pool from the old to the new reserves, in bps.
+///
+/// The spot price is the amount of token b per token a. Rounded down.
+/// Returns [None] if either pool has no spot price.
+pub fn price_change_bps(
+    old_amount_a: u64,
+    old_amount_b: u64,
+    new_amount_a: u64,
+    new_amount_b: u64,
+) -> Option<u64> {
+    // both prices are scaled by the product of the token a amounts
+    let old_value = U256::from(old_amount_b).checked_mul(new_amount_a.into())?;
+    let new_value = U256::from(new_amount_b).checked_mul(old_amount_a.into())?;
+    if old_value.is_zero() || new_value.is_zero() {
+        return None;
+    }
+
+    let change = if new_value > old_value {
+        new_value.checked_sub(old_value)?
+    } else {
+        old_value.checked_sub(new_value)?
+    };
+    let change_bps = change
+        .checked_mul(BPS_PER_WHOLE.into())?
+        .checked_div(old_value)?;
+    u64::try_from(change_bps).ok()
+}
+
+/// Computes how much of a single-sided deposit of `amount_in` source tokens should be
+/// swapped so that the remainder and the swap proceeds match the ratio of the pool.
+///
+/// The trade fee, `fee_numerator / fee_denominator` of the swap output, is assumed to
+/// stay in the pool. Rounded down.
+pub fn single_sided_swap_amount(
+    amount_in: u64,
+    swap_source_amount: u64,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> Option<u64> {
+    if fee_numerator > fee_denominator || fee_denominator == 0 {
+        return None;
+    }
+
+    // Solves (amount_in - x) * (source + f * x) = (1 - f) * x * (source + x) for x:
+    //   d * x^2 + b * x - amount_in * source * d = 0
+    //   b = source * (2 * d - n) - amount_in * n
+    let amount_in = U256::from(amount_in);
+    let source = U256::from(swap_source_amount);
+    let numerator = U256::from(fee_numerator);
+    let denominator = U256::from(fee_denominator);
+
+    let b_positive =
+        source.checked_mul(denominator.checked_mul(2.into())?.checked_sub(numerator)?)?;
+    let b_negative = amount_in.checked_mul(numerator)?;
+    let b_abs = if b_positive > b_negative {
+        b_positive.checked_sub(b_negative)?
+    } else {
+        b_negative.checked_sub(b_positive)?
+    };
+    let discriminant = b_abs.checked_mul(b_abs)?.checked_add(
+        amount_in
+            .checked_mul(source)?
+            .checked_mul(denominator)?
+            .checked_mul(denominator)?
+            .checked_mul(4.into())?,
+    )?;
+
+    let swap_amount = sqrt(discriminant)
+        .checked_add(b_negative)?
+        .checked_sub(b_positive)?
+        .checked_div(denominator.checked_mul(2.into())?)?;
+    u64::try_from(swap_amount.min(amount_in)).ok()
+}
+
+/// Computes the swap of the excess of a deposit of any ratio of the two tokens.
+///
+/// The part of the deposit matching the ratio of the pool is deposited first, rounding the
+/// excess token used up. The rest of the excess token is treated as a
+/// [single_sided_swap_amount] deposit into the resulting pool.
+pub fn imbalanced_deposit_swap(
+    amount_a: u64,
+    amount_b: u64,
+    swap_token_a_amount: u64,
+    swap_token_b_amount: u64,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> Option<ImbalancedDepositSwap> {
+    // a * B >= b * A means token a is in excess
+    let a_value = (amount_a as u128).checked_mul(swap_token_b_amount.into())?;
+    let b_value = (amount_b as u128).checked_mul(swap_token_a_amount.into())?;
+    let is_token_a_swapped = a_value >= b_value;
+    let (excess_amount, balanced_amount, swap_excess_amount, swap_balanced_amount) =
+        if is_token_a_swapped {
+            (amount_a, amount_b, swap_token_a_amount, swap_token_b_amount)
+        } else {
+            (amount_b, amount_a, swap_token_b_amount, swap_token_a_amount)
+        };
+
+    let balanced_excess_amount = rounding::div(
+        (balanced_amount as u128).checked_mul(swap_excess_amount.into())?,
+        swap_balanced_amount.into(),
+        rounding::POOL_INPUT,
+    )?
+    .to_u64()?
+    .min(excess_amount);
+
+    let swap_source_amount = swap_excess_amount.checked_add(balanced_excess_amount)?;
+    let excess_amount = excess_amount.checked_sub(balanced_excess_amount)?;
+    Some(ImbalancedDepositSwap {
+        is_token_a_swapped,
+        excess_amount,
+        swap_amount: single_sided_swap_amount(
+            excess_amount,
+            swap_source_amount,
+            fee_numerator,
+            fee_denominator,
+        )?,
+        swap_source_amount,
+        swap_destination_amount: swap_balanced_amount.checked_add(balanced_amount)?,
+    })
+}
+
+/// Computes the pool tokens minted for a deposit of any ratio of the two tokens.
+///
+/// The part of the deposit matching the ratio of the pool is exchanged for pool tokens
+/// proportionally. The excess of the other token is treated as a [single_sided_swap_amount]
+/// deposit, paying the trade fee, rounded up, on its imbalanced portion. Rounded down.
+pub fn imbalanced_deposit_pool_tokens(
+    amount_a: u64,
+    amount_b: u64,
+    swap_token_a_amount: u64,
+    swap_token_b_amount: u64,
+    pool_token_supply: u64,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> Option<u64> {
+    let ImbalancedDepositSwap {
+        is_token_a_swapped,
+        excess_amount,
+        swap_amount,
+        swap_source_amount: swap_excess_amount,
+        swap_destination_amount: swap_balanced_amount,
+    } = imbalanced_deposit_swap(
+        amount_a,
+        amount_b,
+        swap_token_a_amount,
+        swap_token_b_amount,
+        fee_numerator,
+        fee_denominator,
+    )?;
+    let (balanced_amount, pool_balanced_amount) = if is_token_a_swapped {
+        (amount_b, swap_token_b_amount)
+    } else {
+        (amount_a, swap_token_a_amount)
+    };
+
+    // proportional deposit
+    let balanced_pool_tokens = rounding::mul_div(
+        balanced_amount,
+        pool_token_supply,
+        pool_balanced_amount,
+        rounding::POOL_OUTPUT,
+    )?;
+    let pool_token_supply = pool_token_supply.checked_add(balanced_pool_tokens)?;
+
+    // single-sided deposit of the excess
+    let swap_result = match swap(swap_amount, swap_excess_amount, swap_balanced_amount) {
+        Some(swap_result) => swap_result,
+        // too small to mint any pool tokens, so it remains in the pool
+        None => return Some(balanced_pool_tokens),
+    };
+    let trade_fee = rounding::div(
+        (swap_result.destination_amount_swapped as u128).checked_mul(fee_numerator.into())?,
+        fee_denominator.into(),
+        rounding::POOL_INPUT,
+    )?;
+    let amount_out = swap_result
+        .destination_amount_swapped
+        .checked_sub(trade_fee.to_u64()?)?;
+
+    let new_swap_excess_amount =
+        swap_excess_amount.checked_add(swap_result.source_amount_swapped)?;
+    let new_swap_balanced_amount = swap_balanced_amount.checked_sub(amount_out)?;
+    let excess_pool_tokens = rounding::mul_div(
+        excess_amount.checked_sub(swap_result.source_amount_swapped)?,
+        pool_token_supply,
+        new_swap_excess_amount,
+        rounding::POOL_OUTPUT,
+    )?
+    .min(rounding::mul_div(
+        amount_out,
+        pool_token_supply,
+        new_swap_balanced_amount,
+        rounding::POOL_OUTPUT,
+    )?);
+
+    balanced_pool_tokens.checked_add(excess_pool_tokens)
+}
+
+/// Integer square root, rounded down.
+#[allow(clippy::manual_div_ceil)]
+fn sqrt(value: U256) -> U256 {
+    if value.is_zero() {
+        return value;
+    }
+    // Newton's method, starting from a power of two above the root.
+    let mut root = U256::one() << ((value.bits() + 1) / 2);
+    loop {
+        let next = (root + value / root) >> 1;
+        if next >= root {
+            return root;
+        }
+        root = next;
+    }
+}
+
 /// Get the amount of trading tokens for the given amount of pool tokens,
 /// provided the total trading tokens and supply of pool tokens.
 ///
@@ -106,7 +448,6 @@ mod tests {
     use crate::types::RoundDirection;
     use proptest::prelude::*;
     use spl_math::precise_number::PreciseNumber;
-    use spl_math::uint::U256;
 
     const MAX_SWAP_AMOUNT: u64 = u64::MAX >> 4;
 
@@ -306,6 +647,19 @@ mod tests {
         assert!(results.is_none());
     }
 
+    #[test]
+    fn initial_pool_amount_locks_minimum_liquidity() {
+        assert_eq!(
+            split_initial_swap_pool_amount(1_000_000),
+            Some((999_000, MINIMUM_LIQUIDITY))
+        );
+        assert_eq!(
+            split_initial_swap_pool_amount(MINIMUM_LIQUIDITY),
+            Some((0, MINIMUM_LIQUIDITY))
+        );
+        assert_eq!(split_initial_swap_pool_amount(MINIMUM_LIQUIDITY - 1), None);
+    }
+
     fn test_truncation(
         source_amount: u64,
         swap_source_amount: u64,
@@ -360,6 +714,338 @@ mod tests {
         }
     }
 
+    /// Test function checking that an exact out swap never reduces the invariant
+    /// and that the computed source amount is the smallest one that works.
+    fn check_invariant_from_swap_exact_out(
+        destination_amount: u64,
+        swap_source_amount: u64,
+        swap_destination_amount: u64,
+    ) {
+        let result = swap_exact_out(
+            destination_amount,
+            swap_source_amount,
+            swap_destination_amount,
+        )
+        .unwrap();
+        assert_eq!(result.destination_amount_swapped, destination_amount);
+
+        let invariant = U256::from(swap_source_amount) * U256::from(swap_destination_amount);
+        let new_swap_source_amount =
+            U256::from(swap_source_amount) + U256::from(result.source_amount_swapped);
+        let new_swap_destination_amount = U256::from(swap_destination_amount - destination_amount);
+        assert!(new_swap_source_amount * new_swap_destination_amount >= invariant);
+        assert!((new_swap_source_amount - U256::from(1)) * new_swap_destination_amount < invariant);
+    }
+
+    #[test]
+    fn constant_product_swap_exact_out() {
+        // spot: 10 * 3 / 2.0010 = 14.99, so 10 is required to receive 14
+        check_invariant_from_swap_exact_out(14, 20_000, 30_000);
+        check_invariant_from_swap_exact_out(15, 20_000 - 10, 30_000);
+        check_invariant_from_swap_exact_out(174_999, 4_000_000, 70_000_000_000);
+        assert_eq!(
+            swap_exact_out(14, 20_000, 30_000)
+                .unwrap()
+                .source_amount_swapped,
+            10
+        );
+
+        // cannot drain the pool
+        assert!(swap_exact_out(30_000, 20_000, 30_000).is_none());
+        assert!(swap_exact_out(30_001, 20_000, 30_000).is_none());
+        // zero swap should not execute
+        assert!(swap_exact_out(0, 20_000, 30_000).is_none());
+    }
+
+    #[test]
+    fn constant_product_price_impact() {
+        // 100 in for 90 out at a spot price of 1
+        let result = swap(100, 1_000, 1_000).unwrap();
+        assert_eq!(result.destination_amount_swapped, 90);
+        assert_eq!(price_impact_bps(100, 90, 1_000, 1_000), Some(1_000));
+        // rounded up
+        assert_eq!(price_impact_bps(3, 2, 1_000, 1_000), Some(3_334));
+        // no impact at or above the spot price
+        assert_eq!(price_impact_bps(10, 20, 1_000, 2_000), Some(0));
+        assert_eq!(price_impact_bps(10, 30, 1_000, 2_000), Some(0));
+        // empty pools have no spot price
+        assert!(price_impact_bps(10, 0, 0, 1_000).is_none());
+        assert!(price_impact_bps(10, 0, 1_000, 0).is_none());
+    }
+
+    #[test]
+    fn constant_product_max_source_amount_at_price() {
+        // spot price of 1: a lower limit price allows a partial fill
+        assert_eq!(max_source_amount_at_price(100, 50, 1_000, 1_000), Some(100));
+        assert_eq!(
+            max_source_amount_at_price(1_000, 800, 1_000, 1_000),
+            Some(250)
+        );
+        let result = swap(250, 1_000, 1_000).unwrap();
+        assert_eq!(result.destination_amount_swapped, 200);
+        // limit prices above the spot price cannot be filled
+        assert_eq!(max_source_amount_at_price(100, 101, 1_000, 1_000), Some(0));
+        // no limit price
+        assert_eq!(max_source_amount_at_price(100, 0, 1_000, 1_000), Some(100));
+    }
+
+    proptest! {
+        #[test]
+        fn swap_at_max_source_amount_meets_price(
+            source_amount in 1..MAX_SWAP_AMOUNT,
+            destination_amount in 1..MAX_SWAP_AMOUNT,
+            swap_source_amount in 1..MAX_SWAP_AMOUNT,
+            swap_destination_amount in 1..MAX_SWAP_AMOUNT,
+        ) {
+            let fill = max_source_amount_at_price(
+                source_amount,
+                destination_amount,
+                swap_source_amount,
+                swap_destination_amount,
+            )
+            .unwrap();
+            assert!(fill <= source_amount);
+            if let Some(result) = swap(fill, swap_source_amount, swap_destination_amount) {
+                let minimum = (fill as u128) * (destination_amount as u128) / (source_amount as u128);
+                assert!(result.destination_amount_swapped as u128 >= minimum);
+            }
+        }
+    }
+
+    #[test]
+    fn constant_product_imbalanced_deposit() {
+        // proportional deposits mint proportional pool tokens
+        assert_eq!(
+            imbalanced_deposit_pool_tokens(100, 100, 1_000, 1_000, 1_000, 0, 1),
+            Some(100)
+        );
+        // single-sided deposits pay for the price impact of the swap
+        assert_eq!(
+            imbalanced_deposit_pool_tokens(100, 0, 1_000, 1_000, 1_000, 0, 1),
+            Some(47)
+        );
+        assert_eq!(
+            imbalanced_deposit_pool_tokens(0, 100, 1_000, 1_000, 1_000, 0, 1),
+            Some(47)
+        );
+        assert_eq!(
+            imbalanced_deposit_pool_tokens(100, 50, 1_000, 1_000, 1_000, 0, 1),
+            Some(73)
+        );
+        // and the trade fee on the imbalanced portion
+        assert_eq!(
+            imbalanced_deposit_pool_tokens(100, 50, 1_000, 1_000, 1_000, 3, 1_000),
+            Some(72)
+        );
+        assert_eq!(
+            imbalanced_deposit_pool_tokens(
+                1_000_000, 0, 1_000_000, 1_000_000, 1_000_000, 3_000, 1_000_000
+            ),
+            Some(413_331)
+        );
+        assert_eq!(
+            imbalanced_deposit_pool_tokens(0, 0, 1_000, 1_000, 1_000, 3, 1_000),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn constant_product_imbalanced_deposit_swap() {
+        // the excess is swapped against the reserves after the proportional deposit
+        assert_eq!(
+            imbalanced_deposit_swap(100, 50, 1_000, 1_000, 0, 1),
+            Some(ImbalancedDepositSwap {
+                is_token_a_swapped: true,
+                excess_amount: 50,
+                swap_amount: single_sided_swap_amount(50, 1_050, 0, 1).unwrap(),
+                swap_source_amount: 1_050,
+                swap_destination_amount: 1_050,
+            })
+        );
+        assert_eq!(
+            imbalanced_deposit_swap(0, 100, 1_000, 2_000, 3, 1_000),
+            Some(ImbalancedDepositSwap {
+                is_token_a_swapped: false,
+                excess_amount: 100,
+                swap_amount: single_sided_swap_amount(100, 2_000, 3, 1_000).unwrap(),
+                swap_source_amount: 2_000,
+                swap_destination_amount: 1_000,
+            })
+        );
+        // proportional deposits swap nothing
+        assert_eq!(
+            imbalanced_deposit_swap(100, 200, 1_000, 2_000, 3, 1_000).map(|s| s.swap_amount),
+            Some(0)
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn pool_value_does_not_decrease_from_imbalanced_deposit(
+            amount_a in 0..u32::MAX as u64,
+            amount_b in 0..u32::MAX as u64,
+            swap_token_a_amount in 1..u32::MAX as u64,
+            swap_token_b_amount in 1..u32::MAX as u64,
+            pool_token_supply in 1..u32::MAX as u64,
+            fee_numerator in 0..100_000u64,
+        ) {
+            let pool_token_amount = imbalanced_deposit_pool_tokens(
+                amount_a,
+                amount_b,
+                swap_token_a_amount,
+                swap_token_b_amount,
+                pool_token_supply,
+                fee_numerator,
+                1_000_000,
+            )
+            .unwrap();
+
+            // the product of the reserves per pool token squared must not decrease
+            let pool_token_supply = U256::from(pool_token_supply);
+            let new_pool_token_supply = pool_token_supply + U256::from(pool_token_amount);
+            let invariant = U256::from(swap_token_a_amount) * U256::from(swap_token_b_amount);
+            let new_invariant = U256::from(swap_token_a_amount + amount_a)
+                * U256::from(swap_token_b_amount + amount_b);
+            assert!(
+                new_invariant * pool_token_supply * pool_token_supply
+                    >= invariant * new_pool_token_supply * new_pool_token_supply
+            );
+        }
+    }
+
+    #[test]
+    fn constant_product_invariant_preserved() {
+        let result = swap(100, 1_000, 1_000).unwrap();
+        assert_eq!(
+            is_invariant_preserved(
+                1_000,
+                1_000,
+                1_100,
+                1_000 - result.destination_amount_swapped
+            ),
+            Some(true)
+        );
+        assert_eq!(
+            is_invariant_preserved(1_000, 1_000, 1_100, 909),
+            Some(false)
+        );
+        assert_eq!(
+            is_invariant_preserved(u64::MAX, u64::MAX, u64::MAX, u64::MAX),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn constant_product_price_change() {
+        // the price of token a doubles
+        assert_eq!(price_change_bps(1_000, 1_000, 1_000, 2_000), Some(10_000));
+        // the price of token a halves
+        assert_eq!(price_change_bps(1_000, 1_000, 2_000, 1_000), Some(5_000));
+        // 100 in for 90 out
+        assert_eq!(price_change_bps(1_000, 1_000, 1_100, 910), Some(1_727));
+        // rounded down
+        assert_eq!(price_change_bps(3, 1, 3, 2), Some(10_000));
+        assert_eq!(price_change_bps(30_000, 10_000, 30_000, 10_001), Some(1));
+        assert_eq!(price_change_bps(1_000, 1_000, 1_000, 1_000), Some(0));
+        // empty pools have no spot price
+        assert!(price_change_bps(0, 1_000, 1_000, 1_000).is_none());
+        assert!(price_change_bps(1_000, 1_000, 1_000, 0).is_none());
+    }
+
+    #[test]
+    fn constant_product_single_sided_swap_amount() {
+        // without fees, swapping 1_000 leaves 2_000 to match the 2_000 reserve
+        assert_eq!(single_sided_swap_amount(3_000, 1_000, 0, 1), Some(1_000));
+        assert_eq!(
+            single_sided_swap_amount(1_000_000, 1_000_000, 0, 1_000_000),
+            Some(414_213)
+        );
+        // fees require swapping a bit more
+        assert_eq!(
+            single_sided_swap_amount(1_000_000, 1_000_000, 3_000, 1_000_000),
+            Some(415_093)
+        );
+        assert_eq!(
+            single_sided_swap_amount(0, 1_000, 3_000, 1_000_000),
+            Some(0)
+        );
+        // invalid fees
+        assert!(single_sided_swap_amount(1_000, 1_000, 2, 1).is_none());
+        assert!(single_sided_swap_amount(1_000, 1_000, 0, 0).is_none());
+    }
+
+    proptest! {
+        #[test]
+        fn single_sided_swap_matches_pool_ratio(
+            amount_in in 1_000_000..1_000_000_000_000u64,
+            swap_source_amount in 1_000_000..1_000_000_000_000_000u64,
+            swap_destination_amount in 1_000_000..1_000_000_000_000_000u64,
+            fee_numerator in 0..100_000u64,
+        ) {
+            let fee_denominator = 1_000_000;
+            let swap_amount = single_sided_swap_amount(
+                amount_in,
+                swap_source_amount,
+                fee_numerator,
+                fee_denominator,
+            )
+            .unwrap();
+            assert!(swap_amount <= amount_in);
+
+            let result = swap(swap_amount, swap_source_amount, swap_destination_amount).unwrap();
+            let destination_amount_swapped = result.destination_amount_swapped as f64;
+            let amount_out =
+                destination_amount_swapped * (1.0 - fee_numerator as f64 / fee_denominator as f64);
+            let new_source = (swap_source_amount + swap_amount) as f64;
+            let new_destination = swap_destination_amount as f64 - amount_out;
+
+            let source_ratio = (amount_in - swap_amount) as f64 / new_source;
+            let destination_ratio = amount_out / new_destination;
+            assert!((source_ratio - destination_ratio).abs() <= source_ratio.max(destination_ratio) * 1e-3 + 1e-9);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn price_impact_of_swap_is_bounded(
+            source_amount in 1..MAX_SWAP_AMOUNT,
+            swap_source_amount in 1..MAX_SWAP_AMOUNT,
+            swap_destination_amount in 1..MAX_SWAP_AMOUNT,
+        ) {
+            let result = swap(source_amount, swap_source_amount, swap_destination_amount);
+            prop_assume!(result.is_some());
+            let result = result.unwrap();
+            let impact_bps = price_impact_bps(
+                result.source_amount_swapped,
+                result.destination_amount_swapped,
+                swap_source_amount,
+                swap_destination_amount,
+            )
+            .unwrap();
+            assert!(impact_bps <= BPS_PER_WHOLE);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn invariant_does_not_decrease_from_swap_exact_out(
+            (swap_destination_amount, destination_amount) in total_and_intermediate(),
+            swap_source_amount in 1..MAX_SWAP_AMOUNT,
+        ) {
+            // the source amount must fit in a u64
+            prop_assume!(
+                (swap_source_amount as u128) * (swap_destination_amount as u128)
+                    / ((swap_destination_amount - destination_amount) as u128)
+                    < u64::MAX as u128
+            );
+            check_invariant_from_swap_exact_out(
+                destination_amount,
+                swap_source_amount,
+                swap_destination_amount,
+            );
+        }
+    }
+
     proptest! {
         #[test]
         fn curve_value_does_not_decrease_from_swap(
@@ -0,0 +1,52 @@
+//! Rounding of the swap math.
+//!
+//! Every amount is rounded in favor of the pool, so no deposit, withdrawal or swap
+//! can extract value from the pool through rounding:
+//!
+//! - amounts paid to the pool, such as deposits, exact out swap inputs and fees kept by
+//!   the pool, are rounded with [POOL_INPUT].
+//! - amounts paid by the pool, such as withdrawals, swap outputs, minted LP tokens and
+//!   fees sent out of the pool, are rounded with [POOL_OUTPUT].
+//!
+//! Integrators replicating quotes should round the same way.
+
+use crate::RoundDirection;
+use num_traits::ToPrimitive;
+
+/// Rounding of amounts paid to the pool.
+pub const POOL_INPUT: RoundDirection = RoundDirection::Ceiling;
+
+/// Rounding of amounts paid by the pool.
+pub const POOL_OUTPUT: RoundDirection = RoundDirection::Floor;
+
+/// Divides `numerator` by `denominator`, rounding in the given direction.
+pub fn div(numerator: u128, denominator: u128, direction: RoundDirection) -> Option<u128> {
+    let quotient = numerator.checked_div(denominator)?;
+    match direction {
+        RoundDirection::Ceiling if numerator.checked_rem(denominator)? > 0 => {
+            quotient.checked_add(1)
+        }
+        _ => Some(quotient),
+    }
+}
+
+/// Computes `a * b / c`, rounding in the given direction.
+pub fn mul_div(a: u64, b: u64, c: u64, direction: RoundDirection) -> Option<u64> {
+    div((a as u128).checked_mul(b.into())?, c.into(), direction)?.to_u64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rounding_directions() {
+        assert_eq!(div(7, 2, RoundDirection::Floor), Some(3));
+        assert_eq!(div(7, 2, RoundDirection::Ceiling), Some(4));
+        assert_eq!(div(8, 2, RoundDirection::Ceiling), Some(4));
+        assert_eq!(div(1, 0, RoundDirection::Floor), None);
+        assert_eq!(mul_div(10, 3, 4, POOL_OUTPUT), Some(7));
+        assert_eq!(mul_div(10, 3, 4, POOL_INPUT), Some(8));
+        assert_eq!(mul_div(u64::MAX, 2, 1, POOL_OUTPUT), None);
+    }
+}
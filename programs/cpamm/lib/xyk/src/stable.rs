@@ -0,0 +1,246 @@
+//! The amplified stable swap invariant for pools of like-valued tokens.
+//!
+//! For two tokens with amounts `x` and `y`, the invariant `D` satisfies
+//! `4A(x + y) + D = 4AD + D^3 / (4xy)`, where `A` is the amplification coefficient.
+//! The curve is flat around the balanced point for large `A` and approaches
+//! the constant product curve as `A` approaches zero.
+//!
+//! All swaps are rounded in favor of the pool.
+
+use crate::SwapResult;
+use spl_math::uint::U256;
+use std::convert::TryFrom;
+
+/// Maximum amplification coefficient.
+pub const MAX_AMP: u64 = 1_000_000;
+
+/// Maximum number of Newton iterations before the invariant calculations give up.
+const MAX_ITERATIONS: u8 = 255;
+
+/// Returns true if `a` and `b` differ by at most 1.
+fn within_one(a: U256, b: U256) -> bool {
+    if a > b {
+        a - b <= U256::one()
+    } else {
+        b - a <= U256::one()
+    }
+}
+
+/// Computes the invariant `D` of the amounts of the two tokens of a pool.
+///
+/// Returns [None] if `amp` is zero or above [MAX_AMP], or if only one of the amounts is zero.
+pub fn compute_d(amp: u64, amount_a: u64, amount_b: u64) -> Option<U256> {
+    if amp == 0 || amp > MAX_AMP {
+        return None;
+    }
+    let amount_a = U256::from(amount_a);
+    let amount_b = U256::from(amount_b);
+    let sum = amount_a.checked_add(amount_b)?;
+    if sum.is_zero() {
+        return Some(U256::zero());
+    }
+    if amount_a.is_zero() || amount_b.is_zero() {
+        return None;
+    }
+
+    let ann = U256::from(amp).checked_mul(4.into())?;
+    let mut d = sum;
+    for _ in 0..MAX_ITERATIONS {
+        // d_p = d^3 / (4 * a * b)
+        let d_p = d
+            .checked_mul(d)?
+            .checked_mul(d)?
+            .checked_div(amount_a.checked_mul(amount_b)?.checked_mul(4.into())?)?;
+        let d_prev = d;
+        // d = (ann * sum + 2 * d_p) * d / ((ann - 1) * d + 3 * d_p)
+        let numerator = ann
+            .checked_mul(sum)?
+            .checked_add(d_p.checked_mul(2.into())?)?
+            .checked_mul(d)?;
+        let denominator = ann
+            .checked_sub(U256::one())?
+            .checked_mul(d)?
+            .checked_add(d_p.checked_mul(3.into())?)?;
+        d = numerator.checked_div(denominator)?;
+        if within_one(d, d_prev) {
+            return Some(d);
+        }
+    }
+    None
+}
+
+/// Computes the amount of the other token of a pool with the invariant `d`
+/// when one of its tokens has the amount `amount`. Rounded up.
+pub fn compute_y(amp: u64, amount: u64, d: U256) -> Option<u64> {
+    if amp == 0 || amp > MAX_AMP || amount == 0 {
+        return None;
+    }
+    let amount = U256::from(amount);
+    let ann = U256::from(amp).checked_mul(4.into())?;
+
+    // c = d^3 / (4 * amount * ann), b = amount + d / ann
+    let c = d
+        .checked_mul(d)?
+        .checked_mul(d)?
+        .checked_div(amount.checked_mul(ann)?.checked_mul(4.into())?)?;
+    let b = amount.checked_add(d.checked_div(ann)?)?;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+        // y = (y^2 + c) / (2 * y + b - d)
+        y = y
+            .checked_mul(y)?
+            .checked_add(c)?
+            .checked_div(y.checked_mul(2.into())?.checked_add(b)?.checked_sub(d)?)?;
+        if within_one(y, y_prev) {
+            // the iteration converges to within 1 of the exact amount
+            return u64::try_from(y.checked_add(U256::one())?).ok();
+        }
+    }
+    None
+}
+
+/// Stable swap of an exact source amount.
+///
+/// Returns [None] if the swap would not execute, like [crate::swap].
+pub fn swap(
+    amp: u64,
+    source_amount: u64,
+    swap_source_amount: u64,
+    swap_destination_amount: u64,
+) -> Option<SwapResult> {
+    // zero swap should not execute
+    if source_amount == 0 {
+        return None;
+    }
+    let d = compute_d(amp, swap_source_amount, swap_destination_amount)?;
+    let new_swap_source_amount = swap_source_amount.checked_add(source_amount)?;
+    let new_swap_destination_amount = compute_y(amp, new_swap_source_amount, d)?;
+
+    let destination_amount_swapped = swap_destination_amount
+        .checked_sub(new_swap_destination_amount.min(swap_destination_amount))?;
+    // zero swap should not execute
+    if destination_amount_swapped == 0 {
+        return None;
+    }
+
+    Some(SwapResult {
+        source_amount_swapped: source_amount,
+        destination_amount_swapped,
+    })
+}
+
+/// Stable swap which computes the source amount required to receive
+/// an exact destination amount. The source amount is rounded up.
+///
+/// Returns [None] if the destination amount would empty the pool.
+pub fn swap_exact_out(
+    amp: u64,
+    destination_amount: u64,
+    swap_source_amount: u64,
+    swap_destination_amount: u64,
+) -> Option<SwapResult> {
+    // zero swap should not execute
+    if destination_amount == 0 {
+        return None;
+    }
+    let d = compute_d(amp, swap_source_amount, swap_destination_amount)?;
+    let new_swap_destination_amount = swap_destination_amount.checked_sub(destination_amount)?;
+    if new_swap_destination_amount == 0 {
+        return None;
+    }
+    let new_swap_source_amount = compute_y(amp, new_swap_destination_amount, d)?;
+
+    let source_amount_swapped = new_swap_source_amount.checked_sub(swap_source_amount)?;
+    // zero swap should not execute
+    if source_amount_swapped == 0 {
+        return None;
+    }
+
+    Some(SwapResult {
+        source_amount_swapped,
+        destination_amount_swapped: destination_amount,
+    })
+}
+
+/// Returns true if the invariant of the new amounts is at least the invariant of the old amounts.
+pub fn is_invariant_preserved(
+    amp: u64,
+    old_amount_a: u64,
+    old_amount_b: u64,
+    new_amount_a: u64,
+    new_amount_b: u64,
+) -> Option<bool> {
+    let old_invariant = compute_d(amp, old_amount_a, old_amount_b)?;
+    let new_invariant = compute_d(amp, new_amount_a, new_amount_b)?;
+    Some(new_invariant >= old_invariant)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn balanced_pool_has_invariant_of_the_sum() {
+        let d = compute_d(100, 1_000_000, 1_000_000).unwrap();
+        assert_eq!(d, U256::from(2_000_000));
+        assert_eq!(compute_d(100, 0, 0), Some(U256::zero()));
+        assert_eq!(compute_d(100, 0, 1), None);
+        assert_eq!(compute_d(0, 1, 1), None);
+    }
+
+    #[test]
+    fn stable_swap_is_close_to_one_to_one() {
+        let result = swap(100, 1_000, 1_000_000_000, 1_000_000_000).unwrap();
+        assert_eq!(result.source_amount_swapped, 1_000);
+        assert!(result.destination_amount_swapped < 1_000);
+        assert!(result.destination_amount_swapped >= 998);
+
+        // the constant product curve gives a worse price for the same swap
+        let constant_product = crate::swap(100_000_000, 1_000_000_000, 1_000_000_000).unwrap();
+        let stable = swap(100, 100_000_000, 1_000_000_000, 1_000_000_000).unwrap();
+        assert!(stable.destination_amount_swapped > constant_product.destination_amount_swapped);
+    }
+
+    proptest! {
+        #[test]
+        fn stable_swap_does_not_decrease_invariant(
+            amp in 1..MAX_AMP,
+            source_amount in 1..u32::MAX as u64,
+            swap_source_amount in 1..u32::MAX as u64,
+            swap_destination_amount in 1..u32::MAX as u64,
+        ) {
+            if let Some(result) = swap(amp, source_amount, swap_source_amount, swap_destination_amount) {
+                assert!(is_invariant_preserved(
+                    amp,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    swap_source_amount + result.source_amount_swapped,
+                    swap_destination_amount - result.destination_amount_swapped,
+                )
+                .unwrap());
+            }
+        }
+
+        #[test]
+        fn stable_swap_exact_out_does_not_decrease_invariant(
+            amp in 1..MAX_AMP,
+            destination_amount in 1..u32::MAX as u64,
+            swap_source_amount in 1..u32::MAX as u64,
+            swap_destination_amount in 1..u32::MAX as u64,
+        ) {
+            if let Some(result) = swap_exact_out(amp, destination_amount, swap_source_amount, swap_destination_amount) {
+                assert!(is_invariant_preserved(
+                    amp,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    swap_source_amount + result.source_amount_swapped,
+                    swap_destination_amount - result.destination_amount_swapped,
+                )
+                .unwrap());
+            }
+        }
+    }
+}
@@ -20,6 +20,21 @@ pub struct SwapResult {
     pub destination_amount_swapped: u64,
 }
 
+/// Encodes the swap of the excess of a deposit of any ratio of the two tokens
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImbalancedDepositSwap {
+    /// True if token A is in excess and swapped for token B
+    pub is_token_a_swapped: bool,
+    /// Amount of the excess token left after the proportional part of the deposit
+    pub excess_amount: u64,
+    /// Amount of the excess token swapped
+    pub swap_amount: u64,
+    /// Amount of the excess token in the swap after the proportional part of the deposit
+    pub swap_source_amount: u64,
+    /// Amount of the other token in the swap after the proportional part of the deposit
+    pub swap_destination_amount: u64,
+}
+
 /// Encodes results of depositing both sides at once
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct TradingTokenResult {
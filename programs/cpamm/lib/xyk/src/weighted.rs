@@ -0,0 +1,287 @@
+//! The constant mean invariant of weighted pools, such as 80/20 pools.
+//!
+//! For two tokens with amounts `x` and `y` and weights `w_x` and `w_y`, the invariant
+//! `x^w_x * y^w_y` stays constant across swaps. The weights are small integers of at most
+//! [MAX_WEIGHT], so the invariant is computed exactly with [U512] integers.
+//! Equal weights are the constant product invariant.
+//!
+//! All swaps are rounded in favor of the pool.
+
+// required for clippy
+#![allow(clippy::assign_op_pattern)]
+#![allow(clippy::ptr_offset_with_cast)]
+#![allow(clippy::manual_range_contains)]
+#![allow(clippy::manual_div_ceil)]
+
+use crate::SwapResult;
+use std::convert::TryFrom;
+use uint::construct_uint;
+
+construct_uint! {
+    /// 512-bit unsigned integer, which fits the invariant of amounts with
+    /// a total weight of at most 2 * [MAX_WEIGHT].
+    pub struct U512(8);
+}
+
+/// Maximum weight of a token of a weighted pool.
+pub const MAX_WEIGHT: u8 = 4;
+
+/// Returns the weights divided by their greatest common divisor,
+/// or [None] if either weight is zero or above [MAX_WEIGHT].
+fn reduce_weights(weight_a: u8, weight_b: u8) -> Option<(u32, u32)> {
+    if weight_a == 0 || weight_a > MAX_WEIGHT || weight_b == 0 || weight_b > MAX_WEIGHT {
+        return None;
+    }
+    let (mut a, mut b) = (weight_a, weight_b);
+    while b != 0 {
+        let remainder = a.checked_rem(b)?;
+        a = b;
+        b = remainder;
+    }
+    Some((
+        weight_a.checked_div(a)?.into(),
+        weight_b.checked_div(a)?.into(),
+    ))
+}
+
+/// Computes `value` to the power of `exponent`.
+fn pow(value: u64, exponent: u32) -> Option<U512> {
+    let mut result = U512::one();
+    for _ in 0..exponent {
+        result = result.checked_mul(value.into())?;
+    }
+    Some(result)
+}
+
+/// Divides `numerator` by `denominator`, rounding up.
+fn ceil_div(numerator: U512, denominator: U512) -> Option<U512> {
+    let quotient = numerator.checked_div(denominator)?;
+    if numerator.checked_rem(denominator)?.is_zero() {
+        Some(quotient)
+    } else {
+        quotient.checked_add(U512::one())
+    }
+}
+
+/// Computes the `degree`-th root of `value`, rounding up.
+fn ceil_root(value: U512, degree: u32) -> Option<U512> {
+    if degree == 1 || value.is_zero() {
+        return Some(value);
+    }
+    let degree_minus_one = degree.checked_sub(1)?;
+
+    // Newton's method from an initial guess above the root converges to the floor of the root
+    let bits = u32::try_from(value.bits()).ok()?;
+    let mut root = U512::one() << bits.checked_div(degree)?.checked_add(1)?;
+    loop {
+        let power = root.checked_pow(degree_minus_one.into())?;
+        let next = root
+            .checked_mul(degree_minus_one.into())?
+            .checked_add(value.checked_div(power)?)?
+            .checked_div(degree.into())?;
+        if next >= root {
+            break;
+        }
+        root = next;
+    }
+
+    if root.checked_pow(degree.into())? < value {
+        root.checked_add(U512::one())
+    } else {
+        Some(root)
+    }
+}
+
+/// Weighted swap of an exact source amount.
+///
+/// Returns [None] if the swap would not execute, like [crate::swap].
+pub fn swap(
+    source_amount: u64,
+    swap_source_amount: u64,
+    swap_destination_amount: u64,
+    source_weight: u8,
+    destination_weight: u8,
+) -> Option<SwapResult> {
+    // zero swap should not execute
+    if source_amount == 0 || swap_source_amount == 0 {
+        return None;
+    }
+    let (source_exponent, destination_exponent) =
+        reduce_weights(source_weight, destination_weight)?;
+    let new_swap_source_amount = swap_source_amount.checked_add(source_amount)?;
+
+    // the smallest destination amount which preserves the invariant
+    let invariant = pow(swap_destination_amount, destination_exponent)?
+        .checked_mul(pow(swap_source_amount, source_exponent)?)?;
+    let new_swap_destination_amount = ceil_root(
+        ceil_div(invariant, pow(new_swap_source_amount, source_exponent)?)?,
+        destination_exponent,
+    )?;
+    let new_swap_destination_amount = u64::try_from(new_swap_destination_amount)
+        .ok()?
+        .min(swap_destination_amount);
+
+    let destination_amount_swapped =
+        swap_destination_amount.checked_sub(new_swap_destination_amount)?;
+    // zero swap should not execute
+    if destination_amount_swapped == 0 {
+        return None;
+    }
+
+    Some(SwapResult {
+        source_amount_swapped: source_amount,
+        destination_amount_swapped,
+    })
+}
+
+/// Weighted swap which computes the source amount required to receive
+/// an exact destination amount. The source amount is rounded up.
+///
+/// Returns [None] if the destination amount would empty the pool.
+pub fn swap_exact_out(
+    destination_amount: u64,
+    swap_source_amount: u64,
+    swap_destination_amount: u64,
+    source_weight: u8,
+    destination_weight: u8,
+) -> Option<SwapResult> {
+    // zero swap should not execute
+    if destination_amount == 0 || swap_source_amount == 0 {
+        return None;
+    }
+    let (source_exponent, destination_exponent) =
+        reduce_weights(source_weight, destination_weight)?;
+    let new_swap_destination_amount = swap_destination_amount.checked_sub(destination_amount)?;
+    if new_swap_destination_amount == 0 {
+        return None;
+    }
+
+    // the smallest source amount which preserves the invariant
+    let invariant = pow(swap_destination_amount, destination_exponent)?
+        .checked_mul(pow(swap_source_amount, source_exponent)?)?;
+    let new_swap_source_amount = ceil_root(
+        ceil_div(
+            invariant,
+            pow(new_swap_destination_amount, destination_exponent)?,
+        )?,
+        source_exponent,
+    )?;
+
+    let source_amount_swapped = u64::try_from(new_swap_source_amount)
+        .ok()?
+        .checked_sub(swap_source_amount)?;
+    // zero swap should not execute
+    if source_amount_swapped == 0 {
+        return None;
+    }
+
+    Some(SwapResult {
+        source_amount_swapped,
+        destination_amount_swapped: destination_amount,
+    })
+}
+
+/// Returns true if the invariant of the new amounts is at least the invariant of the old amounts.
+pub fn is_invariant_preserved(
+    weight_a: u8,
+    weight_b: u8,
+    old_amount_a: u64,
+    old_amount_b: u64,
+    new_amount_a: u64,
+    new_amount_b: u64,
+) -> Option<bool> {
+    let (exponent_a, exponent_b) = reduce_weights(weight_a, weight_b)?;
+    let old_invariant =
+        pow(old_amount_a, exponent_a)?.checked_mul(pow(old_amount_b, exponent_b)?)?;
+    let new_invariant =
+        pow(new_amount_a, exponent_a)?.checked_mul(pow(new_amount_b, exponent_b)?)?;
+    Some(new_invariant >= old_invariant)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn equal_weights_are_constant_product() {
+        for (source_amount, swap_source_amount, swap_destination_amount) in
+            [(1_000, 1_000_000, 1_000_000), (12_345, 999_999, 7_654_321)]
+        {
+            assert_eq!(
+                swap(
+                    source_amount,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    2,
+                    2
+                ),
+                crate::swap(source_amount, swap_source_amount, swap_destination_amount)
+            );
+        }
+        assert_eq!(swap(1, 1, 1, 0, 1), None);
+        assert_eq!(swap(1, 1, 1, 1, MAX_WEIGHT + 1), None);
+    }
+
+    #[test]
+    fn heavier_source_weight_receives_more() {
+        // an 80/20 pool with a 1:1 spot price by value holds 4 times more of the heavy token
+        let result = swap(1_000, 4_000_000_000, 1_000_000_000, 4, 1).unwrap();
+        assert!(result.destination_amount_swapped <= 1_000);
+        assert!(result.destination_amount_swapped >= 998);
+    }
+
+    #[test]
+    fn roots_round_up() {
+        assert_eq!(ceil_root(U512::from(27), 3), Some(U512::from(3)));
+        assert_eq!(ceil_root(U512::from(28), 3), Some(U512::from(4)));
+        assert_eq!(
+            ceil_root(U512::from(u64::MAX) * U512::from(u64::MAX), 2),
+            Some(U512::from(u64::MAX))
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn weighted_swap_does_not_decrease_invariant(
+            source_weight in 1..=MAX_WEIGHT,
+            destination_weight in 1..=MAX_WEIGHT,
+            source_amount in 1..u64::MAX >> 4,
+            swap_source_amount in 1..u64::MAX >> 4,
+            swap_destination_amount in 1..u64::MAX >> 4,
+        ) {
+            if let Some(result) = swap(source_amount, swap_source_amount, swap_destination_amount, source_weight, destination_weight) {
+                assert!(is_invariant_preserved(
+                    source_weight,
+                    destination_weight,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    swap_source_amount + result.source_amount_swapped,
+                    swap_destination_amount - result.destination_amount_swapped,
+                )
+                .unwrap());
+            }
+        }
+
+        #[test]
+        fn weighted_swap_exact_out_does_not_decrease_invariant(
+            source_weight in 1..=MAX_WEIGHT,
+            destination_weight in 1..=MAX_WEIGHT,
+            destination_amount in 1..u64::MAX >> 4,
+            swap_source_amount in 1..u64::MAX >> 4,
+            swap_destination_amount in 1..u64::MAX >> 4,
+        ) {
+            if let Some(result) = swap_exact_out(destination_amount, swap_source_amount, swap_destination_amount, source_weight, destination_weight) {
+                assert!(is_invariant_preserved(
+                    source_weight,
+                    destination_weight,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    swap_source_amount + result.source_amount_swapped,
+                    swap_destination_amount - result.destination_amount_swapped,
+                )
+                .unwrap());
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
 //! Accounts structs.
 
 use crate::*;
+use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token::{Mint, Token, TokenAccount};
 
 /// Accounts for a [cpamm::new_factory] instruction.
@@ -31,6 +32,7 @@ pub struct NewFactory<'info> {
 
 /// Accounts for a [cpamm::new_swap] instruction.
 #[derive(Accounts)]
+#[instruction(_bump: u8, pool_fee_tier: u8)]
 pub struct NewSwap<'info> {
     /// The [Factory].
     #[account(mut)]
@@ -43,54 +45,1559 @@ pub struct NewSwap<'info> {
             b"SwapInfo".as_ref(),
             factory.key().to_bytes().as_ref(),
             token_0.mint.key().to_bytes().as_ref(),
+            token_1.mint.key().to_bytes().as_ref(),
+            pool_fee_tier_seed(&pool_fee_tier)
+        ],
+        bump,
+        payer = payer
+    )]
+    pub swap: Box<Account<'info, SwapInfo>>,
+
+    /// The [SwapMeta] of the swap, which indexes the swaps of the [Factory].
+    #[account(
+        init,
+        seeds = [
+            b"SwapMeta".as_ref(),
+            factory.key().to_bytes().as_ref(),
+            factory.num_swaps.to_le_bytes().as_ref()
+        ],
+        bump,
+        payer = payer
+    )]
+    pub swap_meta: Box<Account<'info, SwapMeta>>,
+
+    /// The pool mint of the swap.
+    #[account(mut)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    /// The first token of the swap.
+    pub token_0: InitSwapToken<'info>,
+    /// The second token of the swap. Its mint must be
+    /// after the first mint. using canonical ordering.
+    pub token_1: InitSwapToken<'info>,
+    /// The pool creator's LP [TokenAccount].
+    #[account(mut)]
+    pub output_lp: Box<Account<'info, TokenAccount>>,
+    /// LP [TokenAccount] of the swap which permanently holds the [xyk::MINIMUM_LIQUIDITY].
+    #[account(mut)]
+    pub locked_lp: Box<Account<'info, TokenAccount>>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The creation fee vault of the [Factory], which receives the
+    /// [Factory::creation_fee_lamports].
+    #[account(
+        mut,
+        seeds = [
+            b"CreationFeeVault".as_ref(),
+            factory.key().to_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub creation_fee_vault: SystemAccount<'info>,
+
+    /// The address of the [MintListEntry] of the first mint, which may not exist.
+    /// CHECK: Address checked by seeds; only read to check whether the entry exists.
+    #[account(
+        seeds = [
+            b"MintListEntry".as_ref(),
+            factory.key().to_bytes().as_ref(),
+            token_0.mint.key().to_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub mint_list_entry_0: UncheckedAccount<'info>,
+
+    /// The address of the [MintListEntry] of the second mint, which may not exist.
+    /// CHECK: Address checked by seeds; only read to check whether the entry exists.
+    #[account(
+        seeds = [
+            b"MintListEntry".as_ref(),
+            factory.key().to_bytes().as_ref(),
             token_1.mint.key().to_bytes().as_ref()
         ],
+        bump
+    )]
+    pub mint_list_entry_1: UncheckedAccount<'info>,
+
+    /// [Token] program.
+    pub token_program: Program<'info, Token>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::withdraw_creation_fees] instruction.
+#[derive(Accounts)]
+pub struct WithdrawCreationFees<'info> {
+    /// The [Factory].
+    pub factory: Box<Account<'info, Factory>>,
+    /// The admin of the [Factory] or the holder of [Role::Treasurer].
+    pub admin: Signer<'info>,
+    /// The [FactoryRoles] of the [Factory]. The admin may pass any other account.
+    /// CHECK: Deserialized in the validator if the signer is not the admin.
+    pub roles: UncheckedAccount<'info>,
+    /// The creation fee vault of the [Factory].
+    #[account(
+        mut,
+        seeds = [
+            b"CreationFeeVault".as_ref(),
+            factory.key().to_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub creation_fee_vault: SystemAccount<'info>,
+    /// Receives the lamports of the vault.
+    /// CHECK: Any account may receive lamports.
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::set_pool_metadata] instruction.
+#[derive(Accounts)]
+pub struct SetPoolMetadata<'info> {
+    /// The admin of the [SwapInfo].
+    pub admin: Signer<'info>,
+    /// The [SwapInfo], which is the update authority of the metadata.
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The pool mint of the swap.
+    pub pool_mint: Box<Account<'info, Mint>>,
+    /// The metadata account of the pool mint.
+    /// CHECK: Address checked in the validator; created and updated by the Token Metadata program.
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+    /// Payer of the metadata account.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The Token Metadata program.
+    /// CHECK: Checked by address.
+    #[account(address = crate::addresses::TOKEN_METADATA_PROGRAM)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+    /// [Rent] sysvar.
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Accounts for a [cpamm::set_swap_metadata] instruction.
+#[derive(Accounts)]
+pub struct SetSwapMetadata<'info> {
+    /// The [SwapInfo].
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The [SwapMeta] of the swap, which records its creator.
+    pub swap_meta: Box<Account<'info, SwapMeta>>,
+    /// The [SwapMetadata] of the swap.
+    #[account(
+        init_if_needed,
+        seeds = [
+            b"SwapMetadata".as_ref(),
+            swap.key().to_bytes().as_ref()
+        ],
+        bump,
+        space = SwapMetadata::LEN,
+        payer = payer
+    )]
+    pub swap_metadata: Box<Account<'info, SwapMetadata>>,
+    /// The creator of the swap.
+    pub creator: Signer<'info>,
+    /// Payer of the [SwapMetadata] if it is created.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::new_swap_meta] instruction.
+#[derive(Accounts)]
+pub struct NewSwapMeta<'info> {
+    /// The swap account
+    pub swap: Box<Account<'info, SwapInfo>>,
+
+    /// The swap meta
+    #[account(
+        init,
+        seeds = [
+            b"SwapMeta".as_ref(),
+            swap.factory.to_bytes().as_ref(),
+            swap.index.to_le_bytes().as_ref()
+        ],
+        bump,
+        payer = payer
+    )]
+    pub swap_meta: Box<Account<'info, SwapMeta>>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::swap] instruction.
+/// The remaining accounts may be
+/// `[referrer, host_fee_account, fee_exemption, creator_fee_account, registered_referrer,
+/// holder_discount_config, discount_token_account, volume_rebate_config, trader_stats]`.
+/// The referrer, host fee account and creator fee account are token accounts of the output
+/// token receiving a share of the trade fee. The registered referrer is the [Referrer]
+/// owning the referrer token account. The discount token account is a token account of the
+/// discount mint of the [HolderDiscountConfig] owned by the user authority.
+/// The [TraderStats] of the user authority must be writable.
+/// If the swap has an oracle band, its oracle must follow these.
+/// Pass the program ID to skip one.
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    /// The context of the user performing the swap.
+    pub user: SwapUserContext<'info>,
+    /// The input token of the swap.
+    pub input: SwapTokenWithFees<'info>,
+    /// The output token of the swap.
+    pub output: SwapTokenWithFees<'info>,
+}
+
+/// Accounts for a [cpamm::swap_to_ata] instruction.
+///
+/// The remaining accounts are the same as those of a [cpamm::swap].
+#[derive(Accounts)]
+pub struct SwapToAta<'info> {
+    /// Creates the user's output token account if it does not exist.
+    pub output_ata: InitAta<'info>,
+    /// The swap. Its output user token account is the associated token account.
+    pub swap: Swap<'info>,
+}
+
+/// Accounts for a [cpamm::swap_batch] instruction.
+///
+/// The remaining accounts are the user's `[input, output]` token accounts of every swap
+/// of the batch, in order. The input token determines the direction of each swap.
+#[derive(Accounts)]
+pub struct SwapBatch<'info> {
+    /// The context of the user performing the swaps.
+    pub user: SwapUserContext<'info>,
+    /// The pool's reserves of token 0.
+    #[account(mut)]
+    pub reserve_0: Box<Account<'info, TokenAccount>>,
+    /// The admin fees account of token 0.
+    #[account(mut)]
+    pub fees_0: Box<Account<'info, TokenAccount>>,
+    /// The pool's reserves of token 1.
+    #[account(mut)]
+    pub reserve_1: Box<Account<'info, TokenAccount>>,
+    /// The admin fees account of token 1.
+    #[account(mut)]
+    pub fees_1: Box<Account<'info, TokenAccount>>,
+}
+
+/// Accounts for a [cpamm::new_locked_position] instruction.
+#[derive(Accounts)]
+pub struct NewLockedPosition<'info> {
+    /// Base key to create the [LockedPosition].
+    pub base: Signer<'info>,
+    /// The [SwapInfo] whose LP tokens are locked.
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The pool mint of the swap.
+    pub pool_mint: Box<Account<'info, Mint>>,
+    /// The owner of the [LockedPosition].
+    /// CHECK: Arbitrary. The owner signs deposits and unlocks.
+    pub owner: UncheckedAccount<'info>,
+
+    /// The [LockedPosition].
+    #[account(
+        init,
+        seeds = [
+            b"LockedPosition".as_ref(),
+            base.key().as_ref()
+        ],
+        bump,
+        payer = payer
+    )]
+    pub position: Box<Account<'info, LockedPosition>>,
+
+    /// The escrow of the locked LP tokens.
+    #[account(
+        init,
+        seeds = [
+            b"LockedPositionEscrow".as_ref(),
+            position.key().as_ref()
+        ],
+        bump,
+        token::mint = pool_mint,
+        token::authority = position,
+        payer = payer
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [Token] program.
+    pub token_program: Program<'info, Token>,
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+    /// [Rent] sysvar.
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Accounts for a [cpamm::thaw_lp_account] instruction.
+#[derive(Accounts)]
+pub struct ThawLpAccount<'info> {
+    /// The [SwapInfo].
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The pool mint of the swap.
+    pub pool_mint: Box<Account<'info, Mint>>,
+    /// The LP token account to thaw.
+    #[account(mut)]
+    pub lp_account: Box<Account<'info, TokenAccount>>,
+    /// The [DepositCooldown] of the LP token account.
+    pub cooldown: Box<Account<'info, DepositCooldown>>,
+    /// The [Token] program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Accounts for a [cpamm::new_deposit_cooldown] instruction.
+#[derive(Accounts)]
+pub struct NewDepositCooldown<'info> {
+    /// The [SwapInfo].
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The LP token account of the [DepositCooldown].
+    pub lp_account: Box<Account<'info, TokenAccount>>,
+
+    /// The [DepositCooldown].
+    #[account(
+        init,
+        seeds = [
+            b"DepositCooldown".as_ref(),
+            swap.key().as_ref(),
+            lp_account.key().as_ref()
+        ],
+        bump,
+        payer = payer
+    )]
+    pub cooldown: Box<Account<'info, DepositCooldown>>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::deposit_for] instruction.
+///
+/// The remaining accounts are the same as those of a [cpamm::deposit].
+#[derive(Accounts)]
+pub struct DepositFor<'info> {
+    /// The deposit. Its LP tokens are minted to a token account of the beneficiary.
+    pub deposit: Deposit<'info>,
+    /// The owner of the minted LP tokens.
+    /// CHECK: Arbitrary. Checked to own the `output_lp` of the deposit.
+    pub beneficiary: UncheckedAccount<'info>,
+}
+
+/// Accounts for a [cpamm::deposit_locked] instruction.
+#[derive(Accounts)]
+pub struct DepositLocked<'info> {
+    /// The deposit. Its LP tokens are minted to the escrow of the position.
+    pub deposit: Deposit<'info>,
+    /// The [LockedPosition].
+    #[account(mut)]
+    pub position: Box<Account<'info, LockedPosition>>,
+}
+
+/// Accounts for a [cpamm::unlock_position] instruction.
+#[derive(Accounts)]
+pub struct UnlockPosition<'info> {
+    /// The [LockedPosition].
+    #[account(mut)]
+    pub position: Box<Account<'info, LockedPosition>>,
+    /// The owner of the [LockedPosition].
+    pub owner: Signer<'info>,
+    /// The escrow of the locked LP tokens.
+    #[account(mut)]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+    /// Receives the unlocked LP tokens.
+    #[account(mut)]
+    pub destination: Box<Account<'info, TokenAccount>>,
+    /// [Token] program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Accounts for a [cpamm::commit_swap] instruction.
+#[derive(Accounts)]
+pub struct CommitSwap<'info> {
+    /// The [SwapInfo] account.
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The [Factory] of the swap.
+    pub factory: Box<Account<'info, Factory>>,
+    /// The authority of the user which will perform the swap.
+    pub user_authority: Signer<'info>,
+
+    /// The [SwapCommitment].
+    #[account(
+        init,
+        seeds = [
+            b"SwapCommitment".as_ref(),
+            swap.key().as_ref(),
+            user_authority.key().as_ref()
+        ],
+        bump,
+        payer = payer
+    )]
+    pub commitment: Box<Account<'info, SwapCommitment>>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::reveal_swap] instruction.
+///
+/// The remaining accounts are the same as those of a [cpamm::swap].
+#[derive(Accounts)]
+pub struct RevealSwap<'info> {
+    /// The swap being revealed.
+    pub swap: Swap<'info>,
+    /// The [SwapCommitment] of the swap. It is closed once the swap is revealed.
+    #[account(mut, close = payer)]
+    pub commitment: Box<Account<'info, SwapCommitment>>,
+    /// The payer of the [SwapCommitment], which receives its rent.
+    /// CHECK: Checked against the commitment.
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+/// Accounts for a [cpamm::quote_swap] instruction.
+#[derive(Accounts)]
+pub struct QuoteSwap<'info> {
+    /// The [SwapInfo] account.
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The [Factory] of the swap.
+    pub factory: Box<Account<'info, Factory>>,
+    /// The pool's reserves of the input token.
+    pub input_reserve: Box<Account<'info, TokenAccount>>,
+    /// The pool's reserves of the output token.
+    pub output_reserve: Box<Account<'info, TokenAccount>>,
+}
+
+/// Accounts for a [cpamm::sync] instruction.
+#[derive(Accounts)]
+pub struct SyncReserves<'info> {
+    /// The [SwapInfo] account.
+    #[account(mut)]
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The pool's reserves of token 0.
+    pub reserve_0: Box<Account<'info, TokenAccount>>,
+    /// The pool's reserves of token 1.
+    pub reserve_1: Box<Account<'info, TokenAccount>>,
+}
+
+/// Accounts for a [cpamm::skim] instruction.
+#[derive(Accounts)]
+pub struct Skim<'info> {
+    /// The [SwapInfo] account.
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The pool mint of the swap.
+    pub pool_mint: Box<Account<'info, Mint>>,
+    /// The pool's reserves of token 0.
+    #[account(mut)]
+    pub reserve_0: Box<Account<'info, TokenAccount>>,
+    /// The pool's reserves of token 1.
+    #[account(mut)]
+    pub reserve_1: Box<Account<'info, TokenAccount>>,
+    /// Receives the surplus of token 0.
+    #[account(mut)]
+    pub output_0: Box<Account<'info, TokenAccount>>,
+    /// Receives the surplus of token 1.
+    #[account(mut)]
+    pub output_1: Box<Account<'info, TokenAccount>>,
+    /// The spl_token program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Accounts for a [cpamm::rescue_tokens] instruction.
+#[derive(Accounts)]
+pub struct RescueTokens<'info> {
+    /// The admin of the [SwapInfo] or the holder of [Role::Treasurer].
+    pub admin: Signer<'info>,
+    /// The [SwapInfo] which owns the `source`.
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The [FactoryRoles] of the [Factory] of the swap. The admin may pass any other account.
+    /// CHECK: Deserialized in the validator if the signer is not the admin.
+    pub roles: UncheckedAccount<'info>,
+    /// The token account owned by the swap holding the stray tokens.
+    #[account(mut)]
+    pub source: Box<Account<'info, TokenAccount>>,
+    /// Receives the stray tokens.
+    #[account(mut)]
+    pub destination: Box<Account<'info, TokenAccount>>,
+    /// The spl_token program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Accounts for a [cpamm::migrate_pool] instruction.
+#[derive(Accounts)]
+pub struct MigratePool<'info> {
+    /// The [SwapInfo] to migrate. It may be smaller than the current [SwapInfo],
+    /// so it cannot be deserialized before it is migrated.
+    /// CHECK: Owner checked here; discriminator checked on deserialization in the handler.
+    #[account(mut, owner = crate::ID)]
+    pub swap: UncheckedAccount<'info>,
+    /// The pool's reserves of token 0.
+    pub reserve_0: Box<Account<'info, TokenAccount>>,
+    /// The pool's reserves of token 1.
+    pub reserve_1: Box<Account<'info, TokenAccount>>,
+    /// Pays for the additional rent of the [SwapInfo].
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::migrate_factory] instruction.
+#[derive(Accounts)]
+pub struct MigrateFactory<'info> {
+    /// The [Factory] to migrate.
+    /// CHECK: Owner checked here; discriminator checked on deserialization in the handler.
+    #[account(mut, owner = crate::ID)]
+    pub factory: UncheckedAccount<'info>,
+    /// Pays for the additional rent of the [Factory], if it grows.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::migrate_swap_meta] instruction.
+#[derive(Accounts)]
+pub struct MigrateSwapMeta<'info> {
+    /// The [SwapMeta] to migrate. It may be smaller than the current [SwapMeta],
+    /// so it cannot be deserialized before it is migrated.
+    /// CHECK: Owner checked here; discriminator checked on deserialization in the handler.
+    #[account(mut, owner = crate::ID)]
+    pub swap_meta: UncheckedAccount<'info>,
+    /// Pays for the additional rent of the [SwapMeta].
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::new_observations] instruction.
+#[derive(Accounts)]
+pub struct NewObservations<'info> {
+    /// The [SwapInfo].
+    pub swap: Box<Account<'info, SwapInfo>>,
+
+    /// The [Observations].
+    #[account(
+        init,
+        seeds = [
+            b"Observations".as_ref(),
+            swap.key().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = crate::observations::observations_space(1)
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::increase_observation_cardinality] instruction.
+#[derive(Accounts)]
+pub struct IncreaseObservationCardinality<'info> {
+    /// The [Observations] to grow.
+    #[account(mut)]
+    pub observations: AccountLoader<'info, Observations>,
+    /// Pays for the additional rent of the [Observations].
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::verify_pool] instruction.
+#[derive(Accounts)]
+pub struct VerifyPool<'info> {
+    /// The [SwapInfo] account.
+    #[account(mut)]
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The pool's reserves of token 0.
+    pub reserve_0: Box<Account<'info, TokenAccount>>,
+    /// The pool's reserves of token 1.
+    pub reserve_1: Box<Account<'info, TokenAccount>>,
+    /// The pool mint of the swap.
+    pub pool_mint: Box<Account<'info, Mint>>,
+}
+
+/// Accounts for a [cpamm::write_observation] instruction.
+#[derive(Accounts)]
+pub struct WriteObservation<'info> {
+    /// The [SwapInfo].
+    #[account(mut)]
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The [Observations] of the swap.
+    #[account(mut)]
+    pub observations: AccountLoader<'info, Observations>,
+}
+
+/// Accounts for a [cpamm::observe] instruction.
+#[derive(Accounts)]
+pub struct Observe<'info> {
+    /// The [SwapInfo].
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The [Observations] of the swap.
+    pub observations: AccountLoader<'info, Observations>,
+}
+
+/// Accounts for a [cpamm::new_pair_price] instruction.
+#[derive(Accounts)]
+pub struct NewPairPrice<'info> {
+    /// A [SwapInfo] of the pair.
+    pub swap: Box<Account<'info, SwapInfo>>,
+
+    /// The [PairPrice].
+    #[account(
+        init,
+        seeds = [
+            b"PairPrice".as_ref(),
+            swap.factory.as_ref(),
+            swap.token_0.mint.as_ref(),
+            swap.token_1.mint.as_ref()
+        ],
+        bump,
+        payer = payer
+    )]
+    pub pair_price: Box<Account<'info, PairPrice>>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::update_pair_price] instruction.
+#[derive(Accounts)]
+pub struct UpdatePairPrice<'info> {
+    /// The [PairPrice].
+    #[account(mut)]
+    pub pair_price: Box<Account<'info, PairPrice>>,
+}
+
+/// Accounts for a [cpamm::close_swap] instruction.
+#[derive(Accounts)]
+pub struct CloseSwap<'info> {
+    /// The [SwapInfo] to close.
+    #[account(mut, close = recipient)]
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The [SwapMeta] of the swap.
+    #[account(mut, close = recipient)]
+    pub swap_meta: Box<Account<'info, SwapMeta>>,
+    /// The pool mint of the swap.
+    #[account(mut)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    /// The pool's reserves of token 0.
+    #[account(mut)]
+    pub reserve_0: Box<Account<'info, TokenAccount>>,
+    /// The pool's reserves of token 1.
+    #[account(mut)]
+    pub reserve_1: Box<Account<'info, TokenAccount>>,
+    /// LP [TokenAccount] of the swap holding the locked [xyk::MINIMUM_LIQUIDITY],
+    /// which is burned.
+    #[account(mut)]
+    pub locked_lp: Box<Account<'info, TokenAccount>>,
+    /// The admin fees account of token 0, which receives the tokens left in the reserves.
+    #[account(mut)]
+    pub destination_0: Box<Account<'info, TokenAccount>>,
+    /// The admin fees account of token 1, which receives the tokens left in the reserves.
+    #[account(mut)]
+    pub destination_1: Box<Account<'info, TokenAccount>>,
+    /// The admin of the swap.
+    pub authority: Signer<'info>,
+    /// Receives the rent of the closed accounts.
+    /// CHECK: Any account may receive lamports.
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+    /// The spl_token program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Accounts for a [cpamm::swap_exact_out] instruction.
+#[derive(Accounts)]
+pub struct SwapExactOut<'info> {
+    /// The context of the user performing the swap.
+    pub user: SwapUserContext<'info>,
+    /// The input token of the swap.
+    pub input: SwapTokenWithFees<'info>,
+    /// The output token of the swap.
+    pub output: SwapTokenWithFees<'info>,
+}
+
+/// Accounts for a [cpamm::swap_route] instruction.
+#[derive(Accounts)]
+pub struct SwapRoute<'info> {
+    /// The [Token] program.
+    pub token_program: Program<'info, Token>,
+    /// The owner or the delegate of the user's input token account.
+    pub user_authority: Signer<'info>,
+    /// The user's token account of the input token of the route.
+    #[account(mut)]
+    pub input: Box<Account<'info, TokenAccount>>,
+    /// The first swap of the route.
+    pub hop_0: SwapRouteHop<'info>,
+    /// The second swap of the route. Its input token is the output token of the first swap.
+    pub hop_1: SwapRouteHop<'info>,
+    /// The user's token account of the output token of the route.
+    #[account(mut)]
+    pub output: Box<Account<'info, TokenAccount>>,
+}
+
+/// Accounts for a [cpamm::flash_swap] instruction.
+/// Remaining accounts are passed through to the callback program.
+#[derive(Accounts)]
+pub struct FlashSwap<'info> {
+    /// The context of the user performing the swap.
+    pub user: SwapUserContext<'info>,
+    /// Token 0 of the swap. Borrowed token 0 is sent to `token_0.user`.
+    pub token_0: SwapTokenWithFees<'info>,
+    /// Token 1 of the swap. Borrowed token 1 is sent to `token_1.user`.
+    pub token_1: SwapTokenWithFees<'info>,
+    /// The program invoked after the borrowed tokens are sent.
+    /// CHECK: Chosen by the caller. It is invoked without the swap's signature,
+    /// and the reserves are checked after it returns.
+    pub callback_program: UncheckedAccount<'info>,
+}
+
+/// Accounts for a [cpamm::swap_sol] instruction.
+#[derive(Accounts)]
+pub struct SwapSol<'info> {
+    /// The context of the user performing the swap.
+    pub user: SwapUserContext<'info>,
+    /// The token of the swap which is not wrapped SOL.
+    pub token: SwapTokenWithFees<'info>,
+    /// The pool's reserves of wrapped SOL.
+    #[account(mut)]
+    pub sol_reserve: Box<Account<'info, TokenAccount>>,
+    /// The token account for the fees associated with wrapped SOL.
+    #[account(mut)]
+    pub sol_fees: Box<Account<'info, TokenAccount>>,
+    /// The user's temporary wrapped SOL account.
+    /// CHECK: Created and closed within the instruction.
+    #[account(
+        mut,
+        seeds = [
+            b"WrappedSol".as_ref(),
+            user.swap.key().as_ref(),
+            user.user_authority.key().as_ref()
+        ],
+        bump
+    )]
+    pub wrapped_sol: UncheckedAccount<'info>,
+    /// The wrapped SOL [Mint].
+    /// CHECK: Checked against the native mint address.
+    #[account(address = crate::addresses::NATIVE_MINT)]
+    pub native_mint: UncheckedAccount<'info>,
+    /// Sends the SOL being swapped and receives the unwrapped SOL.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+    /// [Rent] sysvar.
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Accounts for a [cpamm::deposit_sol] instruction.
+#[derive(Accounts)]
+pub struct DepositSol<'info> {
+    /// The context of the user performing the deposit.
+    pub user: SwapUserContext<'info>,
+    /// The token of the swap which is not wrapped SOL.
+    pub token: SwapToken<'info>,
+    /// The pool's reserves of wrapped SOL.
+    #[account(mut)]
+    pub sol_reserve: Box<Account<'info, TokenAccount>>,
+    /// The pool mint of the swap.
+    #[account(mut)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    /// The destination account for LP tokens.
+    #[account(mut)]
+    pub output_lp: Box<Account<'info, TokenAccount>>,
+    /// The user's temporary wrapped SOL account.
+    /// CHECK: Created and closed within the instruction.
+    #[account(
+        mut,
+        seeds = [
+            b"WrappedSol".as_ref(),
+            user.swap.key().as_ref(),
+            user.user_authority.key().as_ref()
+        ],
+        bump
+    )]
+    pub wrapped_sol: UncheckedAccount<'info>,
+    /// The wrapped SOL [Mint].
+    /// CHECK: Checked against the native mint address.
+    #[account(address = crate::addresses::NATIVE_MINT)]
+    pub native_mint: UncheckedAccount<'info>,
+    /// Sends the SOL being deposited and receives the rent of the temporary account.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+    /// [Rent] sysvar.
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Accounts for a [cpamm::set_referral_fee] instruction.
+#[derive(Accounts)]
+pub struct SetReferralFee<'info> {
+    /// The [Factory] of the swap.
+    pub factory: Box<Account<'info, Factory>>,
+    /// The admin of the [Factory] or the holder of [Role::FeeSetter].
+    pub admin: Signer<'info>,
+    /// The [FactoryRoles] of the [Factory]. The admin may pass any other account.
+    /// CHECK: Deserialized in the validator if the signer is not the admin.
+    pub roles: UncheckedAccount<'info>,
+    /// The [SwapInfo] to configure.
+    #[account(mut)]
+    pub swap: Box<Account<'info, SwapInfo>>,
+}
+
+/// Accounts for a [cpamm::set_protocol_fee_share] instruction.
+#[derive(Accounts)]
+pub struct SetProtocolFeeShare<'info> {
+    /// The [Factory] of the swap.
+    pub factory: Box<Account<'info, Factory>>,
+    /// The admin of the [Factory] or the holder of [Role::FeeSetter].
+    pub admin: Signer<'info>,
+    /// The [FactoryRoles] of the [Factory]. The admin may pass any other account.
+    /// CHECK: Deserialized in the validator if the signer is not the admin.
+    pub roles: UncheckedAccount<'info>,
+    /// The [SwapInfo] to configure.
+    #[account(mut)]
+    pub swap: Box<Account<'info, SwapInfo>>,
+}
+
+/// Accounts for a [cpamm::set_auto_compound] instruction.
+#[derive(Accounts)]
+pub struct SetAutoCompound<'info> {
+    /// The [Factory] of the swap.
+    pub factory: Box<Account<'info, Factory>>,
+    /// The admin of the [Factory].
+    pub admin: Signer<'info>,
+    /// The [SwapInfo] to configure.
+    #[account(mut)]
+    pub swap: Box<Account<'info, SwapInfo>>,
+}
+
+/// Accounts for instructions executed by the admin of a [Factory].
+#[derive(Accounts)]
+pub struct FactoryAdminContext<'info> {
+    /// The [Factory] to configure.
+    #[account(mut)]
+    pub factory: Box<Account<'info, Factory>>,
+    /// The admin of the [Factory].
+    pub admin: Signer<'info>,
+}
+
+/// Accounts for a [cpamm::accept_admin] instruction.
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    /// The [Factory].
+    #[account(mut)]
+    pub factory: Box<Account<'info, Factory>>,
+    /// The pending admin of the [Factory].
+    pub pending_admin: Signer<'info>,
+}
+
+/// Accounts for instructions executed by the admin of a [SwapInfo].
+#[derive(Accounts)]
+pub struct SwapAdminContext<'info> {
+    /// The admin of the [SwapInfo].
+    pub admin: Signer<'info>,
+    /// The [SwapInfo] to configure.
+    #[account(mut)]
+    pub swap: Box<Account<'info, SwapInfo>>,
+}
+
+/// Accounts for instructions which the admin of a [Factory] or the holder of a [Role] may call.
+#[derive(Accounts)]
+pub struct FactoryRoleContext<'info> {
+    /// The [Factory] to configure.
+    #[account(mut)]
+    pub factory: Box<Account<'info, Factory>>,
+    /// The [FactoryRoles] of the [Factory]. The admin may pass any other account.
+    /// CHECK: Deserialized in the validator if the authority is not the admin.
+    pub roles: UncheckedAccount<'info>,
+    /// The admin of the [Factory] or the holder of the role.
+    pub authority: Signer<'info>,
+}
+
+/// Accounts for instructions which the admin of a [SwapInfo] or the holder of a [Role]
+/// of its [Factory] may call.
+#[derive(Accounts)]
+pub struct SwapRoleContext<'info> {
+    /// The admin of the [SwapInfo] or the holder of the role.
+    pub authority: Signer<'info>,
+    /// The [SwapInfo] to configure.
+    #[account(mut)]
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The [Factory] of the swap, whose timelock applies to fee changes.
+    pub factory: Box<Account<'info, Factory>>,
+    /// The [FactoryRoles] of the [Factory]. The admin may pass any other account.
+    /// CHECK: Deserialized in the validator if the authority is not the admin.
+    pub roles: UncheckedAccount<'info>,
+}
+
+/// Accounts for a [cpamm::set_fees] instruction.
+#[derive(Accounts)]
+pub struct SetFees<'info> {
+    /// The admin of the [SwapInfo] or the holder of [Role::FeeSetter].
+    pub admin: Signer<'info>,
+    /// The [SwapInfo] to configure.
+    #[account(mut)]
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The [Factory] of the swap, whose timelock applies.
+    pub factory: Box<Account<'info, Factory>>,
+    /// The [FactoryRoles] of the [Factory]. The admin may pass any other account.
+    /// CHECK: Deserialized in the validator if the signer is not the admin.
+    pub roles: UncheckedAccount<'info>,
+}
+
+/// Accounts for a [cpamm::queue_change] instruction.
+#[derive(Accounts)]
+pub struct QueueChange<'info> {
+    /// The [Factory] whose timelock applies.
+    pub factory: Box<Account<'info, Factory>>,
+    /// The [SwapInfo] or the [Factory] to change.
+    /// CHECK: Validated against the change by the instruction.
+    pub target: UncheckedAccount<'info>,
+
+    /// The [PendingChange].
+    #[account(
+        init,
+        seeds = [
+            b"PendingChange".as_ref(),
+            target.key().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = PendingChange::LEN
+    )]
+    pub pending_change: Box<Account<'info, PendingChange>>,
+
+    /// The admin of the target.
+    pub admin: Signer<'info>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for the [cpamm::execute_change] and [cpamm::cancel_change] instructions.
+#[derive(Accounts)]
+pub struct PendingChangeContext<'info> {
+    /// The [Factory] whose timelock applies.
+    #[account(mut)]
+    pub factory: Box<Account<'info, Factory>>,
+    /// The [SwapInfo] or the [Factory] to change.
+    /// CHECK: Validated against the [PendingChange].
+    #[account(mut)]
+    pub target: UncheckedAccount<'info>,
+    /// The [PendingChange].
+    #[account(mut, close = recipient)]
+    pub pending_change: Box<Account<'info, PendingChange>>,
+    /// The admin of the target.
+    pub admin: Signer<'info>,
+    /// Receives the rent of the [PendingChange].
+    /// CHECK: Any account may receive lamports.
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+}
+
+/// Accounts for a [cpamm::set_oracle_band] instruction.
+#[derive(Accounts)]
+pub struct SetOracleBand<'info> {
+    /// The admin of the [SwapInfo].
+    pub admin: Signer<'info>,
+    /// The [SwapInfo] to configure.
+    #[account(mut)]
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The mint of token 0 of the swap.
+    pub token_0_mint: Box<Account<'info, Mint>>,
+    /// The mint of token 1 of the swap.
+    pub token_1_mint: Box<Account<'info, Mint>>,
+    /// The Pyth price account, or the default key to remove the oracle band.
+    /// CHECK: Parsed as a Pyth price account in the handler.
+    pub oracle: UncheckedAccount<'info>,
+}
+
+/// Accounts for a [cpamm::new_fee_exemption] instruction.
+#[derive(Accounts)]
+#[instruction(address: Pubkey)]
+pub struct NewFeeExemption<'info> {
+    /// The [Factory].
+    pub factory: Box<Account<'info, Factory>>,
+    /// The admin of the [Factory].
+    pub admin: Signer<'info>,
+
+    /// The [FeeExemption].
+    #[account(
+        init,
+        seeds = [
+            b"FeeExemption".as_ref(),
+            factory.key().as_ref(),
+            address.as_ref()
+        ],
+        bump,
+        payer = payer
+    )]
+    pub fee_exemption: Box<Account<'info, FeeExemption>>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::new_referrer] instruction.
+#[derive(Accounts)]
+pub struct NewReferrer<'info> {
+    /// Owner of the [Referrer].
+    pub owner: Signer<'info>,
+
+    /// The [Referrer].
+    #[account(
+        init,
+        seeds = [
+            b"Referrer".as_ref(),
+            owner.key().as_ref()
+        ],
+        bump,
+        payer = payer
+    )]
+    pub referrer: Box<Account<'info, Referrer>>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::claim_referral_fees] instruction.
+#[derive(Accounts)]
+pub struct ClaimReferralFees<'info> {
+    /// The [Referrer].
+    pub referrer: Box<Account<'info, Referrer>>,
+    /// Owner of the [Referrer].
+    pub owner: Signer<'info>,
+    /// Token account of the [Referrer] holding the referral fees.
+    #[account(mut)]
+    pub fees: Box<Account<'info, TokenAccount>>,
+    /// Receives the referral fees.
+    #[account(mut)]
+    pub destination: Box<Account<'info, TokenAccount>>,
+    /// The spl_token program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Accounts for a [cpamm::set_treasury] instruction.
+#[derive(Accounts)]
+pub struct SetTreasury<'info> {
+    /// The [Factory].
+    pub factory: Box<Account<'info, Factory>>,
+    /// The admin of the [Factory] or the holder of [Role::Treasurer].
+    pub admin: Signer<'info>,
+    /// The [FactoryRoles] of the [Factory]. The admin may pass any other account.
+    /// CHECK: Deserialized in the validator if the signer is not the admin.
+    pub roles: UncheckedAccount<'info>,
+
+    /// The [FactoryTreasury].
+    #[account(
+        init_if_needed,
+        seeds = [
+            b"FactoryTreasury".as_ref(),
+            factory.key().as_ref()
+        ],
+        bump,
+        payer = payer
+    )]
+    pub treasury: Box<Account<'info, FactoryTreasury>>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::set_holder_discount] instruction.
+#[derive(Accounts)]
+pub struct SetHolderDiscount<'info> {
+    /// The [Factory].
+    pub factory: Box<Account<'info, Factory>>,
+    /// The admin of the [Factory] or the holder of [Role::FeeSetter].
+    pub admin: Signer<'info>,
+    /// The [FactoryRoles] of the [Factory]. The admin may pass any other account.
+    /// CHECK: Deserialized in the validator if the signer is not the admin.
+    pub roles: UncheckedAccount<'info>,
+
+    /// The [HolderDiscountConfig].
+    #[account(
+        init_if_needed,
+        seeds = [
+            b"HolderDiscountConfig".as_ref(),
+            factory.key().as_ref()
+        ],
+        bump,
+        payer = payer
+    )]
+    pub config: Box<Account<'info, HolderDiscountConfig>>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::set_volume_rebates] instruction.
+#[derive(Accounts)]
+pub struct SetVolumeRebates<'info> {
+    /// The [Factory].
+    pub factory: Box<Account<'info, Factory>>,
+    /// The admin of the [Factory] or the holder of [Role::FeeSetter].
+    pub admin: Signer<'info>,
+    /// The [FactoryRoles] of the [Factory]. The admin may pass any other account.
+    /// CHECK: Deserialized in the validator if the signer is not the admin.
+    pub roles: UncheckedAccount<'info>,
+
+    /// The [VolumeRebateConfig].
+    #[account(
+        init_if_needed,
+        seeds = [
+            b"VolumeRebateConfig".as_ref(),
+            factory.key().as_ref()
+        ],
+        bump,
+        payer = payer
+    )]
+    pub config: Box<Account<'info, VolumeRebateConfig>>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::new_trader_stats] instruction.
+#[derive(Accounts)]
+pub struct NewTraderStats<'info> {
+    /// The [SwapInfo].
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The owner of the [TraderStats].
+    /// CHECK: Arbitrary. The owner is the user authority of swaps.
+    pub owner: UncheckedAccount<'info>,
+
+    /// The [TraderStats].
+    #[account(
+        init,
+        seeds = [
+            b"TraderStats".as_ref(),
+            swap.key().as_ref(),
+            owner.key().as_ref()
+        ],
+        bump,
+        payer = payer
+    )]
+    pub trader_stats: Box<Account<'info, TraderStats>>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::set_fee_bounds] instruction.
+#[derive(Accounts)]
+pub struct SetFeeBounds<'info> {
+    /// The [Factory].
+    pub factory: Box<Account<'info, Factory>>,
+    /// The admin of the [Factory].
+    pub admin: Signer<'info>,
+
+    /// The [FeeBounds].
+    #[account(
+        init_if_needed,
+        seeds = [
+            b"FeeBounds".as_ref(),
+            factory.key().as_ref()
+        ],
+        bump,
+        payer = payer
+    )]
+    pub fee_bounds: Box<Account<'info, FeeBounds>>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::set_guardian] instruction.
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    /// The [Factory].
+    pub factory: Box<Account<'info, Factory>>,
+    /// The admin of the [Factory].
+    pub admin: Signer<'info>,
+
+    /// The [FactoryGuardian].
+    #[account(
+        init_if_needed,
+        seeds = [
+            b"FactoryGuardian".as_ref(),
+            factory.key().as_ref()
+        ],
         bump,
         payer = payer
     )]
-    pub swap: Box<Account<'info, SwapInfo>>,
+    pub factory_guardian: Box<Account<'info, FactoryGuardian>>,
 
-    /// The pool mint of the swap.
+    /// Payer.
     #[account(mut)]
-    pub pool_mint: Box<Account<'info, Mint>>,
-    /// The first token of the swap.
-    pub token_0: InitSwapToken<'info>,
-    /// The second token of the swap. Its mint must be
-    /// after the first mint. using canonical ordering.
-    pub token_1: InitSwapToken<'info>,
-    /// The pool creator's LP [TokenAccount].
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::guardian_pause_all] instruction.
+#[derive(Accounts)]
+pub struct GuardianPauseAll<'info> {
+    /// The [Factory] to pause.
     #[account(mut)]
-    pub output_lp: Box<Account<'info, TokenAccount>>,
+    pub factory: Box<Account<'info, Factory>>,
+    /// The [FactoryGuardian] of the [Factory].
+    #[account(
+        seeds = [
+            b"FactoryGuardian".as_ref(),
+            factory.key().as_ref()
+        ],
+        bump = factory_guardian.bump
+    )]
+    pub factory_guardian: Box<Account<'info, FactoryGuardian>>,
+    /// The guardian.
+    pub guardian: Signer<'info>,
+}
+
+/// Accounts for a [cpamm::grant_role] or [cpamm::revoke_role] instruction.
+#[derive(Accounts)]
+pub struct SetRole<'info> {
+    /// The [Factory].
+    pub factory: Box<Account<'info, Factory>>,
+    /// The admin of the [Factory].
+    pub admin: Signer<'info>,
+
+    /// The [FactoryRoles].
+    #[account(
+        init_if_needed,
+        seeds = [
+            b"FactoryRoles".as_ref(),
+            factory.key().as_ref()
+        ],
+        bump,
+        payer = payer
+    )]
+    pub roles: Box<Account<'info, FactoryRoles>>,
 
     /// Payer.
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    /// [Token] program.
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::verify_upgrade_authority] instruction.
+#[derive(Accounts)]
+pub struct VerifyUpgradeAuthority<'info> {
+    /// The [Factory].
+    pub factory: Box<Account<'info, Factory>>,
+    /// The [FactoryRoles] of the [Factory].
+    #[account(
+        seeds = [
+            b"FactoryRoles".as_ref(),
+            factory.key().as_ref()
+        ],
+        bump = roles.bump
+    )]
+    pub roles: Box<Account<'info, FactoryRoles>>,
+    /// The program data account of the program, which holds its upgrade authority.
+    pub program_data: Account<'info, ProgramData>,
+}
+
+/// Accounts for a [cpamm::set_pool_fees] instruction.
+#[derive(Accounts)]
+pub struct SetPoolFees<'info> {
+    /// The [FeeBounds] of the [Factory] of the swap.
+    pub fee_bounds: Box<Account<'info, FeeBounds>>,
+    /// The creator or the admin of the [SwapInfo].
+    pub authority: Signer<'info>,
+    /// The [SwapInfo] to configure.
+    #[account(mut)]
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The [Factory] of the swap, whose timelock applies.
+    pub factory: Box<Account<'info, Factory>>,
+}
+
+/// Accounts for a [cpamm::set_buyback] instruction.
+#[derive(Accounts)]
+pub struct SetBuyback<'info> {
+    /// The [Factory].
+    pub factory: Box<Account<'info, Factory>>,
+    /// The admin of the [Factory].
+    pub admin: Signer<'info>,
+
+    /// The [BuybackConfig].
+    #[account(
+        init_if_needed,
+        seeds = [
+            b"BuybackConfig".as_ref(),
+            factory.key().as_ref()
+        ],
+        bump,
+        payer = payer
+    )]
+    pub config: Box<Account<'info, BuybackConfig>>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::buyback_and_burn] instruction.
+#[derive(Accounts)]
+pub struct BuybackAndBurn<'info> {
+    /// The [BuybackConfig] of the [Factory].
+    pub config: Box<Account<'info, BuybackConfig>>,
+    /// The [SwapInfo] whose admin fees are spent, which owns the accounts of the user of the swap.
+    pub fee_swap: Box<Account<'info, SwapInfo>>,
+    /// The [SwapInfo] pairing the token of the admin fees with the buyback mint.
+    /// This may be the `fee_swap`.
+    #[account(mut)]
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The [Factory] of the swap.
+    pub factory: Box<Account<'info, Factory>>,
+    /// The input token of the swap. The user account is the admin fee account of the `fee_swap`.
+    pub input: SwapTokenWithFees<'info>,
+    /// The output token of the swap. The user account is a token account of the buyback mint
+    /// owned by the `fee_swap`, from which the bought tokens are burned.
+    pub output: SwapTokenWithFees<'info>,
+    /// The buyback mint.
+    #[account(mut)]
+    pub buyback_mint: Box<Account<'info, Mint>>,
+    /// The spl_token program.
     pub token_program: Program<'info, Token>,
+}
+
+/// Accounts for a [cpamm::set_fee_distribution] instruction.
+#[derive(Accounts)]
+pub struct SetFeeDistribution<'info> {
+    /// The [Factory].
+    pub factory: Box<Account<'info, Factory>>,
+    /// The admin of the [Factory] or the holder of [Role::Treasurer].
+    pub admin: Signer<'info>,
+    /// The [FactoryRoles] of the [Factory]. The admin may pass any other account.
+    /// CHECK: Deserialized in the validator if the signer is not the admin.
+    pub roles: UncheckedAccount<'info>,
+
+    /// The [FeeDistribution].
+    #[account(
+        init_if_needed,
+        seeds = [
+            b"FeeDistribution".as_ref(),
+            factory.key().as_ref()
+        ],
+        bump,
+        payer = payer
+    )]
+    pub fee_distribution: Box<Account<'info, FeeDistribution>>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
 
     /// [System] program.
     pub system_program: Program<'info, System>,
 }
 
-/// Accounts for a [cpamm::new_swap_meta] instruction.
+/// Accounts for a [cpamm::collect_protocol_fees] instruction.
+///
+/// If the [FeeDistribution] exists, the remaining accounts are the token accounts of
+/// token 0 and token 1 of each of its recipients, in order.
 #[derive(Accounts)]
-pub struct NewSwapMeta<'info> {
-    /// The swap account
+pub struct CollectProtocolFees<'info> {
+    /// The [SwapInfo], which owns its admin fee accounts.
     pub swap: Box<Account<'info, SwapInfo>>,
+    /// The [FactoryTreasury] of the [Factory] of the swap.
+    pub treasury: Box<Account<'info, FactoryTreasury>>,
+    /// The address of the [FeeDistribution] of the [Factory], which may not exist.
+    /// CHECK: Address checked by seeds; only read if the distribution exists.
+    #[account(
+        seeds = [
+            b"FeeDistribution".as_ref(),
+            treasury.factory.as_ref()
+        ],
+        bump
+    )]
+    pub fee_distribution: UncheckedAccount<'info>,
+    /// The admin fee account of token 0.
+    #[account(mut)]
+    pub fees_0: Box<Account<'info, TokenAccount>>,
+    /// The admin fee account of token 1.
+    #[account(mut)]
+    pub fees_1: Box<Account<'info, TokenAccount>>,
+    /// Token account of the treasury which receives token 0.
+    #[account(mut)]
+    pub treasury_0: Box<Account<'info, TokenAccount>>,
+    /// Token account of the treasury which receives token 1.
+    #[account(mut)]
+    pub treasury_1: Box<Account<'info, TokenAccount>>,
+    /// The spl_token program.
+    pub token_program: Program<'info, Token>,
+}
 
-    /// The swap meta
+/// Accounts for a [cpamm::sweep_lamports] instruction.
+#[derive(Accounts)]
+pub struct SweepLamports<'info> {
+    /// The [SwapInfo].
+    #[account(mut)]
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The [SwapMeta] of the swap.
+    #[account(mut)]
+    pub swap_meta: Box<Account<'info, SwapMeta>>,
+    /// The [FactoryTreasury] of the [Factory] of the swap.
+    pub treasury: Box<Account<'info, FactoryTreasury>>,
+    /// The owner of the [FactoryTreasury], which receives the lamports.
+    /// CHECK: Checked against the owner of the treasury.
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+}
+
+/// Accounts for a [cpamm::set_admin_fee_accounts] instruction.
+#[derive(Accounts)]
+pub struct SetAdminFeeAccounts<'info> {
+    /// The [Factory] of the swap.
+    pub factory: Box<Account<'info, Factory>>,
+    /// The admin of the [Factory] or the holder of [Role::Treasurer].
+    pub admin: Signer<'info>,
+    /// The [FactoryRoles] of the [Factory]. The admin may pass any other account.
+    /// CHECK: Deserialized in the validator if the signer is not the admin.
+    pub roles: UncheckedAccount<'info>,
+    /// The [SwapInfo] to configure.
+    #[account(mut)]
+    pub swap: Box<Account<'info, SwapInfo>>,
+
+    /// The address of the [FactoryTreasury] of the [Factory], which may not exist.
+    /// CHECK: Address checked by seeds; only read if the new admin fee accounts are not owned by the swap.
+    #[account(
+        seeds = [
+            b"FactoryTreasury".as_ref(),
+            factory.key().as_ref()
+        ],
+        bump
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// The new admin fee account of token 0.
+    pub fees_0: Box<Account<'info, TokenAccount>>,
+    /// The new admin fee account of token 1.
+    pub fees_1: Box<Account<'info, TokenAccount>>,
+}
+
+/// Accounts for a [cpamm::compound_admin_fees] instruction.
+#[derive(Accounts)]
+pub struct CompoundAdminFees<'info> {
+    /// The [SwapInfo], which owns its admin fee accounts.
+    #[account(mut)]
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The pool mint of the swap.
+    #[account(mut)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    /// The reserve of token 0.
+    #[account(mut)]
+    pub reserve_0: Box<Account<'info, TokenAccount>>,
+    /// The reserve of token 1.
+    #[account(mut)]
+    pub reserve_1: Box<Account<'info, TokenAccount>>,
+    /// The admin fee account of token 0.
+    #[account(mut)]
+    pub fees_0: Box<Account<'info, TokenAccount>>,
+    /// The admin fee account of token 1.
+    #[account(mut)]
+    pub fees_1: Box<Account<'info, TokenAccount>>,
+    /// LP token account owned by the [Factory] of the swap, which holds the
+    /// protocol-owned liquidity.
+    #[account(mut)]
+    pub protocol_lp: Box<Account<'info, TokenAccount>>,
+    /// The spl_token program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Accounts for a [cpamm::new_mint_list_entry] instruction.
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+pub struct NewMintListEntry<'info> {
+    /// The [Factory].
+    pub factory: Box<Account<'info, Factory>>,
+    /// The admin of the [Factory].
+    pub admin: Signer<'info>,
+
+    /// The [MintListEntry].
     #[account(
         init,
         seeds = [
-            b"SwapMeta".as_ref(),
-            swap.factory.to_bytes().as_ref(),
-            swap.index.to_le_bytes().as_ref()
+            b"MintListEntry".as_ref(),
+            factory.key().as_ref(),
+            mint.as_ref()
         ],
         bump,
         payer = payer
     )]
-    pub swap_meta: Box<Account<'info, SwapMeta>>,
+    pub mint_list_entry: Box<Account<'info, MintListEntry>>,
 
     /// Payer.
     #[account(mut)]
@@ -100,18 +1607,90 @@ pub struct NewSwapMeta<'info> {
     pub system_program: Program<'info, System>,
 }
 
-/// Accounts for a [cpamm::swap] instruction.
+/// Accounts for a [cpamm::close_mint_list_entry] instruction.
 #[derive(Accounts)]
-pub struct Swap<'info> {
-    /// The context of the user performing the swap.
-    pub user: SwapUserContext<'info>,
+pub struct CloseMintListEntry<'info> {
+    /// The [Factory].
+    pub factory: Box<Account<'info, Factory>>,
+    /// The admin of the [Factory].
+    pub admin: Signer<'info>,
+    /// The [MintListEntry].
+    #[account(mut, close = recipient)]
+    pub mint_list_entry: Box<Account<'info, MintListEntry>>,
+    /// Receives the rent of the [MintListEntry].
+    /// CHECK: Any account may receive lamports.
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+}
+
+/// Accounts for a [cpamm::set_fee_exemption] instruction.
+#[derive(Accounts)]
+pub struct SetFeeExemption<'info> {
+    /// The [Factory].
+    pub factory: Box<Account<'info, Factory>>,
+    /// The admin of the [Factory].
+    pub admin: Signer<'info>,
+    /// The [FeeExemption].
+    #[account(mut)]
+    pub fee_exemption: Box<Account<'info, FeeExemption>>,
+}
+
+/// Accounts for a [cpamm::new_signed_swap_authority] instruction.
+#[derive(Accounts)]
+pub struct NewSignedSwapAuthority<'info> {
+    /// The owner of the [SignedSwapAuthority].
+    /// CHECK: Arbitrary. The owner only authorizes swaps by signing [SwapIntent]s.
+    pub owner: UncheckedAccount<'info>,
+
+    /// The [SignedSwapAuthority].
+    #[account(
+        init,
+        seeds = [
+            b"SignedSwapAuthority".as_ref(),
+            owner.key().as_ref()
+        ],
+        bump,
+        payer = payer
+    )]
+    pub signed_swap_authority: Box<Account<'info, SignedSwapAuthority>>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a [cpamm::swap_with_signature] instruction.
+///
+/// The remaining accounts are the same as those of a [cpamm::swap].
+#[derive(Accounts)]
+pub struct SwapWithSignature<'info> {
+    /// The [Token] program.
+    pub token_program: Program<'info, Token>,
+    /// The [SwapInfo] account.
+    #[account(mut)]
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The [Factory] of the swap.
+    pub factory: Box<Account<'info, Factory>>,
+    /// The [SignedSwapAuthority] of the owner of the input token account.
+    #[account(mut)]
+    pub signed_swap_authority: Box<Account<'info, SignedSwapAuthority>>,
     /// The input token of the swap.
     pub input: SwapTokenWithFees<'info>,
     /// The output token of the swap.
     pub output: SwapTokenWithFees<'info>,
+    /// The instructions sysvar, which contains the ed25519 signature verification.
+    /// CHECK: Checked by address.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
 }
 
 /// Accounts for a [cpamm::withdraw] instruction.
+///
+/// The output token accounts may be owned by any wallet, such as a treasury,
+/// not only by the owner of the LP tokens.
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
     /// The context of the user performing the swap.
@@ -122,12 +1701,44 @@ pub struct Withdraw<'info> {
     /// The input token of the swap.
     #[account(mut)]
     pub input_lp: Account<'info, TokenAccount>,
-    /// The 0 output token of the swap.
+    /// The 0 output token of the swap. The user account receives the withdrawn tokens.
     pub output_0: SwapTokenWithFees<'info>,
-    /// The 1 output token of the swap.
+    /// The 1 output token of the swap. The user account receives the withdrawn tokens.
     pub output_1: SwapTokenWithFees<'info>,
 }
 
+/// Accounts for a [cpamm::withdraw_one] instruction.
+#[derive(Accounts)]
+pub struct WithdrawOne<'info> {
+    /// The context of the user performing the withdrawal.
+    pub user: SwapUserContext<'info>,
+    /// The pool mint of the swap.
+    #[account(mut)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    /// The LP tokens to burn.
+    #[account(mut)]
+    pub input_lp: Box<Account<'info, TokenAccount>>,
+    /// The token withdrawn to the user.
+    pub output: SwapTokenWithFees<'info>,
+    /// The pool's reserves of the other token, which is swapped into the output token.
+    #[account(mut)]
+    pub other_reserve: Box<Account<'info, TokenAccount>>,
+    /// The admin fees account of the other token.
+    #[account(mut)]
+    pub other_fees: Box<Account<'info, TokenAccount>>,
+}
+
+/// Accounts for a [cpamm::withdraw_to_ata] instruction.
+#[derive(Accounts)]
+pub struct WithdrawToAta<'info> {
+    /// Creates the user's token 0 output account if it does not exist.
+    pub output_ata_0: InitAta<'info>,
+    /// Creates the user's token 1 output account if it does not exist.
+    pub output_ata_1: InitAta<'info>,
+    /// The withdrawal. Its output user token accounts are the associated token accounts.
+    pub withdraw: Withdraw<'info>,
+}
+
 /// Accounts for a [cpamm::deposit] instruction.
 #[derive(Accounts)]
 pub struct Deposit<'info> {
@@ -145,6 +1756,27 @@ pub struct Deposit<'info> {
     pub output_lp: Box<Account<'info, TokenAccount>>,
 }
 
+/// Accounts for a [cpamm::deposit_single] instruction.
+#[derive(Accounts)]
+pub struct DepositSingle<'info> {
+    /// The context of the user performing the deposit.
+    pub user: SwapUserContext<'info>,
+    /// The deposited token, part of which is swapped into the other token.
+    pub input: SwapToken<'info>,
+    /// The pool's reserves of the other token.
+    #[account(mut)]
+    pub output_reserve: Box<Account<'info, TokenAccount>>,
+    /// The admin fees account of the other token.
+    #[account(mut)]
+    pub output_fees: Box<Account<'info, TokenAccount>>,
+    /// The pool mint of the swap.
+    #[account(mut)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    /// The destination account for LP tokens.
+    #[account(mut)]
+    pub output_lp: Box<Account<'info, TokenAccount>>,
+}
+
 // --------------------------------
 // Various accounts
 // --------------------------------
@@ -160,6 +1792,38 @@ pub struct InitSwapToken<'info> {
     pub fees: Box<Account<'info, TokenAccount>>,
 }
 
+/// Associated token account which is created if it does not exist.
+///
+/// Its accounts are deserialized before those of the instruction which uses the token account,
+/// so the token account exists by then.
+#[derive(Accounts)]
+pub struct InitAta<'info> {
+    /// The [Mint] of the token account.
+    pub mint: Box<Account<'info, Mint>>,
+    /// The owner of the token account.
+    /// CHECK: Arbitrary. Validated by the instruction which uses the token account.
+    pub owner: UncheckedAccount<'info>,
+    /// The associated token account.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = owner
+    )]
+    pub account: Box<Account<'info, TokenAccount>>,
+    /// Pays for the token account if it is created.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// [Token] program.
+    pub token_program: Program<'info, Token>,
+    /// [AssociatedToken] program.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+    /// [Rent] sysvar.
+    pub rent: Sysvar<'info, Rent>,
+}
+
 #[derive(Accounts)]
 /// Context common to all router operations.
 pub struct SwapUserContext<'info> {
@@ -168,6 +1832,8 @@ pub struct SwapUserContext<'info> {
     /// The [SwapInfo] account.
     #[account(mut)]
     pub swap: Box<Account<'info, SwapInfo>>,
+    /// The [Factory] of the swap.
+    pub factory: Box<Account<'info, Factory>>,
     /// The authority of the user.
     pub user_authority: Signer<'info>,
 }
@@ -183,6 +1849,25 @@ pub struct SwapToken<'info> {
     pub reserve: Box<Account<'info, TokenAccount>>,
 }
 
+/// A single swap within a [SwapRoute].
+#[derive(Accounts)]
+pub struct SwapRouteHop<'info> {
+    /// The [SwapInfo] account.
+    #[account(mut)]
+    pub swap: Box<Account<'info, SwapInfo>>,
+    /// The [Factory] of the swap.
+    pub factory: Box<Account<'info, Factory>>,
+    /// The pool's reserves of the input token of this hop.
+    #[account(mut)]
+    pub input_reserve: Box<Account<'info, TokenAccount>>,
+    /// The pool's reserves of the output token of this hop.
+    #[account(mut)]
+    pub output_reserve: Box<Account<'info, TokenAccount>>,
+    /// The token account for the fees associated with the output token of this hop.
+    #[account(mut)]
+    pub output_fees: Box<Account<'info, TokenAccount>>,
+}
+
 /// Token accounts for the destination of a swap instruction.
 #[derive(Accounts)]
 pub struct SwapTokenWithFees<'info> {
@@ -1,21 +1,48 @@
 use crate::*;
 use crate::{
-    Deposit, InitSwapToken, NewFactory, NewSwap, NewSwapMeta, Swap, SwapToken, SwapTokenInfo,
-    SwapTokenWithFees, SwapUserContext, Withdraw,
+    AcceptAdmin, BuybackAndBurn, ClaimReferralFees, CloseMintListEntry, CloseSwap,
+    CollectProtocolFees, CommitSwap, CompoundAdminFees, Deposit, DepositFor, DepositLocked,
+    DepositSingle, DepositSol, FactoryAdminContext, FactoryRoleContext, FlashSwap,
+    GuardianPauseAll, IncreaseObservationCardinality, InitAta, InitSwapToken, MigrateFactory,
+    MigratePool, MigrateSwapMeta, NewDepositCooldown, NewFactory, NewFeeExemption,
+    NewMintListEntry, NewObservations, NewPairPrice, NewReferrer, NewSignedSwapAuthority, NewSwap,
+    NewSwapMeta, NewTraderStats, Observe, PendingChangeContext, QueueChange, QuoteSwap,
+    RescueTokens, RevealSwap, SetAdminFeeAccounts, SetAutoCompound, SetBuyback, SetFeeBounds,
+    SetFeeDistribution, SetFeeExemption, SetFees, SetGuardian, SetHolderDiscount, SetOracleBand,
+    SetPoolFees, SetPoolMetadata, SetProtocolFeeShare, SetReferralFee, SetRole, SetSwapMetadata,
+    SetTreasury, SetVolumeRebates, Skim, Swap, SwapAdminContext, SwapBatch, SwapExactOut,
+    SwapRoleContext, SwapRoute, SwapRouteHop, SwapSol, SwapToAta, SwapToken, SwapTokenInfo,
+    SwapTokenWithFees, SwapUserContext, SwapWithSignature, SweepLamports, SyncReserves,
+    ThawLpAccount, UpdatePairPrice, VerifyPool, VerifyUpgradeAuthority, Withdraw,
+    WithdrawCreationFees, WithdrawOne, WithdrawToAta, WriteObservation,
 };
+use anchor_lang::solana_program::system_program;
+use anchor_spl::associated_token;
+use anchor_spl::token::{self, TokenAccount};
 
 // --------------------------------
 // Instruction account structs
 // --------------------------------
+//
+// The keys of the token, associated token and system programs are asserted by every
+// validator, in addition to the checks of their [Program] accounts. Other programs and
+// sysvars are checked by `address` constraints, except the flash swap callback program,
+// which is chosen by the caller.
+//
+// Mints and token accounts are [Account]s of the SPL Token program, which Anchor checks are
+// owned by it, so no swap can hold Token-2022 mints or their extensions.
 
 impl<'info> Validate<'info> for NewFactory<'info> {
     fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
         Ok(())
     }
 }
 
 impl<'info> Validate<'info> for NewSwap<'info> {
     fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.token_program.key(), token::ID);
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
         let pool_mint_decimals = self.token_0.mint.decimals.max(self.token_1.mint.decimals);
 
         // pool mint belongs to swap
@@ -27,9 +54,43 @@ impl<'info> Validate<'info> for NewSwap<'info> {
         assert_keys_eq!(self.pool_mint.freeze_authority.unwrap(), self.swap);
         invariant!(self.pool_mint.supply == 0, SwapPoolMintSupply);
 
+        if self.factory.is_permissioned {
+            assert_keys_eq!(self.payer, self.factory.admin, PermissionedSwapCreation);
+        }
+
+        // an entry exists if the program has created it
+        let is_listed_0 = *self.mint_list_entry_0.owner == crate::ID;
+        let is_listed_1 = *self.mint_list_entry_1.owner == crate::ID;
+        match self.factory.mint_list_mode {
+            MINT_LIST_MODE_ALLOW => invariant!(is_listed_0 && is_listed_1, MintNotAllowed),
+            MINT_LIST_MODE_DENY => invariant!(!is_listed_0 && !is_listed_1, MintNotAllowed),
+            _ => {}
+        }
+
+        if self.factory.freeze_authority_policy == FREEZE_AUTHORITY_POLICY_REJECT {
+            invariant!(
+                self.token_0.mint.freeze_authority.is_none()
+                    && self.token_1.mint.freeze_authority.is_none(),
+                FreezableMint
+            );
+        }
+
         // output_lp
         assert_keys_eq!(self.output_lp.mint, self.pool_mint);
 
+        // locked_lp
+        // the swap never transfers its own LP tokens, so they are locked forever
+        assert_keys_eq!(self.locked_lp.mint, self.pool_mint);
+        assert_keys_eq!(self.locked_lp.owner, self.swap);
+        invariant!(
+            self.locked_lp.delegate.is_none(),
+            "locked_lp must have no delegate"
+        );
+        invariant!(
+            self.locked_lp.close_authority.is_none(),
+            "locked_lp must have no close authority"
+        );
+
         let token_0_mint = &self.token_0.mint;
         let token_1_mint = &self.token_1.mint;
         assert_keys_neq!(
@@ -49,16 +110,133 @@ impl<'info> Validate<'info> for NewSwap<'info> {
 
 impl<'info> Validate<'info> for NewSwapMeta<'info> {
     fn validate(&self) -> Result<()> {
-        // nothing to validate
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SetSwapMetadata<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        assert_keys_eq!(self.swap_meta.swap, self.swap);
+        assert_keys_eq!(self.creator, self.swap_meta.created_by);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SetPoolMetadata<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        assert_keys_eq!(self.admin, self.swap.admin_key);
+        assert_keys_eq!(self.pool_mint, self.swap.pool_mint);
+        assert_keys_eq!(
+            self.metadata,
+            crate::token_metadata::find_metadata_address(&self.pool_mint.key())
+        );
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for MigratePool<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        // the handler checks the discriminator of the swap
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for MigrateFactory<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        // the handler checks the discriminator of the factory
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for MigrateSwapMeta<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        // the handler checks the discriminator of the meta
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for NewObservations<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for IncreaseObservationCardinality<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for WriteObservation<'info> {
+    fn validate(&self) -> Result<()> {
+        let observations_swap = self.observations.load()?.swap;
+        assert_keys_eq!(observations_swap, self.swap);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for Observe<'info> {
+    fn validate(&self) -> Result<()> {
+        let observations_swap = self.observations.load()?.swap;
+        assert_keys_eq!(observations_swap, self.swap);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for NewPairPrice<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for UpdatePairPrice<'info> {
+    fn validate(&self) -> Result<()> {
+        // the swaps are validated by the instruction
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for NewDepositCooldown<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        assert_keys_eq!(self.lp_account.mint, self.swap.pool_mint);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for ThawLpAccount<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.token_program.key(), token::ID);
+        assert_keys_eq!(self.pool_mint, self.swap.pool_mint);
+        assert_keys_eq!(self.lp_account.mint, self.pool_mint);
+        assert_keys_eq!(self.cooldown.swap, self.swap);
+        assert_keys_eq!(self.cooldown.lp_account, self.lp_account);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for InitAta<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.token_program.key(), token::ID);
+        assert_keys_eq!(self.associated_token_program.key(), associated_token::ID);
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
         Ok(())
     }
 }
 
 impl<'info> Validate<'info> for SwapUserContext<'info> {
     fn validate(&self) -> Result<()> {
-        // ensure no self-dealing
-        assert_keys_neq!(self.user_authority, self.swap);
-        invariant!(!self.swap.is_paused, Paused);
+        self.validate_for_withdraw()?;
+        validate_not_paused(&self.factory, &self.swap)?;
         Ok(())
     }
 }
@@ -66,31 +244,756 @@ impl<'info> Validate<'info> for SwapUserContext<'info> {
 impl<'info> Validate<'info> for Swap<'info> {
     fn validate(&self) -> Result<()> {
         self.user.validate()?;
+        self.user.validate_swap_tokens(&self.input, &self.output)?;
+        Ok(())
+    }
+}
 
-        // inner validation will ensure that token source mint equals respective reserve
-        let (swap_input, swap_output) =
-            if self.input.reserve.key() == self.user.swap.token_0.reserves {
-                (&self.user.swap.token_0, &self.user.swap.token_1)
-            } else {
-                (&self.user.swap.token_1, &self.user.swap.token_0)
-            };
+impl<'info> Validate<'info> for SwapToAta<'info> {
+    fn validate(&self) -> Result<()> {
+        self.swap.validate()?;
+        self.output_ata.validate()?;
+        assert_keys_eq!(self.output_ata.account, self.swap.output.user);
+        Ok(())
+    }
+}
 
-        assert_keys_eq!(self.output.user.owner, self.user.user_authority);
+impl<'info> Validate<'info> for SwapBatch<'info> {
+    fn validate(&self) -> Result<()> {
+        self.user.validate()?;
 
-        self.input.validate_for_swap(swap_input)?;
-        self.output.validate_for_swap(swap_output)?;
+        let swap_info = &self.user.swap;
+        assert_keys_eq!(self.reserve_0, swap_info.token_0.reserves);
+        assert_keys_eq!(self.fees_0, swap_info.token_0.admin_fees);
+        assert_keys_eq!(self.reserve_1, swap_info.token_1.reserves);
+        assert_keys_eq!(self.fees_1, swap_info.token_1.admin_fees);
 
+        // the user token accounts are validated as each swap is loaded
         Ok(())
     }
 }
 
-impl<'info> Validate<'info> for Withdraw<'info> {
+impl<'info> Validate<'info> for NewLockedPosition<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.token_program.key(), token::ID);
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        assert_keys_eq!(self.pool_mint, self.swap.pool_mint);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for DepositLocked<'info> {
+    fn validate(&self) -> Result<()> {
+        self.deposit.validate()?;
+        assert_keys_eq!(self.position.swap, self.deposit.user.swap);
+        assert_keys_eq!(self.position.owner, self.deposit.user.user_authority);
+        assert_keys_eq!(self.deposit.output_lp, self.position.escrow);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for UnlockPosition<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.token_program.key(), token::ID);
+        assert_keys_eq!(self.owner, self.position.owner);
+        assert_keys_eq!(self.escrow, self.position.escrow);
+        assert_keys_eq!(self.destination.mint, self.escrow.mint);
+        assert_keys_neq!(self.destination, self.escrow);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for CommitSwap<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        validate_not_paused(&self.factory, &self.swap)?;
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for RevealSwap<'info> {
+    fn validate(&self) -> Result<()> {
+        self.swap.validate()?;
+        assert_keys_eq!(self.commitment.swap, self.swap.user.swap);
+        assert_keys_eq!(
+            self.commitment.user_authority,
+            self.swap.user.user_authority
+        );
+        assert_keys_eq!(self.payer, self.commitment.payer);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SyncReserves<'info> {
+    fn validate(&self) -> Result<()> {
+        invariant!(!self.swap.is_in_progress, Reentrancy);
+        assert_keys_eq!(self.reserve_0, self.swap.token_0.reserves);
+        assert_keys_eq!(self.reserve_1, self.swap.token_1.reserves);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for Skim<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.token_program.key(), token::ID);
+        invariant!(!self.swap.is_in_progress, Reentrancy);
+        assert_keys_eq!(self.pool_mint, self.swap.pool_mint);
+        assert_keys_eq!(self.reserve_0, self.swap.token_0.reserves);
+        assert_keys_eq!(self.reserve_1, self.swap.token_1.reserves);
+        // the whole reserves would be skimmed from a swap whose reserves are not tracked
+        let (reserve_0_amount, reserve_1_amount) = self.swap.reserve_amounts();
+        invariant!(
+            self.pool_mint.supply == 0 || (reserve_0_amount != 0 && reserve_1_amount != 0),
+            ReservesNotTracked
+        );
+        assert_keys_eq!(self.output_0.mint, self.swap.token_0.mint);
+        assert_keys_eq!(self.output_1.mint, self.swap.token_1.mint);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for VerifyPool<'info> {
+    fn validate(&self) -> Result<()> {
+        // the reserves are lent out during a flash swap
+        invariant!(!self.swap.is_in_progress, Reentrancy);
+        assert_keys_eq!(self.reserve_0, self.swap.token_0.reserves);
+        assert_keys_eq!(self.reserve_1, self.swap.token_1.reserves);
+        assert_keys_eq!(self.pool_mint, self.swap.pool_mint);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for RescueTokens<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.token_program.key(), token::ID);
+        validate_admin_or_role(
+            self.admin.key(),
+            self.swap.admin_key,
+            self.swap.factory,
+            &self.roles,
+            Role::Treasurer,
+        )?;
+        assert_keys_eq!(self.source.owner, self.swap);
+
+        // the reserves, the admin fees and the LP tokens of the swap are never stray
+        let mint = self.source.mint;
+        assert_keys_neq!(mint, self.swap.token_0.mint);
+        assert_keys_neq!(mint, self.swap.token_1.mint);
+        assert_keys_neq!(mint, self.swap.pool_mint);
+
+        assert_keys_eq!(self.destination.mint, mint);
+        assert_keys_neq!(self.destination, self.source);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for CloseSwap<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.token_program.key(), token::ID);
+        assert_keys_eq!(self.swap_meta.swap, self.swap);
+        assert_keys_eq!(self.pool_mint, self.swap.pool_mint);
+        assert_keys_eq!(self.reserve_0, self.swap.token_0.reserves);
+        assert_keys_eq!(self.reserve_1, self.swap.token_1.reserves);
+        assert_keys_eq!(self.locked_lp.mint, self.pool_mint);
+        assert_keys_eq!(self.locked_lp.owner, self.swap);
+        assert_keys_eq!(self.destination_0, self.swap.token_0.admin_fees);
+        assert_keys_eq!(self.destination_1, self.swap.token_1.admin_fees);
+        assert_keys_eq!(self.authority, self.swap.admin_key);
+
+        // all remaining LP tokens must be the locked liquidity of the swap itself
+        invariant!(self.locked_lp.amount == self.pool_mint.supply, SwapNotEmpty);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for QuoteSwap<'info> {
+    fn validate(&self) -> Result<()> {
+        validate_not_paused(&self.factory, &self.swap)?;
+        invariant!(
+            !self.swap.is_direction_paused(self.input_reserve.mint),
+            DirectionPaused
+        );
+
+        let (swap_input, swap_output) = if self.input_reserve.key() == self.swap.token_0.reserves {
+            (&self.swap.token_0, &self.swap.token_1)
+        } else {
+            (&self.swap.token_1, &self.swap.token_0)
+        };
+        assert_keys_eq!(self.input_reserve, swap_input.reserves);
+        assert_keys_eq!(self.output_reserve, swap_output.reserves);
+
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SwapExactOut<'info> {
     fn validate(&self) -> Result<()> {
         self.user.validate()?;
+        self.user.validate_swap_tokens(&self.input, &self.output)?;
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SwapRoute<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.token_program.key(), token::ID);
+        self.hop_0.validate()?;
+        self.hop_1.validate()?;
+        assert_keys_neq!(self.hop_0.swap, self.hop_1.swap);
+
+        // ensure no self-dealing
+        assert_keys_neq!(self.user_authority, self.hop_0.swap);
+        assert_keys_neq!(self.user_authority, self.hop_1.swap);
+
+        // the output of the first hop is the input of the second hop
+        assert_keys_eq!(
+            self.hop_0.output_reserve.mint,
+            self.hop_1.input_reserve.mint,
+            SwapRouteMintMismatch
+        );
+
+        // user input
+        assert_keys_eq!(self.input.mint, self.hop_0.input_reserve.mint);
+        assert_keys_neq!(self.input, self.hop_0.input_reserve);
+
+        // user output
+        let owner = validate_input_authority(&self.input, self.user_authority.key())?;
+        assert_keys_eq!(self.output.mint, self.hop_1.output_reserve.mint);
+        assert_keys_eq!(self.output.owner, owner);
+        assert_keys_neq!(self.output, self.hop_1.output_reserve);
+        assert_keys_neq!(self.output, self.hop_1.output_fees);
+
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SwapRouteHop<'info> {
+    fn validate(&self) -> Result<()> {
+        validate_not_paused(&self.factory, &self.swap)?;
+        invariant!(
+            !self.swap.is_direction_paused(self.input_reserve.mint),
+            DirectionPaused
+        );
+
+        let (swap_input, swap_output) = if self.input_reserve.key() == self.swap.token_0.reserves {
+            (&self.swap.token_0, &self.swap.token_1)
+        } else {
+            (&self.swap.token_1, &self.swap.token_0)
+        };
+        assert_keys_eq!(self.input_reserve, swap_input.reserves);
+        assert_keys_eq!(self.output_reserve, swap_output.reserves);
+        assert_keys_eq!(self.output_fees, swap_output.admin_fees);
+
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for FlashSwap<'info> {
+    fn validate(&self) -> Result<()> {
+        self.user.validate()?;
+        // a flash swap may swap in either direction
+        invariant!(
+            !self.user.swap.is_paused_0_to_1 && !self.user.swap.is_paused_1_to_0,
+            DirectionPaused
+        );
+
+        self.token_0.validate_for_swap(&self.user.swap.token_0)?;
+        self.token_1.validate_for_swap(&self.user.swap.token_1)?;
+
+        // the callback may not re-enter this program
+        invariant!(self.callback_program.executable);
+        assert_keys_neq!(self.callback_program, crate::ID);
+
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SwapSol<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        self.user.validate()?;
+
+        let (swap_sol, swap_token) = if self.user.swap.token_0.mint == crate::addresses::NATIVE_MINT
+        {
+            (&self.user.swap.token_0, &self.user.swap.token_1)
+        } else {
+            (&self.user.swap.token_1, &self.user.swap.token_0)
+        };
+        assert_keys_eq!(swap_sol.mint, crate::addresses::NATIVE_MINT);
+        assert_keys_eq!(self.sol_reserve, swap_sol.reserves);
+        assert_keys_eq!(self.sol_fees, swap_sol.admin_fees);
+
+        // the token may be either the input or the output of the swap
+        self.token.validate_for_swap(swap_token)?;
+        assert_keys_eq!(self.token.user.owner, self.user.user_authority);
+
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for DepositSol<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        self.user.validate()?;
+
+        let (swap_sol, swap_token) = if self.user.swap.token_0.mint == crate::addresses::NATIVE_MINT
+        {
+            (&self.user.swap.token_0, &self.user.swap.token_1)
+        } else {
+            (&self.user.swap.token_1, &self.user.swap.token_0)
+        };
+        assert_keys_eq!(swap_sol.mint, crate::addresses::NATIVE_MINT);
+        assert_keys_eq!(self.sol_reserve, swap_sol.reserves);
+        self.token.validate_for_swap(swap_token)?;
+
+        // should be same as swap
+        assert_keys_eq!(self.pool_mint, self.user.swap.pool_mint);
+
+        // lp output destination
+        assert_keys_eq!(self.output_lp.mint, self.user.swap.pool_mint);
+        assert_keys_neq!(self.output_lp.owner, self.user.swap);
+
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SetReferralFee<'info> {
+    fn validate(&self) -> Result<()> {
+        validate_admin_or_role(
+            self.admin.key(),
+            self.factory.admin,
+            self.factory.key(),
+            &self.roles,
+            Role::FeeSetter,
+        )?;
+        assert_keys_eq!(self.swap.factory, self.factory);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SetProtocolFeeShare<'info> {
+    fn validate(&self) -> Result<()> {
+        validate_admin_or_role(
+            self.admin.key(),
+            self.factory.admin,
+            self.factory.key(),
+            &self.roles,
+            Role::FeeSetter,
+        )?;
+        assert_keys_eq!(self.swap.factory, self.factory);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SetAutoCompound<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.admin, self.factory.admin);
+        assert_keys_eq!(self.swap.factory, self.factory);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for FactoryAdminContext<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.admin, self.factory.admin);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for AcceptAdmin<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_neq!(self.factory.pending_admin, Pubkey::default());
+        assert_keys_eq!(self.pending_admin, self.factory.pending_admin);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for WithdrawCreationFees<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        validate_admin_or_role(
+            self.admin.key(),
+            self.factory.admin,
+            self.factory.key(),
+            &self.roles,
+            Role::Treasurer,
+        )?;
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SwapAdminContext<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.admin, self.swap.admin_key);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SetFees<'info> {
+    fn validate(&self) -> Result<()> {
+        validate_admin_or_role(
+            self.admin.key(),
+            self.swap.admin_key,
+            self.swap.factory,
+            &self.roles,
+            Role::FeeSetter,
+        )?;
+        assert_keys_eq!(self.swap.factory, self.factory);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for QueueChange<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        // the target and the admin are validated against the change by the instruction
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for PendingChangeContext<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.pending_change.factory, self.factory);
+        assert_keys_eq!(self.pending_change.target, self.target);
+        self.pending_change
+            .change
+            .validate_target(&self.factory, &self.target, self.admin.key())
+    }
+}
+
+impl<'info> Validate<'info> for SetOracleBand<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.admin, self.swap.admin_key);
+        assert_keys_eq!(self.token_0_mint, self.swap.token_0.mint);
+        assert_keys_eq!(self.token_1_mint, self.swap.token_1.mint);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for NewFeeExemption<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        assert_keys_eq!(self.admin, self.factory.admin);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for NewReferrer<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for ClaimReferralFees<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.token_program.key(), token::ID);
+        assert_keys_eq!(self.owner, self.referrer.owner);
+        assert_keys_eq!(self.fees.owner, self.referrer);
+        assert_keys_eq!(self.destination.mint, self.fees.mint);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SetTreasury<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        validate_admin_or_role(
+            self.admin.key(),
+            self.factory.admin,
+            self.factory.key(),
+            &self.roles,
+            Role::Treasurer,
+        )?;
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SetHolderDiscount<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        validate_admin_or_role(
+            self.admin.key(),
+            self.factory.admin,
+            self.factory.key(),
+            &self.roles,
+            Role::FeeSetter,
+        )?;
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SetVolumeRebates<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        validate_admin_or_role(
+            self.admin.key(),
+            self.factory.admin,
+            self.factory.key(),
+            &self.roles,
+            Role::FeeSetter,
+        )?;
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for NewTraderStats<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SetFeeBounds<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        assert_keys_eq!(self.admin, self.factory.admin);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SetGuardian<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        assert_keys_eq!(self.admin, self.factory.admin);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for GuardianPauseAll<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_neq!(self.factory_guardian.guardian, Pubkey::default());
+        assert_keys_eq!(self.guardian, self.factory_guardian.guardian);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SetRole<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        assert_keys_eq!(self.admin, self.factory.admin);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for VerifyUpgradeAuthority<'info> {
+    fn validate(&self) -> Result<()> {
+        // the program data account of an upgradeable program is derived from the program ID
+        let (program_data, _) = Pubkey::find_program_address(
+            &[crate::ID.as_ref()],
+            &anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+        );
+        assert_keys_eq!(self.program_data, program_data);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SetPoolFees<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.fee_bounds.factory, self.swap.factory);
+        assert_keys_eq!(self.swap.factory, self.factory);
+        invariant!(
+            self.authority.key() == self.swap.creator
+                || self.authority.key() == self.swap.admin_key,
+            "authority must be the creator or the admin of the swap"
+        );
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SetBuyback<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        assert_keys_eq!(self.admin, self.factory.admin);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for BuybackAndBurn<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.token_program.key(), token::ID);
+        assert_keys_eq!(self.fee_swap.factory, self.config.factory);
+        assert_keys_eq!(self.swap.factory, self.config.factory);
+        validate_not_paused(&self.factory, &self.swap)?;
+
+        // only the admin fees of the fee swap may be spent, never its reserves
+        let fee_swap = &self.fee_swap;
+        invariant!(
+            self.input.user.key() == fee_swap.token_0.admin_fees
+                || self.input.user.key() == fee_swap.token_1.admin_fees,
+            "input must be an admin fee account of the fee swap"
+        );
+        assert_keys_eq!(self.input.user.owner, self.fee_swap);
+
+        // the bought tokens are burned, so the output must not be a reserve of the fee swap
+        assert_keys_neq!(self.output.user, fee_swap.token_0.reserves);
+        assert_keys_neq!(self.output.user, fee_swap.token_1.reserves);
+
+        assert_keys_eq!(self.buyback_mint, self.config.buyback_mint);
+        assert_keys_eq!(self.output.reserve.mint, self.config.buyback_mint);
+        validate_swap_tokens(&self.swap, fee_swap.key(), &self.input, &self.output)?;
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SetFeeDistribution<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        validate_admin_or_role(
+            self.admin.key(),
+            self.factory.admin,
+            self.factory.key(),
+            &self.roles,
+            Role::Treasurer,
+        )?;
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for CollectProtocolFees<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.token_program.key(), token::ID);
+        assert_keys_eq!(self.treasury.factory, self.swap.factory);
+        assert_keys_eq!(self.fees_0, self.swap.token_0.admin_fees);
+        assert_keys_eq!(self.fees_1, self.swap.token_1.admin_fees);
+        assert_keys_eq!(self.fees_0.owner, self.swap);
+        assert_keys_eq!(self.fees_1.owner, self.swap);
+        assert_keys_eq!(self.treasury_0.owner, self.treasury.owner);
+        assert_keys_eq!(self.treasury_1.owner, self.treasury.owner);
+        assert_keys_eq!(self.treasury_0.mint, self.swap.token_0.mint);
+        assert_keys_eq!(self.treasury_1.mint, self.swap.token_1.mint);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SweepLamports<'info> {
+    fn validate(&self) -> Result<()> {
+        invariant!(!self.swap.is_in_progress, Reentrancy);
+        assert_keys_eq!(self.swap_meta.swap, self.swap);
+        assert_keys_eq!(self.treasury.factory, self.swap.factory);
+        assert_keys_eq!(self.recipient, self.treasury.owner);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SetAdminFeeAccounts<'info> {
+    #[allow(deprecated)]
+    fn validate(&self) -> Result<()> {
+        validate_admin_or_role(
+            self.admin.key(),
+            self.factory.admin,
+            self.factory.key(),
+            &self.roles,
+            Role::Treasurer,
+        )?;
+        assert_keys_eq!(self.swap.factory, self.factory);
+
+        for (fees, token_info) in [
+            (&self.fees_0, &self.swap.token_0),
+            (&self.fees_1, &self.swap.token_1),
+        ] {
+            assert_keys_eq!(fees.mint, token_info.mint);
+            invariant!(fees.delegate.is_none());
+            invariant!(fees.close_authority.is_none());
+
+            // otherwise protocol fees would accrue to the LP holders
+            assert_keys_neq!(*fees, token_info.reserves);
+
+            if fees.owner != self.swap.key() {
+                invariant!(
+                    *self.treasury.owner == crate::ID,
+                    "admin fee accounts must be owned by the swap or the treasury"
+                );
+                let treasury: Account<FactoryTreasury> =
+                    Account::try_from(&self.treasury.to_account_info())?;
+                assert_keys_eq!(fees.owner, treasury.owner);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for CompoundAdminFees<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.token_program.key(), token::ID);
+        invariant!(!self.swap.is_in_progress, Reentrancy);
+        invariant!(
+            self.swap.is_auto_compound_enabled,
+            "auto-compounding is disabled"
+        );
+        assert_keys_eq!(self.pool_mint, self.swap.pool_mint);
+        assert_keys_eq!(self.reserve_0, self.swap.token_0.reserves);
+        assert_keys_eq!(self.reserve_1, self.swap.token_1.reserves);
+        assert_keys_eq!(self.fees_0, self.swap.token_0.admin_fees);
+        assert_keys_eq!(self.fees_1, self.swap.token_1.admin_fees);
+        assert_keys_eq!(self.fees_0.owner, self.swap);
+        assert_keys_eq!(self.fees_1.owner, self.swap);
+        assert_keys_eq!(self.protocol_lp.mint, self.swap.pool_mint);
+        assert_keys_eq!(self.protocol_lp.owner, self.swap.factory);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for NewMintListEntry<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        assert_keys_eq!(self.admin, self.factory.admin);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for CloseMintListEntry<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.admin, self.factory.admin);
+        assert_keys_eq!(self.mint_list_entry.factory, self.factory);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SetFeeExemption<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.admin, self.factory.admin);
+        assert_keys_eq!(self.fee_exemption.factory, self.factory);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for NewSignedSwapAuthority<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.system_program.key(), system_program::ID);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SwapWithSignature<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.token_program.key(), token::ID);
+        let owner = self.signed_swap_authority.owner;
+
+        // ensure no self-dealing
+        assert_keys_neq!(owner, self.swap);
+        validate_not_paused(&self.factory, &self.swap)?;
+        validate_swap_tokens(&self.swap, owner, &self.input, &self.output)?;
+
+        // the input tokens are transferred by the signed swap authority
+        assert_keys_eq!(self.input.user.owner, owner);
+        assert_keys_eq!(
+            self.input.user.delegate.unwrap_or_default(),
+            self.signed_swap_authority
+        );
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for Withdraw<'info> {
+    fn validate(&self) -> Result<()> {
+        self.user.validate_for_withdraw()?;
 
         assert_keys_eq!(self.pool_mint, self.user.swap.pool_mint);
         assert_keys_eq!(self.input_lp.mint, self.pool_mint);
 
+        // the destinations may have any owner: only their mints are checked,
+        // and that they are not the reserves or fees of the pool
         self.output_0.validate_for_swap(&self.user.swap.token_0)?;
         self.output_1.validate_for_swap(&self.user.swap.token_1)?;
 
@@ -98,6 +1001,44 @@ impl<'info> Validate<'info> for Withdraw<'info> {
     }
 }
 
+impl<'info> Validate<'info> for WithdrawOne<'info> {
+    fn validate(&self) -> Result<()> {
+        self.user.validate()?;
+
+        let swap_info = &self.user.swap;
+        assert_keys_eq!(self.pool_mint, swap_info.pool_mint);
+        assert_keys_eq!(self.input_lp.mint, self.pool_mint);
+
+        let (output_info, other_info) = if self.output.reserve.key() == swap_info.token_0.reserves {
+            (&swap_info.token_0, &swap_info.token_1)
+        } else {
+            (&swap_info.token_1, &swap_info.token_0)
+        };
+        self.output.validate_for_swap(output_info)?;
+        assert_keys_eq!(self.other_reserve, other_info.reserves);
+        assert_keys_eq!(self.other_fees, other_info.admin_fees);
+
+        // the other token is swapped into the output token
+        invariant!(
+            !swap_info.is_direction_paused(other_info.mint),
+            DirectionPaused
+        );
+
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for WithdrawToAta<'info> {
+    fn validate(&self) -> Result<()> {
+        self.withdraw.validate()?;
+        self.output_ata_0.validate()?;
+        self.output_ata_1.validate()?;
+        assert_keys_eq!(self.output_ata_0.account, self.withdraw.output_0.user);
+        assert_keys_eq!(self.output_ata_1.account, self.withdraw.output_1.user);
+        Ok(())
+    }
+}
+
 impl<'info> Validate<'info> for Deposit<'info> {
     fn validate(&self) -> Result<()> {
         self.user.validate()?;
@@ -117,6 +1058,43 @@ impl<'info> Validate<'info> for Deposit<'info> {
     }
 }
 
+impl<'info> Validate<'info> for DepositFor<'info> {
+    fn validate(&self) -> Result<()> {
+        self.deposit.validate()?;
+        assert_keys_eq!(self.deposit.output_lp.owner, self.beneficiary);
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for DepositSingle<'info> {
+    fn validate(&self) -> Result<()> {
+        self.user.validate()?;
+
+        let swap_info = &self.user.swap;
+        let (input_info, output_info) = if self.input.reserve.key() == swap_info.token_0.reserves {
+            (&swap_info.token_0, &swap_info.token_1)
+        } else {
+            (&swap_info.token_1, &swap_info.token_0)
+        };
+        self.input.validate_for_swap(input_info)?;
+        invariant!(
+            !swap_info.is_direction_paused(input_info.mint),
+            DirectionPaused
+        );
+        assert_keys_eq!(self.output_reserve, output_info.reserves);
+        assert_keys_eq!(self.output_fees, output_info.admin_fees);
+
+        // should be same as swap
+        assert_keys_eq!(self.pool_mint, swap_info.pool_mint);
+
+        // lp output destination
+        assert_keys_eq!(self.output_lp.mint, swap_info.pool_mint);
+        assert_keys_neq!(self.output_lp.owner, self.user.swap);
+
+        Ok(())
+    }
+}
+
 // --------------------------------
 // Account Structs
 // --------------------------------
@@ -147,6 +1125,162 @@ impl<'info> InitSwapToken<'info> {
     }
 }
 
+impl FeeExemption {
+    /// Validates that the [FeeExemption] applies to `user_authority` on the `swap_info`.
+    pub(crate) fn validate_for_swap(
+        &self,
+        swap_info: &SwapInfo,
+        user_authority: Pubkey,
+    ) -> Result<()> {
+        assert_keys_eq!(self.factory, swap_info.factory);
+        assert_keys_eq!(self.address, user_authority);
+        Ok(())
+    }
+}
+
+impl TimelockedChange {
+    /// Validates that the `target` of the change belongs to the `factory`
+    /// and that `admin` is the admin of the target.
+    pub(crate) fn validate_target<'info>(
+        &self,
+        factory: &Account<'info, Factory>,
+        target: &AccountInfo<'info>,
+        admin: Pubkey,
+    ) -> Result<()> {
+        if self.targets_swap() {
+            let swap: Account<SwapInfo> = Account::try_from(target)?;
+            assert_keys_eq!(swap.factory, factory.key());
+            assert_keys_eq!(admin, swap.admin_key);
+        } else {
+            assert_keys_eq!(target.key(), factory.key());
+            assert_keys_eq!(admin, factory.admin);
+        }
+        Ok(())
+    }
+}
+
+impl<'info> FactoryRoleContext<'info> {
+    /// Validates that the authority is the admin of the [Factory] or holds the `role`.
+    pub(crate) fn validate_role(&self, role: Role) -> Result<()> {
+        validate_admin_or_role(
+            self.authority.key(),
+            self.factory.admin,
+            self.factory.key(),
+            &self.roles,
+            role,
+        )
+    }
+}
+
+impl<'info> SwapRoleContext<'info> {
+    /// Validates that the authority is the admin of the [SwapInfo] or holds the `role`
+    /// in the [FactoryRoles] of its [Factory].
+    pub(crate) fn validate_role(&self, role: Role) -> Result<()> {
+        assert_keys_eq!(self.swap.factory, self.factory);
+        validate_admin_or_role(
+            self.authority.key(),
+            self.swap.admin_key,
+            self.swap.factory,
+            &self.roles,
+            role,
+        )
+    }
+}
+
+/// Validates that `authority` is the `admin`, or holds the `role` in the `roles`,
+/// which must be the [FactoryRoles] of the `factory` unless the authority is the admin.
+fn validate_admin_or_role(
+    authority: Pubkey,
+    admin: Pubkey,
+    factory: Pubkey,
+    roles: &AccountInfo,
+    role: Role,
+) -> Result<()> {
+    if authority == admin {
+        return Ok(());
+    }
+    invariant!(*roles.owner == crate::ID, MissingRole);
+    let roles: Account<FactoryRoles> = Account::try_from(roles)?;
+    assert_keys_eq!(roles.factory, factory);
+    invariant!(roles.has_role(authority, role), MissingRole);
+    Ok(())
+}
+
+impl<'info> SwapUserContext<'info> {
+    /// Validates the context of a withdrawal, which is allowed while the swap or its [Factory]
+    /// is paused so that LPs can always exit the pool.
+    pub(crate) fn validate_for_withdraw(&self) -> Result<()> {
+        assert_keys_eq!(self.token_program.key(), token::ID);
+        // ensure no self-dealing
+        assert_keys_neq!(self.user_authority, self.swap);
+        assert_keys_eq!(self.swap.factory, self.factory);
+        invariant!(!self.swap.is_in_progress, Reentrancy);
+        Ok(())
+    }
+
+    /// Validates the input and output tokens of a swap.
+    pub(crate) fn validate_swap_tokens(
+        &self,
+        input: &SwapTokenWithFees<'info>,
+        output: &SwapTokenWithFees<'info>,
+    ) -> Result<()> {
+        validate_swap_tokens(&self.swap, self.user_authority.key(), input, output)
+    }
+}
+
+/// Validates that `user_authority` is the owner or the delegate of the user's `input` token account.
+/// Delegates swap on behalf of the owner. Returns the owner.
+fn validate_input_authority(input: &TokenAccount, user_authority: Pubkey) -> Result<Pubkey> {
+    if input.owner != user_authority {
+        assert_keys_eq!(
+            input.delegate.unwrap_or_default(),
+            user_authority,
+            "user authority must be the owner or the delegate of the input"
+        );
+    }
+    Ok(input.owner)
+}
+
+/// Validates that neither the swap nor all of the swaps of its [Factory] are paused,
+/// and that the swap is not in progress.
+fn validate_not_paused<'info>(
+    factory: &Account<'info, Factory>,
+    swap_info: &Account<'info, SwapInfo>,
+) -> Result<()> {
+    assert_keys_eq!(swap_info.factory, factory.key());
+    invariant!(!factory.is_paused, FactoryPaused);
+    invariant!(!swap_info.is_paused, Paused);
+    invariant!(!swap_info.is_in_progress, Reentrancy);
+    Ok(())
+}
+
+/// Validates the input and output tokens of a swap performed by `user_authority`.
+fn validate_swap_tokens<'info>(
+    swap_info: &SwapInfo,
+    user_authority: Pubkey,
+    input: &SwapTokenWithFees<'info>,
+    output: &SwapTokenWithFees<'info>,
+) -> Result<()> {
+    // inner validation will ensure that token source mint equals respective reserve
+    let (swap_input, swap_output) = if input.reserve.key() == swap_info.token_0.reserves {
+        (&swap_info.token_0, &swap_info.token_1)
+    } else {
+        (&swap_info.token_1, &swap_info.token_0)
+    };
+
+    let owner = validate_input_authority(&input.user, user_authority)?;
+    assert_keys_eq!(output.user.owner, owner);
+
+    input.validate_for_swap(swap_input)?;
+    invariant!(
+        !swap_info.is_direction_paused(swap_input.mint),
+        DirectionPaused
+    );
+    output.validate_for_swap(swap_output)?;
+
+    Ok(())
+}
+
 impl<'info> SwapToken<'info> {
     fn validate_for_swap(&self, swap_info: &SwapTokenInfo) -> Result<()> {
         assert_keys_eq!(self.reserve, swap_info.reserves);
@@ -170,4 +1304,17 @@ impl<'info> SwapTokenWithFees<'info> {
         assert_keys_neq!(self.reserve, self.user);
         Ok(())
     }
+
+    /// Validates a token account which receives a share of the trade fee of this output token.
+    pub(crate) fn validate_fee_recipient(
+        &self,
+        recipient: &Account<'info, TokenAccount>,
+    ) -> Result<()> {
+        assert_keys_eq!(recipient.mint, self.reserve.mint);
+
+        // the fee must leave the pool
+        assert_keys_neq!(*recipient, self.reserve);
+        assert_keys_neq!(*recipient, self.fees);
+        Ok(())
+    }
 }
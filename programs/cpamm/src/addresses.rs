@@ -12,3 +12,23 @@ pub mod admin_account {
 
 /// Account authorized to manage swaps.
 pub static ADMIN_ACCOUNT: Pubkey = admin_account::ID;
+
+/// Wrapper module.
+pub mod native_mint {
+    use anchor_lang::declare_id;
+
+    declare_id!("So11111111111111111111111111111111111111112");
+}
+
+/// The [Mint](anchor_spl::token::Mint) of wrapped SOL.
+pub static NATIVE_MINT: Pubkey = native_mint::ID;
+
+/// Wrapper module.
+pub mod token_metadata_program {
+    use anchor_lang::declare_id;
+
+    declare_id!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+}
+
+/// The Metaplex Token Metadata program.
+pub static TOKEN_METADATA_PROGRAM: Pubkey = token_metadata_program::ID;
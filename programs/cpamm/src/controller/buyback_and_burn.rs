@@ -0,0 +1,78 @@
+//! [crate::cpamm::buyback_and_burn] instruction controller.
+
+use super::swap::{process_swap, SwapOptionalAccounts, SwapUser};
+use crate::*;
+use ::u128::mul_div_u64;
+use anchor_spl::token;
+
+/// Buyback and burn
+pub fn buyback_and_burn<'info>(
+    ctx: Context<'_, '_, '_, 'info, BuybackAndBurn<'info>>,
+    minimum_amount_out: u64,
+) -> Result<()> {
+    let accounts = &mut *ctx.accounts;
+    let amount_in = accounts.input.user.amount;
+    if amount_in == 0 {
+        return Ok(());
+    }
+
+    // the spot price of the swap less the maximum slippage bounds the price of the buyback,
+    // so the keeper cannot sell the fees at a worse price
+    let input_reserve = accounts.swap.reserve_amount(accounts.input.reserve.mint);
+    let output_reserve = accounts.swap.reserve_amount(accounts.output.reserve.mint);
+    let spot_amount_out = unwrap_int!(mul_div_u64(amount_in, output_reserve, input_reserve));
+    let slippage_amount_out = unwrap_int!(mul_div_u64(
+        spot_amount_out,
+        unwrap_int!(xyk::BPS_PER_WHOLE.checked_sub(accounts.config.max_slippage_bps.into())),
+        xyk::BPS_PER_WHOLE
+    ));
+    let minimum_amount_out = minimum_amount_out.max(slippage_amount_out);
+
+    // the fee swap signs for its admin fee account
+    let fee_swap = &accounts.fee_swap;
+    let seeds = gen_swap_signer_seeds!(fee_swap);
+    let signer_seeds = &[&seeds[..]];
+    let optional_accounts =
+        SwapOptionalAccounts::load_oracle(&accounts.swap, ctx.remaining_accounts);
+    let mut user = SwapUser {
+        token_program: &accounts.token_program,
+        user_authority: fee_swap.key(),
+        input_authority: fee_swap.to_account_info(),
+        input_authority_seeds: signer_seeds,
+        swap: &mut accounts.swap,
+    };
+    let return_data = process_swap(
+        &mut user,
+        &accounts.input,
+        &accounts.output,
+        &optional_accounts,
+        amount_in,
+        minimum_amount_out,
+        true,
+    )?;
+
+    // burn everything in the output account, including any leftovers of earlier buybacks
+    let amount_burned = token::accessor::amount(&accounts.output.user.to_account_info())?;
+    if amount_burned > 0 {
+        token::burn(
+            CpiContext::new_with_signer(
+                accounts.token_program.to_account_info(),
+                token::Burn {
+                    mint: accounts.buyback_mint.to_account_info(),
+                    to: accounts.output.user.to_account_info(),
+                    authority: fee_swap.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount_burned,
+        )?;
+    }
+
+    emit!(BuybackEvent {
+        fee_swap: fee_swap.key(),
+        swap: accounts.swap.key(),
+        amount_in: return_data.amount_in,
+        amount_burned,
+    });
+    Ok(())
+}
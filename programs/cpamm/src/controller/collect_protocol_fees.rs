@@ -0,0 +1,94 @@
+//! [crate::cpamm::collect_protocol_fees] instruction controller.
+
+use crate::*;
+use ::u128::mul_div_u64;
+use anchor_spl::token::{self, TokenAccount};
+
+/// Collect protocol fees
+pub fn collect_protocol_fees<'info>(
+    ctx: Context<'_, '_, '_, 'info, CollectProtocolFees<'info>>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let fee_distribution: Option<Account<FeeDistribution>> =
+        if *accounts.fee_distribution.owner == crate::ID {
+            Some(Account::try_from(
+                &accounts.fee_distribution.to_account_info(),
+            )?)
+        } else {
+            None
+        };
+
+    for (token_index, (fees, treasury)) in [
+        (&accounts.fees_0, &accounts.treasury_0),
+        (&accounts.fees_1, &accounts.treasury_1),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let mut treasury_amount = fees.amount;
+
+        // the recipients receive the token accounts of both tokens in order
+        if let Some(fee_distribution) = &fee_distribution {
+            for (recipient_index, recipient) in fee_distribution.active_recipients().enumerate() {
+                let info = unwrap_opt!(
+                    ctx.remaining_accounts
+                        .get(unwrap_int!(
+                            unwrap_int!(recipient_index.checked_mul(2)).checked_add(token_index)
+                        )),
+                    "missing fee recipient token account"
+                );
+                let destination: Account<TokenAccount> = Account::try_from(info)?;
+                assert_keys_eq!(destination.owner, recipient.owner);
+                assert_keys_eq!(destination.mint, fees.mint);
+
+                let share = unwrap_int!(mul_div_u64(
+                    fees.amount,
+                    recipient.share_bps.into(),
+                    xyk::BPS_PER_WHOLE
+                ));
+                accounts.transfer_fees(fees, destination.to_account_info(), share)?;
+                treasury_amount = unwrap_int!(treasury_amount.checked_sub(share));
+            }
+        }
+
+        // the treasury receives the rest, including any rounding
+        accounts.transfer_fees(fees, treasury.to_account_info(), treasury_amount)?;
+    }
+
+    emit!(FeesCollectedEvent {
+        swap: accounts.swap.key(),
+        treasury: accounts.treasury.owner,
+        amount_0: accounts.fees_0.amount,
+        amount_1: accounts.fees_1.amount,
+    });
+    Ok(())
+}
+
+impl<'info> CollectProtocolFees<'info> {
+    /// Transfers `amount` of the admin fees in the `fees` account to the `destination`.
+    fn transfer_fees(
+        &self,
+        fees: &Account<'info, TokenAccount>,
+        destination: AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let swap_info = &self.swap;
+        let seeds = gen_swap_signer_seeds!(swap_info);
+        let signer_seeds = &[&seeds[..]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                token::Transfer {
+                    from: fees.to_account_info(),
+                    to: destination,
+                    authority: swap_info.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+}
@@ -0,0 +1,122 @@
+//! [crate::cpamm::compound_admin_fees] instruction controller.
+
+use crate::*;
+use anchor_spl::token::{self, TokenAccount};
+
+/// Compound admin fees
+pub fn compound_admin_fees(ctx: Context<CompoundAdminFees>) -> Result<()> {
+    // update cumulative price info.
+    // must be called BEFORE mutation.
+    let (reserve_0, reserve_1) = ctx.accounts.swap.reserve_amounts();
+    ctx.accounts.swap.update_price_info()?;
+
+    let pool_mint_supply = ctx.accounts.pool_mint.supply;
+    invariant!(pool_mint_supply > 0, "pool_mint.supply cannot be 0");
+    let amount_0 = ctx.accounts.fees_0.amount;
+    let amount_1 = ctx.accounts.fees_1.amount;
+    if amount_0 == 0 && amount_1 == 0 {
+        return Ok(());
+    }
+
+    // the excess of one token is swapped into the other
+    let swap_info = &ctx.accounts.swap;
+    super::check_constant_product(swap_info)?;
+    let value_0 = unwrap_int!((amount_0 as u128).checked_mul(reserve_1.into()));
+    let value_1 = unwrap_int!((amount_1 as u128).checked_mul(reserve_0.into()));
+    if value_0 > value_1 {
+        invariant!(
+            !swap_info.is_direction_paused(swap_info.token_0.mint),
+            DirectionPaused
+        );
+    } else if value_1 > value_0 {
+        invariant!(
+            !swap_info.is_direction_paused(swap_info.token_1.mint),
+            DirectionPaused
+        );
+    }
+
+    // the trade fee stays in the pool
+    let pool_token_amount = unwrap_int!(xyk::imbalanced_deposit_pool_tokens(
+        amount_0,
+        amount_1,
+        reserve_0,
+        reserve_1,
+        pool_mint_supply,
+        swap_info.swap_fees().trade_fee_kbps,
+        fees::KBPS_PER_WHOLE,
+    ));
+    // the fees are too small to compound yet
+    if pool_token_amount == 0 {
+        return Ok(());
+    }
+
+    ctx.accounts
+        .transfer_fees_to_reserve(&ctx.accounts.fees_0, &ctx.accounts.reserve_0)?;
+    ctx.accounts
+        .transfer_fees_to_reserve(&ctx.accounts.fees_1, &ctx.accounts.reserve_1)?;
+    let accounts = &mut *ctx.accounts;
+    super::track_reserve(&mut accounts.swap, &accounts.reserve_0)?;
+    super::track_reserve(&mut accounts.swap, &accounts.reserve_1)?;
+
+    ctx.accounts.mint_lp_to_protocol(pool_token_amount)?;
+
+    let cumulative_stats = &mut ctx.accounts.swap.cumulative_stats;
+    cumulative_stats.total_lp_minted = unwrap_int!(cumulative_stats
+        .total_lp_minted
+        .checked_add(pool_token_amount.into()));
+
+    emit!(AdminFeesCompoundedEvent {
+        swap: ctx.accounts.swap.key(),
+        amount_0,
+        amount_1,
+        pool_token_amount,
+    });
+    Ok(())
+}
+
+impl<'info> CompoundAdminFees<'info> {
+    /// Transfers all of the admin fees in the `fees` account to the reserve of its token.
+    fn transfer_fees_to_reserve(
+        &self,
+        fees: &Account<'info, TokenAccount>,
+        reserve: &Account<'info, TokenAccount>,
+    ) -> Result<()> {
+        if fees.amount == 0 {
+            return Ok(());
+        }
+        let token_swap = &self.swap;
+        let seeds = gen_swap_signer_seeds!(token_swap);
+        let signer_seeds = &[&seeds[..]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                token::Transfer {
+                    from: fees.to_account_info(),
+                    to: reserve.to_account_info(),
+                    authority: token_swap.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            fees.amount,
+        )
+    }
+
+    /// Mints the LP tokens to the protocol-owned liquidity account.
+    fn mint_lp_to_protocol(&self, amount: u64) -> Result<()> {
+        let token_swap = &self.swap;
+        let seeds = gen_swap_signer_seeds!(token_swap);
+        let signer_seeds = &[&seeds[..]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                token::MintTo {
+                    mint: self.pool_mint.to_account_info(),
+                    to: self.protocol_lp.to_account_info(),
+                    authority: token_swap.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+}
@@ -5,6 +5,8 @@ use anchor_spl::token;
 
 /// Deposit
 pub fn deposit(ctx: Context<Deposit>, args: DepositArgs) -> Result<()> {
+    super::check_deadline(args.deadline)?;
+
     // update cumulative price info.
     // we call this before the short circuit
     // so the numbers are accurate.
@@ -14,28 +16,29 @@ pub fn deposit(ctx: Context<Deposit>, args: DepositArgs) -> Result<()> {
     invariant!(pool_mint_supply > 0, "pool_mint.supply cannot be 0");
     let pool_token_amount = args.pool_token_amount;
     if pool_token_amount == 0 {
-        return Ok(());
+        return super::write_return_data(&DepositReturnData::default());
     }
 
     invariant!(args.maximum_amount_in_0 > 0, "args.maximum_amount_in_a > 0");
     invariant!(args.maximum_amount_in_1 > 0, "args.maximum_amount_in_b > 0");
 
+    let (reserve_0, reserve_1) = ctx.accounts.user.swap.reserve_amounts();
     let result = unwrap_int!(xyk::pool_tokens_to_trading_tokens(
         pool_token_amount,
         pool_mint_supply,
-        ctx.accounts.input_0.reserve.amount,
-        ctx.accounts.input_1.reserve.amount,
-        xyk::RoundDirection::Ceiling,
+        reserve_0,
+        reserve_1,
+        xyk::rounding::POOL_INPUT,
     ));
 
     invariant!(
         result.token_a_amount <= args.maximum_amount_in_0,
-        ExceededSlippage
+        DepositAboveMaximumAmountIn
     );
     invariant!(result.token_a_amount > 0, InsufficientLiquidity);
     invariant!(
         result.token_b_amount <= args.maximum_amount_in_1,
-        ExceededSlippage
+        DepositAboveMaximumAmountIn
     );
     invariant!(result.token_b_amount > 0, InsufficientLiquidity);
 
@@ -45,14 +48,27 @@ pub fn deposit(ctx: Context<Deposit>, args: DepositArgs) -> Result<()> {
     ctx.accounts
         .transfer_user_to_reserve(&ctx.accounts.input_1, result.token_b_amount)?;
 
+    ctx.accounts.track_reserves()?;
+
     // Mint lp tokens to user
     ctx.accounts.mint_lp_to_user(pool_token_amount)?;
+    super::record_deposit(
+        &ctx.accounts.user.swap,
+        &ctx.accounts.user.token_program,
+        &ctx.accounts.pool_mint,
+        &ctx.accounts.output_lp,
+        ctx.remaining_accounts,
+    )?;
 
+    let (reserve_0, reserve_1) = ctx.accounts.user.swap.reserve_amounts();
     emit!(DepositEvent {
         lp_mint: ctx.accounts.user.swap.pool_mint,
         pool_token_amount,
         token_0_amount: result.token_a_amount,
         token_1_amount: result.token_b_amount,
+        swap: ctx.accounts.user.swap.key(),
+        reserve_0,
+        reserve_1,
     });
 
     ctx.accounts.track_cumulative_deposit_volume(
@@ -61,18 +77,27 @@ pub fn deposit(ctx: Context<Deposit>, args: DepositArgs) -> Result<()> {
         result.token_b_amount,
     )?;
 
-    Ok(())
+    super::write_return_data(&DepositReturnData {
+        pool_token_amount,
+        amount_in_0: result.token_a_amount,
+        amount_in_1: result.token_b_amount,
+    })
 }
 
 pub struct DepositArgs {
     pub pool_token_amount: u64,
     pub maximum_amount_in_0: u64,
     pub maximum_amount_in_1: u64,
+    pub deadline: Option<i64>,
 }
 
 impl<'info> Deposit<'info> {
     /// Transfers the user's swap tokens to the reserve.
-    fn transfer_user_to_reserve(&self, input: &SwapToken<'info>, amount: u64) -> Result<()> {
+    pub(super) fn transfer_user_to_reserve(
+        &self,
+        input: &SwapToken<'info>,
+        amount: u64,
+    ) -> Result<()> {
         let token_program = &self.user.token_program;
         let cpi_ctx = CpiContext::new(
             token_program.to_account_info().clone(),
@@ -85,9 +110,15 @@ impl<'info> Deposit<'info> {
         token::transfer(cpi_ctx, amount)
     }
 
-    /// Mints the LP tokens to the user.
-    fn mint_lp_to_user(&self, amount: u64) -> Result<()> {
+    /// Mints the LP tokens to the user, thawing the LP token account if a deposit froze it.
+    pub(super) fn mint_lp_to_user(&self, amount: u64) -> Result<()> {
         let token_swap = &self.user.swap;
+        super::thaw_lp_account(
+            token_swap,
+            &self.user.token_program,
+            &self.pool_mint,
+            &self.output_lp,
+        )?;
         let seeds = gen_swap_signer_seeds!(token_swap);
         let signer_seeds = &[&seeds[..]];
         token::mint_to(
@@ -104,15 +135,20 @@ impl<'info> Deposit<'info> {
         )
     }
 
-    fn update_cumulative_price_info(&mut self) -> Result<()> {
+    /// Tracks the tokens transferred to the reserves, which must not exceed the deposit cap.
+    pub(super) fn track_reserves(&mut self) -> Result<()> {
+        super::track_reserve(&mut self.user.swap, &self.input_0.reserve)?;
+        super::track_reserve(&mut self.user.swap, &self.input_1.reserve)?;
+        super::check_deposit_cap(&self.user.swap)
+    }
+
+    pub(super) fn update_cumulative_price_info(&mut self) -> Result<()> {
         // update price info
-        let price_info = &mut self.user.swap.price_info;
-        price_info
-            .update_cumulative_price_info(self.input_0.reserve.amount, self.input_1.reserve.amount)
+        self.user.swap.update_price_info()
     }
 
     /// Tracks and saves the cumulative deposit volume.
-    fn track_cumulative_deposit_volume(
+    pub(super) fn track_cumulative_deposit_volume(
         &mut self,
         pool_token_amount: u64,
         token_0_amount: u64,
@@ -0,0 +1,159 @@
+//! [crate::cpamm::deposit_imbalanced] instruction controller.
+
+use crate::*;
+
+pub struct DepositImbalancedArgs {
+    pub amount_in_0: u64,
+    pub amount_in_1: u64,
+    pub min_lp_out: u64,
+    pub deadline: Option<i64>,
+}
+
+/// Deposit imbalanced
+pub fn deposit_imbalanced(ctx: Context<Deposit>, args: DepositImbalancedArgs) -> Result<()> {
+    super::check_deadline(args.deadline)?;
+
+    // update cumulative price info.
+    // we call this before the short circuit
+    // so the numbers are accurate.
+    ctx.accounts.update_cumulative_price_info()?;
+
+    let pool_mint_supply = ctx.accounts.pool_mint.supply;
+    invariant!(pool_mint_supply > 0, "pool_mint.supply cannot be 0");
+    if args.amount_in_0 == 0 && args.amount_in_1 == 0 {
+        return super::write_return_data(&DepositReturnData::default());
+    }
+
+    let (reserve_0, reserve_1) = ctx.accounts.user.swap.reserve_amounts();
+
+    // Transfer user's tokens to the reserve.
+    if args.amount_in_0 > 0 {
+        ctx.accounts
+            .transfer_user_to_reserve(&ctx.accounts.input_0, args.amount_in_0)?;
+    }
+    if args.amount_in_1 > 0 {
+        ctx.accounts
+            .transfer_user_to_reserve(&ctx.accounts.input_1, args.amount_in_1)?;
+    }
+    let (amount_in_0, amount_in_1) = (args.amount_in_0, args.amount_in_1);
+
+    // the excess of one token is swapped into the other, subject to the limits of a swap
+    let swap_info = &ctx.accounts.user.swap;
+    super::check_constant_product(swap_info)?;
+    let deposit_swap = unwrap_int!(xyk::imbalanced_deposit_swap(
+        amount_in_0,
+        amount_in_1,
+        reserve_0,
+        reserve_1,
+        swap_info.swap_fees().trade_fee_kbps,
+        fees::KBPS_PER_WHOLE,
+    ));
+    let input_mint = if deposit_swap.is_token_a_swapped {
+        swap_info.token_0.mint
+    } else {
+        swap_info.token_1.mint
+    };
+    if deposit_swap.excess_amount > 0 {
+        invariant!(!swap_info.is_direction_paused(input_mint), DirectionPaused);
+    }
+    let swap_result = if deposit_swap.swap_amount == 0 {
+        None
+    } else {
+        swap_info.curve_swap(
+            input_mint,
+            deposit_swap.swap_amount,
+            deposit_swap.swap_source_amount,
+            deposit_swap.swap_destination_amount,
+        )
+    };
+    let trade_fee_kbps = match swap_result {
+        // too small to swap, so the excess remains in the pool
+        None => swap_info.swap_fees().trade_fee_kbps,
+        Some(swap_result) => {
+            super::check_swap_rate_limit(&mut ctx.accounts.user.swap)?;
+            let swap_info = &ctx.accounts.user.swap;
+            super::check_max_trade_size(
+                swap_info,
+                swap_result.source_amount_swapped,
+                deposit_swap.swap_source_amount,
+            )?;
+            super::check_price_impact(
+                swap_info,
+                &swap_result,
+                deposit_swap.swap_source_amount,
+                deposit_swap.swap_destination_amount,
+            )?;
+            super::check_oracle_band(
+                swap_info,
+                super::find_oracle(swap_info, ctx.remaining_accounts),
+                input_mint,
+                &swap_result,
+            )?;
+            unwrap_int!(swap_info.swap_fees_for_trade(
+                input_mint,
+                swap_result.source_amount_swapped,
+                deposit_swap.swap_source_amount
+            ))
+            .trade_fee_kbps
+        }
+    };
+
+    // the trade fee stays in the pool
+    let pool_token_amount = unwrap_int!(xyk::imbalanced_deposit_pool_tokens(
+        amount_in_0,
+        amount_in_1,
+        reserve_0,
+        reserve_1,
+        pool_mint_supply,
+        trade_fee_kbps,
+        fees::KBPS_PER_WHOLE,
+    ));
+    invariant!(pool_token_amount > 0, InsufficientLiquidity);
+    invariant!(
+        pool_token_amount >= args.min_lp_out,
+        DepositBelowMinimumLpOut
+    );
+
+    ctx.accounts.track_reserves()?;
+    if swap_result.is_some() {
+        let swap_info = &mut ctx.accounts.user.swap;
+        let new_reserves = swap_info.reserve_amounts();
+        let (old_reserves, new_reserves) = if deposit_swap.is_token_a_swapped {
+            ((reserve_0, reserve_1), new_reserves)
+        } else {
+            ((reserve_1, reserve_0), (new_reserves.1, new_reserves.0))
+        };
+        super::update_volatility(swap_info, input_mint, old_reserves, new_reserves)?;
+        super::update_circuit_breaker(swap_info)?;
+    }
+
+    // Mint lp tokens to user
+    ctx.accounts.mint_lp_to_user(pool_token_amount)?;
+    super::record_deposit(
+        &ctx.accounts.user.swap,
+        &ctx.accounts.user.token_program,
+        &ctx.accounts.pool_mint,
+        &ctx.accounts.output_lp,
+        ctx.remaining_accounts,
+    )?;
+
+    let (reserve_0, reserve_1) = ctx.accounts.user.swap.reserve_amounts();
+    emit!(DepositEvent {
+        lp_mint: ctx.accounts.user.swap.pool_mint,
+        pool_token_amount,
+        token_0_amount: amount_in_0,
+        token_1_amount: amount_in_1,
+        swap: ctx.accounts.user.swap.key(),
+        reserve_0,
+        reserve_1,
+    });
+
+    ctx.accounts
+        .track_cumulative_deposit_volume(pool_token_amount, amount_in_0, amount_in_1)?;
+
+    super::write_return_data(&DepositReturnData {
+        pool_token_amount,
+        amount_in_0,
+        amount_in_1,
+    })
+}
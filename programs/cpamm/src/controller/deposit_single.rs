@@ -0,0 +1,291 @@
+//! [crate::cpamm::deposit_single] instruction controller.
+
+use crate::*;
+use anchor_spl::token;
+
+pub struct DepositSingleArgs {
+    pub amount_in: u64,
+    pub minimum_pool_token_amount: u64,
+    pub deadline: Option<i64>,
+}
+
+/// Deposit single
+pub fn deposit_single(ctx: Context<DepositSingle>, args: DepositSingleArgs) -> Result<()> {
+    super::check_deadline(args.deadline)?;
+
+    // update cumulative price info.
+    // must be called BEFORE mutation.
+    ctx.accounts.update_cumulative_price_info()?;
+
+    let pool_mint_supply = ctx.accounts.pool_mint.supply;
+    invariant!(pool_mint_supply > 0, "pool_mint.supply cannot be 0");
+    if args.amount_in == 0 {
+        return super::write_return_data(&DepositReturnData::default());
+    }
+
+    // Transfer user's tokens to the reserve.
+    ctx.accounts.transfer_user_to_reserve(args.amount_in)?;
+    let amount_in = args.amount_in;
+
+    let swap_info = &ctx.accounts.user.swap;
+    super::check_constant_product(swap_info)?;
+    let input_reserve = swap_info.reserve_amount(ctx.accounts.input.reserve.mint);
+    let output_reserve = swap_info.reserve_amount(ctx.accounts.output_reserve.mint);
+
+    // swap the portion of the input which balances the deposit
+    let swap_amount = unwrap_int!(xyk::single_sided_swap_amount(
+        amount_in,
+        input_reserve,
+        ctx.accounts.user.swap.swap_fees().trade_fee_kbps,
+        fees::KBPS_PER_WHOLE,
+    ));
+    let (swap_result, trade_fee, admin_trade_fee) = if swap_amount == 0 {
+        (
+            xyk::SwapResult {
+                source_amount_swapped: 0,
+                destination_amount_swapped: 0,
+            },
+            0,
+            0,
+        )
+    } else {
+        super::check_swap_rate_limit(&mut ctx.accounts.user.swap)?;
+        let swap_info = &ctx.accounts.user.swap;
+        let swap_result = unwrap_int!(swap_info.curve_swap(
+            ctx.accounts.input.reserve.mint,
+            swap_amount,
+            input_reserve,
+            output_reserve
+        ));
+        let (trade_fee, admin_trade_fee) = unwrap_int!(unwrap_int!(swap_info.swap_fees_for_trade(
+            ctx.accounts.input.reserve.mint,
+            swap_result.source_amount_swapped,
+            input_reserve
+        ))
+        .compute_trade_fees(swap_result.destination_amount_swapped));
+        super::check_max_trade_size(swap_info, swap_result.source_amount_swapped, input_reserve)?;
+        super::check_price_impact(swap_info, &swap_result, input_reserve, output_reserve)?;
+        super::check_oracle_band(
+            swap_info,
+            super::find_oracle(swap_info, ctx.remaining_accounts),
+            ctx.accounts.input.reserve.mint,
+            &swap_result,
+        )?;
+        (swap_result, trade_fee, admin_trade_fee)
+    };
+
+    // the reserves after the swap, with the non-admin trade fee remaining in the pool
+    let swapped_input_reserve =
+        unwrap_int!(input_reserve.checked_add(swap_result.source_amount_swapped));
+    let swapped_output_reserve = unwrap_int!(unwrap_int!(
+        output_reserve.checked_sub(swap_result.destination_amount_swapped)
+    )
+    .checked_add(unwrap_int!(trade_fee.checked_sub(admin_trade_fee))));
+    invariant!(swapped_output_reserve > 0, InsufficientLiquidity);
+
+    // the swap proceeds and the rest of the input are deposited.
+    // any excess of either side remains in the pool.
+    let deposit_input_amount =
+        unwrap_int!(amount_in.checked_sub(swap_result.source_amount_swapped));
+    let deposit_output_amount = unwrap_int!(swap_result
+        .destination_amount_swapped
+        .checked_sub(trade_fee));
+    let pool_token_amount = unwrap_int!(xyk::rounding::mul_div(
+        pool_mint_supply,
+        deposit_input_amount,
+        swapped_input_reserve,
+        xyk::rounding::POOL_OUTPUT
+    ))
+    .min(unwrap_int!(xyk::rounding::mul_div(
+        pool_mint_supply,
+        deposit_output_amount,
+        swapped_output_reserve,
+        xyk::rounding::POOL_OUTPUT
+    )));
+    invariant!(pool_token_amount > 0, InsufficientLiquidity);
+    invariant!(
+        pool_token_amount >= args.minimum_pool_token_amount,
+        DepositBelowMinimumLpOut
+    );
+
+    ctx.accounts.transfer_admin_fees(admin_trade_fee)?;
+
+    // Mint lp tokens to user
+    ctx.accounts.mint_lp_to_user(pool_token_amount)?;
+
+    let accounts = &mut *ctx.accounts;
+    super::track_reserve(&mut accounts.user.swap, &accounts.input.reserve)?;
+    super::track_reserve(&mut accounts.user.swap, &accounts.output_reserve)?;
+    super::check_deposit_cap(&accounts.user.swap)?;
+    super::record_deposit(
+        &accounts.user.swap,
+        &accounts.user.token_program,
+        &accounts.pool_mint,
+        &accounts.output_lp,
+        ctx.remaining_accounts,
+    )?;
+    let new_reserves = (
+        accounts
+            .user
+            .swap
+            .reserve_amount(accounts.input.reserve.mint),
+        accounts
+            .user
+            .swap
+            .reserve_amount(accounts.output_reserve.mint),
+    );
+    let (token_0_amount, token_1_amount) = ctx.accounts.to_token_amounts(amount_in, 0);
+    let (reserve_0, reserve_1) = ctx
+        .accounts
+        .to_token_amounts(new_reserves.0, new_reserves.1);
+    emit!(DepositEvent {
+        lp_mint: ctx.accounts.user.swap.pool_mint,
+        pool_token_amount,
+        token_0_amount,
+        token_1_amount,
+        swap: ctx.accounts.user.swap.key(),
+        reserve_0,
+        reserve_1,
+    });
+
+    let input_mint = ctx.accounts.input.reserve.mint;
+    super::update_volatility(
+        &mut ctx.accounts.user.swap,
+        input_mint,
+        (input_reserve, output_reserve),
+        new_reserves,
+    )?;
+    super::update_circuit_breaker(&mut ctx.accounts.user.swap)?;
+
+    ctx.accounts
+        .track_cumulative_volume(pool_token_amount, amount_in, &swap_result, trade_fee)?;
+    let output_mint = ctx.accounts.output_reserve.mint;
+    unwrap_int!(ctx
+        .accounts
+        .user
+        .swap
+        .track_fees_paid_out(output_mint, admin_trade_fee));
+
+    super::write_return_data(&DepositReturnData {
+        pool_token_amount,
+        amount_in_0: token_0_amount,
+        amount_in_1: token_1_amount,
+    })
+}
+
+impl<'info> DepositSingle<'info> {
+    /// Returns true if the input token is token 0 of the swap.
+    fn is_input_token_0(&self) -> bool {
+        self.input.reserve.mint == self.user.swap.token_0.mint
+    }
+
+    /// Orders amounts of the input and output tokens as token 0 and token 1 amounts.
+    fn to_token_amounts(&self, input_amount: u64, output_amount: u64) -> (u64, u64) {
+        if self.is_input_token_0() {
+            (input_amount, output_amount)
+        } else {
+            (output_amount, input_amount)
+        }
+    }
+
+    fn update_cumulative_price_info(&mut self) -> Result<()> {
+        // update price info
+        self.user.swap.update_price_info()
+    }
+
+    /// Transfers the user's input tokens to the reserve.
+    fn transfer_user_to_reserve(&self, amount: u64) -> Result<()> {
+        token::transfer(
+            CpiContext::new(
+                self.user.token_program.to_account_info(),
+                token::Transfer {
+                    from: self.input.user.to_account_info(),
+                    to: self.input.reserve.to_account_info(),
+                    authority: self.user.user_authority.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+
+    /// Transfers the admin trade fee of the swap from the output reserve to the fees account.
+    fn transfer_admin_fees(&self, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let token_swap = &self.user.swap;
+        let seeds = gen_swap_signer_seeds!(token_swap);
+        let signer_seeds = &[&seeds[..]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.user.token_program.to_account_info(),
+                token::Transfer {
+                    from: self.output_reserve.to_account_info(),
+                    to: self.output_fees.to_account_info(),
+                    authority: token_swap.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+
+    /// Mints the LP tokens to the user, thawing the LP token account if a deposit froze it.
+    fn mint_lp_to_user(&self, amount: u64) -> Result<()> {
+        let token_swap = &self.user.swap;
+        super::thaw_lp_account(
+            token_swap,
+            &self.user.token_program,
+            &self.pool_mint,
+            &self.output_lp,
+        )?;
+        let seeds = gen_swap_signer_seeds!(token_swap);
+        let signer_seeds = &[&seeds[..]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                self.user.token_program.to_account_info(),
+                token::MintTo {
+                    mint: self.pool_mint.to_account_info(),
+                    to: self.output_lp.to_account_info(),
+                    authority: token_swap.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+
+    /// Tracks and saves the cumulative deposit volume and the volume of the internal swap.
+    fn track_cumulative_volume(
+        &mut self,
+        pool_token_amount: u64,
+        amount_in: u64,
+        swap_result: &xyk::SwapResult,
+        trade_fee: u64,
+    ) -> Result<()> {
+        // record cumulative volume numbers
+        let is_input_token_0 = self.is_input_token_0();
+        let cumulative_stats = &mut self.user.swap.cumulative_stats;
+        cumulative_stats.total_lp_minted = unwrap_int!(cumulative_stats
+            .total_lp_minted
+            .checked_add(pool_token_amount.into()));
+
+        let (cum_input_token, cum_output_token) = if is_input_token_0 {
+            (&mut cumulative_stats.token_0, &mut cumulative_stats.token_1)
+        } else {
+            (&mut cumulative_stats.token_1, &mut cumulative_stats.token_0)
+        };
+        cum_input_token.total_deposit_volume = unwrap_int!(cum_input_token
+            .total_deposit_volume
+            .checked_add(amount_in.into()));
+        cum_input_token.total_input_volume = unwrap_int!(cum_input_token
+            .total_input_volume
+            .checked_add(swap_result.source_amount_swapped.into()));
+        cum_output_token.total_output_volume = unwrap_int!(cum_output_token
+            .total_output_volume
+            .checked_add(swap_result.destination_amount_swapped.into()));
+        cum_output_token.total_trade_fees =
+            unwrap_int!(cum_output_token.total_trade_fees.checked_add(trade_fee));
+        Ok(())
+    }
+}
@@ -0,0 +1,213 @@
+//! [crate::cpamm::deposit_sol] instruction controller.
+
+use super::swap_sol::WrappedSol;
+use crate::*;
+use anchor_spl::token::{self, TokenAccount};
+
+pub struct DepositSolArgs {
+    pub pool_token_amount: u64,
+    pub maximum_amount_in_0: u64,
+    pub maximum_amount_in_1: u64,
+    pub deadline: Option<i64>,
+}
+
+/// Deposit SOL
+pub fn deposit_sol(ctx: Context<DepositSol>, args: DepositSolArgs) -> Result<()> {
+    super::check_deadline(args.deadline)?;
+
+    // update cumulative price info.
+    // we call this before the short circuit
+    // so the numbers are accurate.
+    ctx.accounts.update_cumulative_price_info()?;
+
+    let pool_mint_supply = ctx.accounts.pool_mint.supply;
+    invariant!(pool_mint_supply > 0, "pool_mint.supply cannot be 0");
+    let pool_token_amount = args.pool_token_amount;
+    if pool_token_amount == 0 {
+        return super::write_return_data(&DepositReturnData::default());
+    }
+
+    let (reserve_0, reserve_1) = ctx.accounts.user.swap.reserve_amounts();
+    let result = unwrap_int!(xyk::pool_tokens_to_trading_tokens(
+        pool_token_amount,
+        pool_mint_supply,
+        reserve_0,
+        reserve_1,
+        xyk::rounding::POOL_INPUT,
+    ));
+
+    invariant!(
+        result.token_a_amount <= args.maximum_amount_in_0,
+        DepositAboveMaximumAmountIn
+    );
+    invariant!(result.token_a_amount > 0, InsufficientLiquidity);
+    invariant!(
+        result.token_b_amount <= args.maximum_amount_in_1,
+        DepositAboveMaximumAmountIn
+    );
+    invariant!(result.token_b_amount > 0, InsufficientLiquidity);
+
+    let (sol_amount, token_amount) = ctx
+        .accounts
+        .to_token_amounts(result.token_a_amount, result.token_b_amount);
+
+    // Wrap the SOL being deposited. Wrapped SOL accounts hold their balance as lamports,
+    // so the amount is deposited together with the rent of the account.
+    let bump = unwrap_bump!(ctx, "wrapped_sol");
+    let accounts = &ctx.accounts;
+    let rent_exempt_lamports = accounts.rent.minimum_balance(TokenAccount::LEN);
+    accounts.wrapped_sol().create(
+        unwrap_int!(rent_exempt_lamports.checked_add(sol_amount)),
+        bump,
+    )?;
+
+    // Transfer user's tokens to the reserve.
+    accounts.transfer_user_to_reserve(
+        accounts.wrapped_sol.to_account_info(),
+        &accounts.sol_reserve,
+        sol_amount,
+    )?;
+    accounts.transfer_user_to_reserve(
+        accounts.token.user.to_account_info(),
+        &accounts.token.reserve,
+        token_amount,
+    )?;
+
+    accounts.wrapped_sol().close()?;
+
+    // Mint lp tokens to user
+    accounts.mint_lp_to_user(pool_token_amount)?;
+
+    let accounts = &mut *ctx.accounts;
+    super::track_reserve(&mut accounts.user.swap, &accounts.sol_reserve)?;
+    super::track_reserve(&mut accounts.user.swap, &accounts.token.reserve)?;
+    super::check_deposit_cap(&accounts.user.swap)?;
+    super::record_deposit(
+        &accounts.user.swap,
+        &accounts.user.token_program,
+        &accounts.pool_mint,
+        &accounts.output_lp,
+        ctx.remaining_accounts,
+    )?;
+    let (reserve_0, reserve_1) = accounts.user.swap.reserve_amounts();
+    emit!(DepositEvent {
+        lp_mint: ctx.accounts.user.swap.pool_mint,
+        pool_token_amount,
+        token_0_amount: result.token_a_amount,
+        token_1_amount: result.token_b_amount,
+        swap: ctx.accounts.user.swap.key(),
+        reserve_0,
+        reserve_1,
+    });
+
+    ctx.accounts.track_cumulative_deposit_volume(
+        pool_token_amount,
+        result.token_a_amount,
+        result.token_b_amount,
+    )?;
+
+    super::write_return_data(&DepositReturnData {
+        pool_token_amount,
+        amount_in_0: result.token_a_amount,
+        amount_in_1: result.token_b_amount,
+    })
+}
+
+impl<'info> DepositSol<'info> {
+    /// Orders amounts of SOL and the other token as token 0 and token 1 amounts.
+    /// The conversion is symmetric, so it also orders token amounts as SOL and token amounts.
+    fn to_token_amounts(&self, sol_amount: u64, token_amount: u64) -> (u64, u64) {
+        if self.user.swap.token_0.mint == addresses::NATIVE_MINT {
+            (sol_amount, token_amount)
+        } else {
+            (token_amount, sol_amount)
+        }
+    }
+
+    /// Accounts of the temporary wrapped SOL account of the user.
+    fn wrapped_sol(&self) -> WrappedSol<'_, 'info> {
+        WrappedSol {
+            token_program: &self.user.token_program,
+            swap: self.user.swap.key(),
+            user_authority: &self.user.user_authority,
+            wrapped_sol: &self.wrapped_sol,
+            native_mint: &self.native_mint,
+            payer: &self.payer,
+            system_program: &self.system_program,
+            rent: &self.rent,
+        }
+    }
+
+    /// Transfers the user's tokens to the reserve.
+    fn transfer_user_to_reserve(
+        &self,
+        from: AccountInfo<'info>,
+        reserve: &Account<'info, TokenAccount>,
+        amount: u64,
+    ) -> Result<()> {
+        token::transfer(
+            CpiContext::new(
+                self.user.token_program.to_account_info(),
+                token::Transfer {
+                    from,
+                    to: reserve.to_account_info(),
+                    authority: self.user.user_authority.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+
+    /// Mints the LP tokens to the user, thawing the LP token account if a deposit froze it.
+    fn mint_lp_to_user(&self, amount: u64) -> Result<()> {
+        let token_swap = &self.user.swap;
+        super::thaw_lp_account(
+            token_swap,
+            &self.user.token_program,
+            &self.pool_mint,
+            &self.output_lp,
+        )?;
+        let seeds = gen_swap_signer_seeds!(token_swap);
+        let signer_seeds = &[&seeds[..]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                self.user.token_program.to_account_info(),
+                token::MintTo {
+                    mint: self.pool_mint.to_account_info(),
+                    to: self.output_lp.to_account_info(),
+                    authority: token_swap.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+
+    fn update_cumulative_price_info(&mut self) -> Result<()> {
+        // update price info
+        self.user.swap.update_price_info()
+    }
+
+    /// Tracks and saves the cumulative deposit volume.
+    fn track_cumulative_deposit_volume(
+        &mut self,
+        pool_token_amount: u64,
+        token_0_amount: u64,
+        token_1_amount: u64,
+    ) -> Result<()> {
+        // record cumulative volume numbers
+        let cumulative_stats = &mut self.user.swap.cumulative_stats;
+        cumulative_stats.total_lp_minted = unwrap_int!(cumulative_stats
+            .total_lp_minted
+            .checked_add(pool_token_amount.into()));
+        cumulative_stats.token_0.total_deposit_volume = unwrap_int!(cumulative_stats
+            .token_0
+            .total_deposit_volume
+            .checked_add(token_0_amount.into()));
+        cumulative_stats.token_1.total_deposit_volume = unwrap_int!(cumulative_stats
+            .token_1
+            .total_deposit_volume
+            .checked_add(token_1_amount.into()));
+        Ok(())
+    }
+}
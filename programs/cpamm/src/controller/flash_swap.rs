@@ -0,0 +1,261 @@
+//! [crate::cpamm::flash_swap] instruction controller.
+
+use crate::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+};
+use anchor_spl::token::{self, TokenAccount};
+
+pub struct FlashSwapArgs {
+    pub amount_out_0: u64,
+    pub amount_out_1: u64,
+    pub data: Vec<u8>,
+}
+
+/// Flash swap
+pub fn flash_swap<'info>(
+    ctx: Context<'_, '_, '_, 'info, FlashSwap<'info>>,
+    args: FlashSwapArgs,
+) -> Result<()> {
+    // update cumulative price info.
+    // must be called BEFORE mutation.
+    ctx.accounts.update_cumulative_price_info()?;
+
+    if args.amount_out_0 == 0 && args.amount_out_1 == 0 {
+        // skip the callback if nothing is being borrowed
+        return Ok(());
+    }
+    super::check_swap_rate_limit(&mut ctx.accounts.user.swap)?;
+
+    let (reserve_0, reserve_1) = ctx.accounts.user.swap.reserve_amounts();
+    let old_balance_0 = ctx.accounts.token_0.reserve.amount;
+    let old_balance_1 = ctx.accounts.token_1.reserve.amount;
+    invariant!(args.amount_out_0 < reserve_0, InsufficientLiquidity);
+    invariant!(args.amount_out_1 < reserve_1, InsufficientLiquidity);
+
+    // the swap is locked until the callback returns
+    super::lock_swap(&mut ctx.accounts.user.swap)?;
+
+    // Optimistically transfer the borrowed tokens.
+    ctx.accounts
+        .transfer_from_reserve(&ctx.accounts.token_0, args.amount_out_0)?;
+    ctx.accounts
+        .transfer_from_reserve(&ctx.accounts.token_1, args.amount_out_1)?;
+
+    // Invoke the callback. The swap does not sign this instruction,
+    // and the runtime does not allow the callback to re-enter this program.
+    let callback_accounts = ctx.remaining_accounts;
+    let callback_ix = Instruction {
+        program_id: ctx.accounts.callback_program.key(),
+        accounts: callback_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data: args.data,
+    };
+    let mut callback_account_infos = callback_accounts.to_vec();
+    callback_account_infos.push(ctx.accounts.callback_program.to_account_info());
+    invoke(&callback_ix, &callback_account_infos)?;
+    super::unlock_swap(&mut ctx.accounts.user.swap);
+
+    // Check that the reserves were repaid.
+    ctx.accounts.token_0.reserve.reload()?;
+    ctx.accounts.token_1.reserve.reload()?;
+    let balance_0 = ctx.accounts.token_0.reserve.amount;
+    let balance_1 = ctx.accounts.token_1.reserve.amount;
+
+    let amount_in_0 =
+        balance_0.saturating_sub(unwrap_int!(old_balance_0.checked_sub(args.amount_out_0)));
+    let amount_in_1 =
+        balance_1.saturating_sub(unwrap_int!(old_balance_1.checked_sub(args.amount_out_1)));
+
+    // tokens sent to the reserves before the flash swap do not count towards the repayment
+    let swap_info = &mut ctx.accounts.user.swap;
+    unwrap_int!(swap_info.track_reserve_balance(
+        ctx.accounts.token_0.reserve.mint,
+        old_balance_0,
+        balance_0
+    ));
+    unwrap_int!(swap_info.track_reserve_balance(
+        ctx.accounts.token_1.reserve.mint,
+        old_balance_1,
+        balance_1
+    ));
+    let (repaid_reserve_0, repaid_reserve_1) = swap_info.reserve_amounts();
+
+    // trade fees are charged on the tokens returned to the reserves as on swaps of them
+    let swap_fees_0 =
+        unwrap_int!(swap_info.swap_fees_for_trade(swap_info.token_0.mint, amount_in_0, reserve_0));
+    let swap_fees_1 =
+        unwrap_int!(swap_info.swap_fees_for_trade(swap_info.token_1.mint, amount_in_1, reserve_1));
+    let (trade_fee_0, admin_trade_fee_0) = unwrap_int!(swap_fees_0.compute_trade_fees(amount_in_0));
+    let (trade_fee_1, admin_trade_fee_1) = unwrap_int!(swap_fees_1.compute_trade_fees(amount_in_1));
+
+    let adjusted_balance_0: u128 = unwrap_int!(repaid_reserve_0.checked_sub(trade_fee_0)).into();
+    let adjusted_balance_1: u128 = unwrap_int!(repaid_reserve_1.checked_sub(trade_fee_1)).into();
+    invariant!(
+        unwrap_int!(adjusted_balance_0.checked_mul(adjusted_balance_1))
+            >= unwrap_int!((reserve_0 as u128).checked_mul(reserve_1.into())),
+        InsufficientFlashSwapRepayment
+    );
+    // the net trade of the flash swap is subject to the same limits as a swap
+    check_net_trade(
+        &ctx.accounts.user.swap,
+        super::find_oracle(&ctx.accounts.user.swap, ctx.remaining_accounts),
+        (reserve_0, reserve_1),
+        (repaid_reserve_0, repaid_reserve_1),
+    )?;
+
+    // Transfer the admin's share of the trade fees to the fees accounts.
+    ctx.accounts
+        .transfer_admin_fees(&ctx.accounts.token_0, admin_trade_fee_0)?;
+    ctx.accounts
+        .transfer_admin_fees(&ctx.accounts.token_1, admin_trade_fee_1)?;
+    let accounts = &mut *ctx.accounts;
+    super::track_reserve(&mut accounts.user.swap, &accounts.token_0.reserve)?;
+    super::track_reserve(&mut accounts.user.swap, &accounts.token_1.reserve)?;
+    super::check_invariant(
+        &accounts.user.swap,
+        accounts.user.swap.token_0.mint,
+        (reserve_0, reserve_1),
+        accounts.user.swap.reserve_amounts(),
+    )?;
+    super::update_circuit_breaker(&mut accounts.user.swap)?;
+
+    emit!(FlashSwapEvent {
+        lp_mint: ctx.accounts.user.swap.pool_mint,
+        callback_program: ctx.accounts.callback_program.key(),
+        amount_out_0: args.amount_out_0,
+        amount_out_1: args.amount_out_1,
+        amount_in_0,
+        amount_in_1,
+    });
+
+    let cumulative_stats = &mut ctx.accounts.user.swap.cumulative_stats;
+    track_cumulative_token_volume(
+        &mut cumulative_stats.token_0,
+        amount_in_0,
+        args.amount_out_0,
+        trade_fee_0,
+    )?;
+    track_cumulative_token_volume(
+        &mut cumulative_stats.token_1,
+        amount_in_1,
+        args.amount_out_1,
+        trade_fee_1,
+    )?;
+    let swap_info = &mut ctx.accounts.user.swap;
+    let (mint_0, mint_1) = (swap_info.token_0.mint, swap_info.token_1.mint);
+    unwrap_int!(swap_info.track_fees_paid_out(mint_0, admin_trade_fee_0));
+    unwrap_int!(swap_info.track_fees_paid_out(mint_1, admin_trade_fee_1));
+
+    Ok(())
+}
+
+/// Ensures that the net trade of a flash swap, which is the change of the reserves from
+/// `reserves` to `repaid_reserves`, passes the limits of the swap on the size, price impact
+/// and oracle price of trades.
+fn check_net_trade(
+    swap_info: &SwapInfo,
+    oracle: Option<&AccountInfo>,
+    reserves: (u64, u64),
+    repaid_reserves: (u64, u64),
+) -> Result<()> {
+    let ((reserve_0, reserve_1), (repaid_reserve_0, repaid_reserve_1)) =
+        (reserves, repaid_reserves);
+    let (input_mint, input_reserve, output_reserve, swap_result) = if repaid_reserve_1 < reserve_1 {
+        (
+            swap_info.token_0.mint,
+            reserve_0,
+            reserve_1,
+            xyk::SwapResult {
+                source_amount_swapped: repaid_reserve_0.saturating_sub(reserve_0),
+                destination_amount_swapped: unwrap_int!(reserve_1.checked_sub(repaid_reserve_1)),
+            },
+        )
+    } else if repaid_reserve_0 < reserve_0 {
+        (
+            swap_info.token_1.mint,
+            reserve_1,
+            reserve_0,
+            xyk::SwapResult {
+                source_amount_swapped: repaid_reserve_1.saturating_sub(reserve_1),
+                destination_amount_swapped: unwrap_int!(reserve_0.checked_sub(repaid_reserve_0)),
+            },
+        )
+    } else {
+        // the borrowed tokens were repaid in kind, so nothing was traded
+        return Ok(());
+    };
+
+    super::check_max_trade_size(swap_info, swap_result.source_amount_swapped, input_reserve)?;
+    super::check_price_impact(swap_info, &swap_result, input_reserve, output_reserve)?;
+    super::check_oracle_band(swap_info, oracle, input_mint, &swap_result)
+}
+
+/// Tracks the cumulative volume of one of the tokens of the flash swap.
+fn track_cumulative_token_volume(
+    token_stats: &mut SwapCumulativeTokenStats,
+    amount_in: u64,
+    amount_out: u64,
+    trade_fee: u64,
+) -> Result<()> {
+    token_stats.total_input_volume =
+        unwrap_int!(token_stats.total_input_volume.checked_add(amount_in.into()));
+    token_stats.total_output_volume = unwrap_int!(token_stats
+        .total_output_volume
+        .checked_add(amount_out.into()));
+    token_stats.total_trade_fees = unwrap_int!(token_stats.total_trade_fees.checked_add(trade_fee));
+    Ok(())
+}
+
+impl<'info> FlashSwap<'info> {
+    fn update_cumulative_price_info(&mut self) -> Result<()> {
+        // update price info
+        self.user.swap.update_price_info()
+    }
+
+    /// Transfers tokens from the reserve to the user.
+    fn transfer_from_reserve(&self, token: &SwapTokenWithFees<'info>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        self.transfer_signed(token, &token.user, amount)
+    }
+
+    /// Transfers the admin trade fees from the reserve to the fees account.
+    fn transfer_admin_fees(&self, token: &SwapTokenWithFees<'info>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        self.transfer_signed(token, &token.fees, amount)
+    }
+
+    fn transfer_signed(
+        &self,
+        token: &SwapTokenWithFees<'info>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+    ) -> Result<()> {
+        let token_swap = &self.user.swap;
+        let seeds = gen_swap_signer_seeds!(token_swap);
+        let signer_seeds = &[&seeds[..]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.user.token_program.to_account_info(),
+                token::Transfer {
+                    from: token.reserve.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: token_swap.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+}
@@ -0,0 +1,43 @@
+//! [crate::cpamm::increase_observation_cardinality] instruction controller.
+
+use crate::*;
+use anchor_lang::solana_program::{program::invoke, system_instruction};
+
+/// Increase observation cardinality
+pub fn increase_observation_cardinality(
+    ctx: Context<IncreaseObservationCardinality>,
+    cardinality_next: u16,
+) -> Result<()> {
+    let observations = ctx.accounts.observations.to_account_info();
+    if cardinality_next <= ctx.accounts.observations.load()?.cardinality_next {
+        // there is already room for the observations
+        return Ok(());
+    }
+
+    // grow the account, zeroing the new observations
+    let new_len = observations::observations_space(cardinality_next);
+    let rent = Rent::get()?;
+    let required_lamports = rent
+        .minimum_balance(new_len)
+        .saturating_sub(observations.lamports());
+    if required_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(
+                &ctx.accounts.payer.key(),
+                observations.key,
+                required_lamports,
+            ),
+            &[
+                ctx.accounts.payer.to_account_info(),
+                observations.clone(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+    }
+    observations.realloc(new_len, true)?;
+
+    let mut data = observations.try_borrow_mut_data()?;
+    let (header, _) = observations::split_observations_mut(&mut data)?;
+    header.cardinality_next = cardinality_next;
+    Ok(())
+}
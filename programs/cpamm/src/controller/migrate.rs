@@ -0,0 +1,96 @@
+//! [crate::cpamm::migrate_pool], [crate::cpamm::migrate_factory] and
+//! [crate::cpamm::migrate_swap_meta] instruction controllers.
+
+use crate::*;
+use anchor_lang::solana_program::{program::invoke, system_instruction};
+use anchor_lang::Discriminator;
+
+/// Migrate pool
+pub fn migrate_pool(ctx: Context<MigratePool>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let old_version =
+        migrate::<SwapInfo>(&accounts.swap, &accounts.payer, &accounts.system_program)?;
+
+    // version 0 did not track the reserves, so they start at the reserve balances
+    let swap = accounts.swap.to_account_info();
+    let mut swap_info: Account<SwapInfo> = Account::try_from(&swap)?;
+    assert_keys_eq!(accounts.reserve_0, swap_info.token_0.reserves);
+    assert_keys_eq!(accounts.reserve_1, swap_info.token_1.reserves);
+    if old_version == 0 {
+        swap_info.reserve_0_amount = accounts.reserve_0.amount;
+        swap_info.reserve_1_amount = accounts.reserve_1.amount;
+        swap_info.exit(&crate::ID)?;
+    }
+    Ok(())
+}
+
+/// Migrate factory
+pub fn migrate_factory(ctx: Context<MigrateFactory>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    migrate::<Factory>(&accounts.factory, &accounts.payer, &accounts.system_program)?;
+    Ok(())
+}
+
+/// Migrate swap meta
+pub fn migrate_swap_meta(ctx: Context<MigrateSwapMeta>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    migrate::<SwapMeta>(
+        &accounts.swap_meta,
+        &accounts.payer,
+        &accounts.system_program,
+    )?;
+    Ok(())
+}
+
+/// Upgrades an account created by an older version of the program to [Versioned::VERSION],
+/// growing it to the current size of `T` if needed. The payer pays for the additional rent.
+/// Returns the version of the account before the upgrade.
+fn migrate<'info, T>(
+    account: &UncheckedAccount<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<u8>
+where
+    T: AccountSerialize
+        + AccountDeserialize
+        + AnchorSerialize
+        + Discriminator
+        + Default
+        + Versioned,
+{
+    let account = account.to_account_info();
+    let new_len = unwrap_int!(T::default()
+        .try_to_vec()?
+        .len()
+        .checked_add(T::discriminator().len()));
+
+    // grow the account, zeroing the fields appended since the account was created
+    let old_len = account.data_len();
+    if old_len < new_len {
+        let rent = Rent::get()?;
+        let required_lamports = rent
+            .minimum_balance(new_len)
+            .saturating_sub(account.lamports());
+        if required_lamports > 0 {
+            invoke(
+                &system_instruction::transfer(&payer.key(), account.key, required_lamports),
+                &[
+                    payer.to_account_info(),
+                    account.clone(),
+                    system_program.to_account_info(),
+                ],
+            )?;
+        }
+        account.realloc(new_len, true)?;
+    }
+
+    let mut state = T::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+    invariant!(
+        state.version() < T::VERSION,
+        "account is already at the latest version"
+    );
+    let old_version = state.version();
+    state.upgrade();
+    state.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+    Ok(old_version)
+}
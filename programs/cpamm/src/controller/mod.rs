@@ -1,6 +1,423 @@
 //! Processes instructions.
 #![deny(clippy::integer_arithmetic, clippy::float_arithmetic)]
 
+use crate::*;
+use anchor_lang::solana_program::program::set_return_data;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+pub(crate) mod buyback_and_burn;
+pub(crate) mod collect_protocol_fees;
+pub(crate) mod compound_admin_fees;
 pub(crate) mod deposit;
+pub(crate) mod deposit_imbalanced;
+pub(crate) mod deposit_single;
+pub(crate) mod deposit_sol;
+pub(crate) mod flash_swap;
+pub(crate) mod increase_observation_cardinality;
+pub(crate) mod migrate;
+pub(crate) mod observe;
+pub(crate) mod reveal_swap;
 pub(crate) mod swap;
+pub(crate) mod swap_batch;
+pub(crate) mod swap_exact_out;
+pub(crate) mod swap_route;
+pub(crate) mod swap_sol;
+pub(crate) mod swap_with_signature;
+pub(crate) mod sweep_lamports;
+pub(crate) mod timelock;
+pub(crate) mod update_pair_price;
+pub(crate) mod verify_pool;
 pub(crate) mod withdraw;
+pub(crate) mod withdraw_one;
+
+/// Locks the swap before invoking another program, writing the lock to the account
+/// so that any nested instruction on the swap sees it.
+fn lock_swap(swap_info: &mut Account<SwapInfo>) -> Result<()> {
+    invariant!(!swap_info.is_in_progress, Reentrancy);
+    swap_info.is_in_progress = true;
+    swap_info.exit(&crate::ID)
+}
+
+/// Unlocks the swap locked by [lock_swap]. The lock is cleared when the account is written
+/// at the end of the instruction.
+fn unlock_swap(swap_info: &mut Account<SwapInfo>) {
+    swap_info.is_in_progress = false;
+}
+
+/// Ensures that the input of a swap does not exceed the maximum trade size of the swap.
+fn check_max_trade_size(swap_info: &SwapInfo, amount_in: u64, input_reserve: u64) -> Result<()> {
+    let max_trade_bps = swap_info.max_trade_bps_of_reserve;
+    if max_trade_bps != 0 {
+        invariant!(
+            unwrap_int!((amount_in as u128).checked_mul(xyk::BPS_PER_WHOLE.into()))
+                <= unwrap_int!((input_reserve as u128).checked_mul(max_trade_bps.into())),
+            TradeTooLarge
+        );
+    }
+    Ok(())
+}
+
+/// Ensures that the price impact of a swap does not exceed the maximum price impact of the swap.
+/// The spot price is that of the constant product curve, so stable swaps are not checked.
+fn check_price_impact(
+    swap_info: &SwapInfo,
+    swap_result: &xyk::SwapResult,
+    input_reserve: u64,
+    output_reserve: u64,
+) -> Result<()> {
+    let max_price_impact_bps = swap_info.max_price_impact_bps;
+    if max_price_impact_bps != 0 && swap_info.is_constant_product() {
+        let price_impact_bps = unwrap_int!(xyk::price_impact_bps(
+            swap_result.source_amount_swapped,
+            swap_result.destination_amount_swapped,
+            input_reserve,
+            output_reserve
+        ));
+        invariant!(
+            price_impact_bps <= max_price_impact_bps.into(),
+            PriceImpactTooHigh
+        );
+    }
+    Ok(())
+}
+
+/// Returns the oracle of the swap among the `remaining_accounts`, if the swap has an oracle band.
+fn find_oracle<'a, 'info>(
+    swap_info: &SwapInfo,
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> Option<&'a AccountInfo<'info>> {
+    if swap_info.oracle == Pubkey::default() {
+        return None;
+    }
+    remaining_accounts
+        .iter()
+        .find(|info| *info.key == swap_info.oracle)
+}
+
+/// Ensures that the execution price of a swap is within the oracle band of the swap,
+/// if it has one. For more information, view [crate::oracle].
+fn check_oracle_band(
+    swap_info: &SwapInfo,
+    oracle: Option<&AccountInfo>,
+    input_mint: Pubkey,
+    swap_result: &xyk::SwapResult,
+) -> Result<()> {
+    if swap_info.oracle == Pubkey::default() {
+        return Ok(());
+    }
+    let oracle = unwrap_opt!(oracle, "missing oracle");
+    assert_keys_eq!(*oracle.key, swap_info.oracle);
+    let oracle_price = unwrap_opt!(
+        oracle::pyth_price(
+            &oracle.try_borrow_data()?,
+            swap_info.oracle_decimals_adjustment,
+            swap_info.is_oracle_inverted
+        ),
+        "oracle price is not trading"
+    );
+    let execution_price = unwrap_int!(oracle::execution_price(swap_info, input_mint, swap_result));
+    let deviation_bps = unwrap_int!(oracle::deviation_bps(execution_price, oracle_price));
+    invariant!(
+        deviation_bps <= swap_info.oracle_band_bps.into(),
+        OraclePriceDeviation
+    );
+    Ok(())
+}
+
+/// Updates the volatility of the swap with the price change caused by a swap.
+/// The volatility is only tracked while the dynamic trade fee is enabled.
+fn update_volatility(
+    swap_info: &mut SwapInfo,
+    input_mint: Pubkey,
+    (old_input_reserve, old_output_reserve): (u64, u64),
+    (new_input_reserve, new_output_reserve): (u64, u64),
+) -> Result<()> {
+    if swap_info.dynamic_fee_kbps_per_volatility_bps == 0 {
+        return Ok(());
+    }
+
+    // prices are measured in token 1 per token 0 in both directions
+    let price_change_bps = unwrap_int!(if input_mint == swap_info.token_0.mint {
+        xyk::price_change_bps(
+            old_input_reserve,
+            old_output_reserve,
+            new_input_reserve,
+            new_output_reserve,
+        )
+    } else {
+        xyk::price_change_bps(
+            old_output_reserve,
+            old_input_reserve,
+            new_output_reserve,
+            new_input_reserve,
+        )
+    });
+
+    // exponentially weighted moving average of the price changes
+    let weight_bps: u128 = swap_info.volatility_weight_bps.into();
+    let previous_weight_bps = unwrap_int!(u128::from(xyk::BPS_PER_WHOLE).checked_sub(weight_bps));
+    let latest = unwrap_int!(u128::from(price_change_bps).checked_mul(weight_bps));
+    let previous =
+        unwrap_int!(u128::from(swap_info.volatility_bps).checked_mul(previous_weight_bps));
+    let volatility_bps = unwrap_int!(
+        unwrap_int!(latest.checked_add(previous)).checked_div(xyk::BPS_PER_WHOLE.into())
+    );
+    swap_info.volatility_bps = unwrap_int!(volatility_bps.try_into().ok());
+    Ok(())
+}
+
+/// Ensures that the invariant of the curve of the swap did not decrease from the old to
+/// the new reserves. This holds independently of the fee math.
+/// The first reserve of each pair is of the token with the mint `mint_a`.
+fn check_invariant(
+    swap_info: &SwapInfo,
+    mint_a: Pubkey,
+    old_reserves: (u64, u64),
+    new_reserves: (u64, u64),
+) -> Result<()> {
+    invariant!(
+        unwrap_int!(swap_info.is_curve_invariant_preserved(mint_a, old_reserves, new_reserves)),
+        InvariantViolated
+    );
+    Ok(())
+}
+
+/// Ensures that the swap uses the constant product curve, for instructions whose math
+/// is specific to it.
+fn check_constant_product(swap_info: &SwapInfo) -> Result<()> {
+    invariant!(swap_info.is_constant_product(), CurveNotSupported);
+    Ok(())
+}
+
+/// Counts a swap against the maximum number of swaps per slot of the swap,
+/// and ensures that the circuit breaker of the swap is not blocking swaps.
+/// Only swaps which trade a non-zero amount are counted, so this is called once the
+/// amounts of the swap are known, before the reserves change.
+fn check_swap_rate_limit(swap_info: &mut SwapInfo) -> Result<()> {
+    let slot = Clock::get()?.slot;
+    invariant!(
+        slot >= swap_info.circuit_breaker_until_slot,
+        CircuitBreakerTripped
+    );
+    if slot != swap_info.last_swap_slot {
+        swap_info.last_swap_slot = slot;
+        swap_info.swaps_in_slot = 0;
+        (
+            swap_info.slot_start_reserve_0,
+            swap_info.slot_start_reserve_1,
+        ) = swap_info.reserve_amounts();
+    }
+    swap_info.swaps_in_slot = unwrap_int!(swap_info.swaps_in_slot.checked_add(1));
+
+    let max_swaps_per_slot = swap_info.max_swaps_per_slot;
+    invariant!(
+        max_swaps_per_slot == 0 || swap_info.swaps_in_slot <= max_swaps_per_slot,
+        SwapRateLimited
+    );
+    Ok(())
+}
+
+/// Trips the circuit breaker of the swap if either reserve changed by more than the maximum
+/// reserve change per slot since the first swap of the slot. The swap which trips it succeeds,
+/// but [check_swap_rate_limit] blocks further swaps until the cooldown has elapsed.
+fn update_circuit_breaker(swap_info: &mut SwapInfo) -> Result<()> {
+    let max_change_bps = swap_info.max_reserve_change_bps_per_slot;
+    let slot = Clock::get()?.slot;
+    // the reserves at the start of the slot are only known once a swap has counted against it
+    if max_change_bps == 0 || slot != swap_info.last_swap_slot {
+        return Ok(());
+    }
+
+    let (reserve_0, reserve_1) = swap_info.reserve_amounts();
+    let is_exceeded_0 = unwrap_int!(is_reserve_change_exceeded(
+        swap_info.slot_start_reserve_0,
+        reserve_0,
+        max_change_bps
+    ));
+    let is_exceeded_1 = unwrap_int!(is_reserve_change_exceeded(
+        swap_info.slot_start_reserve_1,
+        reserve_1,
+        max_change_bps
+    ));
+    if is_exceeded_0 || is_exceeded_1 {
+        swap_info.circuit_breaker_until_slot = unwrap_int!(unwrap_int!(slot.checked_add(1))
+            .checked_add(swap_info.circuit_breaker_cooldown_slots.into()));
+        emit!(CircuitBreakerTrippedEvent {
+            lp_mint: swap_info.pool_mint,
+            reserve_0,
+            reserve_1,
+            until_slot: swap_info.circuit_breaker_until_slot,
+        });
+    }
+    Ok(())
+}
+
+/// Returns true if the reserve changed from `start` to `end` by more than `max_change_bps`
+/// of `start`. An empty starting reserve is never exceeded.
+fn is_reserve_change_exceeded(start: u64, end: u64, max_change_bps: u16) -> Option<bool> {
+    if start == 0 {
+        return Some(false);
+    }
+    let change = end.max(start).checked_sub(end.min(start))?;
+    Some(
+        (change as u128).checked_mul(xyk::BPS_PER_WHOLE.into())?
+            > (start as u128).checked_mul(max_change_bps.into())?,
+    )
+}
+
+/// Ensures that a delegate transferring the user's `input` tokens was approved for `amount`.
+fn check_delegated_amount(
+    input: &TokenAccount,
+    input_authority: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    if input.owner != input_authority {
+        invariant!(
+            input.delegated_amount >= amount,
+            InsufficientDelegatedAmount
+        );
+    }
+    Ok(())
+}
+
+/// Adds the change of the balance of the `reserve` during the instruction to
+/// the amount of its token in the pool.
+/// The `reserve` must hold its balance from the start of the instruction.
+fn track_reserve(swap_info: &mut SwapInfo, reserve: &Account<TokenAccount>) -> Result<()> {
+    let new_balance = token::accessor::amount(&reserve.to_account_info())?;
+    unwrap_int!(swap_info.track_reserve_balance(reserve.mint, reserve.amount, new_balance));
+    Ok(())
+}
+
+/// Ensures that a deposit did not increase the reserves of the swap above their caps.
+fn check_deposit_cap(swap_info: &SwapInfo) -> Result<()> {
+    let (reserve_0, reserve_1) = swap_info.reserve_amounts();
+    invariant!(
+        swap_info.max_reserve_0 == 0 || reserve_0 <= swap_info.max_reserve_0,
+        DepositCapExceeded
+    );
+    invariant!(
+        swap_info.max_reserve_1 == 0 || reserve_1 <= swap_info.max_reserve_1,
+        DepositCapExceeded
+    );
+    Ok(())
+}
+
+/// Loads the [DepositCooldown] of the LP token account, which is the first remaining account,
+/// if the swap has a withdraw cooldown.
+fn load_deposit_cooldown<'info>(
+    swap_info: &SwapInfo,
+    swap: Pubkey,
+    lp_account: Pubkey,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<Option<Account<'info, DepositCooldown>>> {
+    if swap_info.withdraw_cooldown_slots == 0 {
+        return Ok(None);
+    }
+    let info = unwrap_opt!(
+        remaining_accounts.first(),
+        "the deposit cooldown of the LP token account is required"
+    );
+    let cooldown: Account<DepositCooldown> = Account::try_from(info)?;
+    assert_keys_eq!(cooldown.swap, swap);
+    assert_keys_eq!(cooldown.lp_account, lp_account);
+    Ok(Some(cooldown))
+}
+
+/// Thaws an LP token account frozen by [record_deposit],
+/// so that LP tokens can be minted to or burned from it.
+pub(crate) fn thaw_lp_account<'info>(
+    swap: &Account<'info, SwapInfo>,
+    token_program: &Program<'info, Token>,
+    pool_mint: &Account<'info, Mint>,
+    lp_account: &Account<'info, TokenAccount>,
+) -> Result<()> {
+    if !lp_account.is_frozen() {
+        return Ok(());
+    }
+    let seeds = gen_swap_signer_seeds!(swap);
+    let signer_seeds = &[&seeds[..]];
+    token::thaw_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        token::ThawAccount {
+            account: lp_account.to_account_info(),
+            mint: pool_mint.to_account_info(),
+            authority: swap.to_account_info(),
+        },
+        signer_seeds,
+    ))
+}
+
+/// Starts the withdraw cooldown of the LP token account the LP tokens were minted to,
+/// if the swap has a withdraw cooldown.
+///
+/// The LP token account is frozen until the cooldown has passed, so that the LP tokens
+/// cannot be moved to another token account to withdraw them sooner.
+fn record_deposit<'info, 'remaining>(
+    swap: &Account<'info, SwapInfo>,
+    token_program: &Program<'info, Token>,
+    pool_mint: &Account<'info, Mint>,
+    lp_account: &Account<'info, TokenAccount>,
+    remaining_accounts: &[AccountInfo<'remaining>],
+) -> Result<()> {
+    if let Some(mut cooldown) =
+        load_deposit_cooldown(swap, swap.key(), lp_account.key(), remaining_accounts)?
+    {
+        cooldown.last_deposit_slot = Clock::get()?.slot;
+        cooldown.exit(&crate::ID)?;
+
+        let seeds = gen_swap_signer_seeds!(swap);
+        let signer_seeds = &[&seeds[..]];
+        token::freeze_account(CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token::FreezeAccount {
+                account: lp_account.to_account_info(),
+                mint: pool_mint.to_account_info(),
+                authority: swap.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+    }
+    Ok(())
+}
+
+/// Ensures that the withdraw cooldown of the LP token account has passed, if the swap has
+/// a withdraw cooldown, and thaws the LP token account so that the LP tokens can be burned.
+fn check_withdraw_cooldown<'info, 'remaining>(
+    swap: &Account<'info, SwapInfo>,
+    token_program: &Program<'info, Token>,
+    pool_mint: &Account<'info, Mint>,
+    lp_account: &Account<'info, TokenAccount>,
+    remaining_accounts: &[AccountInfo<'remaining>],
+) -> Result<()> {
+    if let Some(cooldown) =
+        load_deposit_cooldown(swap, swap.key(), lp_account.key(), remaining_accounts)?
+    {
+        check_cooldown_passed(swap, &cooldown)?;
+    }
+    thaw_lp_account(swap, token_program, pool_mint, lp_account)
+}
+
+/// Ensures that the withdraw cooldown of the swap has passed since the latest deposit
+/// recorded by the [DepositCooldown].
+pub(crate) fn check_cooldown_passed(swap: &SwapInfo, cooldown: &DepositCooldown) -> Result<()> {
+    let end_slot = unwrap_int!(cooldown
+        .last_deposit_slot
+        .checked_add(swap.withdraw_cooldown_slots.into()));
+    invariant!(Clock::get()?.slot >= end_slot, WithdrawCooldownActive);
+    Ok(())
+}
+
+/// Sets the Borsh-encoded `data` as the return data of the instruction.
+pub(crate) fn write_return_data<T: AnchorSerialize>(data: &T) -> Result<()> {
+    set_return_data(&data.try_to_vec()?);
+    Ok(())
+}
+
+/// Ensures that the instruction is executed before its deadline, if one was provided.
+fn check_deadline(deadline: Option<i64>) -> Result<()> {
+    if let Some(deadline) = deadline {
+        invariant!(Clock::get()?.unix_timestamp <= deadline, DeadlineExceeded);
+    }
+    Ok(())
+}
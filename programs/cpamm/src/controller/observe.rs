@@ -0,0 +1,57 @@
+//! [crate::cpamm::observe] instruction controller.
+
+use crate::*;
+
+/// Observe
+pub fn observe(ctx: Context<Observe>, seconds_ago: u32) -> Result<()> {
+    invariant!(seconds_ago > 0, "window must be at least one second");
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    let start_ts = unwrap_int!(now.checked_sub(seconds_ago.into()));
+
+    let current = Observation::of_swap(&ctx.accounts.swap, &clock)?;
+    let observations = ctx.accounts.observations.to_account_info();
+    let data = observations.try_borrow_data()?;
+    let (header, entries) = observations::split_observations(&data)?;
+    let start = header.observation_at(entries, current, start_ts)?;
+    let (price_0_low, price_0_high, num_observations) =
+        header.price_0_range_since(entries, current, start_ts)?;
+    let latest_observation_ts = unwrap_opt!(
+        entries.get(usize::from(header.index)),
+        "missing observation"
+    )
+    .timestamp;
+
+    // the cumulative prices may wrap around
+    let elapsed = u128::from(seconds_ago);
+    let twap = |end: u128, start: u128| end.wrapping_sub(start).checked_div(elapsed);
+    let price_0_twap = unwrap_int!(twap(current.price_0_cumulative, start.price_0_cumulative));
+    let price_0_width_bps = if price_0_twap == 0 {
+        0
+    } else {
+        unwrap_int!(price_0_high
+            .saturating_sub(price_0_low)
+            .checked_mul(xyk::BPS_PER_WHOLE.into())
+            .and_then(|width| width.checked_div(price_0_twap))
+            .map(|width| u64::try_from(width).unwrap_or(u64::MAX)))
+    };
+    super::write_return_data(&SwapTwap {
+        start_ts,
+        end_ts: now,
+        price_0_twap,
+        price_1_twap: unwrap_int!(twap(current.price_1_cumulative, start.price_1_cumulative)),
+        median_price_0_twap: unwrap_int!(twap(
+            current.median_price_0_cumulative,
+            start.median_price_0_cumulative
+        )),
+        median_price_1_twap: unwrap_int!(twap(
+            current.median_price_1_cumulative,
+            start.median_price_1_cumulative
+        )),
+        price_0_low,
+        price_0_high,
+        price_0_width_bps,
+        num_observations,
+        latest_observation_ts,
+    })
+}
@@ -0,0 +1,51 @@
+//! [crate::cpamm::reveal_swap] instruction controller.
+
+use super::swap::{process_swap, SwapOptionalAccounts, SwapUser};
+use crate::*;
+
+pub struct RevealSwapArgs {
+    pub amount_in: u64,
+    pub minimum_amount_out: u64,
+    pub salt: [u8; 32],
+}
+
+/// Reveal swap
+pub fn reveal_swap<'info>(
+    ctx: Context<'_, '_, '_, 'info, RevealSwap<'info>>,
+    args: RevealSwapArgs,
+) -> Result<()> {
+    let accounts = &mut *ctx.accounts;
+
+    // the swap cannot be executed in the slot it was committed in,
+    // so it cannot be sandwiched by a bundle built around the commitment
+    invariant!(
+        Clock::get()?.slot > accounts.commitment.slot,
+        SwapRevealedInCommitSlot
+    );
+    invariant!(
+        SwapCommitment::hash_swap(
+            accounts.swap.input.user.key(),
+            accounts.swap.output.user.key(),
+            args.amount_in,
+            args.minimum_amount_out,
+            args.salt,
+        ) == accounts.commitment.commitment,
+        SwapCommitmentMismatch
+    );
+
+    let swap = &mut accounts.swap;
+    let mut user = SwapUser::from(&mut swap.user);
+    let optional_accounts =
+        SwapOptionalAccounts::load(ctx.remaining_accounts, &user, &swap.output)?;
+
+    let return_data = process_swap(
+        &mut user,
+        &swap.input,
+        &swap.output,
+        &optional_accounts,
+        args.amount_in,
+        args.minimum_amount_out,
+        false,
+    )?;
+    super::write_return_data(&return_data)
+}
@@ -1,133 +1,495 @@
 //! [crate::cpamm::swap] instruction controller.
 
 use crate::*;
-use anchor_spl::token;
+use ::u128::mul_div_u64;
+use anchor_spl::token::{self, Token, TokenAccount};
 use xyk::SwapResult;
 
 pub struct SwapArgs {
     pub amount_in: u64,
     pub minimum_amount_out: u64,
+    pub deadline: Option<i64>,
+    pub allow_partial_fill: bool,
 }
 
 /// Swap
-pub fn swap(ctx: Context<Swap>, args: SwapArgs) -> Result<()> {
+pub fn swap<'info>(ctx: Context<'_, '_, '_, 'info, Swap<'info>>, args: SwapArgs) -> Result<()> {
+    super::check_deadline(args.deadline)?;
+
+    let accounts = &mut *ctx.accounts;
+    let mut user = SwapUser::from(&mut accounts.user);
+    let optional_accounts =
+        SwapOptionalAccounts::load(ctx.remaining_accounts, &user, &accounts.output)?;
+
+    let return_data = process_swap(
+        &mut user,
+        &accounts.input,
+        &accounts.output,
+        &optional_accounts,
+        args.amount_in,
+        args.minimum_amount_out,
+        args.allow_partial_fill,
+    )?;
+    super::write_return_data(&return_data)
+}
+
+/// The user performing a swap.
+pub(super) struct SwapUser<'a, 'info> {
+    /// The [Token] program.
+    pub(super) token_program: &'a Program<'info, Token>,
+    /// The [SwapInfo].
+    pub(super) swap: &'a mut Account<'info, SwapInfo>,
+    /// The authority of the user, on whose behalf the swap is performed.
+    pub(super) user_authority: Pubkey,
+    /// Signs the transfer of the input tokens.
+    /// This is the owner or a delegate of the user's input token account.
+    pub(super) input_authority: AccountInfo<'info>,
+    /// Seeds with which the program signs as the `input_authority`, if it is a program address.
+    pub(super) input_authority_seeds: &'a [&'a [&'a [u8]]],
+}
+
+impl<'a, 'info> From<&'a mut SwapUserContext<'info>> for SwapUser<'a, 'info> {
+    fn from(user: &'a mut SwapUserContext<'info>) -> Self {
+        Self {
+            token_program: &user.token_program,
+            user_authority: user.user_authority.key(),
+            input_authority: user.user_authority.to_account_info(),
+            input_authority_seeds: &[],
+            swap: &mut user.swap,
+        }
+    }
+}
+
+/// Optional accounts of a swap, passed as remaining accounts in the order
+/// `[referrer, host_fee_account, fee_exemption, creator_fee_account, registered_referrer,
+/// holder_discount_config, discount_token_account, volume_rebate_config, trader_stats]`.
+/// An account is skipped if it is omitted or if the program ID is passed in its place.
+#[derive(Default)]
+pub(super) struct SwapOptionalAccounts<'info> {
+    /// Token account of the output token which receives the referral fee.
+    referrer: Option<Account<'info, TokenAccount>>,
+    /// Token account of the output token which receives the host fee.
+    host_fee_account: Option<Account<'info, TokenAccount>>,
+    /// [FeeExemption] of the user.
+    fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Token account of the output token owned by the creator of the swap,
+    /// which receives the creator fee.
+    creator_fee_account: Option<Account<'info, TokenAccount>>,
+    /// [Referrer] which owns the referrer token account and records the referral.
+    registered_referrer: Option<Account<'info, Referrer>>,
+    /// [HolderDiscountConfig] of the [Factory] of the swap.
+    holder_discount_config: Option<Account<'info, HolderDiscountConfig>>,
+    /// Token account of the discount mint owned by the user authority.
+    discount_token_account: Option<Account<'info, TokenAccount>>,
+    /// [VolumeRebateConfig] of the [Factory] of the swap.
+    volume_rebate_config: Option<Account<'info, VolumeRebateConfig>>,
+    /// [TraderStats] of the user authority, which tracks the volume of the swap.
+    trader_stats: Option<Account<'info, TraderStats>>,
+    /// Oracle of the oracle band of the swap, passed anywhere in the remaining accounts.
+    oracle: Option<AccountInfo<'info>>,
+}
+
+impl<'info> SwapOptionalAccounts<'info> {
+    /// Loads and validates the optional accounts from the remaining accounts of a swap.
+    pub(super) fn load(
+        remaining_accounts: &[AccountInfo<'info>],
+        user: &SwapUser<'_, 'info>,
+        output: &SwapTokenWithFees<'info>,
+    ) -> Result<Self> {
+        let referrer = load_optional_account::<TokenAccount>(remaining_accounts, 0)?;
+        if let Some(referrer) = &referrer {
+            output.validate_fee_recipient(referrer)?;
+        }
+        let host_fee_account = load_optional_account::<TokenAccount>(remaining_accounts, 1)?;
+        if let Some(host_fee_account) = &host_fee_account {
+            output.validate_fee_recipient(host_fee_account)?;
+        }
+        let fee_exemption = load_optional_account::<FeeExemption>(remaining_accounts, 2)?;
+        if let Some(fee_exemption) = &fee_exemption {
+            fee_exemption.validate_for_swap(user.swap, user.user_authority)?;
+        }
+        let creator_fee_account = load_optional_account::<TokenAccount>(remaining_accounts, 3)?;
+        if let Some(creator_fee_account) = &creator_fee_account {
+            output.validate_fee_recipient(creator_fee_account)?;
+            assert_keys_eq!(creator_fee_account.owner, user.swap.creator);
+        }
+        let registered_referrer = load_optional_account::<Referrer>(remaining_accounts, 4)?;
+        if let Some(registered_referrer) = &registered_referrer {
+            let referrer = unwrap_opt!(referrer.as_ref(), "missing referrer token account");
+            assert_keys_eq!(referrer.owner, registered_referrer.key());
+        }
+        let holder_discount_config =
+            load_optional_account::<HolderDiscountConfig>(remaining_accounts, 5)?;
+        let discount_token_account = load_optional_account::<TokenAccount>(remaining_accounts, 6)?;
+        if let Some(holder_discount_config) = &holder_discount_config {
+            assert_keys_eq!(holder_discount_config.factory, user.swap.factory);
+            let discount_token_account = unwrap_opt!(
+                discount_token_account.as_ref(),
+                "missing discount token account"
+            );
+            assert_keys_eq!(
+                discount_token_account.mint,
+                holder_discount_config.discount_mint
+            );
+            assert_keys_eq!(discount_token_account.owner, user.user_authority);
+        }
+        let volume_rebate_config =
+            load_optional_account::<VolumeRebateConfig>(remaining_accounts, 7)?;
+        if let Some(volume_rebate_config) = &volume_rebate_config {
+            assert_keys_eq!(volume_rebate_config.factory, user.swap.factory);
+        }
+        let trader_stats = load_optional_account::<TraderStats>(remaining_accounts, 8)?;
+        if let Some(trader_stats) = &trader_stats {
+            invariant!(
+                volume_rebate_config.is_some(),
+                "missing volume rebate config"
+            );
+            assert_keys_eq!(trader_stats.swap, user.swap.key());
+            assert_keys_eq!(trader_stats.owner, user.user_authority);
+        }
+        let oracle = super::find_oracle(user.swap, remaining_accounts).cloned();
+        Ok(Self {
+            referrer,
+            host_fee_account,
+            fee_exemption,
+            creator_fee_account,
+            registered_referrer,
+            holder_discount_config,
+            discount_token_account,
+            volume_rebate_config,
+            trader_stats,
+            oracle,
+        })
+    }
+
+    /// Loads only the oracle of the swap from the remaining accounts, for swaps which take
+    /// no other optional accounts.
+    pub(super) fn load_oracle(
+        swap_info: &SwapInfo,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Self {
+        Self {
+            oracle: super::find_oracle(swap_info, remaining_accounts).cloned(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the largest trade fee discount of the [FeeExemption] of the user,
+    /// the [HolderDiscountConfig] tier of its discount token account and
+    /// the [VolumeRebateConfig] tier of its [TraderStats] at `now`, thousands of bps.
+    fn trade_fee_discount_kbps(&self, now: i64) -> u64 {
+        let exemption_discount_kbps = self
+            .fee_exemption
+            .as_ref()
+            .map_or(0, |fee_exemption| fee_exemption.trade_fee_discount_kbps);
+        let holder_discount_kbps =
+            match (&self.holder_discount_config, &self.discount_token_account) {
+                (Some(config), Some(token_account)) => {
+                    config.trade_fee_discount_kbps(token_account.amount)
+                }
+                _ => 0,
+            };
+        let volume_rebate_kbps = match (&self.volume_rebate_config, &self.trader_stats) {
+            (Some(config), Some(trader_stats)) => config
+                .trade_fee_rebate_kbps(trader_stats.rolling_volume(now, config.window_seconds)),
+            _ => 0,
+        };
+        exemption_discount_kbps
+            .max(holder_discount_kbps)
+            .max(volume_rebate_kbps)
+    }
+
+    /// Adds the volume of a swap to the [TraderStats] of the user, if provided.
+    fn track_trader_volume(&self, now: i64, volume: u64) -> Result<()> {
+        if let (Some(config), Some(trader_stats)) = (&self.volume_rebate_config, &self.trader_stats)
+        {
+            let mut trader_stats = trader_stats.clone();
+            unwrap_int!(trader_stats.track_volume(now, config.window_seconds, volume));
+            trader_stats.exit(&crate::ID)?;
+        }
+        Ok(())
+    }
+}
+
+/// Loads the remaining account at `index`, unless it is omitted or is the program ID.
+fn load_optional_account<'info, T: AccountSerialize + AccountDeserialize + Owner + Clone>(
+    remaining_accounts: &[AccountInfo<'info>],
+    index: usize,
+) -> Result<Option<Account<'info, T>>> {
+    match remaining_accounts.get(index) {
+        Some(info) if info.key() != crate::ID => Ok(Some(Account::try_from(info)?)),
+        _ => Ok(None),
+    }
+}
+
+/// Swaps `amount_in` of the `input` token for the `output` token.
+/// If `allow_partial_fill` is true, only as much of `amount_in` is swapped as meets
+/// the price implied by `amount_in` and `minimum_amount_out`.
+/// The caller is responsible for setting the returned [SwapReturnData] as return data.
+pub(super) fn process_swap<'info>(
+    user: &mut SwapUser<'_, 'info>,
+    input: &SwapTokenWithFees<'info>,
+    output: &SwapTokenWithFees<'info>,
+    optional_accounts: &SwapOptionalAccounts<'info>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    allow_partial_fill: bool,
+) -> Result<SwapReturnData> {
     // update cumulative price info.
     // must be called BEFORE mutation.
-    ctx.accounts.update_cumulative_price_info()?;
+    user.update_cumulative_price_info()?;
 
     let (input_reserve, output_reserve) = (
-        ctx.accounts.input.reserve.amount,
-        ctx.accounts.output.reserve.amount,
+        user.swap.reserve_amount(input.reserve.mint),
+        user.swap.reserve_amount(output.reserve.mint),
     );
 
-    let swap_fees = ctx.accounts.user.swap.fees;
-    let token_swap = &ctx.accounts.user.swap;
+    // Exempt users, holders of the discount mint and high-volume traders
+    // pay a discounted trade fee.
+    let now = Clock::get()?.unix_timestamp;
+    let trade_fees =
+        unwrap_int!(user
+            .swap
+            .swap_fees_for_trade(input.reserve.mint, amount_in, input_reserve));
+    let swap_fees = unwrap_int!(
+        trade_fees.apply_trade_fee_discount(optional_accounts.trade_fee_discount_kbps(now))
+    );
+    let token_swap = &user.swap;
+
+    // The input which is not filled stays with the user.
+    let (amount_in, minimum_amount_out) = if allow_partial_fill {
+        super::check_constant_product(token_swap)?;
+        let fill_amount = unwrap_int!(xyk::max_source_amount_at_price(
+            amount_in,
+            minimum_amount_out,
+            input_reserve,
+            output_reserve
+        ));
+        if fill_amount == 0 {
+            // skip the transfers if no input can be filled at the price
+            return Ok(SwapReturnData::default());
+        }
+        // the minimum output is scaled down to the filled input
+        (
+            fill_amount,
+            unwrap_int!(mul_div_u64(minimum_amount_out, fill_amount, amount_in)),
+        )
+    } else {
+        (amount_in, minimum_amount_out)
+    };
 
     // compute the swap
-    let swap_result = unwrap_int!(xyk::swap(args.amount_in, input_reserve, output_reserve));
+    let swap_result = unwrap_int!(token_swap.curve_swap(
+        input.reserve.mint,
+        amount_in,
+        input_reserve,
+        output_reserve
+    ));
     if swap_result.destination_amount_swapped == 0 {
         // skip the transfers if nothing is being swapped
-        return Ok(());
+        return Ok(SwapReturnData::default());
     }
+    super::check_swap_rate_limit(user.swap)?;
+    let token_swap = &user.swap;
 
-    invariant!(
-        swap_result.destination_amount_swapped >= args.minimum_amount_out,
-        ExceededSlippage
-    );
-
-    let (trade_fee, admin_trade_fee) =
-        unwrap_int!(swap_fees.compute_trade_fees(swap_result.destination_amount_swapped));
+    super::check_max_trade_size(token_swap, swap_result.source_amount_swapped, input_reserve)?;
+    super::check_price_impact(token_swap, &swap_result, input_reserve, output_reserve)?;
+    super::check_oracle_band(
+        token_swap,
+        optional_accounts.oracle.as_ref(),
+        input.reserve.mint,
+        &swap_result,
+    )?;
 
     // Transfer user's tokens to the pool.
-    let token_program = &ctx.accounts.user.token_program;
+    super::check_delegated_amount(
+        &input.user,
+        user.input_authority.key(),
+        swap_result.source_amount_swapped,
+    )?;
     token::transfer(
-        CpiContext::new(
-            token_program.to_account_info(),
+        CpiContext::new_with_signer(
+            user.token_program.to_account_info(),
             token::Transfer {
-                from: ctx.accounts.input.user.to_account_info(),
-                to: ctx.accounts.input.reserve.to_account_info(),
-                authority: ctx.accounts.user.user_authority.to_account_info(),
+                from: input.user.to_account_info(),
+                to: input.reserve.to_account_info(),
+                authority: user.input_authority.clone(),
             },
+            user.input_authority_seeds,
         ),
         swap_result.source_amount_swapped,
     )?;
 
-    let seeds = gen_swap_signer_seeds!(token_swap);
+    invariant!(
+        swap_result.destination_amount_swapped >= minimum_amount_out,
+        ExceededSlippage
+    );
+
+    let (trade_fee, admin_trade_fee) =
+        unwrap_int!(swap_fees.compute_trade_fees(swap_result.destination_amount_swapped));
 
-    if admin_trade_fee > 0 {
-        // Transfer user's fee to the fees account.
-        let signer_seeds = &[&seeds[..]];
-        token::transfer(
-            CpiContext::new_with_signer(
-                token_program.to_account_info(),
-                token::Transfer {
-                    from: ctx.accounts.output.reserve.to_account_info(),
-                    to: ctx.accounts.output.fees.to_account_info(),
-                    authority: token_swap.to_account_info(),
-                },
-                signer_seeds,
-            ),
+    // Hosts receive a share of the admin trade fee.
+    let host_fee = match &optional_accounts.host_fee_account {
+        Some(_) => unwrap_int!(SwapFees::compute_host_fee(
             admin_trade_fee,
-        )?;
+            token_swap.host_fee_kbps
+        )),
+        None => 0,
+    };
+    // Creators receive a share of the rest of the admin trade fee.
+    let creator_fee = match &optional_accounts.creator_fee_account {
+        Some(_) => unwrap_int!(SwapFees::compute_creator_fee(
+            admin_trade_fee,
+            host_fee,
+            token_swap.creator_fee_kbps
+        )),
+        None => 0,
+    };
+    // Referrers receive a share of the rest of the admin trade fee.
+    let referral_fee = match &optional_accounts.referrer {
+        Some(_) => unwrap_int!(SwapFees::compute_referral_fee(
+            admin_trade_fee,
+            host_fee,
+            creator_fee,
+            token_swap.referral_fee_kbps
+        )),
+        None => 0,
+    };
+
+    // Transfer user's fee to the fees account.
+    user.transfer_from_reserve(
+        &output.reserve,
+        &output.fees,
+        unwrap_int!(unwrap_int!(
+            unwrap_int!(admin_trade_fee.checked_sub(host_fee)).checked_sub(creator_fee)
+        )
+        .checked_sub(referral_fee)),
+    )?;
+    if let Some(host_fee_account) = &optional_accounts.host_fee_account {
+        user.transfer_from_reserve(&output.reserve, host_fee_account, host_fee)?;
+    }
+    if let Some(referrer) = &optional_accounts.referrer {
+        user.transfer_from_reserve(&output.reserve, referrer, referral_fee)?;
+    }
+    if let Some(registered_referrer) = &optional_accounts.registered_referrer {
+        if referral_fee > 0 {
+            let mut registered_referrer = registered_referrer.clone();
+            registered_referrer.num_referrals =
+                unwrap_int!(registered_referrer.num_referrals.checked_add(1));
+            registered_referrer.exit(&crate::ID)?;
+        }
+    }
+    if let Some(creator_fee_account) = &optional_accounts.creator_fee_account {
+        user.transfer_from_reserve(&output.reserve, creator_fee_account, creator_fee)?;
     }
 
     // Transfer pool's tokens to the user.
-    let signer_seeds = &[&seeds[..]];
-    token::transfer(
-        CpiContext::new_with_signer(
-            token_program.to_account_info(),
-            token::Transfer {
-                from: ctx.accounts.output.reserve.to_account_info(),
-                to: ctx.accounts.output.user.to_account_info(),
-                authority: token_swap.to_account_info(),
-            },
-            signer_seeds,
-        ),
-        unwrap_int!(swap_result
-            .destination_amount_swapped
-            .checked_sub(trade_fee)),
+    let amount_out = unwrap_int!(swap_result
+        .destination_amount_swapped
+        .checked_sub(trade_fee));
+    user.transfer_from_reserve(&output.reserve, &output.user, amount_out)?;
+
+    super::track_reserve(user.swap, &input.reserve)?;
+    super::track_reserve(user.swap, &output.reserve)?;
+    let new_input_reserve = user.swap.reserve_amount(input.reserve.mint);
+    let new_output_reserve = user.swap.reserve_amount(output.reserve.mint);
+    super::check_invariant(
+        user.swap,
+        input.reserve.mint,
+        (input_reserve, output_reserve),
+        (new_input_reserve, new_output_reserve),
     )?;
+    super::update_volatility(
+        user.swap,
+        input.reserve.mint,
+        (input_reserve, output_reserve),
+        (new_input_reserve, new_output_reserve),
+    )?;
+    super::update_circuit_breaker(user.swap)?;
 
     emit!(SwapEvent {
-        lp_mint: ctx.accounts.user.swap.pool_mint,
-        input_mint: ctx.accounts.input.reserve.mint,
-        output_mint: ctx.accounts.output.reserve.mint,
+        lp_mint: user.swap.pool_mint,
+        input_mint: input.reserve.mint,
+        output_mint: output.reserve.mint,
         source_amount_swapped: swap_result.source_amount_swapped,
         destination_amount_swapped: swap_result.destination_amount_swapped,
+        swap: user.swap.key(),
+        user_authority: user.user_authority,
+        trade_fee,
+        admin_trade_fee,
+        input_reserve: new_input_reserve,
+        output_reserve: new_output_reserve,
+        timestamp: now,
     });
 
-    ctx.accounts
-        .track_cumulative_swap_volume(&swap_result, trade_fee)?;
+    user.track_cumulative_swap_volume(input, &swap_result, trade_fee)?;
+    // the volume of traders is measured in token 0
+    optional_accounts.track_trader_volume(
+        now,
+        if input.reserve.mint == user.swap.token_0.mint {
+            swap_result.source_amount_swapped
+        } else {
+            swap_result.destination_amount_swapped
+        },
+    )?;
+    unwrap_int!(user
+        .swap
+        .record_last_trade(input.reserve.mint, &swap_result, Clock::get()?.slot));
+    unwrap_int!(user
+        .swap
+        .track_fees_paid_out(output.reserve.mint, admin_trade_fee));
 
-    Ok(())
+    Ok(SwapReturnData {
+        amount_in: swap_result.source_amount_swapped,
+        amount_out,
+    })
 }
 
-impl<'info> Swap<'info> {
+impl<'a, 'info> SwapUser<'a, 'info> {
+    /// Transfers `amount` tokens from one of the reserves of the swap.
+    fn transfer_from_reserve(
+        &self,
+        reserve: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+    ) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let token_swap = &*self.swap;
+        let seeds = gen_swap_signer_seeds!(token_swap);
+        let signer_seeds = &[&seeds[..]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                token::Transfer {
+                    from: reserve.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: token_swap.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+
     fn update_cumulative_price_info(&mut self) -> Result<()> {
         // update price info
-        let swap_info = &mut self.user.swap;
-        let (reserve_a, reserve_b) = if self.input.reserve.mint == swap_info.token_0.mint {
-            (self.input.reserve.amount, self.output.reserve.amount)
-        } else {
-            (self.output.reserve.amount, self.input.reserve.amount)
-        };
-        swap_info
-            .price_info
-            .update_cumulative_price_info(reserve_a, reserve_b)?;
-        Ok(())
+        self.swap.update_price_info()
     }
 
     /// Tracks and saves the cumulative swap volume.
     fn track_cumulative_swap_volume(
         &mut self,
+        input: &SwapTokenWithFees<'info>,
         swap_result: &SwapResult,
         trade_fee: u64,
     ) -> Result<()> {
         // record cumulative volume numbers
-        let token_0_mint = self.user.swap.token_0.mint;
-        let swap_info = &mut self.user.swap;
+        let token_0_mint = self.swap.token_0.mint;
+        let swap_info = &mut self.swap;
         let cumulative_stats = &mut swap_info.cumulative_stats;
-        let (cum_input_token, cum_output_token) = if self.input.reserve.mint == token_0_mint {
+        let (cum_input_token, cum_output_token) = if input.reserve.mint == token_0_mint {
             (&mut cumulative_stats.token_0, &mut cumulative_stats.token_1)
         } else {
             (&mut cumulative_stats.token_1, &mut cumulative_stats.token_0)
@@ -0,0 +1,79 @@
+//! [crate::cpamm::swap_batch] instruction controller.
+
+use super::swap::{process_swap, SwapOptionalAccounts};
+use crate::*;
+use anchor_spl::token::TokenAccount;
+
+pub struct SwapBatchArgs {
+    pub swaps: Vec<BatchSwap>,
+    pub deadline: Option<i64>,
+}
+
+/// Swap batch
+pub fn swap_batch<'info>(
+    ctx: Context<'_, '_, '_, 'info, SwapBatch<'info>>,
+    args: SwapBatchArgs,
+) -> Result<()> {
+    super::check_deadline(args.deadline)?;
+    let num_token_accounts = unwrap_int!(args.swaps.len().checked_mul(2));
+    invariant!(
+        ctx.remaining_accounts.len() >= num_token_accounts,
+        "each swap of the batch requires an input and an output token account"
+    );
+    // the oracle of the oracle band of the swap, if any, follows the token accounts
+    let (user_token_accounts, other_accounts) = ctx.remaining_accounts.split_at(num_token_accounts);
+    let optional_accounts =
+        SwapOptionalAccounts::load_oracle(&ctx.accounts.user.swap, other_accounts);
+
+    let accounts = &mut *ctx.accounts;
+    let mut results = Vec::with_capacity(args.swaps.len());
+    for (batch_swap, user_tokens) in args.swaps.iter().zip(user_token_accounts.chunks_exact(2)) {
+        let (input, output) = accounts.load_swap_tokens(&user_tokens[0], &user_tokens[1])?;
+        results.push(process_swap(
+            &mut (&mut accounts.user).into(),
+            &input,
+            &output,
+            &optional_accounts,
+            batch_swap.amount_in,
+            batch_swap.minimum_amount_out,
+            false,
+        )?);
+    }
+    super::write_return_data(&results)
+}
+
+impl<'info> SwapBatch<'info> {
+    /// Loads and validates the tokens of one of the swaps of the batch.
+    ///
+    /// The reserves are loaded again for every swap, since the previous swaps change them.
+    fn load_swap_tokens(
+        &self,
+        input_user: &AccountInfo<'info>,
+        output_user: &AccountInfo<'info>,
+    ) -> Result<(SwapTokenWithFees<'info>, SwapTokenWithFees<'info>)> {
+        let input_user: Box<Account<'info, TokenAccount>> =
+            Box::new(Account::try_from(input_user)?);
+        let output_user: Box<Account<'info, TokenAccount>> =
+            Box::new(Account::try_from(output_user)?);
+
+        let (input_reserve, input_fees, output_reserve, output_fees) =
+            if input_user.mint == self.user.swap.token_0.mint {
+                (&self.reserve_0, &self.fees_0, &self.reserve_1, &self.fees_1)
+            } else {
+                (&self.reserve_1, &self.fees_1, &self.reserve_0, &self.fees_0)
+            };
+        let input = SwapTokenWithFees {
+            user: input_user,
+            reserve: Box::new(Account::try_from(&input_reserve.to_account_info())?),
+            fees: input_fees.clone(),
+        };
+        let output = SwapTokenWithFees {
+            user: output_user,
+            reserve: Box::new(Account::try_from(&output_reserve.to_account_info())?),
+            fees: output_fees.clone(),
+        };
+
+        self.user.validate_swap_tokens(&input, &output)?;
+        Ok((input, output))
+    }
+}
@@ -0,0 +1,217 @@
+//! [crate::cpamm::swap_exact_out] instruction controller.
+
+use crate::*;
+use anchor_spl::token;
+
+pub struct SwapExactOutArgs {
+    pub amount_out: u64,
+    pub maximum_amount_in: u64,
+    pub deadline: Option<i64>,
+}
+
+/// Swap exact out
+pub fn swap_exact_out(ctx: Context<SwapExactOut>, args: SwapExactOutArgs) -> Result<()> {
+    super::check_deadline(args.deadline)?;
+
+    // update cumulative price info.
+    // must be called BEFORE mutation.
+    ctx.accounts.update_cumulative_price_info()?;
+
+    if args.amount_out == 0 {
+        // skip the transfers if nothing is being swapped
+        return super::write_return_data(&SwapReturnData::default());
+    }
+    super::check_swap_rate_limit(&mut ctx.accounts.user.swap)?;
+
+    let (input_reserve, output_reserve) = (
+        ctx.accounts
+            .user
+            .swap
+            .reserve_amount(ctx.accounts.input.reserve.mint),
+        ctx.accounts
+            .user
+            .swap
+            .reserve_amount(ctx.accounts.output.reserve.mint),
+    );
+    invariant!(args.amount_out < output_reserve, InsufficientLiquidity);
+
+    let token_swap = &ctx.accounts.user.swap;
+
+    // compute the swap
+    let swap_result = unwrap_int!(token_swap.curve_swap_exact_out(
+        ctx.accounts.input.reserve.mint,
+        args.amount_out,
+        input_reserve,
+        output_reserve
+    ));
+    let swap_fees = unwrap_int!(token_swap.swap_fees_for_trade(
+        ctx.accounts.input.reserve.mint,
+        swap_result.source_amount_swapped,
+        input_reserve
+    ));
+
+    // fees are charged on top of the amount the curve requires
+    let (amount_in, trade_fee, admin_trade_fee) =
+        unwrap_int!(swap_fees.compute_trade_fees_on_input(swap_result.source_amount_swapped));
+    invariant!(amount_in <= args.maximum_amount_in, ExceededSlippage);
+    super::check_max_trade_size(token_swap, amount_in, input_reserve)?;
+    super::check_price_impact(token_swap, &swap_result, input_reserve, output_reserve)?;
+    super::check_oracle_band(
+        token_swap,
+        super::find_oracle(token_swap, ctx.remaining_accounts),
+        ctx.accounts.input.reserve.mint,
+        &swap_result,
+    )?;
+
+    // Transfer user's tokens to the pool.
+    super::check_delegated_amount(
+        &ctx.accounts.input.user,
+        ctx.accounts.user.user_authority.key(),
+        amount_in,
+    )?;
+    // The LP portion of the trade fee stays in the reserve.
+    let token_program = &ctx.accounts.user.token_program;
+    token::transfer(
+        CpiContext::new(
+            token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.input.user.to_account_info(),
+                to: ctx.accounts.input.reserve.to_account_info(),
+                authority: ctx.accounts.user.user_authority.to_account_info(),
+            },
+        ),
+        unwrap_int!(amount_in.checked_sub(admin_trade_fee)),
+    )?;
+
+    if admin_trade_fee > 0 {
+        // Transfer user's fee to the fees account.
+        token::transfer(
+            CpiContext::new(
+                token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.input.user.to_account_info(),
+                    to: ctx.accounts.input.fees.to_account_info(),
+                    authority: ctx.accounts.user.user_authority.to_account_info(),
+                },
+            ),
+            admin_trade_fee,
+        )?;
+    }
+
+    // Transfer pool's tokens to the user.
+    let seeds = gen_swap_signer_seeds!(token_swap);
+    let signer_seeds = &[&seeds[..]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.output.reserve.to_account_info(),
+                to: ctx.accounts.output.user.to_account_info(),
+                authority: token_swap.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        swap_result.destination_amount_swapped,
+    )?;
+
+    let accounts = &mut *ctx.accounts;
+    super::track_reserve(&mut accounts.user.swap, &accounts.input.reserve)?;
+    super::track_reserve(&mut accounts.user.swap, &accounts.output.reserve)?;
+    let new_input_reserve = accounts
+        .user
+        .swap
+        .reserve_amount(accounts.input.reserve.mint);
+    let new_output_reserve = accounts
+        .user
+        .swap
+        .reserve_amount(accounts.output.reserve.mint);
+    super::check_invariant(
+        &accounts.user.swap,
+        accounts.input.reserve.mint,
+        (input_reserve, output_reserve),
+        (new_input_reserve, new_output_reserve),
+    )?;
+    let input_mint = ctx.accounts.input.reserve.mint;
+    super::update_volatility(
+        &mut ctx.accounts.user.swap,
+        input_mint,
+        (input_reserve, output_reserve),
+        (new_input_reserve, new_output_reserve),
+    )?;
+    super::update_circuit_breaker(&mut ctx.accounts.user.swap)?;
+
+    emit!(SwapEvent {
+        lp_mint: ctx.accounts.user.swap.pool_mint,
+        input_mint: ctx.accounts.input.reserve.mint,
+        output_mint: ctx.accounts.output.reserve.mint,
+        source_amount_swapped: amount_in,
+        destination_amount_swapped: swap_result.destination_amount_swapped,
+        swap: ctx.accounts.user.swap.key(),
+        user_authority: ctx.accounts.user.user_authority.key(),
+        trade_fee,
+        admin_trade_fee,
+        input_reserve: new_input_reserve,
+        output_reserve: new_output_reserve,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    ctx.accounts.track_cumulative_swap_volume(
+        amount_in,
+        swap_result.destination_amount_swapped,
+        trade_fee,
+    )?;
+    // the fees are charged on the input
+    let input_mint = ctx.accounts.input.reserve.mint;
+    unwrap_int!(ctx
+        .accounts
+        .user
+        .swap
+        .track_fees_paid_out(input_mint, admin_trade_fee));
+    unwrap_int!(ctx.accounts.user.swap.record_last_trade(
+        input_mint,
+        &swap_result,
+        Clock::get()?.slot
+    ));
+
+    super::write_return_data(&SwapReturnData {
+        amount_in,
+        amount_out: swap_result.destination_amount_swapped,
+    })
+}
+
+impl<'info> SwapExactOut<'info> {
+    fn update_cumulative_price_info(&mut self) -> Result<()> {
+        // update price info
+        self.user.swap.update_price_info()
+    }
+
+    /// Tracks and saves the cumulative swap volume.
+    /// Exact out trade fees are tracked on the input token.
+    fn track_cumulative_swap_volume(
+        &mut self,
+        amount_in: u64,
+        amount_out: u64,
+        trade_fee: u64,
+    ) -> Result<()> {
+        // record cumulative volume numbers
+        let token_0_mint = self.user.swap.token_0.mint;
+        let swap_info = &mut self.user.swap;
+        let cumulative_stats = &mut swap_info.cumulative_stats;
+        let (cum_input_token, cum_output_token) = if self.input.reserve.mint == token_0_mint {
+            (&mut cumulative_stats.token_0, &mut cumulative_stats.token_1)
+        } else {
+            (&mut cumulative_stats.token_1, &mut cumulative_stats.token_0)
+        };
+
+        cum_input_token.total_input_volume = unwrap_int!(cum_input_token
+            .total_input_volume
+            .checked_add(amount_in.into()));
+        cum_output_token.total_output_volume = unwrap_int!(cum_output_token
+            .total_output_volume
+            .checked_add(amount_out.into()));
+        cum_input_token.total_trade_fees =
+            unwrap_int!(cum_input_token.total_trade_fees.checked_add(trade_fee));
+
+        Ok(())
+    }
+}
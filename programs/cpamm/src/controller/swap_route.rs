@@ -0,0 +1,284 @@
+//! [crate::cpamm::swap_route] instruction controller.
+
+use crate::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+use xyk::SwapResult;
+
+pub struct SwapRouteArgs {
+    pub amount_in: u64,
+    pub minimum_amount_out: u64,
+    pub deadline: Option<i64>,
+}
+
+/// Swap route
+pub fn swap_route(ctx: Context<SwapRoute>, args: SwapRouteArgs) -> Result<()> {
+    super::check_deadline(args.deadline)?;
+
+    // update cumulative price info.
+    // must be called BEFORE mutation.
+    ctx.accounts.hop_0.update_cumulative_price_info()?;
+    ctx.accounts.hop_1.update_cumulative_price_info()?;
+
+    // compute the first hop
+    let hop_0 = &ctx.accounts.hop_0;
+    let (input_reserve_0, output_reserve_0) = hop_0.reserve_amounts();
+    let swap_result_0 = unwrap_int!(hop_0.swap.curve_swap(
+        hop_0.input_reserve.mint,
+        args.amount_in,
+        input_reserve_0,
+        output_reserve_0
+    ));
+    let (trade_fee_0, admin_trade_fee_0) = unwrap_int!(unwrap_int!(hop_0
+        .swap
+        .swap_fees_for_trade(hop_0.input_reserve.mint, args.amount_in, input_reserve_0))
+    .compute_trade_fees(swap_result_0.destination_amount_swapped));
+    let intermediate_amount = unwrap_int!(swap_result_0
+        .destination_amount_swapped
+        .checked_sub(trade_fee_0));
+
+    // compute the second hop using what the first hop sends to the user
+    let hop_1 = &ctx.accounts.hop_1;
+    let (input_reserve_1, output_reserve_1) = hop_1.reserve_amounts();
+    let swap_result_1 = unwrap_int!(hop_1.swap.curve_swap(
+        hop_1.input_reserve.mint,
+        intermediate_amount,
+        input_reserve_1,
+        output_reserve_1
+    ));
+    let (trade_fee_1, admin_trade_fee_1) =
+        unwrap_int!(unwrap_int!(hop_1.swap.swap_fees_for_trade(
+            hop_1.input_reserve.mint,
+            intermediate_amount,
+            input_reserve_1
+        ))
+        .compute_trade_fees(swap_result_1.destination_amount_swapped));
+    let amount_out = unwrap_int!(swap_result_1
+        .destination_amount_swapped
+        .checked_sub(trade_fee_1));
+
+    invariant!(amount_out >= args.minimum_amount_out, ExceededSlippage);
+    super::check_max_trade_size(
+        &hop_0.swap,
+        swap_result_0.source_amount_swapped,
+        input_reserve_0,
+    )?;
+    super::check_max_trade_size(
+        &hop_1.swap,
+        swap_result_1.source_amount_swapped,
+        input_reserve_1,
+    )?;
+    super::check_price_impact(
+        &hop_0.swap,
+        &swap_result_0,
+        input_reserve_0,
+        output_reserve_0,
+    )?;
+    super::check_price_impact(
+        &hop_1.swap,
+        &swap_result_1,
+        input_reserve_1,
+        output_reserve_1,
+    )?;
+    super::check_oracle_band(
+        &hop_0.swap,
+        super::find_oracle(&hop_0.swap, ctx.remaining_accounts),
+        hop_0.input_reserve.mint,
+        &swap_result_0,
+    )?;
+    super::check_oracle_band(
+        &hop_1.swap,
+        super::find_oracle(&hop_1.swap, ctx.remaining_accounts),
+        hop_1.input_reserve.mint,
+        &swap_result_1,
+    )?;
+    super::check_swap_rate_limit(&mut ctx.accounts.hop_0.swap)?;
+    super::check_swap_rate_limit(&mut ctx.accounts.hop_1.swap)?;
+    let (hop_0, hop_1) = (&ctx.accounts.hop_0, &ctx.accounts.hop_1);
+
+    // Transfer user's tokens to the first pool.
+    super::check_delegated_amount(
+        &ctx.accounts.input,
+        ctx.accounts.user_authority.key(),
+        swap_result_0.source_amount_swapped,
+    )?;
+    let token_program = &ctx.accounts.token_program;
+    token::transfer(
+        CpiContext::new(
+            token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.input.to_account_info(),
+                to: hop_0.input_reserve.to_account_info(),
+                authority: ctx.accounts.user_authority.to_account_info(),
+            },
+        ),
+        swap_result_0.source_amount_swapped,
+    )?;
+
+    // Transfer the intermediate tokens from the first pool to the second pool.
+    // Any intermediate tokens which the second hop could not use remain in the first pool.
+    hop_0.transfer_fees(token_program, admin_trade_fee_0)?;
+    hop_0.transfer_output(
+        token_program,
+        &hop_1.input_reserve,
+        swap_result_1.source_amount_swapped,
+    )?;
+
+    // Transfer the second pool's tokens to the user.
+    hop_1.transfer_fees(token_program, admin_trade_fee_1)?;
+    hop_1.transfer_output(token_program, &ctx.accounts.output, amount_out)?;
+
+    ctx.accounts.hop_0.process_new_reserves()?;
+    ctx.accounts.hop_1.process_new_reserves()?;
+
+    let hop_0 = &ctx.accounts.hop_0;
+    let hop_1 = &ctx.accounts.hop_1;
+    let (new_input_reserve_0, new_output_reserve_0) = hop_0.reserve_amounts();
+    let (new_input_reserve_1, new_output_reserve_1) = hop_1.reserve_amounts();
+    let user_authority = ctx.accounts.user_authority.key();
+    let timestamp = Clock::get()?.unix_timestamp;
+    emit!(SwapEvent {
+        lp_mint: hop_0.swap.pool_mint,
+        input_mint: hop_0.input_reserve.mint,
+        output_mint: hop_0.output_reserve.mint,
+        source_amount_swapped: swap_result_0.source_amount_swapped,
+        destination_amount_swapped: swap_result_0.destination_amount_swapped,
+        swap: hop_0.swap.key(),
+        user_authority,
+        trade_fee: trade_fee_0,
+        admin_trade_fee: admin_trade_fee_0,
+        input_reserve: new_input_reserve_0,
+        output_reserve: new_output_reserve_0,
+        timestamp,
+    });
+    emit!(SwapEvent {
+        lp_mint: hop_1.swap.pool_mint,
+        input_mint: hop_1.input_reserve.mint,
+        output_mint: hop_1.output_reserve.mint,
+        source_amount_swapped: swap_result_1.source_amount_swapped,
+        destination_amount_swapped: swap_result_1.destination_amount_swapped,
+        swap: hop_1.swap.key(),
+        user_authority,
+        trade_fee: trade_fee_1,
+        admin_trade_fee: admin_trade_fee_1,
+        input_reserve: new_input_reserve_1,
+        output_reserve: new_output_reserve_1,
+        timestamp,
+    });
+
+    ctx.accounts
+        .hop_0
+        .track_cumulative_swap_volume(&swap_result_0, trade_fee_0)?;
+    ctx.accounts
+        .hop_1
+        .track_cumulative_swap_volume(&swap_result_1, trade_fee_1)?;
+    let slot = Clock::get()?.slot;
+    for (hop, swap_result, admin_trade_fee) in [
+        (&mut ctx.accounts.hop_0, &swap_result_0, admin_trade_fee_0),
+        (&mut ctx.accounts.hop_1, &swap_result_1, admin_trade_fee_1),
+    ] {
+        let (input_mint, output_mint) = (hop.input_reserve.mint, hop.output_reserve.mint);
+        unwrap_int!(hop.swap.track_fees_paid_out(output_mint, admin_trade_fee));
+        unwrap_int!(hop.swap.record_last_trade(input_mint, swap_result, slot));
+    }
+
+    super::write_return_data(&SwapReturnData {
+        amount_in: swap_result_0.source_amount_swapped,
+        amount_out,
+    })
+}
+
+impl<'info> SwapRouteHop<'info> {
+    /// Returns the amounts of the input and output tokens in the pool.
+    fn reserve_amounts(&self) -> (u64, u64) {
+        (
+            self.swap.reserve_amount(self.input_reserve.mint),
+            self.swap.reserve_amount(self.output_reserve.mint),
+        )
+    }
+
+    fn update_cumulative_price_info(&mut self) -> Result<()> {
+        // update price info
+        self.swap.update_price_info()
+    }
+
+    /// Tracks the reserves, then checks the invariant and updates the volatility and
+    /// the circuit breaker of the swap from the reserves before and after the hop.
+    fn process_new_reserves(&mut self) -> Result<()> {
+        let old_reserves = self.reserve_amounts();
+        super::track_reserve(&mut self.swap, &self.input_reserve)?;
+        super::track_reserve(&mut self.swap, &self.output_reserve)?;
+        let new_reserves = self.reserve_amounts();
+        super::check_invariant(
+            &self.swap,
+            self.input_reserve.mint,
+            old_reserves,
+            new_reserves,
+        )?;
+        super::update_volatility(
+            &mut self.swap,
+            self.input_reserve.mint,
+            old_reserves,
+            new_reserves,
+        )?;
+        super::update_circuit_breaker(&mut self.swap)
+    }
+
+    /// Transfers the admin trade fee from the output reserve to the fees account.
+    fn transfer_fees(&self, token_program: &Program<'info, Token>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        self.transfer_output(token_program, &self.output_fees, amount)
+    }
+
+    /// Transfers tokens out of the output reserve.
+    fn transfer_output(
+        &self,
+        token_program: &Program<'info, Token>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+    ) -> Result<()> {
+        let token_swap = &self.swap;
+        let seeds = gen_swap_signer_seeds!(token_swap);
+        let signer_seeds = &[&seeds[..]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                token::Transfer {
+                    from: self.output_reserve.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: token_swap.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+
+    /// Tracks and saves the cumulative swap volume.
+    fn track_cumulative_swap_volume(
+        &mut self,
+        swap_result: &SwapResult,
+        trade_fee: u64,
+    ) -> Result<()> {
+        // record cumulative volume numbers
+        let token_0_mint = self.swap.token_0.mint;
+        let cumulative_stats = &mut self.swap.cumulative_stats;
+        let (cum_input_token, cum_output_token) = if self.input_reserve.mint == token_0_mint {
+            (&mut cumulative_stats.token_0, &mut cumulative_stats.token_1)
+        } else {
+            (&mut cumulative_stats.token_1, &mut cumulative_stats.token_0)
+        };
+
+        cum_input_token.total_input_volume = unwrap_int!(cum_input_token
+            .total_input_volume
+            .checked_add(swap_result.source_amount_swapped.into()));
+        cum_output_token.total_output_volume = unwrap_int!(cum_output_token
+            .total_output_volume
+            .checked_add(swap_result.destination_amount_swapped.into()));
+        cum_output_token.total_trade_fees =
+            unwrap_int!(cum_output_token.total_trade_fees.checked_add(trade_fee));
+
+        Ok(())
+    }
+}
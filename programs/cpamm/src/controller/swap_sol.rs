@@ -0,0 +1,168 @@
+//! [crate::cpamm::swap_sol] instruction controller.
+
+use crate::*;
+use anchor_lang::solana_program::{program::invoke_signed, system_instruction};
+use anchor_spl::token::{self, Token, TokenAccount};
+
+pub struct SwapSolArgs {
+    pub amount_in: u64,
+    pub minimum_amount_out: u64,
+    pub sol_is_input: bool,
+    pub deadline: Option<i64>,
+}
+
+/// Swap SOL
+pub fn swap_sol(ctx: Context<SwapSol>, args: SwapSolArgs) -> Result<()> {
+    super::check_deadline(args.deadline)?;
+
+    let input_mint = if args.sol_is_input {
+        addresses::NATIVE_MINT
+    } else {
+        ctx.accounts.token.reserve.mint
+    };
+    invariant!(
+        !ctx.accounts.user.swap.is_direction_paused(input_mint),
+        DirectionPaused
+    );
+
+    let bump = unwrap_bump!(ctx, "wrapped_sol");
+    let accounts = &mut *ctx.accounts;
+
+    // Wrap the SOL being swapped. Wrapped SOL accounts hold their balance as lamports,
+    // so the amount is deposited together with the rent of the account.
+    let rent_exempt_lamports = accounts.rent.minimum_balance(TokenAccount::LEN);
+    let lamports = if args.sol_is_input {
+        unwrap_int!(rent_exempt_lamports.checked_add(args.amount_in))
+    } else {
+        rent_exempt_lamports
+    };
+    accounts.wrapped_sol().create(lamports, bump)?;
+
+    let sol = SwapTokenWithFees {
+        user: Box::new(Account::try_from(&accounts.wrapped_sol.to_account_info())?),
+        reserve: accounts.sol_reserve.clone(),
+        fees: accounts.sol_fees.clone(),
+    };
+    let (input, output) = if args.sol_is_input {
+        (&sol, &accounts.token)
+    } else {
+        (&accounts.token, &sol)
+    };
+    let return_data = super::swap::process_swap(
+        &mut (&mut accounts.user).into(),
+        input,
+        output,
+        &Default::default(),
+        args.amount_in,
+        args.minimum_amount_out,
+        false,
+    )?;
+
+    accounts.wrapped_sol().close()?;
+
+    super::write_return_data(&return_data)
+}
+
+impl<'info> SwapSol<'info> {
+    /// Accounts of the temporary wrapped SOL account of the user.
+    fn wrapped_sol(&self) -> WrappedSol<'_, 'info> {
+        WrappedSol {
+            token_program: &self.user.token_program,
+            swap: self.user.swap.key(),
+            user_authority: &self.user.user_authority,
+            wrapped_sol: &self.wrapped_sol,
+            native_mint: &self.native_mint,
+            payer: &self.payer,
+            system_program: &self.system_program,
+            rent: &self.rent,
+        }
+    }
+}
+
+/// The temporary wrapped SOL account of a user, seeded by the swap and the user authority.
+pub(super) struct WrappedSol<'a, 'info> {
+    pub(super) token_program: &'a Program<'info, Token>,
+    pub(super) swap: Pubkey,
+    pub(super) user_authority: &'a Signer<'info>,
+    pub(super) wrapped_sol: &'a UncheckedAccount<'info>,
+    pub(super) native_mint: &'a UncheckedAccount<'info>,
+    pub(super) payer: &'a Signer<'info>,
+    pub(super) system_program: &'a Program<'info, System>,
+    pub(super) rent: &'a Sysvar<'info, Rent>,
+}
+
+impl<'a, 'info> WrappedSol<'a, 'info> {
+    /// Creates the temporary wrapped SOL account of the user holding `lamports`.
+    pub(super) fn create(&self, lamports: u64, bump: u8) -> Result<()> {
+        let wrapped_sol = self.wrapped_sol.to_account_info();
+        let payer = self.payer.to_account_info();
+        let system_program = self.system_program.to_account_info();
+
+        let user_authority_key = self.user_authority.key();
+        let seeds: &[&[u8]] = &[
+            b"WrappedSol",
+            self.swap.as_ref(),
+            user_authority_key.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds = &[seeds];
+
+        let current_lamports = wrapped_sol.lamports();
+        if current_lamports == 0 {
+            invoke_signed(
+                &system_instruction::create_account(
+                    payer.key,
+                    wrapped_sol.key,
+                    lamports,
+                    TokenAccount::LEN as u64,
+                    &token::ID,
+                ),
+                &[payer, wrapped_sol.clone(), system_program],
+                signer_seeds,
+            )?;
+        } else {
+            // Someone sent lamports to the address of the account,
+            // so it cannot be created with `create_account`.
+            let required_lamports = lamports.saturating_sub(current_lamports);
+            if required_lamports > 0 {
+                invoke_signed(
+                    &system_instruction::transfer(payer.key, wrapped_sol.key, required_lamports),
+                    &[payer, wrapped_sol.clone(), system_program.clone()],
+                    signer_seeds,
+                )?;
+            }
+            invoke_signed(
+                &system_instruction::allocate(wrapped_sol.key, TokenAccount::LEN as u64),
+                &[wrapped_sol.clone(), system_program.clone()],
+                signer_seeds,
+            )?;
+            invoke_signed(
+                &system_instruction::assign(wrapped_sol.key, &token::ID),
+                &[wrapped_sol.clone(), system_program],
+                signer_seeds,
+            )?;
+        }
+
+        token::initialize_account(CpiContext::new(
+            self.token_program.to_account_info(),
+            token::InitializeAccount {
+                account: wrapped_sol,
+                mint: self.native_mint.to_account_info(),
+                authority: self.user_authority.to_account_info(),
+                rent: self.rent.to_account_info(),
+            },
+        ))
+    }
+
+    /// Unwraps by closing the temporary account, which sends all of its lamports to the payer.
+    pub(super) fn close(&self) -> Result<()> {
+        token::close_account(CpiContext::new(
+            self.token_program.to_account_info(),
+            token::CloseAccount {
+                account: self.wrapped_sol.to_account_info(),
+                destination: self.payer.to_account_info(),
+                authority: self.user_authority.to_account_info(),
+            },
+        ))
+    }
+}
@@ -0,0 +1,136 @@
+//! [crate::cpamm::swap_with_signature] instruction controller.
+
+use super::swap::{process_swap, SwapOptionalAccounts, SwapUser};
+use crate::*;
+use anchor_lang::solana_program::{
+    ed25519_program,
+    pubkey::PUBKEY_BYTES,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+};
+
+pub struct SwapWithSignatureArgs {
+    pub amount_in: u64,
+    pub minimum_amount_out: u64,
+    pub nonce: u64,
+    pub expiry: i64,
+}
+
+/// Size of the header of an ed25519 program instruction: the number of signatures and padding.
+const ED25519_HEADER_LEN: usize = 2;
+/// Number of `u16` offsets describing a signature in an ed25519 program instruction.
+const ED25519_NUM_OFFSETS: usize = 7;
+/// Instruction index referring to the ed25519 program instruction itself.
+const ED25519_CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Swap with signature
+pub fn swap_with_signature<'info>(
+    ctx: Context<'_, '_, '_, 'info, SwapWithSignature<'info>>,
+    args: SwapWithSignatureArgs,
+) -> Result<()> {
+    let accounts = &mut *ctx.accounts;
+    let owner = accounts.signed_swap_authority.owner;
+
+    let intent = SwapIntent {
+        swap: accounts.swap.key(),
+        input: accounts.input.user.key(),
+        output: accounts.output.user.key(),
+        amount_in: args.amount_in,
+        minimum_amount_out: args.minimum_amount_out,
+        nonce: args.nonce,
+        expiry: args.expiry,
+    };
+    verify_ed25519_signature(&accounts.instructions, &owner, &intent.try_to_vec()?)?;
+
+    // each intent may only be executed once, before it expires
+    super::check_deadline(Some(intent.expiry))?;
+    let signed_swap_authority = &mut accounts.signed_swap_authority;
+    invariant!(
+        intent.nonce == signed_swap_authority.nonce,
+        InvalidSwapIntentNonce
+    );
+    signed_swap_authority.nonce = unwrap_int!(signed_swap_authority.nonce.checked_add(1));
+
+    let bump = [signed_swap_authority.bump];
+    let seeds: &[&[u8]] = &[b"SignedSwapAuthority", owner.as_ref(), &bump];
+    let mut user = SwapUser {
+        token_program: &accounts.token_program,
+        user_authority: owner,
+        input_authority: accounts.signed_swap_authority.to_account_info(),
+        input_authority_seeds: &[seeds],
+        swap: &mut accounts.swap,
+    };
+    let optional_accounts =
+        SwapOptionalAccounts::load(ctx.remaining_accounts, &user, &accounts.output)?;
+
+    let return_data = process_swap(
+        &mut user,
+        &accounts.input,
+        &accounts.output,
+        &optional_accounts,
+        intent.amount_in,
+        intent.minimum_amount_out,
+        false,
+    )?;
+    super::write_return_data(&return_data)
+}
+
+/// Ensures that the instruction preceding this one is an ed25519 program instruction
+/// which verifies a single signature of `signer` over `message`.
+///
+/// The ed25519 program fails the transaction if the signature is invalid,
+/// so only the public key and message it verified need to be checked.
+fn verify_ed25519_signature(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current_index = load_current_index_checked(instructions)?;
+    let verify_index = unwrap_opt!(current_index.checked_sub(1), InvalidSwapIntentSignature);
+    let verify_ix = load_instruction_at_checked(verify_index.into(), instructions)?;
+    invariant!(
+        verify_ix.program_id == ed25519_program::ID,
+        InvalidSwapIntentSignature
+    );
+
+    let data = &verify_ix.data;
+    invariant!(data.first() == Some(&1), InvalidSwapIntentSignature);
+    // the offsets are, in order: signature, signature instruction index, public key,
+    // public key instruction index, message, message size, message instruction index
+    let offsets: Vec<u16> = unwrap_opt!(data.get(ED25519_HEADER_LEN..), InvalidSwapIntentSignature)
+        .chunks_exact(2)
+        .take(ED25519_NUM_OFFSETS)
+        .map(|offset| u16::from_le_bytes([offset[0], offset[1]]))
+        .collect();
+    invariant!(
+        offsets.len() == ED25519_NUM_OFFSETS,
+        InvalidSwapIntentSignature
+    );
+    let (public_key_offset, message_offset, message_size) = (offsets[2], offsets[4], offsets[5]);
+
+    // all of the signed data must be in the ed25519 program instruction itself
+    invariant!(
+        [offsets[1], offsets[3], offsets[6]]
+            .iter()
+            .all(|&index| index == ED25519_CURRENT_INSTRUCTION),
+        InvalidSwapIntentSignature
+    );
+
+    let public_key = unwrap_opt!(
+        read_slice(data, public_key_offset, PUBKEY_BYTES),
+        InvalidSwapIntentSignature
+    );
+    invariant!(public_key == signer.as_ref(), InvalidSwapIntentSignature);
+    let signed_message = unwrap_opt!(
+        read_slice(data, message_offset, message_size.into()),
+        InvalidSwapIntentSignature
+    );
+    invariant!(signed_message == message, InvalidSwapIntentSignature);
+
+    Ok(())
+}
+
+/// Reads `len` bytes at `offset` of `data`.
+fn read_slice(data: &[u8], offset: u16, len: usize) -> Option<&[u8]> {
+    let start: usize = offset.into();
+    data.get(start..start.checked_add(len)?)
+}
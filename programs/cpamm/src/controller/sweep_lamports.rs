@@ -0,0 +1,35 @@
+//! [crate::cpamm::sweep_lamports] instruction controller.
+
+use crate::*;
+
+/// Sweep lamports
+pub fn sweep_lamports(ctx: Context<SweepLamports>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let rent = Rent::get()?;
+    let recipient = accounts.recipient.to_account_info();
+
+    let mut amount: u64 = 0;
+    for account in [
+        accounts.swap.to_account_info(),
+        accounts.swap_meta.to_account_info(),
+    ] {
+        let excess = account
+            .lamports()
+            .saturating_sub(rent.minimum_balance(account.data_len()));
+        if excess == 0 {
+            continue;
+        }
+        // the accounts are owned by the program, so it may debit them directly
+        **account.try_borrow_mut_lamports()? = unwrap_int!(account.lamports().checked_sub(excess));
+        **recipient.try_borrow_mut_lamports()? =
+            unwrap_int!(recipient.lamports().checked_add(excess));
+        amount = unwrap_int!(amount.checked_add(excess));
+    }
+
+    emit!(LamportsSweptEvent {
+        swap: accounts.swap.key(),
+        recipient: recipient.key(),
+        amount,
+    });
+    Ok(())
+}
@@ -0,0 +1,67 @@
+//! [crate::cpamm::queue_change] and [crate::cpamm::execute_change] instruction controllers.
+
+use crate::*;
+
+/// Queue change
+pub fn queue_change(ctx: Context<QueueChange>, change: TimelockedChange) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let target = accounts.target.to_account_info();
+    change.validate_target(&accounts.factory, &target, accounts.admin.key())?;
+    match change {
+        TimelockedChange::Fees { new_fees } => new_fees.validate_settable()?,
+        TimelockedChange::AmpFactor { amp_factor } => {
+            let swap: Account<SwapInfo> = Account::try_from(&target)?;
+            invariant!(swap.amp_factor != 0, CurveNotSupported);
+            invariant!(
+                amp_factor != 0 && amp_factor <= xyk::stable::MAX_AMP,
+                "invalid amplification coefficient"
+            );
+        }
+        TimelockedChange::Admin { .. } => {}
+        TimelockedChange::TimelockDelay { delay_seconds } => {
+            invariant!(
+                (0..=MAX_TIMELOCK_DELAY_SECONDS).contains(&delay_seconds),
+                "invalid timelock delay"
+            );
+        }
+    }
+
+    let eta = unwrap_int!(Clock::get()?
+        .unix_timestamp
+        .checked_add(accounts.factory.timelock_delay_seconds));
+    let pending_change = &mut ctx.accounts.pending_change;
+    pending_change.factory = ctx.accounts.factory.key();
+    pending_change.target = target.key();
+    pending_change.bump = unwrap_bump!(ctx, "pending_change");
+    pending_change.eta = eta;
+    pending_change.change = change;
+    Ok(())
+}
+
+/// Execute change
+pub fn execute_change(ctx: Context<PendingChangeContext>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let pending_change = &ctx.accounts.pending_change;
+    invariant!(now >= pending_change.eta, TimelockNotReady);
+
+    match pending_change.change {
+        TimelockedChange::Fees { new_fees } => {
+            let mut swap: Account<SwapInfo> = Account::try_from(&ctx.accounts.target)?;
+            swap.fees = new_fees;
+            swap.fees_last_changed_at = now;
+            swap.exit(&crate::ID)?;
+        }
+        TimelockedChange::AmpFactor { amp_factor } => {
+            let mut swap: Account<SwapInfo> = Account::try_from(&ctx.accounts.target)?;
+            swap.amp_factor = amp_factor;
+            swap.exit(&crate::ID)?;
+        }
+        TimelockedChange::Admin { new_admin } => {
+            ctx.accounts.factory.pending_admin = new_admin;
+        }
+        TimelockedChange::TimelockDelay { delay_seconds } => {
+            ctx.accounts.factory.timelock_delay_seconds = delay_seconds;
+        }
+    }
+    Ok(())
+}
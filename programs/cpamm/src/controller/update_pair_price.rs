@@ -0,0 +1,93 @@
+//! [crate::cpamm::update_pair_price] instruction controller.
+
+use crate::*;
+use num_traits::ToPrimitive;
+
+/// Returns `price * weight / total_weight`, without overflowing on large prices.
+fn weighted_price(price: u128, weight: u64, total_weight: u64) -> Option<u128> {
+    let total_weight: u128 = total_weight.into();
+    let quotient = price.checked_div(total_weight)?;
+    let remainder = price.checked_rem(total_weight)?;
+    quotient.checked_mul(weight.into())?.checked_add(
+        remainder
+            .checked_mul(weight.into())?
+            .checked_div(total_weight)?,
+    )
+}
+
+/// Update pair price
+pub fn update_pair_price(ctx: Context<UpdatePairPrice>) -> Result<()> {
+    let pair_price = &mut ctx.accounts.pair_price;
+
+    // (price_0, price_1, liquidity) of each swap with liquidity
+    let mut prices: Vec<(u128, u128, u64)> = vec![];
+    let mut last_pool_fee_tier: Option<u8> = None;
+    for info in ctx.remaining_accounts.iter() {
+        let swap: Account<SwapInfo> = Account::try_from(info)?;
+        assert_keys_eq!(swap.factory, pair_price.factory);
+        assert_keys_eq!(swap.token_0.mint, pair_price.token_0_mint);
+        assert_keys_eq!(swap.token_1.mint, pair_price.token_1_mint);
+        // each pool fee tier has one swap, so this prevents counting a swap twice
+        if let Some(last_pool_fee_tier) = last_pool_fee_tier {
+            invariant!(
+                last_pool_fee_tier < swap.pool_fee_tier,
+                "swaps must be in ascending order of pool fee tier"
+            );
+        }
+        last_pool_fee_tier = Some(swap.pool_fee_tier);
+
+        let (reserve_0, reserve_1) = swap.reserve_amounts();
+        if reserve_0 == 0 || reserve_1 == 0 {
+            continue;
+        }
+        prices.push((
+            unwrap_int!(quote::spot_price(&swap, swap.token_0.mint)),
+            unwrap_int!(quote::spot_price(&swap, swap.token_1.mint)),
+            unwrap_int!(xyk::calculate_initial_swap_pool_amount(
+                reserve_0, reserve_1
+            )),
+        ));
+    }
+
+    let total_liquidity = prices.iter().try_fold(0u64, |total, (_, _, liquidity)| {
+        total.checked_add(*liquidity)
+    });
+    let total_liquidity = unwrap_int!(total_liquidity);
+    invariant!(total_liquidity > 0, "the pair has no liquidity");
+
+    let (mut price_0, mut price_1) = (0u128, 0u128);
+    for (swap_price_0, swap_price_1, liquidity) in prices.iter() {
+        price_0 = unwrap_int!(price_0.checked_add(unwrap_int!(weighted_price(
+            *swap_price_0,
+            *liquidity,
+            total_liquidity
+        ))));
+        price_1 = unwrap_int!(price_1.checked_add(unwrap_int!(weighted_price(
+            *swap_price_1,
+            *liquidity,
+            total_liquidity
+        ))));
+    }
+
+    // the previous prices held since the last update
+    let now = Clock::get()?.unix_timestamp;
+    if pair_price.last_update_ts != 0 && now > pair_price.last_update_ts {
+        let time_elapsed: u128 = unwrap_int!(now
+            .checked_sub(pair_price.last_update_ts)
+            .and_then(|v| v.to_u128()));
+        // overflow is desired
+        pair_price.price_0_cumulative = pair_price
+            .price_0_cumulative
+            .wrapping_add(pair_price.price_0.wrapping_mul(time_elapsed));
+        pair_price.price_1_cumulative = pair_price
+            .price_1_cumulative
+            .wrapping_add(pair_price.price_1.wrapping_mul(time_elapsed));
+    }
+
+    pair_price.num_swaps = unwrap_int!(prices.len().to_u8());
+    pair_price.total_liquidity = total_liquidity;
+    pair_price.price_0 = price_0;
+    pair_price.price_1 = price_1;
+    pair_price.last_update_ts = now;
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! [crate::cpamm::verify_pool] instruction controller.
+
+use crate::*;
+use anchor_lang::solana_program::program_option::COption;
+
+/// Verify pool
+pub fn verify_pool(ctx: Context<VerifyPool>) -> Result<()> {
+    let violation = match ctx.accounts.find_violation() {
+        Some(violation) => violation,
+        None => return Ok(()),
+    };
+
+    // the swap is paused instead of failing the instruction, so the pause persists
+    let swap_info = &mut ctx.accounts.swap;
+    swap_info.is_paused = true;
+
+    emit!(PoolViolationEvent {
+        lp_mint: swap_info.pool_mint,
+        swap: swap_info.key(),
+        violation,
+    });
+    Ok(())
+}
+
+impl<'info> VerifyPool<'info> {
+    /// Returns the first inconsistency of the swap with its reserves and pool mint, if any.
+    fn find_violation(&self) -> Option<PoolViolation> {
+        let swap_info = &self.swap;
+        for (reserve, reserve_amount) in [
+            (&self.reserve_0, swap_info.reserve_0_amount),
+            (&self.reserve_1, swap_info.reserve_1_amount),
+        ] {
+            let mint = reserve.mint;
+            // tokens sent directly to the reserve are a surplus, not a violation
+            if reserve.amount < reserve_amount {
+                return Some(PoolViolation::ReserveShortfall { mint });
+            }
+            if reserve.is_frozen() {
+                return Some(PoolViolation::ReserveFrozen { mint });
+            }
+            if reserve.owner != swap_info.key()
+                || reserve.delegate.is_some()
+                || reserve.close_authority.is_some()
+            {
+                return Some(PoolViolation::ReserveAuthority { mint });
+            }
+        }
+
+        if self.pool_mint.mint_authority != COption::Some(swap_info.key()) {
+            return Some(PoolViolation::PoolMintAuthority);
+        }
+        // the balances are used since swaps which predate reserve tracking record no reserves
+        if self.pool_mint.supply > 0 && (self.reserve_0.amount == 0 || self.reserve_1.amount == 0) {
+            return Some(PoolViolation::PoolMintSupply);
+        }
+        None
+    }
+}
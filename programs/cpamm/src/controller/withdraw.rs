@@ -3,16 +3,26 @@
 use crate::*;
 use anchor_spl::token;
 
-use xyk::{pool_tokens_to_trading_tokens, RoundDirection};
+use xyk::{pool_tokens_to_trading_tokens, rounding};
 
 pub struct WithdrawArgs {
     pub amount_in: u64,
     pub minimum_amount_out_0: u64,
     pub minimum_amount_out_1: u64,
+    pub deadline: Option<i64>,
 }
 
 /// Withdraw
 pub fn withdraw(ctx: Context<Withdraw>, args: WithdrawArgs) -> Result<()> {
+    super::check_deadline(args.deadline)?;
+    super::check_withdraw_cooldown(
+        &ctx.accounts.user.swap,
+        &ctx.accounts.user.token_program,
+        &ctx.accounts.pool_mint,
+        &ctx.accounts.input_lp,
+        ctx.remaining_accounts,
+    )?;
+
     // update cumulative price info.
     // we call this before the short circuit
     // so the numbers are accurate.
@@ -20,19 +30,20 @@ pub fn withdraw(ctx: Context<Withdraw>, args: WithdrawArgs) -> Result<()> {
 
     // skip the withdrawal if nothing is being withdrawn
     if args.amount_in == 0 {
-        return Ok(());
+        return super::write_return_data(&WithdrawReturnData::default());
     }
 
+    let (reserve_0, reserve_1) = ctx.accounts.user.swap.reserve_amounts();
     let result = unwrap_int!(pool_tokens_to_trading_tokens(
         args.amount_in,
         ctx.accounts.pool_mint.supply,
-        ctx.accounts.output_0.reserve.amount,
-        ctx.accounts.output_1.reserve.amount,
-        RoundDirection::Floor,
+        reserve_0,
+        reserve_1,
+        rounding::POOL_OUTPUT,
     ));
 
-    let token_0_amount = std::cmp::min(ctx.accounts.output_0.reserve.amount, result.token_a_amount);
-    let token_1_amount = std::cmp::min(ctx.accounts.output_1.reserve.amount, result.token_b_amount);
+    let token_0_amount = std::cmp::min(reserve_0, result.token_a_amount);
+    let token_1_amount = std::cmp::min(reserve_1, result.token_b_amount);
 
     // pool token output should be at least 1 for each token
     invariant!(token_0_amount > 0, InsufficientLiquidity);
@@ -41,11 +52,11 @@ pub fn withdraw(ctx: Context<Withdraw>, args: WithdrawArgs) -> Result<()> {
     // ensure we are meeting the max slippage
     invariant!(
         token_0_amount >= args.minimum_amount_out_0,
-        ExceededSlippage
+        WithdrawBelowMinimumAmountOut
     );
     invariant!(
         token_1_amount >= args.minimum_amount_out_1,
-        ExceededSlippage
+        WithdrawBelowMinimumAmountOut
     );
 
     // Burn LP tokens
@@ -63,16 +74,25 @@ pub fn withdraw(ctx: Context<Withdraw>, args: WithdrawArgs) -> Result<()> {
     )?;
 
     // Transfer tokens from reserve to user
-    ctx.accounts
+    let amount_out_0 = ctx
+        .accounts
         .withdraw_token(&ctx.accounts.output_0, token_0_amount)?;
-    ctx.accounts
+    let amount_out_1 = ctx
+        .accounts
         .withdraw_token(&ctx.accounts.output_1, token_1_amount)?;
+    let accounts = &mut *ctx.accounts;
+    super::track_reserve(&mut accounts.user.swap, &accounts.output_0.reserve)?;
+    super::track_reserve(&mut accounts.user.swap, &accounts.output_1.reserve)?;
 
+    let (reserve_0, reserve_1) = ctx.accounts.user.swap.reserve_amounts();
     emit!(WithdrawEvent {
         lp_mint: ctx.accounts.pool_mint.key(),
         pool_token_amount: args.amount_in,
         token_0_amount,
-        token_1_amount
+        token_1_amount,
+        swap: ctx.accounts.user.swap.key(),
+        reserve_0,
+        reserve_1,
     });
 
     ctx.accounts.track_cumulative_withdraw_volume(
@@ -87,17 +107,17 @@ pub fn withdraw(ctx: Context<Withdraw>, args: WithdrawArgs) -> Result<()> {
         InsufficientLiquidityPostWithdrawal
     );
 
-    Ok(())
+    super::write_return_data(&WithdrawReturnData {
+        pool_token_amount: args.amount_in,
+        amount_out_0,
+        amount_out_1,
+    })
 }
 
 impl<'info> Withdraw<'info> {
     fn update_cumulative_price_info(&mut self) -> Result<()> {
         // update price info
-        let price_info = &mut self.user.swap.price_info;
-        price_info.update_cumulative_price_info(
-            self.output_0.reserve.amount,
-            self.output_1.reserve.amount,
-        )
+        self.user.swap.update_price_info()
     }
 
     /// Tracks and saves the cumulative swap volume.
@@ -123,8 +143,8 @@ impl<'info> Withdraw<'info> {
         Ok(())
     }
 
-    /// Withdraws a token.
-    fn withdraw_token(&self, output: &SwapTokenWithFees<'info>, amount: u64) -> Result<()> {
+    /// Withdraws a token, returning the amount sent to the user.
+    fn withdraw_token(&self, output: &SwapTokenWithFees<'info>, amount: u64) -> Result<u64> {
         let token_swap = &self.user.swap;
         let token_program = &self.user.token_program;
 
@@ -133,6 +153,7 @@ impl<'info> Withdraw<'info> {
             unwrap_int!(swap_fees.compute_withdraw_fees(amount));
 
         // Transfer token from reserve to user
+        let amount_out = unwrap_int!(amount.checked_sub(withdraw_fee));
         let seeds = gen_swap_signer_seeds!(token_swap);
         let signer_seeds = &[&seeds[..]];
         token::transfer(
@@ -145,7 +166,7 @@ impl<'info> Withdraw<'info> {
                 },
                 signer_seeds,
             ),
-            unwrap_int!(amount.checked_sub(withdraw_fee)),
+            amount_out,
         )?;
 
         if admin_withdraw_fee > 0 {
@@ -165,6 +186,6 @@ impl<'info> Withdraw<'info> {
             )?;
         }
 
-        Ok(())
+        Ok(amount_out)
     }
 }
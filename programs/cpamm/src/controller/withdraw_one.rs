@@ -0,0 +1,296 @@
+//! [crate::cpamm::withdraw_one] instruction processor.
+
+use crate::*;
+use anchor_spl::token::{self, TokenAccount};
+
+use xyk::{pool_tokens_to_trading_tokens, rounding, SwapResult};
+
+pub struct WithdrawOneArgs {
+    pub pool_token_amount: u64,
+    pub minimum_amount_out: u64,
+    pub deadline: Option<i64>,
+}
+
+/// Withdraw one
+pub fn withdraw_one(ctx: Context<WithdrawOne>, args: WithdrawOneArgs) -> Result<()> {
+    super::check_deadline(args.deadline)?;
+    super::check_withdraw_cooldown(
+        &ctx.accounts.user.swap,
+        &ctx.accounts.user.token_program,
+        &ctx.accounts.pool_mint,
+        &ctx.accounts.input_lp,
+        ctx.remaining_accounts,
+    )?;
+
+    // update cumulative price info.
+    // we call this before the short circuit
+    // so the numbers are accurate.
+    ctx.accounts.update_cumulative_price_info()?;
+
+    // skip the withdrawal if nothing is being withdrawn
+    if args.pool_token_amount == 0 {
+        return super::write_return_data(&WithdrawReturnData::default());
+    }
+
+    let swap_info = &ctx.accounts.user.swap;
+    let output_reserve = swap_info.reserve_amount(ctx.accounts.output.reserve.mint);
+    let other_reserve = swap_info.reserve_amount(ctx.accounts.other_reserve.mint);
+    let result = unwrap_int!(pool_tokens_to_trading_tokens(
+        args.pool_token_amount,
+        ctx.accounts.pool_mint.supply,
+        output_reserve,
+        other_reserve,
+        rounding::POOL_OUTPUT,
+    ));
+    let output_amount = std::cmp::min(output_reserve, result.token_a_amount);
+    let other_amount = std::cmp::min(other_reserve, result.token_b_amount);
+
+    let swap_fees = ctx.accounts.user.swap.fees;
+    let (output_withdraw_fee, output_admin_withdraw_fee) =
+        unwrap_int!(swap_fees.compute_withdraw_fees(output_amount));
+    let (other_withdraw_fee, other_admin_withdraw_fee) =
+        unwrap_int!(swap_fees.compute_withdraw_fees(other_amount));
+
+    // the reserves after the withdrawal, with the non-admin withdraw fees remaining in the pool
+    let withdrawn_output_reserve = unwrap_int!(unwrap_int!(
+        output_reserve.checked_sub(output_amount)
+    )
+    .checked_add(unwrap_int!(
+        output_withdraw_fee.checked_sub(output_admin_withdraw_fee)
+    )));
+    let withdrawn_other_reserve = unwrap_int!(unwrap_int!(other_reserve.checked_sub(other_amount))
+        .checked_add(unwrap_int!(
+            other_withdraw_fee.checked_sub(other_admin_withdraw_fee)
+        )));
+
+    // swap the withdrawn other token back into the pool for the output token
+    let swap_amount = unwrap_int!(other_amount.checked_sub(other_withdraw_fee));
+    let (swap_result, trade_fee, admin_trade_fee) = match swap_info.curve_swap(
+        ctx.accounts.other_reserve.mint,
+        swap_amount,
+        withdrawn_other_reserve,
+        withdrawn_output_reserve,
+    ) {
+        Some(swap_result) => {
+            super::check_swap_rate_limit(&mut ctx.accounts.user.swap)?;
+            let swap_info = &ctx.accounts.user.swap;
+            let (trade_fee, admin_trade_fee) = unwrap_int!(unwrap_int!(swap_info
+                .swap_fees_for_trade(
+                    ctx.accounts.other_reserve.mint,
+                    swap_result.source_amount_swapped,
+                    withdrawn_other_reserve
+                ))
+            .compute_trade_fees(swap_result.destination_amount_swapped));
+            super::check_max_trade_size(
+                swap_info,
+                swap_result.source_amount_swapped,
+                withdrawn_other_reserve,
+            )?;
+            super::check_price_impact(
+                swap_info,
+                &swap_result,
+                withdrawn_other_reserve,
+                withdrawn_output_reserve,
+            )?;
+            super::check_oracle_band(
+                swap_info,
+                super::find_oracle(swap_info, ctx.remaining_accounts),
+                ctx.accounts.other_reserve.mint,
+                &swap_result,
+            )?;
+            (swap_result, trade_fee, admin_trade_fee)
+        }
+        // too small to swap, so it remains in the pool
+        None => (
+            SwapResult {
+                source_amount_swapped: 0,
+                destination_amount_swapped: 0,
+            },
+            0,
+            0,
+        ),
+    };
+
+    let amount_out = unwrap_int!(unwrap_int!(output_amount.checked_sub(output_withdraw_fee))
+        .checked_add(unwrap_int!(swap_result
+            .destination_amount_swapped
+            .checked_sub(trade_fee))));
+    invariant!(amount_out > 0, InsufficientLiquidity);
+    invariant!(
+        amount_out >= args.minimum_amount_out,
+        WithdrawBelowMinimumAmountOut
+    );
+
+    // Burn LP tokens
+    token::burn(
+        CpiContext::new(
+            ctx.accounts.user.token_program.to_account_info(),
+            token::Burn {
+                mint: ctx.accounts.pool_mint.to_account_info(),
+                to: ctx.accounts.input_lp.to_account_info(),
+                authority: ctx.accounts.user.user_authority.to_account_info(),
+            },
+        ),
+        args.pool_token_amount,
+    )?;
+
+    // Transfer tokens from reserve to user, and the admin fees to the fees accounts
+    let accounts = &ctx.accounts;
+    accounts.transfer_from_reserve(&accounts.output.reserve, &accounts.output.user, amount_out)?;
+    accounts.transfer_from_reserve(
+        &accounts.output.reserve,
+        &accounts.output.fees,
+        unwrap_int!(output_admin_withdraw_fee.checked_add(admin_trade_fee)),
+    )?;
+    accounts.transfer_from_reserve(
+        &accounts.other_reserve,
+        &accounts.other_fees,
+        other_admin_withdraw_fee,
+    )?;
+
+    let accounts = &mut *ctx.accounts;
+    super::track_reserve(&mut accounts.user.swap, &accounts.output.reserve)?;
+    super::track_reserve(&mut accounts.user.swap, &accounts.other_reserve)?;
+    let new_reserves = (
+        accounts
+            .user
+            .swap
+            .reserve_amount(accounts.other_reserve.mint),
+        accounts
+            .user
+            .swap
+            .reserve_amount(accounts.output.reserve.mint),
+    );
+    let (token_0_amount, token_1_amount) = ctx.accounts.to_token_amounts(amount_out, 0);
+    let (reserve_0, reserve_1) = ctx
+        .accounts
+        .to_token_amounts(new_reserves.1, new_reserves.0);
+    emit!(WithdrawEvent {
+        lp_mint: ctx.accounts.pool_mint.key(),
+        pool_token_amount: args.pool_token_amount,
+        token_0_amount,
+        token_1_amount,
+        swap: ctx.accounts.user.swap.key(),
+        reserve_0,
+        reserve_1,
+    });
+
+    let other_mint = ctx.accounts.other_reserve.mint;
+    super::update_volatility(
+        &mut ctx.accounts.user.swap,
+        other_mint,
+        (other_reserve, output_reserve),
+        new_reserves,
+    )?;
+    super::update_circuit_breaker(&mut ctx.accounts.user.swap)?;
+
+    ctx.accounts.track_cumulative_volume(
+        args.pool_token_amount,
+        (output_amount, other_amount),
+        &swap_result,
+        trade_fee,
+    )?;
+    let output_mint = ctx.accounts.output.reserve.mint;
+    unwrap_int!(ctx
+        .accounts
+        .user
+        .swap
+        .track_fees_paid_out(output_mint, admin_trade_fee));
+
+    ctx.accounts.pool_mint.reload()?;
+    invariant!(
+        ctx.accounts.pool_mint.supply >= xyk::MINIMUM_LIQUIDITY,
+        InsufficientLiquidityPostWithdrawal
+    );
+
+    super::write_return_data(&WithdrawReturnData {
+        pool_token_amount: args.pool_token_amount,
+        amount_out_0: token_0_amount,
+        amount_out_1: token_1_amount,
+    })
+}
+
+impl<'info> WithdrawOne<'info> {
+    /// Returns true if the output token is token 0 of the swap.
+    fn is_output_token_0(&self) -> bool {
+        self.output.reserve.mint == self.user.swap.token_0.mint
+    }
+
+    /// Orders amounts of the output and other tokens as token 0 and token 1 amounts.
+    fn to_token_amounts(&self, output_amount: u64, other_amount: u64) -> (u64, u64) {
+        if self.is_output_token_0() {
+            (output_amount, other_amount)
+        } else {
+            (other_amount, output_amount)
+        }
+    }
+
+    fn update_cumulative_price_info(&mut self) -> Result<()> {
+        // update price info
+        self.user.swap.update_price_info()
+    }
+
+    /// Transfers tokens out of one of the reserves.
+    fn transfer_from_reserve(
+        &self,
+        reserve: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+    ) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let token_swap = &self.user.swap;
+        let seeds = gen_swap_signer_seeds!(token_swap);
+        let signer_seeds = &[&seeds[..]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.user.token_program.to_account_info(),
+                token::Transfer {
+                    from: reserve.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: token_swap.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+
+    /// Tracks and saves the cumulative withdraw volume and the volume of the internal swap.
+    fn track_cumulative_volume(
+        &mut self,
+        pool_token_amount: u64,
+        (output_amount, other_amount): (u64, u64),
+        swap_result: &SwapResult,
+        trade_fee: u64,
+    ) -> Result<()> {
+        // record cumulative volume numbers
+        let is_output_token_0 = self.is_output_token_0();
+        let cumulative_stats = &mut self.user.swap.cumulative_stats;
+        cumulative_stats.total_lp_redeemed = unwrap_int!(cumulative_stats
+            .total_lp_redeemed
+            .checked_add(pool_token_amount.into()));
+
+        let (cum_output_token, cum_other_token) = if is_output_token_0 {
+            (&mut cumulative_stats.token_0, &mut cumulative_stats.token_1)
+        } else {
+            (&mut cumulative_stats.token_1, &mut cumulative_stats.token_0)
+        };
+        cum_output_token.total_withdraw_volume = unwrap_int!(cum_output_token
+            .total_withdraw_volume
+            .checked_add(output_amount.into()));
+        cum_other_token.total_withdraw_volume = unwrap_int!(cum_other_token
+            .total_withdraw_volume
+            .checked_add(other_amount.into()));
+        cum_other_token.total_input_volume = unwrap_int!(cum_other_token
+            .total_input_volume
+            .checked_add(swap_result.source_amount_swapped.into()));
+        cum_output_token.total_output_volume = unwrap_int!(cum_output_token
+            .total_output_volume
+            .checked_add(swap_result.destination_amount_swapped.into()));
+        cum_output_token.total_trade_fees =
+            unwrap_int!(cum_output_token.total_trade_fees.checked_add(trade_fee));
+        Ok(())
+    }
+}
@@ -17,7 +17,85 @@ pub struct NewPoolEvent {
     pub initial_liquidity: u64,
 }
 
+/// Emitted on a successful [crate::cpamm::new_swap] with the full parameters of the swap,
+/// after the [NewPoolEvent].
+#[event]
+pub struct PoolCreatedEvent {
+    /// The [crate::SwapInfo].
+    #[index]
+    pub swap: Pubkey,
+    /// The [crate::Factory].
+    pub factory: Pubkey,
+    /// Mint of the LP token.
+    pub lp_mint: Pubkey,
+    /// Mint of token 0.
+    pub mint_0: Pubkey,
+    /// Mint of token 1.
+    pub mint_1: Pubkey,
+    /// Initial reserves of token 0.
+    pub reserve_0: u64,
+    /// Initial reserves of token 1.
+    pub reserve_1: u64,
+    /// Fees of the swap.
+    pub fees: crate::SwapFees,
+    /// Pool fee tier of the swap.
+    pub pool_fee_tier: u8,
+    /// Amplification coefficient of a stable swap, or zero.
+    pub amp_factor: u64,
+    /// Weight of token 0 of a weighted swap, or zero.
+    pub weight_0: u8,
+    /// Weight of token 1 of a weighted swap, or zero.
+    pub weight_1: u8,
+    /// Creator of the swap.
+    pub creator: Pubkey,
+    /// When the swap was created.
+    pub timestamp: i64,
+}
+
+/// Emitted on a successful [crate::cpamm::collect_protocol_fees].
+#[event]
+pub struct FeesCollectedEvent {
+    /// The [crate::SwapInfo].
+    #[index]
+    pub swap: Pubkey,
+    /// Owner of the treasury token accounts which received the fees.
+    pub treasury: Pubkey,
+    /// Amount of token 0 collected.
+    pub amount_0: u64,
+    /// Amount of token 1 collected.
+    pub amount_1: u64,
+}
+
+/// Emitted on a successful [crate::cpamm::buyback_and_burn].
+#[event]
+pub struct BuybackEvent {
+    /// The [crate::SwapInfo] whose admin fees were spent.
+    #[index]
+    pub fee_swap: Pubkey,
+    /// The [crate::SwapInfo] through which the buyback mint was bought.
+    pub swap: Pubkey,
+    /// Amount of admin fees spent.
+    pub amount_in: u64,
+    /// Amount of the buyback mint burned.
+    pub amount_burned: u64,
+}
+
+/// Emitted on a successful [crate::cpamm::compound_admin_fees].
+#[event]
+pub struct AdminFeesCompoundedEvent {
+    /// The [crate::SwapInfo].
+    #[index]
+    pub swap: Pubkey,
+    /// Amount of token 0 deposited from the admin fees.
+    pub amount_0: u64,
+    /// Amount of token 1 deposited from the admin fees.
+    pub amount_1: u64,
+    /// Amount of LP tokens minted to the protocol-owned liquidity account.
+    pub pool_token_amount: u64,
+}
+
 /// Emitted on a successful [crate::cpamm::deposit].
+/// Also emitted by the other deposit instructions.
 #[event]
 pub struct DepositEvent {
     /// Mint of the LP token.
@@ -29,9 +107,16 @@ pub struct DepositEvent {
     pub token_0_amount: u64,
     /// Amount of token 1 deposited.
     pub token_1_amount: u64,
+    /// The [crate::SwapInfo].
+    pub swap: Pubkey,
+    /// Reserves of token 0 after the deposit.
+    pub reserve_0: u64,
+    /// Reserves of token 1 after the deposit.
+    pub reserve_1: u64,
 }
 
 /// Emitted on a successful [crate::cpamm::withdraw].
+/// Also emitted by [crate::cpamm::withdraw_one].
 #[event]
 pub struct WithdrawEvent {
     /// Mint of the LP token.
@@ -43,9 +128,34 @@ pub struct WithdrawEvent {
     pub token_0_amount: u64,
     /// Amount of token 1 withdrawn.
     pub token_1_amount: u64,
+    /// The [crate::SwapInfo].
+    pub swap: Pubkey,
+    /// Reserves of token 0 after the withdrawal.
+    pub reserve_0: u64,
+    /// Reserves of token 1 after the withdrawal.
+    pub reserve_1: u64,
+}
+
+/// Emitted on a successful [crate::cpamm::flash_swap].
+#[event]
+pub struct FlashSwapEvent {
+    /// Mint of the LP token.
+    #[index]
+    pub lp_mint: Pubkey,
+    /// The program invoked with the borrowed tokens.
+    pub callback_program: Pubkey,
+    /// Amount of token 0 borrowed.
+    pub amount_out_0: u64,
+    /// Amount of token 1 borrowed.
+    pub amount_out_1: u64,
+    /// Amount of token 0 returned to the reserves.
+    pub amount_in_0: u64,
+    /// Amount of token 1 returned to the reserves.
+    pub amount_in_1: u64,
 }
 
 /// Emitted on a successful [crate::cpamm::swap].
+/// Also emitted by the other swap instructions, once for each pool swapped against.
 #[event]
 pub struct SwapEvent {
     /// Mint of the LP token.
@@ -59,4 +169,96 @@ pub struct SwapEvent {
     pub source_amount_swapped: u64,
     /// Amount of destination token swapped
     pub destination_amount_swapped: u64,
+    /// The [crate::SwapInfo].
+    pub swap: Pubkey,
+    /// The authority of the user performing the swap.
+    pub user_authority: Pubkey,
+    /// Trade fee, including the admin trade fee.
+    /// Exact out swaps charge it on the input token; other swaps charge it on the output token.
+    pub trade_fee: u64,
+    /// Portion of the trade fee sent to the admin.
+    pub admin_trade_fee: u64,
+    /// Reserves of the input token after the swap.
+    pub input_reserve: u64,
+    /// Reserves of the output token after the swap.
+    pub output_reserve: u64,
+    /// When the swap was executed.
+    pub timestamp: i64,
+}
+
+/// Emitted when a swap trips the circuit breaker of its [crate::SwapInfo].
+#[event]
+pub struct CircuitBreakerTrippedEvent {
+    /// Mint of the LP token.
+    #[index]
+    pub lp_mint: Pubkey,
+    /// Reserves of token 0 after the swap.
+    pub reserve_0: u64,
+    /// Reserves of token 1 after the swap.
+    pub reserve_1: u64,
+    /// Swaps are blocked until this slot.
+    pub until_slot: u64,
+}
+
+/// An inconsistency of a [crate::SwapInfo] found by [crate::cpamm::verify_pool].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolViolation {
+    /// The reserve of the mint holds less than the amount recorded by the swap.
+    ReserveShortfall {
+        /// Mint of the reserve.
+        mint: Pubkey,
+    },
+    /// The reserve of the mint is frozen.
+    ReserveFrozen {
+        /// Mint of the reserve.
+        mint: Pubkey,
+    },
+    /// The reserve of the mint is not solely controlled by the swap: it has another owner,
+    /// a delegate or a close authority.
+    ReserveAuthority {
+        /// Mint of the reserve.
+        mint: Pubkey,
+    },
+    /// The swap is not the mint authority of its pool mint.
+    PoolMintAuthority,
+    /// LP tokens are outstanding, but a reserve is empty.
+    PoolMintSupply,
+}
+
+/// Emitted when [crate::cpamm::verify_pool] finds a violation and pauses the swap.
+#[event]
+pub struct PoolViolationEvent {
+    /// Mint of the LP token.
+    #[index]
+    pub lp_mint: Pubkey,
+    /// The [crate::SwapInfo].
+    pub swap: Pubkey,
+    /// The first violation found.
+    pub violation: PoolViolation,
+}
+
+/// Emitted on a successful [crate::cpamm::verify_upgrade_authority].
+#[event]
+pub struct UpgradeAuthorityVerifiedEvent {
+    /// The [crate::Factory].
+    #[index]
+    pub factory: Pubkey,
+    /// Upgrade authority of the program. The default key if the program cannot be upgraded.
+    pub upgrade_authority: Pubkey,
+    /// Slot in which the program was last deployed.
+    pub last_deploy_slot: u64,
+    /// When the upgrade authority was verified.
+    pub timestamp: i64,
+}
+
+/// Emitted on a successful [crate::cpamm::sweep_lamports].
+#[event]
+pub struct LamportsSweptEvent {
+    /// The [crate::SwapInfo].
+    #[index]
+    pub swap: Pubkey,
+    /// The owner of the [crate::FactoryTreasury], which received the lamports.
+    pub recipient: Pubkey,
+    /// Lamports swept from the swap and its meta.
+    pub amount: u64,
 }
@@ -1,12 +1,25 @@
 //! Fees.
+//!
+//! Fees kept by the pool are rounded up, and fees sent out of the pool are rounded down,
+//! following [xyk::rounding].
 #![deny(missing_docs)]
 
 use crate::*;
-use ::u128::mul_div_u64;
+use xyk::rounding::{self, mul_div, POOL_INPUT, POOL_OUTPUT};
 
 /// Thousands of BPS in 100%.
 pub const KBPS_PER_WHOLE: u64 = 10_000_000;
 
+/// Maximum trade fee settable by [crate::cpamm::set_fees], thousands of bps. 10%.
+pub const MAX_TRADE_FEE_KBPS: u64 = 1_000_000;
+
+/// Maximum withdraw fee settable by [crate::cpamm::set_fees], thousands of bps. 10%.
+pub const MAX_WITHDRAW_FEE_KBPS: u64 = 1_000_000;
+
+/// Maximum proportion of the trade fee sent to the admin fee accounts, thousands of bps. 50%.
+/// The rest of the trade fee stays in the reserves for the LPs.
+pub const MAX_PROTOCOL_FEE_SHARE_KBPS: u64 = 5_000_000;
+
 /// Initial [SwapFees] for new pools.
 pub const INITIAL: SwapFees = SwapFees {
     trade_fee_kbps: 30_000,
@@ -16,6 +29,19 @@ pub const INITIAL: SwapFees = SwapFees {
 };
 
 impl SwapFees {
+    /// Validates the fees settable by [crate::cpamm::set_fees], which cannot exceed
+    /// [MAX_TRADE_FEE_KBPS], [MAX_WITHDRAW_FEE_KBPS] and [MAX_PROTOCOL_FEE_SHARE_KBPS].
+    pub fn validate_settable(&self) -> Result<()> {
+        self.validate()?;
+        invariant!(self.trade_fee_kbps <= MAX_TRADE_FEE_KBPS, InvalidFee);
+        invariant!(self.withdraw_fee_kbps <= MAX_WITHDRAW_FEE_KBPS, InvalidFee);
+        invariant!(
+            self.admin_trade_fee_kbps <= MAX_PROTOCOL_FEE_SHARE_KBPS,
+            InvalidFee
+        );
+        Ok(())
+    }
+
     /// Validates the fees.
     pub fn validate(&self) -> Result<()> {
         invariant!(
@@ -28,25 +54,143 @@ impl SwapFees {
         Ok(())
     }
 
+    /// Returns these fees with the trade fee reduced by `trade_fee_discount_kbps`.
+    pub fn apply_trade_fee_discount(&self, trade_fee_discount_kbps: u64) -> Option<SwapFees> {
+        let trade_fee_discount = mul_div(
+            self.trade_fee_kbps,
+            trade_fee_discount_kbps,
+            KBPS_PER_WHOLE,
+            POOL_OUTPUT,
+        )?;
+        Some(SwapFees {
+            trade_fee_kbps: self.trade_fee_kbps.checked_sub(trade_fee_discount)?,
+            ..*self
+        })
+    }
+
+    /// Returns these fees with `kbps_per_volatility_bps` added to the trade fee
+    /// for every bps of `volatility_bps`.
+    ///
+    /// The trade fee is capped at `max_trade_fee_kbps`, but is never lowered below its base value.
+    pub fn with_volatility_fee(
+        &self,
+        volatility_bps: u64,
+        kbps_per_volatility_bps: u64,
+        max_trade_fee_kbps: u64,
+    ) -> SwapFees {
+        let volatility_fee_kbps = volatility_bps.saturating_mul(kbps_per_volatility_bps);
+        SwapFees {
+            trade_fee_kbps: self
+                .trade_fee_kbps
+                .saturating_add(volatility_fee_kbps)
+                .min(max_trade_fee_kbps.max(self.trade_fee_kbps)),
+            ..*self
+        }
+    }
+
     /// Compute trade and admin trade fee from the trade amount
     pub fn compute_trade_fees(&self, destination_amount_swapped: u64) -> Option<(u64, u64)> {
-        let trade_fee = mul_div_u64(
+        let trade_fee = mul_div(
             destination_amount_swapped,
             self.trade_fee_kbps,
             KBPS_PER_WHOLE,
+            POOL_INPUT,
         )?;
 
-        let admin_trade_fee = mul_div_u64(trade_fee, self.admin_trade_fee_kbps, KBPS_PER_WHOLE)?;
+        let admin_trade_fee = mul_div(
+            trade_fee,
+            self.admin_trade_fee_kbps,
+            KBPS_PER_WHOLE,
+            POOL_OUTPUT,
+        )?;
 
         Some((trade_fee, admin_trade_fee))
     }
 
+    /// Compute the total input amount, trade fee, and admin trade fee required
+    /// for `source_amount_swapped` to reach the curve.
+    ///
+    /// This is used by exact out swaps, which charge the trade fee on the input token.
+    /// The total input is rounded up so the pool is never shorted.
+    pub fn compute_trade_fees_on_input(
+        &self,
+        source_amount_swapped: u64,
+    ) -> Option<(u64, u64, u64)> {
+        let amount_after_fee_kbps = KBPS_PER_WHOLE.checked_sub(self.trade_fee_kbps)?;
+        let numerator = (source_amount_swapped as u128).checked_mul(KBPS_PER_WHOLE.into())?;
+        let amount_in: u64 = rounding::div(numerator, amount_after_fee_kbps.into(), POOL_INPUT)?
+            .try_into()
+            .ok()?;
+
+        let trade_fee = amount_in.checked_sub(source_amount_swapped)?;
+        let admin_trade_fee = mul_div(
+            trade_fee,
+            self.admin_trade_fee_kbps,
+            KBPS_PER_WHOLE,
+            POOL_OUTPUT,
+        )?;
+
+        Some((amount_in, trade_fee, admin_trade_fee))
+    }
+
+    /// Compute the host fee from the admin trade fee.
+    pub fn compute_host_fee(admin_trade_fee: u64, host_fee_kbps: u64) -> Option<u64> {
+        mul_div(admin_trade_fee, host_fee_kbps, KBPS_PER_WHOLE, POOL_OUTPUT)
+    }
+
+    /// Compute the creator fee from the admin trade fee remaining after the host fee.
+    pub fn compute_creator_fee(
+        admin_trade_fee: u64,
+        host_fee: u64,
+        creator_fee_kbps: u64,
+    ) -> Option<u64> {
+        let creator_fee = mul_div(
+            admin_trade_fee,
+            creator_fee_kbps,
+            KBPS_PER_WHOLE,
+            POOL_OUTPUT,
+        )?;
+        Some(creator_fee.min(admin_trade_fee.checked_sub(host_fee)?))
+    }
+
+    /// Compute the referral fee from the admin trade fee remaining after the host and
+    /// creator fees.
+    pub fn compute_referral_fee(
+        admin_trade_fee: u64,
+        host_fee: u64,
+        creator_fee: u64,
+        referral_fee_kbps: u64,
+    ) -> Option<u64> {
+        let referral_fee = mul_div(
+            admin_trade_fee,
+            referral_fee_kbps,
+            KBPS_PER_WHOLE,
+            POOL_OUTPUT,
+        )?;
+        Some(
+            referral_fee.min(
+                admin_trade_fee
+                    .checked_sub(host_fee)?
+                    .checked_sub(creator_fee)?,
+            ),
+        )
+    }
+
     /// Compute withdraw and admin withdraw fees from the withdrawal amount
     pub fn compute_withdraw_fees(&self, withdrawal_amount: u64) -> Option<(u64, u64)> {
-        let withdraw_fee = mul_div_u64(withdrawal_amount, self.withdraw_fee_kbps, KBPS_PER_WHOLE)?;
+        let withdraw_fee = mul_div(
+            withdrawal_amount,
+            self.withdraw_fee_kbps,
+            KBPS_PER_WHOLE,
+            POOL_INPUT,
+        )?;
 
-        let admin_withdraw_fee =
-            mul_div_u64(withdraw_fee, self.admin_withdraw_fee_kbps, KBPS_PER_WHOLE)?;
+        let admin_withdraw_fee = mul_div(
+            withdraw_fee,
+            self.admin_withdraw_fee_kbps,
+            KBPS_PER_WHOLE,
+            POOL_OUTPUT,
+        )?;
 
         Some((withdraw_fee, admin_withdraw_fee))
     }
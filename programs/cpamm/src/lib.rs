@@ -15,9 +15,13 @@ mod addresses;
 mod controller;
 mod events;
 mod state;
+mod token_metadata;
 
 pub mod fees;
+pub mod observations;
+pub mod oracle;
 pub mod price_info;
+pub mod quote;
 
 pub use account_structs::*;
 pub use events::*;
@@ -25,9 +29,53 @@ pub use state::*;
 
 declare_id!("SCHAtsf8mbjyjiv4LkhLKutTf6JnZAbdJKFkXQNMFHZ");
 
+/// Finds the canonical address and bump seed of the [SwapInfo] of a pair of tokens
+/// and a pool fee tier of a [Factory]. The mints may be in either order.
+pub fn find_swap_address(
+    factory: Pubkey,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    pool_fee_tier: u8,
+) -> (Pubkey, u8) {
+    let (token_0_mint, token_1_mint) = if mint_a < mint_b {
+        (mint_a, mint_b)
+    } else {
+        (mint_b, mint_a)
+    };
+    Pubkey::find_program_address(
+        &[
+            b"SwapInfo",
+            factory.as_ref(),
+            token_0_mint.as_ref(),
+            token_1_mint.as_ref(),
+            pool_fee_tier_seed(&pool_fee_tier),
+        ],
+        &crate::ID,
+    )
+}
+
+/// Finds the address and bump seed of the [SwapMeta] of the swap with the given index
+/// in a [Factory].
+pub fn find_swap_meta_address(factory: Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"SwapMeta", factory.as_ref(), &index.to_le_bytes()],
+        &crate::ID,
+    )
+}
+
+/// Finds the address and bump seed of the creation fee vault of a [Factory],
+/// which holds the [Factory::creation_fee_lamports] paid for new swaps.
+pub fn find_creation_fee_vault_address(factory: Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"CreationFeeVault", factory.as_ref()], &crate::ID)
+}
+
 /// Constant product AMM.
 #[program]
 pub mod cpamm {
+    use anchor_lang::solana_program::{
+        program::{invoke, invoke_signed},
+        system_instruction,
+    };
     use anchor_spl::token;
 
     use super::*;
@@ -41,32 +89,150 @@ pub mod cpamm {
         factory.bump = unwrap_bump!(ctx, "factory");
         factory.num_swaps = 0;
         factory.admin = addresses::ADMIN_ACCOUNT;
+        factory.has_default_fees = true;
+        factory.default_fees = fees::INITIAL;
+        factory.version = FACTORY_VERSION;
 
         Ok(())
     }
 
     /// Creates a new [SwapInfo].
+    ///
+    /// If the [Factory] is permissioned, the payer must be the admin of the [Factory].
+    /// The mints must be allowed by the [Factory::mint_list_mode].
+    /// The payer pays the [Factory::creation_fee_lamports] to the creation fee vault.
+    ///
+    /// The trade fee of the swap is that of the `pool_fee_tier` of the [Factory].
+    /// A pair of tokens may have one swap per fee tier, at the address given by
+    /// [crate::find_swap_address].
+    ///
+    /// A non-zero `amp_factor` creates a stable swap with that amplification coefficient
+    /// for like-valued tokens, such as two stablecoins. Otherwise, the swap uses the
+    /// constant product curve. [cpamm::deposit_single], [cpamm::deposit_imbalanced]
+    /// and partially filled swaps only support the constant product curve.
+    ///
+    /// Non-zero `weight_0` and `weight_1` of at most [xyk::weighted::MAX_WEIGHT] create a
+    /// weighted swap, such as an 80/20 swap with weights of 4 and 1, which uses the constant
+    /// mean curve of [xyk::weighted]. The weights are fixed for the lifetime of the swap.
+    /// A swap cannot be both stable and weighted.
+    ///
+    /// The [SwapMeta] of the swap is created at the next index of the [Factory], so all swaps
+    /// can be enumerated via [crate::find_swap_meta_address] for indices below
+    /// [Factory::num_swaps].
+    ///
+    /// If `initial_amount_0` and `initial_amount_1` are zero, the tokens already in the
+    /// reserves are the initial liquidity. Otherwise, the reserves must be empty, and these
+    /// amounts are transferred from the `[source_0, source_1]` token accounts of the payer,
+    /// passed as the remaining accounts. The initial price of token 0 is then exactly
+    /// `initial_amount_1 / initial_amount_0`.
+    ///
+    /// The first [xyk::MINIMUM_LIQUIDITY] LP tokens are minted to the `locked_lp` account of
+    /// the swap, where they stay forever. This prevents the first depositor from inflating
+    /// the value of a single LP token to round down the shares of later depositors.
     #[access_control(ctx.accounts.validate())]
-    pub fn new_swap(ctx: Context<NewSwap>, _bump: u8) -> Result<()> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, NewSwap<'info>>,
+        _bump: u8,
+        pool_fee_tier: u8,
+        amp_factor: u64,
+        weight_0: u8,
+        weight_1: u8,
+        initial_amount_0: u64,
+        initial_amount_1: u64,
+    ) -> Result<()> {
+        invariant!(
+            amp_factor <= xyk::stable::MAX_AMP,
+            "amplification coefficient is too large"
+        );
+        if weight_0 != 0 || weight_1 != 0 {
+            invariant!(
+                weight_0 != 0
+                    && weight_0 <= xyk::weighted::MAX_WEIGHT
+                    && weight_1 != 0
+                    && weight_1 <= xyk::weighted::MAX_WEIGHT,
+                "invalid weights"
+            );
+            invariant!(amp_factor == 0, CurveNotSupported);
+        }
+        let trade_fee_kbps = unwrap_opt!(
+            ctx.accounts.factory.pool_trade_fee_kbps(pool_fee_tier),
+            InvalidPoolFeeTier
+        );
+        let creation_fee_lamports = ctx.accounts.factory.creation_fee_lamports;
+        if creation_fee_lamports > 0 {
+            invoke(
+                &system_instruction::transfer(
+                    &ctx.accounts.payer.key(),
+                    &ctx.accounts.creation_fee_vault.key(),
+                    creation_fee_lamports,
+                ),
+                &[
+                    ctx.accounts.payer.to_account_info(),
+                    ctx.accounts.creation_fee_vault.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
+
         let token_0 = &ctx.accounts.token_0;
         let token_1 = &ctx.accounts.token_1;
-        invariant!(token_0.reserve.amount != 0, NewSwapMustHaveNonZeroSupply);
-        invariant!(token_1.reserve.amount != 0, NewSwapMustHaveNonZeroSupply);
+        let (amount_0, amount_1) = if initial_amount_0 == 0 && initial_amount_1 == 0 {
+            (token_0.reserve.amount, token_1.reserve.amount)
+        } else {
+            invariant!(
+                token_0.reserve.amount == 0 && token_1.reserve.amount == 0,
+                "reserves must be empty when initial amounts are given"
+            );
+            for (index, reserve, amount) in [
+                (0, &token_0.reserve, initial_amount_0),
+                (1, &token_1.reserve, initial_amount_1),
+            ] {
+                let source = unwrap_opt!(
+                    ctx.remaining_accounts.get(index),
+                    "missing source token account"
+                );
+                token::transfer(
+                    CpiContext::new(
+                        ctx.accounts.token_program.to_account_info(),
+                        token::Transfer {
+                            from: source.clone(),
+                            to: reserve.to_account_info(),
+                            authority: ctx.accounts.payer.to_account_info(),
+                        },
+                    ),
+                    amount,
+                )?;
+            }
+            (initial_amount_0, initial_amount_1)
+        };
+        invariant!(amount_0 != 0, NewSwapMustHaveNonZeroSupply);
+        invariant!(amount_1 != 0, NewSwapMustHaveNonZeroSupply);
 
-        let initial_liquidity = unwrap_int!(xyk::calculate_initial_swap_pool_amount(
-            token_0.reserve.amount,
-            token_1.reserve.amount
-        ));
+        let initial_liquidity =
+            unwrap_int!(xyk::calculate_initial_swap_pool_amount(amount_0, amount_1));
         invariant!(
             initial_liquidity >= xyk::MINIMUM_LIQUIDITY,
             InitialLiquidityTooLow
         );
+        let (initial_liquidity_out, locked_liquidity) =
+            unwrap_int!(xyk::split_initial_swap_pool_amount(initial_liquidity));
 
         // update factory index
         let factory = &mut ctx.accounts.factory;
         let index = factory.num_swaps;
         factory.num_swaps = unwrap_int!(index.checked_add(1));
 
+        // index the swap
+        let meta = &mut ctx.accounts.swap_meta;
+        meta.factory = factory.key();
+        meta.index = index;
+        meta.bump = unwrap_bump!(ctx, "swap_meta");
+        meta.swap = ctx.accounts.swap.key();
+        meta.created_at = Clock::get()?.unix_timestamp;
+        meta.created_by = ctx.accounts.payer.key();
+        meta.version = SWAP_META_VERSION;
+
         // init info
         let swap_info = &mut ctx.accounts.swap;
         swap_info.factory = factory.key();
@@ -76,10 +242,37 @@ pub mod cpamm {
         swap_info.admin_key = factory.admin;
         swap_info.token_0 = SwapTokenInfo::from(token_0);
         swap_info.token_1 = SwapTokenInfo::from(token_1);
+        swap_info.weight_0 = weight_0;
+        swap_info.weight_1 = weight_1;
 
         swap_info.is_paused = false;
+        swap_info.is_paused_0_to_1 = false;
+        swap_info.is_paused_1_to_0 = false;
         swap_info.pool_mint = ctx.accounts.pool_mint.key();
-        swap_info.fees = fees::INITIAL;
+        swap_info.reserve_0_amount = amount_0;
+        swap_info.reserve_1_amount = amount_1;
+        swap_info.pool_fee_tier = pool_fee_tier;
+        swap_info.amp_factor = amp_factor;
+        swap_info.fees = SwapFees {
+            trade_fee_kbps,
+            ..factory.new_swap_fees()
+        };
+        // Referral and host fees are disabled until configured.
+        swap_info.referral_fee_kbps = 0;
+        swap_info.host_fee_kbps = 0;
+        swap_info.creator = ctx.accounts.payer.key();
+        swap_info.creator_fee_kbps = 0;
+        swap_info.version = SWAP_INFO_VERSION;
+        swap_info.has_freezable_mint = factory.freeze_authority_policy
+            == FREEZE_AUTHORITY_POLICY_FLAG
+            && (ctx.accounts.token_0.mint.freeze_authority.is_some()
+                || ctx.accounts.token_1.mint.freeze_authority.is_some());
+        let now = Clock::get()?.unix_timestamp;
+        swap_info.fees_last_changed_at = now;
+        swap_info.created_at = now;
+        // The launch fee is disabled until configured.
+        swap_info.launch_trade_fee_kbps = 0;
+        swap_info.launch_fee_duration_seconds = 0;
 
         // Zero cumulative stats.
         swap_info.cumulative_stats = Default::default();
@@ -92,9 +285,21 @@ pub mod cpamm {
             price_1_cumulative_last: 0,
         };
 
-        // mint initial liquidity to initial staker
+        // mint initial liquidity to initial staker, locking the minimum liquidity
         let seeds = gen_swap_signer_seeds!(swap_info);
         let signer_seeds = &[&seeds[..]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.pool_mint.to_account_info(),
+                    to: ctx.accounts.locked_lp.to_account_info(),
+                    authority: swap_info.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            locked_liquidity,
+        )?;
         token::mint_to(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
@@ -105,7 +310,7 @@ pub mod cpamm {
                 },
                 signer_seeds,
             ),
-            initial_liquidity,
+            initial_liquidity_out,
         )?;
 
         emit!(NewPoolEvent {
@@ -114,11 +319,80 @@ pub mod cpamm {
             mint_1: token_1.mint.key(),
             initial_liquidity
         });
+        emit!(PoolCreatedEvent {
+            swap: swap_info.key(),
+            factory: swap_info.factory,
+            lp_mint: swap_info.pool_mint,
+            mint_0: swap_info.token_0.mint,
+            mint_1: swap_info.token_1.mint,
+            reserve_0: swap_info.reserve_0_amount,
+            reserve_1: swap_info.reserve_1_amount,
+            fees: swap_info.fees,
+            pool_fee_tier: swap_info.pool_fee_tier,
+            amp_factor: swap_info.amp_factor,
+            weight_0: swap_info.weight_0,
+            weight_1: swap_info.weight_1,
+            creator: swap_info.creator,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Creates or updates the Metaplex metadata of the pool mint of a [SwapInfo],
+    /// such as the name `Sencha LP: X-Y`, so wallets can display the LP token.
+    /// The swap is the update authority of the metadata.
+    /// Only the admin of the [SwapInfo] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_pool_metadata(
+        ctx: Context<SetPoolMetadata>,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
+        let swap_info = &ctx.accounts.swap;
+        let seeds = gen_swap_signer_seeds!(swap_info);
+        token_metadata::set_metadata(
+            &token_metadata::MetadataAccounts {
+                metadata: ctx.accounts.metadata.to_account_info(),
+                mint: ctx.accounts.pool_mint.to_account_info(),
+                authority: swap_info.to_account_info(),
+                payer: ctx.accounts.payer.to_account_info(),
+                token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
+            },
+            &token_metadata::MetadataData { name, symbol, uri },
+            &[&seeds[..]],
+        )
+    }
+
+    /// Sets the [SwapMetadata] of a swap, creating it if it does not exist.
+    /// Only the creator of the swap, as recorded in its [SwapMeta], may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_swap_metadata(
+        ctx: Context<SetSwapMetadata>,
+        name: String,
+        uri: String,
+        tags: Vec<String>,
+    ) -> Result<()> {
+        invariant!(name.len() <= MAX_SWAP_METADATA_NAME_LEN, "name is too long");
+        invariant!(uri.len() <= MAX_SWAP_METADATA_URI_LEN, "URI is too long");
+        invariant!(tags.len() <= MAX_SWAP_METADATA_TAGS, "too many tags");
+        for tag in tags.iter() {
+            invariant!(tag.len() <= MAX_SWAP_METADATA_TAG_LEN, "tag is too long");
+        }
 
+        let swap_metadata = &mut ctx.accounts.swap_metadata;
+        swap_metadata.swap = ctx.accounts.swap.key();
+        swap_metadata.bump = unwrap_bump!(ctx, "swap_metadata");
+        swap_metadata.name = name;
+        swap_metadata.uri = uri;
+        swap_metadata.tags = tags;
         Ok(())
     }
 
-    /// Creates a new [SwapMeta].
+    /// Creates a new [SwapMeta] for a swap created before [cpamm::new_swap] created them.
     #[access_control(ctx.accounts.validate())]
     pub fn new_swap_meta(ctx: Context<NewSwapMeta>, _bump: u8) -> Result<()> {
         let swap_info = &ctx.accounts.swap;
@@ -131,18 +405,45 @@ pub mod cpamm {
         meta.swap = swap_info.key();
         meta.created_at = Clock::get()?.unix_timestamp;
         meta.created_by = ctx.accounts.payer.key();
+        meta.version = SWAP_META_VERSION;
 
         Ok(())
     }
 
     /// Performs a swap.
+    ///
+    /// The user authority may be the owner or an approved delegate of the input token account.
+    /// The output token account must belong to the owner of the input token account.
+    /// If a `deadline` is provided, the swap fails if it is executed after that Unix timestamp.
+    /// If referrer or host fee token accounts are provided as remaining accounts,
+    /// they receive a portion of the trade fee. If the [FeeExemption] of the user is
+    /// provided, the trade fee is discounted. If a token account of the creator of the swap
+    /// is provided, it receives the creator fee. If the [HolderDiscountConfig] of the [Factory]
+    /// and a token account of its discount mint owned by the user authority are provided,
+    /// the trade fee is discounted by the tier of the balance. If the [VolumeRebateConfig] of
+    /// the [Factory] and the [TraderStats] of the user authority are provided, the volume of
+    /// the swap is tracked and the trade fee is rebated by the tier of the rolling volume.
+    /// Only the largest of these discounts applies.
+    ///
+    /// If `allow_partial_fill` is true, the swap does not fail when `minimum_amount_out`
+    /// cannot be met. Instead, it swaps the largest part of `amount_in` which receives
+    /// at least the price of `minimum_amount_out` per `amount_in`, and the rest of the input
+    /// stays with the user.
     #[access_control(ctx.accounts.validate())]
-    pub fn swap(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u64) -> Result<()> {
+    pub fn swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        deadline: Option<i64>,
+        allow_partial_fill: bool,
+    ) -> Result<()> {
         controller::swap::swap(
             ctx,
             controller::swap::SwapArgs {
                 amount_in,
                 minimum_amount_out,
+                deadline,
+                allow_partial_fill,
             },
         )
     }
@@ -150,87 +451,2042 @@ pub mod cpamm {
     /// Performs a swap of the maximum amount possible.
     /// This is useful for order routers.
     #[access_control(ctx.accounts.validate())]
-    pub fn swap_max(ctx: Context<Swap>, minimum_amount_out: u64) -> Result<()> {
+    pub fn swap_max<'info>(
+        ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
+        minimum_amount_out: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
         let amount_in = ctx.accounts.input.user.amount;
         controller::swap::swap(
             ctx,
             controller::swap::SwapArgs {
                 amount_in,
                 minimum_amount_out,
+                deadline,
+                allow_partial_fill: false,
             },
         )
     }
 
-    /// Performs a withdraw.
+    /// Performs a [cpamm::swap] to the associated token account of the owner of the input
+    /// token account, which the payer creates if it does not exist.
     #[access_control(ctx.accounts.validate())]
-    pub fn withdraw(
-        ctx: Context<Withdraw>,
+    pub fn swap_to_ata<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapToAta<'info>>,
         amount_in: u64,
-        minimum_amount_out_0: u64,
-        minimum_amount_out_1: u64,
+        minimum_amount_out: u64,
+        deadline: Option<i64>,
     ) -> Result<()> {
-        controller::withdraw::withdraw(
+        controller::swap::swap(
+            Context::new(
+                ctx.program_id,
+                &mut ctx.accounts.swap,
+                ctx.remaining_accounts,
+                ctx.bumps.clone(),
+            ),
+            controller::swap::SwapArgs {
+                amount_in,
+                minimum_amount_out,
+                deadline,
+                allow_partial_fill: false,
+            },
+        )
+    }
+
+    /// Performs several independent swaps against the same pool, such as for multiple
+    /// token accounts of a market maker. Each swap fails the whole batch if it fails.
+    ///
+    /// The remaining accounts are the input and output token accounts of every swap,
+    /// followed by the oracle of the swap if it has an oracle band.
+    /// The [SwapReturnData] of every swap is set as the return data of the instruction.
+    #[access_control(ctx.accounts.validate())]
+    pub fn swap_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapBatch<'info>>,
+        swaps: Vec<BatchSwap>,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        controller::swap_batch::swap_batch(
             ctx,
-            controller::withdraw::WithdrawArgs {
+            controller::swap_batch::SwapBatchArgs { swaps, deadline },
+        )
+    }
+
+    /// Commits to a swap, which is executed by a [cpamm::reveal_swap] in a later slot.
+    ///
+    /// The `commitment` is the [SwapCommitment::hash_swap] of the swap. Since the parameters
+    /// of the swap are only revealed once it is executed, it cannot be sandwiched.
+    #[access_control(ctx.accounts.validate())]
+    pub fn commit_swap(ctx: Context<CommitSwap>, commitment: [u8; 32]) -> Result<()> {
+        let swap_commitment = &mut ctx.accounts.commitment;
+        swap_commitment.swap = ctx.accounts.swap.key();
+        swap_commitment.user_authority = ctx.accounts.user_authority.key();
+        swap_commitment.bump = unwrap_bump!(ctx, "commitment");
+        swap_commitment.commitment = commitment;
+        swap_commitment.slot = Clock::get()?.slot;
+        swap_commitment.payer = ctx.accounts.payer.key();
+        Ok(())
+    }
+
+    /// Reveals and performs a swap committed to by a [cpamm::commit_swap]
+    /// in an earlier slot, then closes the [SwapCommitment].
+    #[access_control(ctx.accounts.validate())]
+    pub fn reveal_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, RevealSwap<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        controller::reveal_swap::reveal_swap(
+            ctx,
+            controller::reveal_swap::RevealSwapArgs {
                 amount_in,
-                minimum_amount_out_0,
-                minimum_amount_out_1,
+                minimum_amount_out,
+                salt,
             },
         )
     }
 
-    /// Performs a deposit.
+    /// Computes the result of a [cpamm::swap] of `amount_in` without performing it.
+    ///
+    /// The [SwapQuote] is set as the return data of the instruction.
     #[access_control(ctx.accounts.validate())]
-    pub fn deposit(
-        ctx: Context<Deposit>,
-        pool_token_amount: u64,
-        maximum_amount_in_0: u64,
-        maximum_amount_in_1: u64,
+    pub fn quote_swap(ctx: Context<QuoteSwap>, amount_in: u64) -> Result<()> {
+        let quote = unwrap_int!(quote::get_amount_out(
+            &ctx.accounts.swap,
+            ctx.accounts.input_reserve.mint,
+            amount_in
+        ));
+        controller::write_return_data(&quote)
+    }
+
+    /// Performs a swap which receives exactly `amount_out` of the output token.
+    /// The trade fee is charged on the input token.
+    #[access_control(ctx.accounts.validate())]
+    pub fn swap_exact_out(
+        ctx: Context<SwapExactOut>,
+        amount_out: u64,
+        maximum_amount_in: u64,
+        deadline: Option<i64>,
     ) -> Result<()> {
-        // update price info
-        let price_info = &mut ctx.accounts.user.swap.price_info;
-        price_info.update_cumulative_price_info(
-            ctx.accounts.input_0.reserve.amount,
-            ctx.accounts.input_1.reserve.amount,
-        )?;
+        controller::swap_exact_out::swap_exact_out(
+            ctx,
+            controller::swap_exact_out::SwapExactOutArgs {
+                amount_out,
+                maximum_amount_in,
+                deadline,
+            },
+        )
+    }
 
-        controller::deposit::deposit(
+    /// Performs a swap through two pools which share an intermediate token.
+    /// The intermediate tokens are sent directly between the pools' reserves.
+    #[access_control(ctx.accounts.validate())]
+    pub fn swap_route(
+        ctx: Context<SwapRoute>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        controller::swap_route::swap_route(
             ctx,
-            controller::deposit::DepositArgs {
-                pool_token_amount,
-                maximum_amount_in_0,
-                maximum_amount_in_1,
+            controller::swap_route::SwapRouteArgs {
+                amount_in,
+                minimum_amount_out,
+                deadline,
             },
-        )?;
+        )
+    }
+
+    /// Creates the [SignedSwapAuthority] of `owner`, which allows relayers to submit
+    /// swaps signed off-chain by the `owner` with [cpamm::swap_with_signature].
+    #[access_control(ctx.accounts.validate())]
+    pub fn new_signed_swap_authority(ctx: Context<NewSignedSwapAuthority>) -> Result<()> {
+        let signed_swap_authority = &mut ctx.accounts.signed_swap_authority;
+        signed_swap_authority.owner = ctx.accounts.owner.key();
+        signed_swap_authority.bump = unwrap_bump!(ctx, "signed_swap_authority");
+        signed_swap_authority.nonce = 0;
         Ok(())
     }
-}
 
-// Error codes
-#[error_code]
-#[allow(missing_docs)]
-pub enum ErrorCode {
-    #[msg("Swap pool is paused")]
-    Paused,
-    #[msg("Swap instruction exceeds desired slippage limit")]
-    ExceededSlippage,
-    #[msg("Insufficient liquidity")]
-    InsufficientLiquidity,
-    #[msg(
-        "The withdrawal will result in the pool having too little liquidity. Withdraw less tokens."
-    )]
-    InsufficientLiquidityPostWithdrawal,
+    /// Performs a swap authorized by an ed25519 signature of the owner of the input token
+    /// account over the Borsh-serialized [SwapIntent]. Any account may submit it.
+    ///
+    /// The instruction preceding this one must be an ed25519 program instruction verifying
+    /// the signature. The owner must have approved their [SignedSwapAuthority] as the delegate
+    /// of the input token account. Each intent is executed at most once, before its `expiry`.
+    #[access_control(ctx.accounts.validate())]
+    pub fn swap_with_signature<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapWithSignature<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        nonce: u64,
+        expiry: i64,
+    ) -> Result<()> {
+        controller::swap_with_signature::swap_with_signature(
+            ctx,
+            controller::swap_with_signature::SwapWithSignatureArgs {
+                amount_in,
+                minimum_amount_out,
+                nonce,
+                expiry,
+            },
+        )
+    }
 
-    #[msg("New swap must have non-zero supply on its reserves", offset = 10)]
-    NewSwapMustHaveNonZeroSupply,
-    #[msg("Initial liquidity too low")]
-    InitialLiquidityTooLow,
-    #[msg("Swap's token mints must be sorted")]
-    SwapTokensNotSorted,
-    #[msg("Swap's token mints cannot be the same")]
-    SwapTokensCannotBeEqual,
-    #[msg("Swap's pool mint supply must be zero")]
-    SwapPoolMintSupply,
-    #[msg("Invalid fee", offset = 20)]
-    InvalidFee,
+    /// Performs a swap between SOL and the other token of a wrapped SOL pool.
+    ///
+    /// A temporary wrapped SOL account is created for the user and closed back to
+    /// the `payer` once the swap is done, so the user never holds wrapped SOL.
+    /// If `sol_is_input` is true, `amount_in` lamports are taken from the `payer`.
+    #[access_control(ctx.accounts.validate())]
+    pub fn swap_sol(
+        ctx: Context<SwapSol>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        sol_is_input: bool,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        controller::swap_sol::swap_sol(
+            ctx,
+            controller::swap_sol::SwapSolArgs {
+                amount_in,
+                minimum_amount_out,
+                sol_is_input,
+                deadline,
+            },
+        )
+    }
+
+    /// Sends tokens out of the reserves, invokes a callback program, then checks
+    /// that the reserves were repaid enough to keep the invariant after trade fees.
+    /// The tokens returned to each reserve are charged the trade fee of a swap of
+    /// them, including the fee tiers, directional, launch and dynamic fees.
+    ///
+    /// Remaining accounts and `data` are passed through to the callback program.
+    /// The swap is locked by [SwapInfo::is_in_progress] during the callback.
+    ///
+    /// The net trade of the flash swap, the change of the reserves after they are repaid,
+    /// is subject to the maximum trade size, the maximum price impact and the oracle band
+    /// of the swap. If the swap has an oracle band, its oracle must be passed among the
+    /// remaining accounts.
+    #[access_control(ctx.accounts.validate())]
+    pub fn flash_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, FlashSwap<'info>>,
+        amount_out_0: u64,
+        amount_out_1: u64,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        controller::flash_swap::flash_swap(
+            ctx,
+            controller::flash_swap::FlashSwapArgs {
+                amount_out_0,
+                amount_out_1,
+                data,
+            },
+        )
+    }
+
+    /// Adds the tokens sent directly to the reserves to the pool.
+    ///
+    /// Anyone may sync a swap.
+    #[access_control(ctx.accounts.validate())]
+    pub fn sync(ctx: Context<SyncReserves>) -> Result<()> {
+        // accumulate the price up to now at the previous reserves
+        let swap_info = &mut ctx.accounts.swap;
+        swap_info.update_price_info()?;
+
+        swap_info.reserve_0_amount = ctx.accounts.reserve_0.amount;
+        swap_info.reserve_1_amount = ctx.accounts.reserve_1.amount;
+        Ok(())
+    }
+
+    /// Sends the tokens sent directly to the reserves, which are not part of the pool,
+    /// to the output token accounts. Anyone may skim a swap.
+    ///
+    /// Fails with [ErrorCode::ReservesNotTracked] if the pool has LP tokens but its
+    /// reserves are not tracked yet.
+    #[access_control(ctx.accounts.validate())]
+    pub fn skim(ctx: Context<Skim>) -> Result<()> {
+        let swap_info = &ctx.accounts.swap;
+        let seeds = gen_swap_signer_seeds!(swap_info);
+        let signer_seeds = &[&seeds[..]];
+        for (reserve, reserve_amount, output) in [
+            (
+                &ctx.accounts.reserve_0,
+                swap_info.reserve_0_amount,
+                &ctx.accounts.output_0,
+            ),
+            (
+                &ctx.accounts.reserve_1,
+                swap_info.reserve_1_amount,
+                &ctx.accounts.output_1,
+            ),
+        ] {
+            let surplus = unwrap_int!(reserve.amount.checked_sub(reserve_amount));
+            if surplus == 0 {
+                continue;
+            }
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: reserve.to_account_info(),
+                        to: output.to_account_info(),
+                        authority: swap_info.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                surplus,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Checks a [SwapInfo] against its reserves and pool mint, pausing the swap and
+    /// emitting a [PoolViolationEvent] if they are inconsistent. Anyone may verify a swap,
+    /// so watchdogs can halt a broken pool without waiting for its admin.
+    #[access_control(ctx.accounts.validate())]
+    pub fn verify_pool(ctx: Context<VerifyPool>) -> Result<()> {
+        controller::verify_pool::verify_pool(ctx)
+    }
+
+    /// Pauses a [SwapInfo], such as during an incident. Like [cpamm::pause_all], this blocks
+    /// swaps and deposits but not withdrawals.
+    /// Only the admin of the [SwapInfo] or the holder of [Role::Pauser] may call this.
+    #[access_control(ctx.accounts.validate_role(Role::Pauser))]
+    pub fn pause_swap(ctx: Context<SwapRoleContext>) -> Result<()> {
+        ctx.accounts.swap.is_paused = true;
+        Ok(())
+    }
+
+    /// Unpauses a [SwapInfo] paused by [cpamm::pause_swap] or [cpamm::verify_pool].
+    /// Only the admin of the [SwapInfo] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn unpause_swap(ctx: Context<SwapAdminContext>) -> Result<()> {
+        ctx.accounts.swap.is_paused = false;
+        Ok(())
+    }
+
+    /// Sends all of the tokens of a token account owned by a [SwapInfo] to the `destination`,
+    /// recovering tokens sent to the swap by mistake. The mint cannot be either token
+    /// of the swap nor its pool mint, so the reserves and the admin fees are never at risk.
+    /// Only the admin of the [SwapInfo] or the holder of [Role::Treasurer] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn rescue_tokens(ctx: Context<RescueTokens>) -> Result<()> {
+        let amount = ctx.accounts.source.amount;
+        if amount == 0 {
+            return Ok(());
+        }
+        let swap_info = &ctx.accounts.swap;
+        let seeds = gen_swap_signer_seeds!(swap_info);
+        let signer_seeds = &[&seeds[..]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.source.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: swap_info.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+
+    /// Closes a [SwapInfo] whose only LP tokens are its locked [xyk::MINIMUM_LIQUIDITY],
+    /// along with its [SwapMeta], reserve token accounts and `locked_lp` account, and sends
+    /// their rent to the `recipient`. The locked LP tokens are burned, and the tokens left in
+    /// the reserves are sent to the admin fees accounts of the swap. The index of the swap in
+    /// its [Factory] then has no [SwapMeta].
+    ///
+    /// Only the admin of the swap may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn close_swap(ctx: Context<CloseSwap>) -> Result<()> {
+        let swap_info = &ctx.accounts.swap;
+        let seeds = gen_swap_signer_seeds!(swap_info);
+        let signer_seeds = &[&seeds[..]];
+        let locked_lp = &ctx.accounts.locked_lp;
+        if locked_lp.amount > 0 {
+            token::burn(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Burn {
+                        mint: ctx.accounts.pool_mint.to_account_info(),
+                        to: locked_lp.to_account_info(),
+                        authority: swap_info.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                locked_lp.amount,
+            )?;
+        }
+        for (reserve, destination) in [
+            (&ctx.accounts.reserve_0, &ctx.accounts.destination_0),
+            (&ctx.accounts.reserve_1, &ctx.accounts.destination_1),
+        ] {
+            if reserve.amount > 0 {
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        token::Transfer {
+                            from: reserve.to_account_info(),
+                            to: destination.to_account_info(),
+                            authority: swap_info.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    reserve.amount,
+                )?;
+            }
+        }
+        for account in [
+            &ctx.accounts.reserve_0,
+            &ctx.accounts.reserve_1,
+            &ctx.accounts.locked_lp,
+        ] {
+            token::close_account(CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::CloseAccount {
+                    account: account.to_account_info(),
+                    destination: ctx.accounts.recipient.to_account_info(),
+                    authority: swap_info.to_account_info(),
+                },
+                signer_seeds,
+            ))?;
+        }
+        Ok(())
+    }
+
+    /// Upgrades a [SwapInfo] created by an older version of the program to the latest
+    /// [SWAP_INFO_VERSION], growing the account if needed. The payer pays for the additional
+    /// rent. Anyone may migrate a swap.
+    ///
+    /// Swaps of the first release start tracking their reserves at the reserve balances.
+    #[access_control(ctx.accounts.validate())]
+    pub fn migrate_pool(ctx: Context<MigratePool>) -> Result<()> {
+        controller::migrate::migrate_pool(ctx)
+    }
+
+    /// Upgrades a [Factory] created by an older version of the program to the latest
+    /// [FACTORY_VERSION]. Anyone may migrate a factory.
+    #[access_control(ctx.accounts.validate())]
+    pub fn migrate_factory(ctx: Context<MigrateFactory>) -> Result<()> {
+        controller::migrate::migrate_factory(ctx)
+    }
+
+    /// Upgrades a [SwapMeta] created by an older version of the program to the latest
+    /// [SWAP_META_VERSION], growing the account if needed. The payer pays for the additional
+    /// rent. Anyone may migrate a meta.
+    #[access_control(ctx.accounts.validate())]
+    pub fn migrate_swap_meta(ctx: Context<MigrateSwapMeta>) -> Result<()> {
+        controller::migrate::migrate_swap_meta(ctx)
+    }
+
+    /// Creates the [Observations] of a [SwapInfo] with room for one observation,
+    /// which is written right away. Anyone may create it, since it only records the prices
+    /// of the swap. For more information, view [crate::observations].
+    #[access_control(ctx.accounts.validate())]
+    pub fn new_observations(ctx: Context<NewObservations>) -> Result<()> {
+        let observation = Observation::of_swap(&ctx.accounts.swap, &Clock::get()?)?;
+        let observations = ctx.accounts.observations.to_account_info();
+        let mut data = observations.try_borrow_mut_data()?;
+        let (header, entries) = observations::split_observations_mut(&mut data)?;
+        header.swap = ctx.accounts.swap.key();
+        header.bump = unwrap_bump!(ctx, "observations");
+        header.index = 0;
+        header.cardinality = 1;
+        header.cardinality_next = 1;
+        *unwrap_opt!(entries.first_mut(), "missing observation") = observation;
+        Ok(())
+    }
+
+    /// Grows the [Observations] of a [SwapInfo] to have room for `cardinality_next`
+    /// observations, so they cover longer windows. The ring buffer grows the next time
+    /// it wraps around. The payer pays for the additional rent. An account may grow by about
+    /// 250 observations per instruction. Anyone may grow the observations.
+    #[access_control(ctx.accounts.validate())]
+    pub fn increase_observation_cardinality(
+        ctx: Context<IncreaseObservationCardinality>,
+        cardinality_next: u16,
+    ) -> Result<()> {
+        controller::increase_observation_cardinality::increase_observation_cardinality(
+            ctx,
+            cardinality_next,
+        )
+    }
+
+    /// Writes the current cumulative prices of a [SwapInfo] to its [Observations],
+    /// with the range of the price since the previous observation.
+    /// At most one observation is written per second. Anyone may write an observation,
+    /// so keepers should call this regularly to keep the observations fresh.
+    #[access_control(ctx.accounts.validate())]
+    pub fn write_observation(ctx: Context<WriteObservation>) -> Result<()> {
+        let observation = Observation::of_swap(&ctx.accounts.swap, &Clock::get()?)?;
+        let observations = ctx.accounts.observations.to_account_info();
+        let mut data = observations.try_borrow_mut_data()?;
+        let (header, entries) = observations::split_observations_mut(&mut data)?;
+        if unwrap_int!(header.write(entries, observation)) {
+            // the next observation records the range of the price since this one
+            let swap = &mut ctx.accounts.swap;
+            swap.price_0_low_since_observation = 0;
+            swap.price_0_high_since_observation = 0;
+        }
+        Ok(())
+    }
+
+    /// Computes the TWAPs of the tokens of a [SwapInfo] over the last `seconds_ago` seconds
+    /// from its [Observations], interpolating linearly between observations.
+    /// The window cannot start before the oldest observation. The TWAPs of the median-filtered
+    /// prices of [SwapMedianPriceInfo] are harder to manipulate within a single block.
+    /// Consumers should reject readings whose [SwapTwap::price_0_width_bps] is too wide
+    /// or whose [SwapTwap::latest_observation_ts] is too old.
+    ///
+    /// The [SwapTwap] is set as the return data of the instruction, so other programs
+    /// such as lending protocols may read the prices of the swap via CPI.
+    #[access_control(ctx.accounts.validate())]
+    pub fn observe(ctx: Context<Observe>, seconds_ago: u32) -> Result<()> {
+        controller::observe::observe(ctx, seconds_ago)
+    }
+
+    /// Creates the [PairPrice] of the token pair of a [SwapInfo], which aggregates the prices
+    /// of all of the pool fee tiers of the pair. Anyone may create it.
+    #[access_control(ctx.accounts.validate())]
+    pub fn new_pair_price(ctx: Context<NewPairPrice>) -> Result<()> {
+        let swap = &ctx.accounts.swap;
+        let pair_price = &mut ctx.accounts.pair_price;
+        pair_price.factory = swap.factory;
+        pair_price.token_0_mint = swap.token_0.mint;
+        pair_price.token_1_mint = swap.token_1.mint;
+        pair_price.bump = unwrap_bump!(ctx, "pair_price");
+        Ok(())
+    }
+
+    /// Updates a [PairPrice] to the liquidity-weighted spot prices of the [SwapInfo]s
+    /// of its pair, which are passed as the remaining accounts in ascending order
+    /// of pool fee tier. Swaps without liquidity are skipped. Anyone may update it,
+    /// so consumers should check [PairPrice::num_swaps] and [PairPrice::total_liquidity].
+    #[access_control(ctx.accounts.validate())]
+    pub fn update_pair_price(ctx: Context<UpdatePairPrice>) -> Result<()> {
+        controller::update_pair_price::update_pair_price(ctx)
+    }
+
+    /// Pauses all swaps of a [Factory] at once, such as during an incident.
+    /// This is separate from the pause of each swap, which stays as it is.
+    /// Like the pause of a swap, this blocks swaps and deposits but not withdrawals.
+    /// Only the admin of the [Factory] or the holder of [Role::Pauser] may call this.
+    #[access_control(ctx.accounts.validate_role(Role::Pauser))]
+    pub fn pause_all(ctx: Context<FactoryRoleContext>) -> Result<()> {
+        ctx.accounts.factory.is_paused = true;
+        Ok(())
+    }
+
+    /// Unpauses all swaps of a [Factory] paused by [cpamm::pause_all] or
+    /// [cpamm::guardian_pause_all]. Only the admin of the [Factory] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn unpause_all(ctx: Context<FactoryAdminContext>) -> Result<()> {
+        ctx.accounts.factory.is_paused = false;
+        Ok(())
+    }
+
+    /// Sets the guardian of the [FactoryGuardian] of a [Factory], creating it if it does not
+    /// exist. The default key removes the guardian. Only the admin of the [Factory] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+        let factory_guardian = &mut ctx.accounts.factory_guardian;
+        factory_guardian.factory = ctx.accounts.factory.key();
+        factory_guardian.bump = unwrap_bump!(ctx, "factory_guardian");
+        factory_guardian.guardian = guardian;
+        Ok(())
+    }
+
+    /// Pauses all swaps of a [Factory] like [cpamm::pause_all]. Only the admin may unpause them.
+    /// Only the guardian of the [FactoryGuardian] of the [Factory] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn guardian_pause_all(ctx: Context<GuardianPauseAll>) -> Result<()> {
+        ctx.accounts.factory.is_paused = true;
+        Ok(())
+    }
+
+    /// Grants the `role` of a [Factory] to the `holder`, creating the [FactoryRoles] of the
+    /// [Factory] if it does not exist. Each role has one holder, who replaces any previous one.
+    /// Only the admin of the [Factory] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn grant_role(ctx: Context<SetRole>, role: Role, holder: Pubkey) -> Result<()> {
+        let roles = &mut ctx.accounts.roles;
+        roles.factory = ctx.accounts.factory.key();
+        roles.bump = unwrap_bump!(ctx, "roles");
+        roles.set_holder(role, holder);
+        Ok(())
+    }
+
+    /// Revokes the `role` of a [Factory] from its holder.
+    /// Only the admin of the [Factory] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn revoke_role(ctx: Context<SetRole>, role: Role) -> Result<()> {
+        let roles = &mut ctx.accounts.roles;
+        roles.factory = ctx.accounts.factory.key();
+        roles.bump = unwrap_bump!(ctx, "roles");
+        roles.set_holder(role, Pubkey::default());
+        Ok(())
+    }
+
+    /// Verifies that the upgrade authority of the program is the holder of [Role::Upgrader]
+    /// of a [Factory], emitting an [UpgradeAuthorityVerifiedEvent]. If the role is revoked,
+    /// this verifies that the program can no longer be upgraded. Anyone may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn verify_upgrade_authority(ctx: Context<VerifyUpgradeAuthority>) -> Result<()> {
+        let upgrade_authority = ctx
+            .accounts
+            .program_data
+            .upgrade_authority_address
+            .unwrap_or_default();
+        assert_keys_eq!(
+            upgrade_authority,
+            ctx.accounts.roles.upgrader,
+            UpgradeAuthorityMismatch
+        );
+
+        emit!(UpgradeAuthorityVerifiedEvent {
+            factory: ctx.accounts.factory.key(),
+            upgrade_authority,
+            last_deploy_slot: ctx.accounts.program_data.slot,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Sets whether anyone may create swaps on a [Factory]. While it is false, the payer
+    /// of [cpamm::new_swap] must be the admin of the [Factory], such as during a phased launch.
+    /// Only the admin of the [Factory] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_factory_permissionless(
+        ctx: Context<FactoryAdminContext>,
+        is_permissionless: bool,
+    ) -> Result<()> {
+        ctx.accounts.factory.is_permissioned = !is_permissionless;
+        Ok(())
+    }
+
+    /// Sets the trade fees of the pool fee tiers `1..=MAX_POOL_FEE_TIERS` of a [Factory],
+    /// such as 5, 30 and 100 bps. Unused tiers have a zero trade fee. Existing swaps keep
+    /// their fees. The trade fees cannot exceed [fees::MAX_TRADE_FEE_KBPS].
+    /// Rejected while the [Factory] has a timelock.
+    /// Only the admin of the [Factory] or the holder of [Role::FeeSetter] may call this.
+    #[access_control(ctx.accounts.validate_role(Role::FeeSetter))]
+    pub fn set_pool_fee_tiers(
+        ctx: Context<FactoryRoleContext>,
+        pool_fee_tiers_kbps: Vec<u64>,
+    ) -> Result<()> {
+        invariant!(
+            ctx.accounts.factory.timelock_delay_seconds == 0,
+            TimelockRequired
+        );
+        invariant!(
+            pool_fee_tiers_kbps.len() <= MAX_POOL_FEE_TIERS,
+            "too many pool fee tiers"
+        );
+        for trade_fee_kbps in pool_fee_tiers_kbps.iter() {
+            invariant!(*trade_fee_kbps <= fees::MAX_TRADE_FEE_KBPS, InvalidFee);
+        }
+        let factory = &mut ctx.accounts.factory;
+        factory.pool_fee_tiers_kbps = Default::default();
+        factory.pool_fee_tiers_kbps[..pool_fee_tiers_kbps.len()]
+            .copy_from_slice(&pool_fee_tiers_kbps);
+        Ok(())
+    }
+
+    /// Proposes a new admin of a [Factory], who takes over once they call [cpamm::accept_admin].
+    /// Replaces any earlier proposal. If the [Factory] has a timelock, the admin must be
+    /// proposed with [cpamm::queue_change]. Only the admin of the [Factory] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn propose_admin(ctx: Context<FactoryAdminContext>, new_admin: Pubkey) -> Result<()> {
+        invariant!(
+            ctx.accounts.factory.timelock_delay_seconds == 0,
+            TimelockRequired
+        );
+        ctx.accounts.factory.pending_admin = new_admin;
+        Ok(())
+    }
+
+    /// Increases the [Factory::timelock_delay_seconds] of the changes queued with
+    /// [cpamm::queue_change], up to [MAX_TIMELOCK_DELAY_SECONDS]. The delay takes effect
+    /// at once, so it may only be decreased through the timelock itself.
+    /// Only the admin of the [Factory] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_timelock_delay(ctx: Context<FactoryAdminContext>, delay_seconds: i64) -> Result<()> {
+        let factory = &mut ctx.accounts.factory;
+        invariant!(
+            delay_seconds >= factory.timelock_delay_seconds,
+            TimelockRequired
+        );
+        invariant!(
+            delay_seconds <= MAX_TIMELOCK_DELAY_SECONDS,
+            "timelock delay is too long"
+        );
+        factory.timelock_delay_seconds = delay_seconds;
+        Ok(())
+    }
+
+    /// Queues a [TimelockedChange] of a [SwapInfo] or a [Factory] as a [PendingChange],
+    /// which may be executed with [cpamm::execute_change] after the
+    /// [Factory::timelock_delay_seconds] of the [Factory]. The change is validated when
+    /// it is queued. Each target has at most one pending change, which may be cancelled
+    /// with [cpamm::cancel_change]. Only the admin of the target may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn queue_change(ctx: Context<QueueChange>, change: TimelockedChange) -> Result<()> {
+        controller::timelock::queue_change(ctx, change)
+    }
+
+    /// Executes a [PendingChange] whose ETA has passed, closing it.
+    /// Only the admin of the target may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn execute_change(ctx: Context<PendingChangeContext>) -> Result<()> {
+        controller::timelock::execute_change(ctx)
+    }
+
+    /// Cancels a [PendingChange], closing it. Only the admin of the target may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn cancel_change(ctx: Context<PendingChangeContext>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Makes the pending admin of a [Factory] its admin. Only the pending admin may call this,
+    /// which proves that they control the proposed key.
+    ///
+    /// Existing swaps keep their admin.
+    #[access_control(ctx.accounts.validate())]
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let factory = &mut ctx.accounts.factory;
+        factory.admin = factory.pending_admin;
+        factory.pending_admin = Pubkey::default();
+        Ok(())
+    }
+
+    /// Sets the [Factory::default_fees] of swaps created afterwards. The trade fee applies to
+    /// the default pool fee tier 0. Existing swaps keep their fees.
+    /// Rejected while the [Factory] has a timelock.
+    /// Only the admin of the [Factory] or the holder of [Role::FeeSetter] may call this.
+    #[access_control(ctx.accounts.validate_role(Role::FeeSetter))]
+    pub fn set_default_fees(
+        ctx: Context<FactoryRoleContext>,
+        default_fees: SwapFees,
+    ) -> Result<()> {
+        invariant!(
+            ctx.accounts.factory.timelock_delay_seconds == 0,
+            TimelockRequired
+        );
+        default_fees.validate()?;
+        let factory = &mut ctx.accounts.factory;
+        factory.has_default_fees = true;
+        factory.default_fees = default_fees;
+        Ok(())
+    }
+
+    /// Sets the [Factory::creation_fee_lamports] charged for new swaps. A non-zero fee must be
+    /// at least the rent exemption minimum of the creation fee vault, so that the first
+    /// fee can be paid into the empty vault.
+    /// Rejected while the [Factory] has a timelock.
+    /// Only the admin of the [Factory] or the holder of [Role::FeeSetter] may call this.
+    #[access_control(ctx.accounts.validate_role(Role::FeeSetter))]
+    pub fn set_creation_fee(
+        ctx: Context<FactoryRoleContext>,
+        creation_fee_lamports: u64,
+    ) -> Result<()> {
+        invariant!(
+            ctx.accounts.factory.timelock_delay_seconds == 0,
+            TimelockRequired
+        );
+        invariant!(
+            creation_fee_lamports == 0 || creation_fee_lamports >= Rent::get()?.minimum_balance(0),
+            "creation fee is below the rent exemption minimum"
+        );
+        ctx.accounts.factory.creation_fee_lamports = creation_fee_lamports;
+        Ok(())
+    }
+
+    /// Sends all lamports of the creation fee vault of a [Factory] to the `recipient`.
+    /// Only the admin of the [Factory] or the holder of [Role::Treasurer] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn withdraw_creation_fees(ctx: Context<WithdrawCreationFees>) -> Result<()> {
+        let vault = ctx.accounts.creation_fee_vault.to_account_info();
+        let factory_key = ctx.accounts.factory.key();
+        let seeds: &[&[u8]] = &[
+            b"CreationFeeVault",
+            factory_key.as_ref(),
+            &[unwrap_bump!(ctx, "creation_fee_vault")],
+        ];
+        invoke_signed(
+            &system_instruction::transfer(
+                vault.key,
+                &ctx.accounts.recipient.key(),
+                vault.lamports(),
+            ),
+            &[
+                vault.clone(),
+                ctx.accounts.recipient.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+        Ok(())
+    }
+
+    /// Sets the proportion of the admin trade fee of a [SwapInfo] which is sent to referrers.
+    /// Referrers are paid after the host and creator fees.
+    /// Rejected while the [Factory] has a timelock.
+    /// Only the admin of the [Factory] or the holder of [Role::FeeSetter] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_referral_fee(ctx: Context<SetReferralFee>, referral_fee_kbps: u64) -> Result<()> {
+        invariant!(
+            ctx.accounts.factory.timelock_delay_seconds == 0,
+            TimelockRequired
+        );
+        invariant!(referral_fee_kbps <= fees::KBPS_PER_WHOLE, InvalidFee);
+        ctx.accounts.swap.referral_fee_kbps = referral_fee_kbps;
+        Ok(())
+    }
+
+    /// Sets the proportion of the admin trade fee of a [SwapInfo] which is sent to hosts.
+    /// Rejected while the [Factory] has a timelock.
+    /// Only the admin of the [SwapInfo] or the holder of [Role::FeeSetter] may call this.
+    #[access_control(ctx.accounts.validate_role(Role::FeeSetter))]
+    pub fn set_host_fee(ctx: Context<SwapRoleContext>, host_fee_kbps: u64) -> Result<()> {
+        invariant!(
+            ctx.accounts.factory.timelock_delay_seconds == 0,
+            TimelockRequired
+        );
+        invariant!(host_fee_kbps <= fees::KBPS_PER_WHOLE, InvalidFee);
+        ctx.accounts.swap.host_fee_kbps = host_fee_kbps;
+        Ok(())
+    }
+
+    /// Sets the proportion of the admin trade fee of a [SwapInfo] which is sent to its creator.
+    /// Rejected while the [Factory] has a timelock.
+    /// Only the admin of the [SwapInfo] or the holder of [Role::FeeSetter] may call this.
+    #[access_control(ctx.accounts.validate_role(Role::FeeSetter))]
+    pub fn set_creator_fee(ctx: Context<SwapRoleContext>, creator_fee_kbps: u64) -> Result<()> {
+        invariant!(
+            ctx.accounts.factory.timelock_delay_seconds == 0,
+            TimelockRequired
+        );
+        invariant!(creator_fee_kbps <= fees::KBPS_PER_WHOLE, InvalidFee);
+        invariant!(
+            ctx.accounts.swap.creator != Pubkey::default(),
+            "swap has no creator"
+        );
+        ctx.accounts.swap.creator_fee_kbps = creator_fee_kbps;
+        Ok(())
+    }
+
+    /// Pauses or unpauses swaps in each direction of a [SwapInfo].
+    /// Only the admin of the [SwapInfo] or the holder of [Role::Pauser] may call this,
+    /// and only the admin may unpause a direction.
+    #[access_control(ctx.accounts.validate_role(Role::Pauser))]
+    pub fn set_directional_pause(
+        ctx: Context<SwapRoleContext>,
+        is_paused_0_to_1: bool,
+        is_paused_1_to_0: bool,
+    ) -> Result<()> {
+        let swap_info = &mut ctx.accounts.swap;
+        if ctx.accounts.authority.key() != swap_info.admin_key {
+            invariant!(
+                (is_paused_0_to_1 || !swap_info.is_paused_0_to_1)
+                    && (is_paused_1_to_0 || !swap_info.is_paused_1_to_0),
+                MissingRole
+            );
+        }
+        swap_info.is_paused_0_to_1 = is_paused_0_to_1;
+        swap_info.is_paused_1_to_0 = is_paused_1_to_0;
+        Ok(())
+    }
+
+    /// Sets the maximum input of a swap as a proportion of the input reserve, in bps.
+    /// Zero removes the limit. Only the admin of the [SwapInfo] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_max_trade_size(
+        ctx: Context<SwapAdminContext>,
+        max_trade_bps_of_reserve: u16,
+    ) -> Result<()> {
+        invariant!(
+            u64::from(max_trade_bps_of_reserve) <= xyk::BPS_PER_WHOLE,
+            "max trade size cannot exceed the reserve"
+        );
+        ctx.accounts.swap.max_trade_bps_of_reserve = max_trade_bps_of_reserve;
+        Ok(())
+    }
+
+    /// Sets the maximum amounts of tokens 0 and 1 in the pool after a deposit,
+    /// such as for a guarded launch. Zero removes a cap.
+    /// Only the admin of the [SwapInfo] may call this.
+    ///
+    /// Deposits exceeding a cap fail with [ErrorCode::DepositCapExceeded].
+    /// Swaps are not capped.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_deposit_cap(
+        ctx: Context<SwapAdminContext>,
+        max_reserve_0: u64,
+        max_reserve_1: u64,
+    ) -> Result<()> {
+        let swap = &mut ctx.accounts.swap;
+        swap.max_reserve_0 = max_reserve_0;
+        swap.max_reserve_1 = max_reserve_1;
+        Ok(())
+    }
+
+    /// Sets the number of slots after a deposit during which the depositor cannot withdraw,
+    /// which deters just-in-time liquidity around large swaps. Zero removes the cooldown.
+    /// Only the admin of the [SwapInfo] may call this.
+    ///
+    /// While a cooldown is set, deposits and withdrawals must pass the [DepositCooldown]
+    /// of their LP token account as their first remaining account. Deposits freeze the
+    /// LP token account until the cooldown has passed, so that its LP tokens cannot be
+    /// transferred to another token account to withdraw them sooner. Withdrawals thaw it,
+    /// as does [cpamm::thaw_lp_account].
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_withdraw_cooldown(
+        ctx: Context<SwapAdminContext>,
+        withdraw_cooldown_slots: u32,
+    ) -> Result<()> {
+        ctx.accounts.swap.withdraw_cooldown_slots = withdraw_cooldown_slots;
+        Ok(())
+    }
+
+    /// Creates the [DepositCooldown] of an LP token account of a [SwapInfo].
+    /// Anyone may create it, since it only records the slot of the latest deposit to the
+    /// LP token account.
+    #[access_control(ctx.accounts.validate())]
+    pub fn new_deposit_cooldown(ctx: Context<NewDepositCooldown>) -> Result<()> {
+        let cooldown = &mut ctx.accounts.cooldown;
+        cooldown.swap = ctx.accounts.swap.key();
+        cooldown.lp_account = ctx.accounts.lp_account.key();
+        cooldown.bump = unwrap_bump!(ctx, "cooldown");
+        Ok(())
+    }
+
+    /// Thaws an LP token account frozen by a deposit once its withdraw cooldown has passed,
+    /// so that its LP tokens can be transferred. Anyone may call this.
+    ///
+    /// Fails with [ErrorCode::WithdrawCooldownActive] if the cooldown has not passed.
+    #[access_control(ctx.accounts.validate())]
+    pub fn thaw_lp_account(ctx: Context<ThawLpAccount>) -> Result<()> {
+        controller::check_cooldown_passed(&ctx.accounts.swap, &ctx.accounts.cooldown)?;
+        controller::thaw_lp_account(
+            &ctx.accounts.swap,
+            &ctx.accounts.token_program,
+            &ctx.accounts.pool_mint,
+            &ctx.accounts.lp_account,
+        )
+    }
+
+    /// Sets the maximum number of swaps per slot of a [SwapInfo].
+    /// Zero removes the limit. Only the admin of the [SwapInfo] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_max_swaps_per_slot(
+        ctx: Context<SwapAdminContext>,
+        max_swaps_per_slot: u16,
+    ) -> Result<()> {
+        ctx.accounts.swap.max_swaps_per_slot = max_swaps_per_slot;
+        Ok(())
+    }
+
+    /// Sets the maximum price impact of a swap versus the spot price, in bps.
+    /// Zero removes the limit. Only the admin of the [SwapInfo] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_max_price_impact(
+        ctx: Context<SwapAdminContext>,
+        max_price_impact_bps: u16,
+    ) -> Result<()> {
+        invariant!(
+            u64::from(max_price_impact_bps) <= xyk::BPS_PER_WHOLE,
+            "max price impact cannot exceed 100%"
+        );
+        ctx.accounts.swap.max_price_impact_bps = max_price_impact_bps;
+        Ok(())
+    }
+
+    /// Configures the circuit breaker of a [SwapInfo], which blocks swaps for the rest of
+    /// the slot and `cooldown_slots` more slots once either reserve has changed by more than
+    /// `max_reserve_change_bps_per_slot` within a slot. Zero disables the circuit breaker.
+    /// This also clears the circuit breaker if it has tripped.
+    /// Only the admin of the [SwapInfo] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_circuit_breaker(
+        ctx: Context<SwapAdminContext>,
+        max_reserve_change_bps_per_slot: u16,
+        cooldown_slots: u32,
+    ) -> Result<()> {
+        let swap_info = &mut ctx.accounts.swap;
+        swap_info.max_reserve_change_bps_per_slot = max_reserve_change_bps_per_slot;
+        swap_info.circuit_breaker_cooldown_slots = cooldown_slots;
+        swap_info.circuit_breaker_until_slot = 0;
+        Ok(())
+    }
+
+    /// Configures the dynamic trade fee of a [SwapInfo], which adds
+    /// `fee_kbps_per_volatility_bps` to the trade fee for every bps of the volatility of the swap,
+    /// up to `max_trade_fee_kbps`. The volatility is a moving average of the price changes of swaps,
+    /// weighting the latest price change by `volatility_weight_bps`.
+    ///
+    /// A `fee_kbps_per_volatility_bps` of zero disables the dynamic trade fee.
+    /// The `max_trade_fee_kbps` cannot exceed [fees::MAX_TRADE_FEE_KBPS].
+    /// Rejected while the [Factory] has a timelock.
+    /// Only the admin of the [SwapInfo] or the holder of [Role::FeeSetter] may call this.
+    #[access_control(ctx.accounts.validate_role(Role::FeeSetter))]
+    pub fn set_dynamic_fee(
+        ctx: Context<SwapRoleContext>,
+        fee_kbps_per_volatility_bps: u64,
+        max_trade_fee_kbps: u64,
+        volatility_weight_bps: u16,
+    ) -> Result<()> {
+        invariant!(
+            ctx.accounts.factory.timelock_delay_seconds == 0,
+            TimelockRequired
+        );
+        invariant!(max_trade_fee_kbps <= fees::MAX_TRADE_FEE_KBPS, InvalidFee);
+        invariant!(
+            u64::from(volatility_weight_bps) <= xyk::BPS_PER_WHOLE,
+            "volatility weight cannot exceed 100%"
+        );
+
+        let swap = &mut ctx.accounts.swap;
+        if fee_kbps_per_volatility_bps == 0 {
+            // stale volatility should not carry over if the dynamic fee is enabled again
+            swap.volatility_bps = 0;
+        }
+        swap.dynamic_fee_kbps_per_volatility_bps = fee_kbps_per_volatility_bps;
+        swap.max_dynamic_trade_fee_kbps = max_trade_fee_kbps;
+        swap.volatility_weight_bps = volatility_weight_bps;
+        Ok(())
+    }
+
+    /// Sets the [SwapFees] of a [SwapInfo]. The trade and withdraw fees cannot exceed
+    /// [fees::MAX_TRADE_FEE_KBPS] and [fees::MAX_WITHDRAW_FEE_KBPS], and the admin trade fee
+    /// cannot exceed [fees::MAX_PROTOCOL_FEE_SHARE_KBPS].
+    /// Records the time of the change in [SwapInfo::fees_last_changed_at].
+    /// If the [Factory] has a timelock, the fees must be changed with [cpamm::queue_change].
+    /// Only the admin of the [SwapInfo] or the holder of [Role::FeeSetter] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_fees(ctx: Context<SetFees>, new_fees: SwapFees) -> Result<()> {
+        invariant!(
+            ctx.accounts.factory.timelock_delay_seconds == 0,
+            TimelockRequired
+        );
+        new_fees.validate_settable()?;
+
+        let swap = &mut ctx.accounts.swap;
+        swap.fees = new_fees;
+        swap.fees_last_changed_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Sets the launch trade fee of a [SwapInfo], which decays linearly from
+    /// `launch_trade_fee_kbps` at the creation of the swap to its trade fee over
+    /// `launch_fee_duration_seconds`. This is usually set right after [cpamm::new_swap].
+    /// The launch trade fee cannot exceed [fees::MAX_TRADE_FEE_KBPS].
+    /// Rejected while the [Factory] has a timelock.
+    /// Only the admin of the [SwapInfo] or the holder of [Role::FeeSetter] may call this.
+    #[access_control(ctx.accounts.validate_role(Role::FeeSetter))]
+    pub fn set_launch_fee(
+        ctx: Context<SwapRoleContext>,
+        launch_trade_fee_kbps: u64,
+        launch_fee_duration_seconds: i64,
+    ) -> Result<()> {
+        invariant!(
+            ctx.accounts.factory.timelock_delay_seconds == 0,
+            TimelockRequired
+        );
+        invariant!(
+            launch_trade_fee_kbps <= fees::MAX_TRADE_FEE_KBPS,
+            InvalidFee
+        );
+        invariant!(
+            launch_fee_duration_seconds >= 0,
+            "launch fee duration cannot be negative"
+        );
+        let swap = &mut ctx.accounts.swap;
+        swap.launch_trade_fee_kbps = launch_trade_fee_kbps;
+        swap.launch_fee_duration_seconds = launch_fee_duration_seconds;
+        Ok(())
+    }
+
+    /// Sets the trade fees added to the swaps of each direction of a [SwapInfo], which
+    /// discourages trading in one direction, such as selling a token with a transfer tax.
+    /// The surcharges cannot exceed [fees::MAX_TRADE_FEE_KBPS].
+    /// Rejected while the [Factory] has a timelock.
+    /// Only the admin of the [SwapInfo] or the holder of [Role::FeeSetter] may call this.
+    #[access_control(ctx.accounts.validate_role(Role::FeeSetter))]
+    pub fn set_directional_fees(
+        ctx: Context<SwapRoleContext>,
+        trade_fee_surcharge_0_to_1_kbps: u64,
+        trade_fee_surcharge_1_to_0_kbps: u64,
+    ) -> Result<()> {
+        invariant!(
+            ctx.accounts.factory.timelock_delay_seconds == 0,
+            TimelockRequired
+        );
+        invariant!(
+            trade_fee_surcharge_0_to_1_kbps <= fees::MAX_TRADE_FEE_KBPS
+                && trade_fee_surcharge_1_to_0_kbps <= fees::MAX_TRADE_FEE_KBPS,
+            InvalidFee
+        );
+        let swap = &mut ctx.accounts.swap;
+        swap.trade_fee_surcharge_0_to_1_kbps = trade_fee_surcharge_0_to_1_kbps;
+        swap.trade_fee_surcharge_1_to_0_kbps = trade_fee_surcharge_1_to_0_kbps;
+        Ok(())
+    }
+
+    /// Sets the Pyth price account guarding the execution prices of the swaps of a [SwapInfo].
+    /// Swaps whose execution price deviates from the price of the oracle by more than
+    /// `oracle_band_bps` fail with [ErrorCode::OraclePriceDeviation]. The default key as the
+    /// `oracle` removes the guard. Only the admin of the [SwapInfo] may call this.
+    ///
+    /// If `is_oracle_inverted` is false, the oracle prices token 0 in token 1.
+    /// Otherwise, it prices token 1 in token 0. While the guard is set, the oracle must be
+    /// passed anywhere in the remaining accounts of swaps, including implicit swaps of
+    /// single-sided deposits and withdrawals. For more information, view [crate::oracle].
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_oracle_band(
+        ctx: Context<SetOracleBand>,
+        is_oracle_inverted: bool,
+        oracle_band_bps: u16,
+    ) -> Result<()> {
+        let oracle = ctx.accounts.oracle.key();
+        let (decimals_0, decimals_1) = (
+            unwrap_int!(i8::try_from(ctx.accounts.token_0_mint.decimals).ok()),
+            unwrap_int!(i8::try_from(ctx.accounts.token_1_mint.decimals).ok()),
+        );
+        let oracle_decimals_adjustment = unwrap_int!(if is_oracle_inverted {
+            decimals_0.checked_sub(decimals_1)
+        } else {
+            decimals_1.checked_sub(decimals_0)
+        });
+        if oracle != Pubkey::default() {
+            invariant!(
+                u64::from(oracle_band_bps) <= xyk::BPS_PER_WHOLE,
+                "oracle band cannot exceed 100%"
+            );
+            invariant!(
+                oracle::pyth_price(
+                    &ctx.accounts.oracle.try_borrow_data()?,
+                    oracle_decimals_adjustment,
+                    is_oracle_inverted
+                )
+                .is_some(),
+                "oracle must be a trading Pyth price account"
+            );
+        }
+
+        let swap = &mut ctx.accounts.swap;
+        swap.oracle = oracle;
+        swap.is_oracle_inverted = is_oracle_inverted;
+        swap.oracle_decimals_adjustment = oracle_decimals_adjustment;
+        swap.oracle_band_bps = oracle_band_bps;
+        Ok(())
+    }
+
+    /// Sets the [FeeBounds] of a [Factory], creating them if they do not exist. The bounds
+    /// cannot exceed [fees::MAX_TRADE_FEE_KBPS] and [fees::MAX_WITHDRAW_FEE_KBPS].
+    /// Only the admin of the [Factory] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_fee_bounds(
+        ctx: Context<SetFeeBounds>,
+        min_trade_fee_kbps: u64,
+        max_trade_fee_kbps: u64,
+        min_withdraw_fee_kbps: u64,
+        max_withdraw_fee_kbps: u64,
+    ) -> Result<()> {
+        invariant!(
+            min_trade_fee_kbps <= max_trade_fee_kbps
+                && max_trade_fee_kbps <= fees::MAX_TRADE_FEE_KBPS,
+            InvalidFee
+        );
+        invariant!(
+            min_withdraw_fee_kbps <= max_withdraw_fee_kbps
+                && max_withdraw_fee_kbps <= fees::MAX_WITHDRAW_FEE_KBPS,
+            InvalidFee
+        );
+
+        let fee_bounds = &mut ctx.accounts.fee_bounds;
+        fee_bounds.factory = ctx.accounts.factory.key();
+        fee_bounds.bump = unwrap_bump!(ctx, "fee_bounds");
+        fee_bounds.min_trade_fee_kbps = min_trade_fee_kbps;
+        fee_bounds.max_trade_fee_kbps = max_trade_fee_kbps;
+        fee_bounds.min_withdraw_fee_kbps = min_withdraw_fee_kbps;
+        fee_bounds.max_withdraw_fee_kbps = max_withdraw_fee_kbps;
+        Ok(())
+    }
+
+    /// Sets the trade and withdraw fees of a [SwapInfo] within the [FeeBounds] of its [Factory].
+    /// The admin shares of the fees are unchanged.
+    /// Records the time of the change in [SwapInfo::fees_last_changed_at].
+    /// Rejected while the [Factory] has a timelock.
+    /// Only the creator or the admin of the [SwapInfo] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_pool_fees(
+        ctx: Context<SetPoolFees>,
+        trade_fee_kbps: u64,
+        withdraw_fee_kbps: u64,
+    ) -> Result<()> {
+        invariant!(
+            ctx.accounts.factory.timelock_delay_seconds == 0,
+            TimelockRequired
+        );
+        let fee_bounds = &ctx.accounts.fee_bounds;
+        invariant!(
+            trade_fee_kbps >= fee_bounds.min_trade_fee_kbps
+                && trade_fee_kbps <= fee_bounds.max_trade_fee_kbps,
+            InvalidFee
+        );
+        invariant!(
+            withdraw_fee_kbps >= fee_bounds.min_withdraw_fee_kbps
+                && withdraw_fee_kbps <= fee_bounds.max_withdraw_fee_kbps,
+            InvalidFee
+        );
+
+        let swap = &mut ctx.accounts.swap;
+        swap.fees.trade_fee_kbps = trade_fee_kbps;
+        swap.fees.withdraw_fee_kbps = withdraw_fee_kbps;
+        swap.fees_last_changed_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Sets the proportion of the trade fee of a [SwapInfo] which is sent to its admin fee
+    /// accounts as the protocol fee, up to [fees::MAX_PROTOCOL_FEE_SHARE_KBPS]. The rest of the
+    /// trade fee accrues to the LPs in the reserves. This is the
+    /// [SwapFees::admin_trade_fee_kbps].
+    /// Rejected while the [Factory] has a timelock.
+    /// Only the admin of the [Factory] or the holder of [Role::FeeSetter] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_protocol_fee_share(
+        ctx: Context<SetProtocolFeeShare>,
+        protocol_fee_share_kbps: u64,
+    ) -> Result<()> {
+        invariant!(
+            ctx.accounts.factory.timelock_delay_seconds == 0,
+            TimelockRequired
+        );
+        invariant!(
+            protocol_fee_share_kbps <= fees::MAX_PROTOCOL_FEE_SHARE_KBPS,
+            InvalidFee
+        );
+        let swap = &mut ctx.accounts.swap;
+        swap.fees.admin_trade_fee_kbps = protocol_fee_share_kbps;
+        swap.fees_last_changed_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Sets the [SwapFeeTier]s of a [SwapInfo], in ascending order of trade size.
+    /// The trade fee of the largest tier a trade reaches replaces the base trade fee.
+    /// An empty list removes the tiers. The trade fees cannot exceed [fees::MAX_TRADE_FEE_KBPS].
+    /// Rejected while the [Factory] has a timelock.
+    /// Only the admin of the [SwapInfo] or the holder of [Role::FeeSetter] may call this.
+    #[access_control(ctx.accounts.validate_role(Role::FeeSetter))]
+    pub fn set_fee_tiers(ctx: Context<SwapRoleContext>, fee_tiers: Vec<SwapFeeTier>) -> Result<()> {
+        invariant!(
+            ctx.accounts.factory.timelock_delay_seconds == 0,
+            TimelockRequired
+        );
+        invariant!(fee_tiers.len() <= MAX_FEE_TIERS, "too many fee tiers");
+        let mut min_trade_bps_of_reserve = 0;
+        for tier in fee_tiers.iter() {
+            invariant!(
+                tier.min_trade_bps_of_reserve > min_trade_bps_of_reserve
+                    && u64::from(tier.min_trade_bps_of_reserve) <= xyk::BPS_PER_WHOLE,
+                "fee tiers must have ascending, non-zero trade sizes of at most 100%"
+            );
+            invariant!(tier.trade_fee_kbps <= fees::MAX_TRADE_FEE_KBPS, InvalidFee);
+            min_trade_bps_of_reserve = tier.min_trade_bps_of_reserve;
+        }
+
+        let swap = &mut ctx.accounts.swap;
+        swap.fee_tiers = Default::default();
+        swap.fee_tiers[..fee_tiers.len()].copy_from_slice(&fee_tiers);
+        Ok(())
+    }
+
+    /// Registers a [Referrer] for the `owner`.
+    ///
+    /// Swaps reference the [Referrer] by passing it after a referrer token account it owns,
+    /// which receives the referral fee and is counted in [Referrer::num_referrals].
+    #[access_control(ctx.accounts.validate())]
+    pub fn new_referrer(ctx: Context<NewReferrer>) -> Result<()> {
+        let referrer = &mut ctx.accounts.referrer;
+        referrer.owner = ctx.accounts.owner.key();
+        referrer.bump = unwrap_bump!(ctx, "referrer");
+        referrer.num_referrals = 0;
+        Ok(())
+    }
+
+    /// Sends all referral fees in a token account of a [Referrer] to the `destination`.
+    /// Only the owner of the [Referrer] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn claim_referral_fees(ctx: Context<ClaimReferralFees>) -> Result<()> {
+        let referrer = &ctx.accounts.referrer;
+        let seeds: &[&[u8]] = &[b"Referrer", referrer.owner.as_ref(), &[referrer.bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.fees.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: referrer.to_account_info(),
+                },
+                &[seeds],
+            ),
+            ctx.accounts.fees.amount,
+        )
+    }
+
+    /// Sets the owner of the token accounts of the [FactoryTreasury] of a [Factory],
+    /// creating the [FactoryTreasury] if it does not exist.
+    /// Only the admin of the [Factory] or the holder of [Role::Treasurer] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_treasury(ctx: Context<SetTreasury>, owner: Pubkey) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.factory = ctx.accounts.factory.key();
+        treasury.bump = unwrap_bump!(ctx, "treasury");
+        treasury.owner = owner;
+        Ok(())
+    }
+
+    /// Sets the discount mint and the [HolderDiscountTier]s of the [HolderDiscountConfig] of a
+    /// [Factory], creating it if it does not exist. The tiers are in ascending order of balance.
+    /// An empty list removes the discounts.
+    /// Rejected while the [Factory] has a timelock.
+    /// Only the admin of the [Factory] or the holder of [Role::FeeSetter] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_holder_discount(
+        ctx: Context<SetHolderDiscount>,
+        discount_mint: Pubkey,
+        tiers: Vec<HolderDiscountTier>,
+    ) -> Result<()> {
+        invariant!(
+            ctx.accounts.factory.timelock_delay_seconds == 0,
+            TimelockRequired
+        );
+        invariant!(
+            tiers.len() <= MAX_HOLDER_DISCOUNT_TIERS,
+            "too many holder discount tiers"
+        );
+        let mut min_balance = 0;
+        for tier in tiers.iter() {
+            invariant!(
+                tier.min_balance > min_balance,
+                "holder discount tiers must have ascending, non-zero balances"
+            );
+            invariant!(
+                tier.trade_fee_discount_kbps <= fees::KBPS_PER_WHOLE,
+                InvalidFee
+            );
+            min_balance = tier.min_balance;
+        }
+
+        let config = &mut ctx.accounts.config;
+        config.factory = ctx.accounts.factory.key();
+        config.bump = unwrap_bump!(ctx, "config");
+        config.discount_mint = discount_mint;
+        config.tiers = Default::default();
+        config.tiers[..tiers.len()].copy_from_slice(&tiers);
+        Ok(())
+    }
+
+    /// Sets the window and the [VolumeRebateTier]s of the [VolumeRebateConfig] of a [Factory],
+    /// creating it if it does not exist. The tiers are in ascending order of volume.
+    /// An empty list removes the rebates.
+    /// Rejected while the [Factory] has a timelock.
+    /// Only the admin of the [Factory] or the holder of [Role::FeeSetter] may call this.
+    ///
+    /// Traders earn the rebates by passing the [VolumeRebateConfig] and their [TraderStats]
+    /// to swaps, which track the volume of the trader.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_volume_rebates(
+        ctx: Context<SetVolumeRebates>,
+        window_seconds: i64,
+        tiers: Vec<VolumeRebateTier>,
+    ) -> Result<()> {
+        invariant!(
+            ctx.accounts.factory.timelock_delay_seconds == 0,
+            TimelockRequired
+        );
+        invariant!(window_seconds > 0, "volume window must be positive");
+        invariant!(
+            tiers.len() <= MAX_VOLUME_REBATE_TIERS,
+            "too many volume rebate tiers"
+        );
+        let mut min_volume = 0;
+        for tier in tiers.iter() {
+            invariant!(
+                tier.min_volume > min_volume,
+                "volume rebate tiers must have ascending, non-zero volumes"
+            );
+            invariant!(
+                tier.trade_fee_rebate_kbps <= fees::KBPS_PER_WHOLE,
+                InvalidFee
+            );
+            min_volume = tier.min_volume;
+        }
+
+        let config = &mut ctx.accounts.config;
+        config.factory = ctx.accounts.factory.key();
+        config.bump = unwrap_bump!(ctx, "config");
+        config.window_seconds = window_seconds;
+        config.tiers = Default::default();
+        config.tiers[..tiers.len()].copy_from_slice(&tiers);
+        Ok(())
+    }
+
+    /// Creates the [TraderStats] of `owner` for a [SwapInfo].
+    /// Anyone may create it, since it only records the volume swapped by the owner.
+    #[access_control(ctx.accounts.validate())]
+    pub fn new_trader_stats(ctx: Context<NewTraderStats>) -> Result<()> {
+        let trader_stats = &mut ctx.accounts.trader_stats;
+        trader_stats.swap = ctx.accounts.swap.key();
+        trader_stats.owner = ctx.accounts.owner.key();
+        trader_stats.bump = unwrap_bump!(ctx, "trader_stats");
+        Ok(())
+    }
+
+    /// Sets the buyback mint and the maximum slippage of the [BuybackConfig] of a [Factory],
+    /// creating it if it does not exist. Only the admin of the [Factory] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_buyback(
+        ctx: Context<SetBuyback>,
+        buyback_mint: Pubkey,
+        max_slippage_bps: u16,
+    ) -> Result<()> {
+        invariant!(
+            max_slippage_bps > 0 && u64::from(max_slippage_bps) <= xyk::BPS_PER_WHOLE,
+            "max slippage must be non-zero and at most 100%"
+        );
+        let config = &mut ctx.accounts.config;
+        config.factory = ctx.accounts.factory.key();
+        config.bump = unwrap_bump!(ctx, "config");
+        config.buyback_mint = buyback_mint;
+        config.max_slippage_bps = max_slippage_bps;
+        Ok(())
+    }
+
+    /// Swaps the admin fees of a [SwapInfo] for the buyback mint of the [BuybackConfig]
+    /// and burns the bought tokens. The fees must be of a token paired with the buyback mint
+    /// in the swap they are sold to, which must be a constant product swap of the same [Factory].
+    ///
+    /// Only as much of the fees is swapped as receives at least the spot price of the swap,
+    /// less the maximum slippage of the [BuybackConfig], and at least the price of
+    /// `minimum_amount_out` for all of the fees. The rest stays in the admin fee account.
+    /// Anyone may call this, such as a keeper.
+    ///
+    /// If the swap has an oracle band, its oracle must be passed as a remaining account.
+    #[access_control(ctx.accounts.validate())]
+    pub fn buyback_and_burn<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuybackAndBurn<'info>>,
+        minimum_amount_out: u64,
+    ) -> Result<()> {
+        controller::buyback_and_burn::buyback_and_burn(ctx, minimum_amount_out)
+    }
+
+    /// Sets the [FeeRecipient]s of the [FeeDistribution] of a [Factory], creating it if it
+    /// does not exist. The shares cannot add up to more than 100%. An empty list sends all
+    /// collected fees to the [FactoryTreasury].
+    /// Only the admin of the [Factory] or the holder of [Role::Treasurer] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_fee_distribution(
+        ctx: Context<SetFeeDistribution>,
+        recipients: Vec<FeeRecipient>,
+    ) -> Result<()> {
+        invariant!(
+            recipients.len() <= MAX_FEE_RECIPIENTS,
+            "too many fee recipients"
+        );
+        let mut total_share_bps: u64 = 0;
+        for recipient in recipients.iter() {
+            invariant!(recipient.share_bps > 0, "fee recipients must have a share");
+            total_share_bps = unwrap_int!(total_share_bps.checked_add(recipient.share_bps.into()));
+        }
+        invariant!(
+            total_share_bps <= xyk::BPS_PER_WHOLE,
+            "fee shares cannot add up to more than 100%"
+        );
+
+        let fee_distribution = &mut ctx.accounts.fee_distribution;
+        fee_distribution.factory = ctx.accounts.factory.key();
+        fee_distribution.bump = unwrap_bump!(ctx, "fee_distribution");
+        fee_distribution.recipients = Default::default();
+        fee_distribution.recipients[..recipients.len()].copy_from_slice(&recipients);
+        Ok(())
+    }
+
+    /// Sends the balances of the admin fee accounts of a [SwapInfo] to the token accounts
+    /// of the [FactoryTreasury]. If the [Factory] has a [FeeDistribution], its recipients
+    /// receive their shares first. The admin fee accounts must be owned by the swap.
+    /// Anyone may collect the fees, since they can only go to the treasury and the recipients.
+    #[access_control(ctx.accounts.validate())]
+    pub fn collect_protocol_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, CollectProtocolFees<'info>>,
+    ) -> Result<()> {
+        controller::collect_protocol_fees::collect_protocol_fees(ctx)
+    }
+
+    /// Sends the lamports of a [SwapInfo] and its [SwapMeta] above their rent exemption
+    /// minimums, such as SOL sent to the address of the pool by mistake, to the owner of the
+    /// [FactoryTreasury]. The reserves are token accounts, whose lamports cannot be swept.
+    /// Anyone may sweep the lamports, since they can only go to the treasury.
+    #[access_control(ctx.accounts.validate())]
+    pub fn sweep_lamports(ctx: Context<SweepLamports>) -> Result<()> {
+        controller::sweep_lamports::sweep_lamports(ctx)
+    }
+
+    /// Points the admin fee accounts of a [SwapInfo] to new token accounts, such as when
+    /// the treasury rotates its accounts. The accounts must be owned by the swap or by the owner
+    /// of the [FactoryTreasury].
+    /// Only the admin of the [Factory] or the holder of [Role::Treasurer] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_admin_fee_accounts(ctx: Context<SetAdminFeeAccounts>) -> Result<()> {
+        let swap = &mut ctx.accounts.swap;
+        swap.token_0.admin_fees = ctx.accounts.fees_0.key();
+        swap.token_1.admin_fees = ctx.accounts.fees_1.key();
+        Ok(())
+    }
+
+    /// Sets whether anyone may compound the admin fees of a [SwapInfo] into protocol-owned
+    /// liquidity with [cpamm::compound_admin_fees]. Only the admin of the [Factory] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_auto_compound(
+        ctx: Context<SetAutoCompound>,
+        is_auto_compound_enabled: bool,
+    ) -> Result<()> {
+        ctx.accounts.swap.is_auto_compound_enabled = is_auto_compound_enabled;
+        Ok(())
+    }
+
+    /// Deposits the admin fees of a [SwapInfo] into its reserves. The excess of one token
+    /// is swapped into the other like in [cpamm::deposit_imbalanced], so about half of it
+    /// is swapped. The LP tokens are minted to an account owned by the [Factory] of the swap as
+    /// protocol-owned liquidity. Anyone may call this while [SwapInfo::is_auto_compound_enabled].
+    #[access_control(ctx.accounts.validate())]
+    pub fn compound_admin_fees(ctx: Context<CompoundAdminFees>) -> Result<()> {
+        controller::compound_admin_fees::compound_admin_fees(ctx)
+    }
+
+    /// Sets how [MintListEntry]s restrict the mints of new swaps on a [Factory].
+    /// Only the admin of the [Factory] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_mint_list_mode(ctx: Context<FactoryAdminContext>, mint_list_mode: u8) -> Result<()> {
+        invariant!(
+            mint_list_mode <= MINT_LIST_MODE_DENY,
+            "invalid mint list mode"
+        );
+        ctx.accounts.factory.mint_list_mode = mint_list_mode;
+        Ok(())
+    }
+
+    /// Sets how new swaps on a [Factory] treat mints with a freeze authority.
+    /// Only the admin of the [Factory] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_freeze_authority_policy(
+        ctx: Context<FactoryAdminContext>,
+        freeze_authority_policy: u8,
+    ) -> Result<()> {
+        invariant!(
+            freeze_authority_policy <= FREEZE_AUTHORITY_POLICY_REJECT,
+            "invalid freeze authority policy"
+        );
+        ctx.accounts.factory.freeze_authority_policy = freeze_authority_policy;
+        Ok(())
+    }
+
+    /// Creates a [MintListEntry] for `mint` on a [Factory].
+    /// Only the admin of the [Factory] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn new_mint_list_entry(ctx: Context<NewMintListEntry>, mint: Pubkey) -> Result<()> {
+        let mint_list_entry = &mut ctx.accounts.mint_list_entry;
+        mint_list_entry.factory = ctx.accounts.factory.key();
+        mint_list_entry.mint = mint;
+        mint_list_entry.bump = unwrap_bump!(ctx, "mint_list_entry");
+        Ok(())
+    }
+
+    /// Closes a [MintListEntry], removing its mint from the list.
+    /// Only the admin of the [Factory] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn close_mint_list_entry(ctx: Context<CloseMintListEntry>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Creates a [FeeExemption] for `address`, which then pays a discounted trade fee
+    /// on the swaps of the [Factory]. Only the admin of the [Factory] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn new_fee_exemption(
+        ctx: Context<NewFeeExemption>,
+        address: Pubkey,
+        trade_fee_discount_kbps: u64,
+    ) -> Result<()> {
+        invariant!(trade_fee_discount_kbps <= fees::KBPS_PER_WHOLE, InvalidFee);
+
+        let fee_exemption = &mut ctx.accounts.fee_exemption;
+        fee_exemption.factory = ctx.accounts.factory.key();
+        fee_exemption.address = address;
+        fee_exemption.bump = unwrap_bump!(ctx, "fee_exemption");
+        fee_exemption.trade_fee_discount_kbps = trade_fee_discount_kbps;
+
+        Ok(())
+    }
+
+    /// Sets the trade fee discount of a [FeeExemption].
+    /// Only the admin of the [Factory] may call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_fee_exemption(
+        ctx: Context<SetFeeExemption>,
+        trade_fee_discount_kbps: u64,
+    ) -> Result<()> {
+        invariant!(trade_fee_discount_kbps <= fees::KBPS_PER_WHOLE, InvalidFee);
+        ctx.accounts.fee_exemption.trade_fee_discount_kbps = trade_fee_discount_kbps;
+        Ok(())
+    }
+
+    /// Performs a withdraw.
+    ///
+    /// The LP portion of the trade fees stays in the reserves, so the withdrawn tokens
+    /// already include the fees earned by the LP tokens. There are no fees to claim separately.
+    ///
+    /// Fails with [ErrorCode::WithdrawBelowMinimumAmountOut] if fewer than the minimum amounts
+    /// of either token would be received, and with [ErrorCode::WithdrawCooldownActive] if LP
+    /// tokens were deposited to the input LP token account within the withdraw cooldown of
+    /// the pool.
+    /// If a `deadline` is provided, the withdraw fails if it is executed after that Unix timestamp.
+    #[access_control(ctx.accounts.validate())]
+    pub fn withdraw(
+        ctx: Context<Withdraw>,
+        amount_in: u64,
+        minimum_amount_out_0: u64,
+        minimum_amount_out_1: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        controller::withdraw::withdraw(
+            ctx,
+            controller::withdraw::WithdrawArgs {
+                amount_in,
+                minimum_amount_out_0,
+                minimum_amount_out_1,
+                deadline,
+            },
+        )
+    }
+
+    /// Withdraws all of the LP tokens in the user's input LP token account.
+    ///
+    /// The amount is read when the instruction is executed, so no LP tokens are left behind
+    /// if the balance changed after the withdrawal was quoted.
+    #[access_control(ctx.accounts.validate())]
+    pub fn withdraw_all(
+        ctx: Context<Withdraw>,
+        minimum_amount_out_0: u64,
+        minimum_amount_out_1: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        let amount_in = ctx.accounts.input_lp.amount;
+        controller::withdraw::withdraw(
+            ctx,
+            controller::withdraw::WithdrawArgs {
+                amount_in,
+                minimum_amount_out_0,
+                minimum_amount_out_1,
+                deadline,
+            },
+        )
+    }
+
+    /// Performs a [cpamm::withdraw] to associated token accounts,
+    /// which the payer creates if they do not exist.
+    #[access_control(ctx.accounts.validate())]
+    pub fn withdraw_to_ata<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawToAta<'info>>,
+        amount_in: u64,
+        minimum_amount_out_0: u64,
+        minimum_amount_out_1: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        controller::withdraw::withdraw(
+            Context::new(
+                ctx.program_id,
+                &mut ctx.accounts.withdraw,
+                ctx.remaining_accounts,
+                ctx.bumps.clone(),
+            ),
+            controller::withdraw::WithdrawArgs {
+                amount_in,
+                minimum_amount_out_0,
+                minimum_amount_out_1,
+                deadline,
+            },
+        )
+    }
+
+    /// Withdraws only one of the two tokens of the swap.
+    ///
+    /// The other token withdrawn for the LP tokens is swapped into the output token,
+    /// paying the trade fee. Fails if fewer than `minimum_amount_out` tokens would be received.
+    /// If a `deadline` is provided, the withdraw fails if it is executed after that Unix timestamp.
+    #[access_control(ctx.accounts.validate())]
+    pub fn withdraw_one(
+        ctx: Context<WithdrawOne>,
+        pool_token_amount: u64,
+        minimum_amount_out: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        controller::withdraw_one::withdraw_one(
+            ctx,
+            controller::withdraw_one::WithdrawOneArgs {
+                pool_token_amount,
+                minimum_amount_out,
+                deadline,
+            },
+        )
+    }
+
+    /// Performs a deposit.
+    ///
+    /// Fails with [ErrorCode::DepositAboveMaximumAmountIn] if minting `pool_token_amount`
+    /// requires more than the maximum amounts of either token, and with
+    /// [ErrorCode::DepositCapExceeded] if the reserves would exceed the deposit cap of the pool.
+    /// If the pool has a withdraw cooldown, the deposit starts the cooldown of the output
+    /// LP token account.
+    /// If a `deadline` is provided, the deposit fails if it is executed after that Unix timestamp.
+    #[access_control(ctx.accounts.validate())]
+    pub fn deposit(
+        ctx: Context<Deposit>,
+        pool_token_amount: u64,
+        maximum_amount_in_0: u64,
+        maximum_amount_in_1: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        // update price info
+        let swap_info = &mut ctx.accounts.user.swap;
+        swap_info.update_price_info()?;
+
+        controller::deposit::deposit(
+            ctx,
+            controller::deposit::DepositArgs {
+                pool_token_amount,
+                maximum_amount_in_0,
+                maximum_amount_in_1,
+                deadline,
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Performs a [cpamm::deposit] paid by the user authority whose LP tokens are minted
+    /// to a token account of the `beneficiary`, such as for treasuries or programs
+    /// depositing on behalf of their users.
+    ///
+    /// The withdraw cooldown of the pool, if any, applies to the LP token account of the
+    /// beneficiary.
+    #[access_control(ctx.accounts.validate())]
+    pub fn deposit_for<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositFor<'info>>,
+        pool_token_amount: u64,
+        maximum_amount_in_0: u64,
+        maximum_amount_in_1: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        controller::deposit::deposit(
+            Context::new(
+                ctx.program_id,
+                &mut ctx.accounts.deposit,
+                ctx.remaining_accounts,
+                ctx.bumps.clone(),
+            ),
+            controller::deposit::DepositArgs {
+                pool_token_amount,
+                maximum_amount_in_0,
+                maximum_amount_in_1,
+                deadline,
+            },
+        )
+    }
+
+    /// Performs a deposit into a wrapped SOL pool, paying the SOL side in lamports.
+    ///
+    /// A temporary wrapped SOL account is created for the user and closed back to
+    /// the `payer` once the deposit is done, so the user never holds wrapped SOL.
+    /// The SOL deposited is taken from the `payer`.
+    /// If a `deadline` is provided, the deposit fails if it is executed after that Unix timestamp.
+    #[access_control(ctx.accounts.validate())]
+    pub fn deposit_sol(
+        ctx: Context<DepositSol>,
+        pool_token_amount: u64,
+        maximum_amount_in_0: u64,
+        maximum_amount_in_1: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        controller::deposit_sol::deposit_sol(
+            ctx,
+            controller::deposit_sol::DepositSolArgs {
+                pool_token_amount,
+                maximum_amount_in_0,
+                maximum_amount_in_1,
+                deadline,
+            },
+        )
+    }
+
+    /// Creates a [LockedPosition] of `owner`, which holds LP tokens of the swap
+    /// in escrow until `unlock_ts`.
+    #[access_control(ctx.accounts.validate())]
+    pub fn new_locked_position(ctx: Context<NewLockedPosition>, unlock_ts: i64) -> Result<()> {
+        let position = &mut ctx.accounts.position;
+        position.base = ctx.accounts.base.key();
+        position.bump = unwrap_bump!(ctx, "position");
+        position.swap = ctx.accounts.swap.key();
+        position.owner = ctx.accounts.owner.key();
+        position.escrow = ctx.accounts.escrow.key();
+        position.unlock_ts = unlock_ts;
+        position.pool_token_amount = 0;
+        Ok(())
+    }
+
+    /// Performs a deposit whose LP tokens are locked in a [LockedPosition] of the user.
+    ///
+    /// The LP tokens can only leave the position with [cpamm::unlock_position]
+    /// once its unlock timestamp has passed.
+    #[access_control(ctx.accounts.validate())]
+    pub fn deposit_locked<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositLocked<'info>>,
+        pool_token_amount: u64,
+        maximum_amount_in_0: u64,
+        maximum_amount_in_1: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        controller::deposit::deposit(
+            Context::new(
+                ctx.program_id,
+                &mut ctx.accounts.deposit,
+                ctx.remaining_accounts,
+                ctx.bumps.clone(),
+            ),
+            controller::deposit::DepositArgs {
+                pool_token_amount,
+                maximum_amount_in_0,
+                maximum_amount_in_1,
+                deadline,
+            },
+        )?;
+
+        let position = &mut ctx.accounts.position;
+        position.pool_token_amount =
+            unwrap_int!(position.pool_token_amount.checked_add(pool_token_amount));
+        Ok(())
+    }
+
+    /// Sends all of the LP tokens of a [LockedPosition] to the `destination`.
+    /// Fails before the unlock timestamp of the position.
+    #[access_control(ctx.accounts.validate())]
+    pub fn unlock_position(ctx: Context<UnlockPosition>) -> Result<()> {
+        let position = &ctx.accounts.position;
+        invariant!(
+            Clock::get()?.unix_timestamp >= position.unlock_ts,
+            PositionLocked
+        );
+
+        let seeds: &[&[u8]] = &[b"LockedPosition", position.base.as_ref(), &[position.bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.escrow.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: position.to_account_info(),
+                },
+                &[seeds],
+            ),
+            ctx.accounts.escrow.amount,
+        )?;
+
+        ctx.accounts.position.pool_token_amount = 0;
+        Ok(())
+    }
+
+    /// Deposits any amounts of the two tokens of the swap.
+    ///
+    /// The portion of the deposit which does not match the ratio of the pool pays the trade fee
+    /// as if it were swapped into the other token. The fee stays in the pool.
+    /// The implied swap is charged the trade fee of a swap of its size and direction, and
+    /// is subject to the rate limit, circuit breaker, maximum trade size, maximum price impact
+    /// and oracle band of the swap. If the swap has an oracle band, its oracle must be passed
+    /// among the remaining accounts.
+    /// Fails if fewer than `min_lp_out` LP tokens would be minted.
+    /// If a `deadline` is provided, the deposit fails if it is executed after that Unix timestamp.
+    #[access_control(ctx.accounts.validate())]
+    pub fn deposit_imbalanced(
+        ctx: Context<Deposit>,
+        amount_in_0: u64,
+        amount_in_1: u64,
+        min_lp_out: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        controller::deposit_imbalanced::deposit_imbalanced(
+            ctx,
+            controller::deposit_imbalanced::DepositImbalancedArgs {
+                amount_in_0,
+                amount_in_1,
+                min_lp_out,
+                deadline,
+            },
+        )
+    }
+
+    /// Deposits only one of the two tokens of the swap.
+    ///
+    /// The portion of `amount_in` which balances the deposit is swapped into the other token,
+    /// paying the trade fee, and the LP tokens for both sides are minted to the user.
+    /// If a `deadline` is provided, the deposit fails if it is executed after that Unix timestamp.
+    #[access_control(ctx.accounts.validate())]
+    pub fn deposit_single(
+        ctx: Context<DepositSingle>,
+        amount_in: u64,
+        minimum_pool_token_amount: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        controller::deposit_single::deposit_single(
+            ctx,
+            controller::deposit_single::DepositSingleArgs {
+                amount_in,
+                minimum_pool_token_amount,
+                deadline,
+            },
+        )
+    }
+}
+
+// Error codes
+#[error_code]
+#[allow(missing_docs)]
+pub enum ErrorCode {
+    #[msg("Swap pool is paused")]
+    Paused,
+    #[msg("Swap instruction exceeds desired slippage limit")]
+    ExceededSlippage,
+    #[msg("Insufficient liquidity")]
+    InsufficientLiquidity,
+    #[msg(
+        "The withdrawal will result in the pool having too little liquidity. Withdraw less tokens."
+    )]
+    InsufficientLiquidityPostWithdrawal,
+    #[msg("Transaction was executed after its deadline")]
+    DeadlineExceeded,
+    #[msg("Output mint of the first hop must be the input mint of the second hop")]
+    SwapRouteMintMismatch,
+    #[msg("Flash swap was not repaid enough to cover the trade fee")]
+    InsufficientFlashSwapRepayment,
+    #[msg("Swaps in this direction are paused")]
+    DirectionPaused,
+    #[msg("Delegate is not approved for the input amount of the swap")]
+    InsufficientDelegatedAmount,
+    #[msg("Product of the reserves decreased")]
+    InvariantViolated,
+
+    #[msg("New swap must have non-zero supply on its reserves", offset = 10)]
+    NewSwapMustHaveNonZeroSupply,
+    #[msg("Initial liquidity too low")]
+    InitialLiquidityTooLow,
+    #[msg("Swap's token mints must be sorted")]
+    SwapTokensNotSorted,
+    #[msg("Swap's token mints cannot be the same")]
+    SwapTokensCannotBeEqual,
+    #[msg("Swap's pool mint supply must be zero")]
+    SwapPoolMintSupply,
+    #[msg("Only the factory admin may create swaps on this factory")]
+    PermissionedSwapCreation,
+    #[msg("Mint is not allowed by the mint list of the factory")]
+    MintNotAllowed,
+    #[msg("Invalid fee", offset = 20)]
+    InvalidFee,
+    #[msg("Pool fee tier does not exist on the factory")]
+    InvalidPoolFeeTier,
+
+    #[msg("Swap input exceeds the maximum trade size of the pool", offset = 30)]
+    TradeTooLarge,
+    #[msg("Too many swaps on the pool in this slot")]
+    SwapRateLimited,
+    #[msg("Price impact of the swap exceeds the maximum of the pool")]
+    PriceImpactTooHigh,
+
+    #[msg("Swap intent was not signed by the owner", offset = 40)]
+    InvalidSwapIntentSignature,
+    #[msg("Swap intent nonce does not match the signed swap authority")]
+    InvalidSwapIntentNonce,
+    #[msg("Revealed swap does not match its commitment")]
+    SwapCommitmentMismatch,
+    #[msg("Committed swaps must be revealed after the slot of the commitment")]
+    SwapRevealedInCommitSlot,
+
+    #[msg("Deposit requires more tokens than the maximum amount in", offset = 50)]
+    DepositAboveMaximumAmountIn,
+    #[msg("Deposit mints fewer LP tokens than the minimum LP out")]
+    DepositBelowMinimumLpOut,
+    #[msg("Withdraw returns fewer tokens than the minimum amount out")]
+    WithdrawBelowMinimumAmountOut,
+
+    #[msg(
+        "LP tokens of the position are locked until its unlock timestamp",
+        offset = 60
+    )]
+    PositionLocked,
+
+    #[msg("Deposit exceeds the deposit cap of the pool", offset = 70)]
+    DepositCapExceeded,
+
+    #[msg(
+        "Withdraw cooldown after the latest deposit has not passed",
+        offset = 80
+    )]
+    WithdrawCooldownActive,
+
+    #[msg("Instruction is not supported by the curve of the swap", offset = 90)]
+    CurveNotSupported,
+
+    #[msg(
+        "Swap must have no LP tokens other than its locked liquidity to be closed",
+        offset = 100
+    )]
+    SwapNotEmpty,
+
+    #[msg(
+        "Execution price deviates from the oracle price by more than the oracle band",
+        offset = 120
+    )]
+    OraclePriceDeviation,
+
+    #[msg("All swaps of the factory are paused", offset = 130)]
+    FactoryPaused,
+
+    #[msg("Change must be queued through the timelock", offset = 140)]
+    TimelockRequired,
+    #[msg("Timelocked change is not ready to be executed")]
+    TimelockNotReady,
+
+    #[msg("Swap is in progress and cannot be re-entered", offset = 150)]
+    Reentrancy,
+
+    #[msg(
+        "Mint has a freeze authority, which the factory does not allow",
+        offset = 160
+    )]
+    FreezableMint,
+
+    #[msg("Swaps are blocked by the circuit breaker of the swap", offset = 170)]
+    CircuitBreakerTripped,
+
+    #[msg("Signer is neither the admin nor the holder of the role", offset = 180)]
+    MissingRole,
+
+    #[msg(
+        "Upgrade authority of the program is not the upgrader of the factory",
+        offset = 190
+    )]
+    UpgradeAuthorityMismatch,
+
+    #[msg("Reserves of the swap are not tracked", offset = 200)]
+    ReservesNotTracked,
 }
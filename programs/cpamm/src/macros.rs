@@ -6,6 +6,7 @@ macro_rules! gen_swap_signer_seeds {
             &$swap.factory.to_bytes(),
             &$swap.token_0.mint.to_bytes(),
             &$swap.token_1.mint.to_bytes(),
+            $crate::pool_fee_tier_seed(&$swap.pool_fee_tier),
             &[$swap.bump],
         ]
     };
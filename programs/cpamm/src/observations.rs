@@ -0,0 +1,228 @@
+//! Swap observations of historical cumulative prices.
+//! This is based on the Uniswap V3 TWAP oracle, which keeps a growable ring buffer
+//! of the cumulative prices of the Uniswap V2 oracle. For more information, view [crate::price_info].
+#![deny(missing_docs)]
+
+use crate::*;
+use std::mem::size_of;
+
+/// Offset of the first [Observation] in the data of an [Observations] account.
+pub const OBSERVATIONS_OFFSET: usize = 8 + size_of::<Observations>();
+
+/// Returns the size of an [Observations] account with room for `cardinality` observations.
+pub fn observations_space(cardinality: u16) -> usize {
+    OBSERVATIONS_OFFSET + size_of::<Observation>() * usize::from(cardinality)
+}
+
+/// Splits the data of an [Observations] account into its header and its observations.
+pub fn split_observations(data: &[u8]) -> Result<(&Observations, &[Observation])> {
+    invariant!(
+        data.len() >= OBSERVATIONS_OFFSET,
+        "observations account is too small"
+    );
+    let (header, observations) = data.split_at(OBSERVATIONS_OFFSET);
+    let header = unwrap_opt!(
+        bytemuck::try_from_bytes(&header[8..]).ok(),
+        "invalid observations header"
+    );
+    let observations = unwrap_opt!(
+        bytemuck::try_cast_slice(observations).ok(),
+        "invalid observations"
+    );
+    Ok((header, observations))
+}
+
+/// Splits the data of an [Observations] account into its header and its observations,
+/// for writing.
+pub fn split_observations_mut(data: &mut [u8]) -> Result<(&mut Observations, &mut [Observation])> {
+    invariant!(
+        data.len() >= OBSERVATIONS_OFFSET,
+        "observations account is too small"
+    );
+    let (header, observations) = data.split_at_mut(OBSERVATIONS_OFFSET);
+    let header = unwrap_opt!(
+        bytemuck::try_from_bytes_mut(&mut header[8..]).ok(),
+        "invalid observations header"
+    );
+    let observations = unwrap_opt!(
+        bytemuck::try_cast_slice_mut(observations).ok(),
+        "invalid observations"
+    );
+    Ok((header, observations))
+}
+
+impl Observations {
+    /// Writes the `observation` after the most recent one, growing the ring buffer to
+    /// [Observations::cardinality_next] when it wraps around.
+    /// Nothing is written if the most recent observation was made at the same time.
+    /// Returns true if the observation was written.
+    pub fn write(
+        &mut self,
+        observations: &mut [Observation],
+        observation: Observation,
+    ) -> Option<bool> {
+        let latest_timestamp = observations.get(usize::from(self.index))?.timestamp;
+        if latest_timestamp == observation.timestamp {
+            return Some(false);
+        }
+
+        let next_index = self.index.checked_add(1)?;
+        let cardinality =
+            if self.cardinality_next > self.cardinality && next_index == self.cardinality {
+                self.cardinality_next
+            } else {
+                self.cardinality
+            };
+        self.index = next_index.checked_rem(cardinality)?;
+        self.cardinality = cardinality;
+        *observations.get_mut(usize::from(self.index))? = observation;
+        Some(true)
+    }
+
+    /// Returns the lowest and the highest prices of token 0 since `start_ts`, and the number
+    /// of observations made after it. The `current` observation follows the most recent one.
+    /// The ranges of the observations after `start_ts` cover the window, so the range may
+    /// include prices from shortly before it.
+    pub fn price_0_range_since(
+        &self,
+        observations: &[Observation],
+        current: Observation,
+        start_ts: i64,
+    ) -> Result<(u128, u128, u16)> {
+        let cardinality = usize::from(self.cardinality);
+        let (mut low, mut high) = (current.price_0_low, current.price_0_high);
+        let mut num_observations: u16 = 0;
+        let mut index = usize::from(self.index);
+        while num_observations < self.cardinality {
+            let observation = unwrap_opt!(observations.get(index), "missing observation");
+            if observation.timestamp == 0 || observation.timestamp <= start_ts {
+                break;
+            }
+            if observation.price_0_low != 0 {
+                low = if low == 0 {
+                    observation.price_0_low
+                } else {
+                    low.min(observation.price_0_low)
+                };
+                high = high.max(observation.price_0_high);
+            }
+            num_observations = unwrap_int!(num_observations.checked_add(1));
+            index = unwrap_int!(index
+                .checked_add(cardinality)
+                .and_then(|index| index.checked_sub(1))
+                .and_then(|index| index.checked_rem(cardinality)));
+        }
+        Ok((low, high, num_observations))
+    }
+
+    /// Returns the observation of the cumulative prices at `target`, interpolated linearly
+    /// between the observations around it. The `current` observation follows the most recent one.
+    ///
+    /// Fails if `target` is before the oldest observation.
+    pub fn observation_at(
+        &self,
+        observations: &[Observation],
+        current: Observation,
+        target: i64,
+    ) -> Result<Observation> {
+        let cardinality = usize::from(self.cardinality);
+        let latest_index = usize::from(self.index);
+        let latest = *unwrap_opt!(observations.get(latest_index), "missing observation");
+        if target >= latest.timestamp {
+            return Ok(interpolate(&latest, &current, target));
+        }
+
+        // the oldest observation follows the most recent one,
+        // unless the ring buffer has not been filled since it last grew
+        let next_index = unwrap_int!(latest_index.checked_add(1)).checked_rem(cardinality);
+        let (oldest_index, len) = match next_index {
+            Some(next_index) if observations[next_index].timestamp != 0 => {
+                (next_index, cardinality)
+            }
+            _ => (0, unwrap_int!(latest_index.checked_add(1))),
+        };
+        let observation_at = |position: usize| -> Result<Observation> {
+            let index = unwrap_int!(oldest_index.checked_add(position)).checked_rem(cardinality);
+            Ok(*unwrap_opt!(
+                index.and_then(|index| observations.get(index)),
+                "missing observation"
+            ))
+        };
+        invariant!(
+            observation_at(0)?.timestamp <= target,
+            "window starts before the oldest observation"
+        );
+
+        // binary search for the observations before and after the target
+        let (mut before, mut after) = (0, unwrap_int!(len.checked_sub(1)));
+        while unwrap_int!(after.checked_sub(before)) > 1 {
+            let middle = unwrap_int!(before.checked_add(after)) / 2;
+            if observation_at(middle)?.timestamp <= target {
+                before = middle;
+            } else {
+                after = middle;
+            }
+        }
+        Ok(interpolate(
+            &observation_at(before)?,
+            &observation_at(after)?,
+            target,
+        ))
+    }
+}
+
+/// Linearly interpolates the cumulative prices at `target` between the `before` and
+/// `after` observations. Cumulative prices may wrap around, so this uses wrapping arithmetic.
+fn interpolate(before: &Observation, after: &Observation, target: i64) -> Observation {
+    let total_elapsed = after.timestamp.saturating_sub(before.timestamp);
+    if total_elapsed <= 0 {
+        return *before;
+    }
+    let elapsed = target
+        .saturating_sub(before.timestamp)
+        .clamp(0, total_elapsed) as u128;
+    let total_elapsed = total_elapsed as u128;
+    let interpolate_price = |before: u128, after: u128| {
+        before.wrapping_add((after.wrapping_sub(before) / total_elapsed).wrapping_mul(elapsed))
+    };
+    Observation {
+        timestamp: target,
+        price_0_cumulative: interpolate_price(before.price_0_cumulative, after.price_0_cumulative),
+        price_1_cumulative: interpolate_price(before.price_1_cumulative, after.price_1_cumulative),
+        median_price_0_cumulative: interpolate_price(
+            before.median_price_0_cumulative,
+            after.median_price_0_cumulative,
+        ),
+        median_price_1_cumulative: interpolate_price(
+            before.median_price_1_cumulative,
+            after.median_price_1_cumulative,
+        ),
+        price_0_low: after.price_0_low,
+        price_0_high: after.price_0_high,
+    }
+}
+
+impl Observation {
+    /// Returns the observation of the cumulative prices of the `swap` at the time of the `clock`.
+    pub fn of_swap(swap: &SwapInfo, clock: &Clock) -> Result<Self> {
+        let timestamp = clock.unix_timestamp;
+        let (reserve_0, reserve_1) = swap.reserve_amounts();
+        let (price_0_cumulative, price_1_cumulative) = swap
+            .price_info
+            .cumulative_prices_at(timestamp, reserve_0, reserve_1)?;
+        let (median_price_0_cumulative, median_price_1_cumulative) = swap
+            .median_price_info
+            .cumulative_prices_at(timestamp, clock.slot, reserve_0, reserve_1)?;
+        let (price_0_low, price_0_high) =
+            swap.price_0_range_since_observation().unwrap_or_default();
+        Ok(Self {
+            timestamp,
+            price_0_cumulative,
+            price_1_cumulative,
+            median_price_0_cumulative,
+            median_price_1_cumulative,
+            price_0_low,
+            price_0_high,
+        })
+    }
+}
@@ -0,0 +1,101 @@
+//! External price oracles guarding the execution prices of swaps.
+//!
+//! Swaps with an oracle band reject trades whose execution price deviates from the price
+//! of their oracle by more than the band, which protects thin pools from being traded
+//! at manipulated prices. The oracle must be a Pyth price account.
+#![deny(missing_docs)]
+
+use crate::price_info::PRICE_SCALE;
+use crate::*;
+use std::convert::TryInto;
+
+/// Magic number at the start of Pyth accounts.
+const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+/// Account type of Pyth price accounts.
+const PYTH_PRICE_ACCOUNT_TYPE: u32 = 3;
+/// Status of Pyth aggregate prices which are currently trading.
+const PYTH_STATUS_TRADING: u32 = 1;
+
+/// Offset of the account type of a Pyth account.
+const PYTH_ACCOUNT_TYPE_OFFSET: usize = 8;
+/// Offset of the price exponent of a Pyth price account.
+const PYTH_EXPONENT_OFFSET: usize = 20;
+/// Offset of the aggregate price of a Pyth price account.
+const PYTH_AGGREGATE_PRICE_OFFSET: usize = 208;
+/// Offset of the status of the aggregate price of a Pyth price account.
+const PYTH_AGGREGATE_STATUS_OFFSET: usize = 224;
+
+fn read_bytes<const N: usize>(data: &[u8], offset: usize) -> Option<[u8; N]> {
+    data.get(offset..offset.checked_add(N)?)?.try_into().ok()
+}
+
+/// Returns the aggregate price of the data of a Pyth price account as the price of token 0
+/// in token 1 in their smallest units, scaled by [PRICE_SCALE].
+///
+/// The price is multiplied by ten to the power of `decimals_adjustment` to convert it to
+/// the smallest units of the tokens, and inverted if the feed prices token 1 in token 0.
+/// Returns [None] if the account is not a Pyth price account or its price is not trading.
+pub fn pyth_price(data: &[u8], decimals_adjustment: i8, is_inverted: bool) -> Option<u128> {
+    if u32::from_le_bytes(read_bytes(data, 0)?) != PYTH_MAGIC
+        || u32::from_le_bytes(read_bytes(data, PYTH_ACCOUNT_TYPE_OFFSET)?)
+            != PYTH_PRICE_ACCOUNT_TYPE
+        || u32::from_le_bytes(read_bytes(data, PYTH_AGGREGATE_STATUS_OFFSET)?)
+            != PYTH_STATUS_TRADING
+    {
+        return None;
+    }
+    let exponent = i32::from_le_bytes(read_bytes(data, PYTH_EXPONENT_OFFSET)?);
+    let price = i64::from_le_bytes(read_bytes(data, PYTH_AGGREGATE_PRICE_OFFSET)?);
+    if price <= 0 {
+        return None;
+    }
+
+    let exponent = exponent
+        .checked_add(decimals_adjustment.into())?
+        .checked_add(PRICE_SCALE.into())?;
+    let scale = 10u128.checked_pow(exponent.unsigned_abs())?;
+    let price = if exponent >= 0 {
+        (price as u128).checked_mul(scale)?
+    } else {
+        (price as u128).checked_div(scale)?
+    };
+    if is_inverted {
+        10u128
+            .checked_pow(u32::from(PRICE_SCALE).checked_mul(2)?)?
+            .checked_div(price)
+    } else {
+        Some(price)
+    }
+}
+
+/// Returns the execution price of a swap of the token with the `input_mint` before fees,
+/// as the price of token 0 in token 1 in their smallest units, scaled by [PRICE_SCALE].
+pub fn execution_price(
+    swap_info: &SwapInfo,
+    input_mint: Pubkey,
+    swap_result: &xyk::SwapResult,
+) -> Option<u128> {
+    let (amount_0, amount_1) = if input_mint == swap_info.token_0.mint {
+        (
+            swap_result.source_amount_swapped,
+            swap_result.destination_amount_swapped,
+        )
+    } else {
+        (
+            swap_result.destination_amount_swapped,
+            swap_result.source_amount_swapped,
+        )
+    };
+    (amount_1 as u128)
+        .checked_mul(10u128.pow(PRICE_SCALE.into()))?
+        .checked_div(amount_0.into())
+}
+
+/// Returns the deviation of the `price` from the `oracle_price`, in bps.
+pub fn deviation_bps(price: u128, oracle_price: u128) -> Option<u128> {
+    price
+        .max(oracle_price)
+        .checked_sub(price.min(oracle_price))?
+        .checked_mul(xyk::BPS_PER_WHOLE.into())?
+        .checked_div(oracle_price)
+}
@@ -32,6 +32,27 @@ impl SwapPriceInfo {
             return Ok(());
         }
 
+        let (price_0_cumulative, price_1_cumulative) =
+            self.cumulative_prices_at(now, reserve_0, reserve_1)?;
+        self.price_0_cumulative_last = price_0_cumulative;
+        self.price_1_cumulative_last = price_1_cumulative;
+        self.last_update_ts = now;
+
+        Ok(())
+    }
+
+    /// Returns the cumulative prices of token 0 and token 1 at `now`, if the reserves
+    /// have not changed since they were last updated. This does not update the price information.
+    pub fn cumulative_prices_at(
+        &self,
+        now: i64,
+        reserve_0: u64,
+        reserve_1: u64,
+    ) -> Result<(u128, u128)> {
+        if self.last_update_ts > now || reserve_0 == 0 || reserve_1 == 0 {
+            return Ok((self.price_0_cumulative_last, self.price_1_cumulative_last));
+        }
+
         let time_elapsed: u64 = unwrap_int!(now
             .checked_sub(self.last_update_ts)
             .and_then(|v| v.to_u64()));
@@ -50,14 +71,148 @@ impl SwapPriceInfo {
 
         let (next_price_0_cumulative, _) =
             self.price_0_cumulative_last.overflowing_add(price_0_add);
-        self.price_0_cumulative_last = next_price_0_cumulative;
-
         let (next_price_1_cumulative, _) =
             self.price_1_cumulative_last.overflowing_add(price_1_add);
-        self.price_1_cumulative_last = next_price_1_cumulative;
 
-        self.last_update_ts = now;
+        Ok((next_price_0_cumulative, next_price_1_cumulative))
+    }
+}
+
+/// Returns the price of token 0 in token 1 at the reserves, scaled by [PRICE_SCALE].
+fn price_0_of_reserves(reserve_0: u64, reserve_1: u64) -> Option<u128> {
+    to_precise(reserve_1)?.checked_div(reserve_0.into())
+}
+
+/// Returns the median of the `samples`, or the mean of the two middle samples
+/// if there is an even number of them.
+fn median(samples: &[u128]) -> Option<u128> {
+    let mut sorted = samples.to_vec();
+    sorted.sort_unstable();
+    let middle = sorted.len().checked_div(2)?;
+    let upper = *sorted.get(middle)?;
+    if sorted.len() % 2 == 1 {
+        return Some(upper);
+    }
+    let lower = *sorted.get(middle.checked_sub(1)?)?;
+    // the mean without overflowing
+    lower.checked_add(upper.checked_sub(lower)? / 2)
+}
+
+impl SwapMedianPriceInfo {
+    /// Samples the price of the reserves at `now` in `slot`, settling the median of the
+    /// previous slot of the samples when a new slot begins.
+    /// This should be called before the pool is mutated.
+    pub fn update(&mut self, now: i64, slot: u64, reserve_0: u64, reserve_1: u64) -> Result<()> {
+        if self.last_update_ts > now || reserve_0 == 0 || reserve_1 == 0 {
+            return Ok(());
+        }
+        let price_0 = unwrap_int!(price_0_of_reserves(reserve_0, reserve_1));
 
+        if slot != self.slot {
+            self.accumulate(now)?;
+            if self.num_samples > 0 {
+                // the slot of the samples ended with the price of the current reserves
+                self.push_sample(price_0);
+                let num_samples = usize::from(self.num_samples);
+                self.median_price_0 = unwrap_int!(median(&self.samples[..num_samples]));
+            }
+            self.slot = slot;
+            self.num_samples = 0;
+        } else {
+            self.accumulate(now)?;
+        }
+        self.push_sample(price_0);
         Ok(())
     }
+
+    /// Returns the cumulative median prices of token 0 and token 1 at `now` in `slot`,
+    /// if the reserves have not changed since they were last sampled.
+    /// This does not update the price information.
+    pub fn cumulative_prices_at(
+        &self,
+        now: i64,
+        slot: u64,
+        reserve_0: u64,
+        reserve_1: u64,
+    ) -> Result<(u128, u128)> {
+        let mut info = *self;
+        info.update(now, slot, reserve_0, reserve_1)?;
+        Ok((info.price_0_cumulative_last, info.price_1_cumulative_last))
+    }
+
+    /// Accumulates the median price up to `ts`.
+    fn accumulate(&mut self, ts: i64) -> Result<()> {
+        if ts <= self.last_update_ts {
+            return Ok(());
+        }
+        if self.median_price_0 > 0 {
+            let time_elapsed: u128 = unwrap_int!(ts
+                .checked_sub(self.last_update_ts)
+                .and_then(|v| v.to_u128()));
+            let median_price_1 = unwrap_int!(to_precise(1)
+                .and_then(|one| one.checked_mul(one))
+                .and_then(|one_squared| one_squared.checked_div(self.median_price_0)));
+
+            // * never overflows, and + overflow is desired
+            self.price_0_cumulative_last = self
+                .price_0_cumulative_last
+                .wrapping_add(unwrap_int!(self.median_price_0.checked_mul(time_elapsed)));
+            self.price_1_cumulative_last = self
+                .price_1_cumulative_last
+                .wrapping_add(unwrap_int!(median_price_1.checked_mul(time_elapsed)));
+        }
+        self.last_update_ts = ts;
+        Ok(())
+    }
+
+    /// Adds a sample, replacing the last one if the samples are full.
+    fn push_sample(&mut self, price_0: u128) {
+        let index = usize::from(self.num_samples);
+        if index < MAX_MEDIAN_SAMPLES {
+            self.samples[index] = price_0;
+            self.num_samples = self.num_samples.saturating_add(1);
+        } else {
+            self.samples[MAX_MEDIAN_SAMPLES - 1] = price_0;
+        }
+    }
+}
+
+impl SwapInfo {
+    /// Updates the cumulative price information and the median price information
+    /// at the current reserves. This should be called before the pool is mutated.
+    pub fn update_price_info(&mut self) -> Result<()> {
+        let clock = Clock::get()?;
+        let (reserve_0, reserve_1) = self.reserve_amounts();
+        self.price_info
+            .update_cumulative_price_info(reserve_0, reserve_1)?;
+        self.median_price_info
+            .update(clock.unix_timestamp, clock.slot, reserve_0, reserve_1)?;
+        if let Some((low, high)) = self.price_0_range_since_observation() {
+            self.price_0_low_since_observation = low;
+            self.price_0_high_since_observation = high;
+        }
+        Ok(())
+    }
+
+    /// Returns the lowest and the highest prices of token 0 in token 1 since the last
+    /// [Observation] of the swap was written, including the price of the current reserves.
+    /// None if a reserve is empty and the price has not been sampled since.
+    pub fn price_0_range_since_observation(&self) -> Option<(u128, u128)> {
+        let (reserve_0, reserve_1) = self.reserve_amounts();
+        let price_0 = if reserve_0 == 0 || reserve_1 == 0 {
+            None
+        } else {
+            price_0_of_reserves(reserve_0, reserve_1)
+        };
+        let (low, high) = (
+            self.price_0_low_since_observation,
+            self.price_0_high_since_observation,
+        );
+        match price_0 {
+            Some(price_0) if low == 0 => Some((price_0, price_0)),
+            Some(price_0) => Some((low.min(price_0), high.max(price_0))),
+            None if low == 0 => None,
+            None => Some((low, high)),
+        }
+    }
 }
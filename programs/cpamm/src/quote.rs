@@ -0,0 +1,113 @@
+//! Quotes of swaps and deposits.
+//!
+//! These pure functions compute the same numbers as the instructions, so downstream programs
+//! and bots may use them by depending on this crate with the `no-entrypoint` feature.
+//! They do not include the discounts of the optional accounts of a swap.
+#![deny(missing_docs)]
+
+use crate::price_info::PRICE_SCALE;
+use crate::*;
+use ::u128::mul_div_u64;
+
+/// Returns the mint of the token of the `swap` other than `mint`.
+fn other_mint(swap: &SwapInfo, mint: Pubkey) -> Pubkey {
+    if mint == swap.token_0.mint {
+        swap.token_1.mint
+    } else {
+        swap.token_0.mint
+    }
+}
+
+/// Returns the spot price of the token with the `input_mint` in the other token of the `swap`,
+/// scaled by [PRICE_SCALE]. This is the price of an infinitesimal swap without fees.
+///
+/// Stable swaps have no closed-form spot price, so their price is that of a swap
+/// of one bps of the input reserve.
+pub fn spot_price(swap: &SwapInfo, input_mint: Pubkey) -> Option<u128> {
+    let input_reserve = swap.reserve_amount(input_mint);
+    let output_reserve = swap.reserve_amount(other_mint(swap, input_mint));
+    let scale = 10u128.pow(PRICE_SCALE.into());
+    if swap.is_weighted() {
+        let (input_weight, output_weight) = if input_mint == swap.token_0.mint {
+            (swap.weight_0, swap.weight_1)
+        } else {
+            (swap.weight_1, swap.weight_0)
+        };
+        (output_reserve as u128)
+            .checked_mul(input_weight.into())?
+            .checked_mul(scale)?
+            .checked_div((input_reserve as u128).checked_mul(output_weight.into())?)
+    } else if swap.is_constant_product() {
+        (output_reserve as u128)
+            .checked_mul(scale)?
+            .checked_div(input_reserve.into())
+    } else {
+        let source_amount = (input_reserve / xyk::BPS_PER_WHOLE).max(1);
+        let swap_result =
+            swap.curve_swap(input_mint, source_amount, input_reserve, output_reserve)?;
+        (swap_result.destination_amount_swapped as u128)
+            .checked_mul(scale)?
+            .checked_div(swap_result.source_amount_swapped.into())
+    }
+}
+
+/// Returns the quote of a [crate::cpamm::swap] of `amount_in` of the token with
+/// the `input_mint`, as computed by [crate::cpamm::quote_swap].
+pub fn get_amount_out(swap: &SwapInfo, input_mint: Pubkey, amount_in: u64) -> Option<SwapQuote> {
+    let input_reserve = swap.reserve_amount(input_mint);
+    let swap_result = swap.curve_swap(
+        input_mint,
+        amount_in,
+        input_reserve,
+        swap.reserve_amount(other_mint(swap, input_mint)),
+    )?;
+    let swap_fees = swap.swap_fees_for_trade(input_mint, amount_in, input_reserve)?;
+    let (trade_fee, admin_trade_fee) =
+        swap_fees.compute_trade_fees(swap_result.destination_amount_swapped)?;
+    Some(SwapQuote {
+        amount_in: swap_result.source_amount_swapped,
+        amount_out: swap_result
+            .destination_amount_swapped
+            .checked_sub(trade_fee)?,
+        trade_fee,
+        admin_trade_fee,
+    })
+}
+
+/// Returns the quote of a [crate::cpamm::swap_exact_out] receiving exactly `amount_out`
+/// for the token with the `input_mint`. The trade fee is charged on the input token.
+pub fn get_amount_in(swap: &SwapInfo, input_mint: Pubkey, amount_out: u64) -> Option<SwapQuote> {
+    let input_reserve = swap.reserve_amount(input_mint);
+    let output_reserve = swap.reserve_amount(other_mint(swap, input_mint));
+    if amount_out >= output_reserve {
+        return None;
+    }
+    let swap_result =
+        swap.curve_swap_exact_out(input_mint, amount_out, input_reserve, output_reserve)?;
+    let swap_fees =
+        swap.swap_fees_for_trade(input_mint, swap_result.source_amount_swapped, input_reserve)?;
+    let (amount_in, trade_fee, admin_trade_fee) =
+        swap_fees.compute_trade_fees_on_input(swap_result.source_amount_swapped)?;
+    Some(SwapQuote {
+        amount_in,
+        amount_out,
+        trade_fee,
+        admin_trade_fee,
+    })
+}
+
+/// Returns the largest amount of LP tokens a [crate::cpamm::deposit] may mint for at most
+/// `maximum_amount_in_0` and `maximum_amount_in_1` of the tokens of the `swap`,
+/// given the `pool_mint_supply`.
+pub fn lp_tokens_for_deposit(
+    swap: &SwapInfo,
+    pool_mint_supply: u64,
+    maximum_amount_in_0: u64,
+    maximum_amount_in_1: u64,
+) -> Option<u64> {
+    let (reserve_0, reserve_1) = swap.reserve_amounts();
+    // the deposit rounds the amounts in up, so these are the exact maximums
+    let pool_tokens_0 = mul_div_u64(maximum_amount_in_0, pool_mint_supply, reserve_0)?;
+    let pool_tokens_1 = mul_div_u64(maximum_amount_in_1, pool_mint_supply, reserve_1)?;
+    Some(pool_tokens_0.min(pool_tokens_1))
+}
@@ -15,10 +15,582 @@ pub struct Factory {
     pub bump: u8,
     /// Total number of swaps that have been created.
     pub num_swaps: u64,
-    /// Admin of the [Factory]. Currently unused.
+    /// Admin of the [Factory]. Configures the referral fees and fee exemptions of its swaps.
     pub admin: Pubkey,
+    /// If true, only the admin of the [Factory] may create swaps.
+    /// False for factories which predate this flag, which stay permissionless.
+    pub is_permissioned: bool,
+    /// If false, the [Factory] predates [Factory::default_fees], which are then ignored.
+    pub has_default_fees: bool,
+    /// How [MintListEntry]s restrict the mints of new swaps: [MINT_LIST_MODE_NONE],
+    /// [MINT_LIST_MODE_ALLOW] or [MINT_LIST_MODE_DENY].
+    pub mint_list_mode: u8,
+    /// If true, all swaps of the [Factory] are paused by its admin or its [FactoryGuardian].
+    /// For more information, view [crate::cpamm::pause_all].
+    pub is_paused: bool,
+    /// How new swaps treat mints with a freeze authority: [FREEZE_AUTHORITY_POLICY_ALLOW],
+    /// [FREEZE_AUTHORITY_POLICY_FLAG] or [FREEZE_AUTHORITY_POLICY_REJECT].
+    pub freeze_authority_policy: u8,
+    /// Version of the layout of the factory. For more information, view [FACTORY_VERSION].
+    pub version: u8,
+    /// Padding which keeps the size of the [Factory] unchanged.
+    pub padding: [u8; 2],
+    /// Trade fees of the pool fee tiers `1..=MAX_POOL_FEE_TIERS`, thousands of bps.
+    /// Unused tiers have a zero trade fee. Tier 0 has the trade fee of the default fees.
+    pub pool_fee_tiers_kbps: [u64; MAX_POOL_FEE_TIERS],
+    /// Lamports charged to the payer of [crate::cpamm::new_swap], which are sent to
+    /// the creation fee vault of the [Factory]. Deters the creation of spam swaps.
+    pub creation_fee_lamports: u64,
+    /// Fees of new swaps, apart from the trade fees of the pool fee tiers.
+    /// Use [Factory::new_swap_fees] to read them.
+    pub default_fees: SwapFees,
+    /// Admin proposed by [crate::cpamm::propose_admin], who becomes the admin
+    /// upon [crate::cpamm::accept_admin]. The default key if there is none.
+    pub pending_admin: Pubkey,
+    /// Delay of the changes queued with [crate::cpamm::queue_change], in seconds.
+    /// While it is non-zero, [crate::cpamm::set_fees] and [crate::cpamm::propose_admin]
+    /// must go through the timelock, and the other fee setters are rejected.
+    /// Zero if the [Factory] has no timelock.
+    pub timelock_delay_seconds: i64,
     /// Reserved for future program upgrades.
-    pub reserved: [u64; 16],
+    pub reserved: [u64; 1],
+}
+
+impl Factory {
+    /// Returns the fees of new swaps of the default pool fee tier 0.
+    /// These are [crate::fees::INITIAL] unless the admin has set default fees.
+    pub fn new_swap_fees(&self) -> SwapFees {
+        if self.has_default_fees {
+            self.default_fees
+        } else {
+            crate::fees::INITIAL
+        }
+    }
+
+    /// Returns the trade fee of new swaps of a pool fee tier, if the tier exists.
+    pub fn pool_trade_fee_kbps(&self, pool_fee_tier: u8) -> Option<u64> {
+        match pool_fee_tier.checked_sub(1) {
+            None => Some(self.new_swap_fees().trade_fee_kbps),
+            Some(index) => self
+                .pool_fee_tiers_kbps
+                .get(usize::from(index))
+                .copied()
+                .filter(|&trade_fee_kbps| trade_fee_kbps != 0),
+        }
+    }
+}
+
+/// [MintListEntry]s do not restrict the mints of new swaps.
+pub const MINT_LIST_MODE_NONE: u8 = 0;
+/// Both mints of new swaps must have a [MintListEntry].
+pub const MINT_LIST_MODE_ALLOW: u8 = 1;
+/// Neither mint of new swaps may have a [MintListEntry].
+pub const MINT_LIST_MODE_DENY: u8 = 2;
+
+/// New swaps may have mints with a freeze authority.
+pub const FREEZE_AUTHORITY_POLICY_ALLOW: u8 = 0;
+/// New swaps may have mints with a freeze authority, which sets [SwapInfo::has_freezable_mint].
+pub const FREEZE_AUTHORITY_POLICY_FLAG: u8 = 1;
+/// New swaps may not have mints with a freeze authority.
+pub const FREEZE_AUTHORITY_POLICY_REJECT: u8 = 2;
+
+/// Version of the layout of new [Factory]s.
+///
+/// Factories created before the version was recorded are version 0. New fields take the place
+/// of [Factory::padding] and [Factory::reserved], so [crate::cpamm::migrate_factory] upgrades
+/// older factories without growing them.
+///
+/// Version 1 added [Factory::version].
+pub const FACTORY_VERSION: u8 = 1;
+
+/// Maximum number of pool fee tiers of a [Factory], excluding the default tier 0.
+pub const MAX_POOL_FEE_TIERS: usize = 4;
+
+/// Returns the seed of the pool fee tier in the address of a [SwapInfo].
+/// The default tier 0 has no seed, so the addresses of its swaps do not depend on the tier.
+pub fn pool_fee_tier_seed(pool_fee_tier: &u8) -> &[u8] {
+    if *pool_fee_tier == 0 {
+        &[]
+    } else {
+        std::slice::from_ref(pool_fee_tier)
+    }
+}
+
+/// An address which pays a discounted trade fee on the swaps of a [Factory].
+/// Managed by the admin of the [Factory].
+#[account]
+#[derive(Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeExemption {
+    /// The [Factory].
+    pub factory: Pubkey,
+    /// The user authority which receives the discount.
+    pub address: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+    /// Proportion of the trade fee waived, thousands of bps.
+    /// [crate::fees::KBPS_PER_WHOLE] waives the trade fee entirely.
+    pub trade_fee_discount_kbps: u64,
+}
+
+/// Bounds of the fees which the creators and admins of the swaps of a [Factory] may set with
+/// [crate::cpamm::set_pool_fees]. Managed by the admin of the [Factory].
+#[account]
+#[derive(Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeBounds {
+    /// The [Factory].
+    pub factory: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+    /// Minimum trade fee, thousands of bps.
+    pub min_trade_fee_kbps: u64,
+    /// Maximum trade fee, thousands of bps.
+    pub max_trade_fee_kbps: u64,
+    /// Minimum withdraw fee, thousands of bps.
+    pub min_withdraw_fee_kbps: u64,
+    /// Maximum withdraw fee, thousands of bps.
+    pub max_withdraw_fee_kbps: u64,
+}
+
+/// Guardian of a [Factory], a hot key which may pause all of its swaps with
+/// [crate::cpamm::guardian_pause_all] in an incident, but may not unpause them nor change
+/// anything else. Managed by the admin of the [Factory].
+#[account]
+#[derive(Copy, Debug, Default, PartialEq, Eq)]
+pub struct FactoryGuardian {
+    /// The [Factory].
+    pub factory: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+    /// The guardian. The default key if the [Factory] has no guardian.
+    pub guardian: Pubkey,
+}
+
+/// Keys which may call some of the admin instructions of a [Factory] and its swaps in place
+/// of the admin, which keeps every [Role]. Managed by the admin of the [Factory].
+#[account]
+#[derive(Copy, Debug, Default, PartialEq, Eq)]
+pub struct FactoryRoles {
+    /// The [Factory].
+    pub factory: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+    /// Holder of [Role::FeeSetter]. The default key if the role is not granted.
+    pub fee_setter: Pubkey,
+    /// Holder of [Role::Pauser]. The default key if the role is not granted.
+    pub pauser: Pubkey,
+    /// Holder of [Role::Treasurer]. The default key if the role is not granted.
+    pub treasurer: Pubkey,
+    /// Holder of [Role::Upgrader]. The default key if the role is not granted.
+    pub upgrader: Pubkey,
+}
+
+impl FactoryRoles {
+    /// Returns the holder of the `role`, which is the default key if the role is not granted.
+    pub fn holder(&self, role: Role) -> Pubkey {
+        match role {
+            Role::FeeSetter => self.fee_setter,
+            Role::Pauser => self.pauser,
+            Role::Treasurer => self.treasurer,
+            Role::Upgrader => self.upgrader,
+        }
+    }
+
+    /// Sets the holder of the `role`. The default key revokes the role.
+    pub fn set_holder(&mut self, role: Role, holder: Pubkey) {
+        let slot = match role {
+            Role::FeeSetter => &mut self.fee_setter,
+            Role::Pauser => &mut self.pauser,
+            Role::Treasurer => &mut self.treasurer,
+            Role::Upgrader => &mut self.upgrader,
+        };
+        *slot = holder;
+    }
+
+    /// Returns true if `key` holds the `role`.
+    pub fn has_role(&self, key: Pubkey, role: Role) -> bool {
+        key != Pubkey::default() && self.holder(role) == key
+    }
+}
+
+/// A role of the [FactoryRoles] of a [Factory].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// Sets the fees of the [Factory] and of its swaps.
+    FeeSetter,
+    /// Pauses all swaps of the [Factory], like its [FactoryGuardian].
+    Pauser,
+    /// Manages the treasury of the [Factory], withdraws its creation fees and rescues
+    /// tokens sent to its swaps by mistake.
+    Treasurer,
+    /// The upgrade authority of the program, which [crate::cpamm::verify_upgrade_authority]
+    /// attests to.
+    Upgrader,
+}
+
+/// Maximum number of [HolderDiscountTier]s of a [HolderDiscountConfig].
+pub const MAX_HOLDER_DISCOUNT_TIERS: usize = 4;
+
+/// Trade fee discount of holders of at least an amount of the discount mint.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HolderDiscountTier {
+    /// Minimum balance of the discount mint. Zero for unused tiers.
+    pub min_balance: u64,
+    /// Proportion of the trade fee waived, thousands of bps.
+    pub trade_fee_discount_kbps: u64,
+}
+
+/// Trade fee discounts of the swaps of a [Factory] for holders of its discount mint,
+/// who pass a token account of the mint to the swap. Managed by the admin of the [Factory].
+#[account]
+#[derive(Copy, Debug, Default, PartialEq, Eq)]
+pub struct HolderDiscountConfig {
+    /// The [Factory].
+    pub factory: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+    /// Mint the holders of which receive the discounts.
+    pub discount_mint: Pubkey,
+    /// Discounts in ascending order of the balance of the holder.
+    pub tiers: [HolderDiscountTier; MAX_HOLDER_DISCOUNT_TIERS],
+}
+
+impl HolderDiscountConfig {
+    /// Returns the trade fee discount of a holder of `balance` of the discount mint,
+    /// which is that of the largest tier the balance reaches, thousands of bps.
+    pub fn trade_fee_discount_kbps(&self, balance: u64) -> u64 {
+        self.tiers
+            .iter()
+            .rev()
+            .find(|tier| tier.min_balance != 0 && balance >= tier.min_balance)
+            .map_or(0, |tier| tier.trade_fee_discount_kbps)
+    }
+}
+
+/// Configuration of the buyback of a [Factory], which buys its buyback mint with
+/// the admin fees of its swaps and burns it. For more information,
+/// view [crate::cpamm::buyback_and_burn].
+#[account]
+#[derive(Copy, Debug, Default, PartialEq, Eq)]
+pub struct BuybackConfig {
+    /// The [Factory].
+    pub factory: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+    /// Mint which is bought and burned.
+    pub buyback_mint: Pubkey,
+    /// Maximum slippage of a buyback from the spot price of the pool, in bps.
+    pub max_slippage_bps: u16,
+}
+
+/// Maximum number of [FeeRecipient]s of a [FeeDistribution].
+pub const MAX_FEE_RECIPIENTS: usize = 4;
+
+/// Recipient of a share of the protocol fees collected from the swaps of a [Factory].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeRecipient {
+    /// Owner of the token accounts which receive the share.
+    pub owner: Pubkey,
+    /// Share of the collected fees, in bps. Zero for unused recipients.
+    pub share_bps: u16,
+}
+
+/// Split of the protocol fees collected by [crate::cpamm::collect_protocol_fees] between
+/// recipients, such as a DAO, a treasury and grants. The [FactoryTreasury] receives
+/// the rest of the fees. Managed by the admin of the [Factory].
+#[account]
+#[derive(Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeDistribution {
+    /// The [Factory].
+    pub factory: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+    /// Recipients of the fees. The used recipients come first.
+    pub recipients: [FeeRecipient; MAX_FEE_RECIPIENTS],
+}
+
+impl FeeDistribution {
+    /// Returns the recipients which receive a share of the fees.
+    pub fn active_recipients(&self) -> impl Iterator<Item = &FeeRecipient> {
+        self.recipients
+            .iter()
+            .filter(|recipient| recipient.share_bps != 0)
+    }
+}
+
+/// Maximum number of [VolumeRebateTier]s of a [VolumeRebateConfig].
+pub const MAX_VOLUME_REBATE_TIERS: usize = 4;
+
+/// Trade fee rebate of traders with at least an amount of rolling volume.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VolumeRebateTier {
+    /// Minimum rolling volume of the trader, in token 0 of the swap. Zero for unused tiers.
+    pub min_volume: u64,
+    /// Proportion of the trade fee rebated, thousands of bps.
+    pub trade_fee_rebate_kbps: u64,
+}
+
+/// Trade fee rebates of the swaps of a [Factory] for high-volume traders,
+/// who pass their [TraderStats] to the swap. Managed by the admin of the [Factory].
+#[account]
+#[derive(Copy, Debug, Default, PartialEq, Eq)]
+pub struct VolumeRebateConfig {
+    /// The [Factory].
+    pub factory: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+    /// Length of the windows over which the volume of traders is tracked, in seconds.
+    pub window_seconds: i64,
+    /// Rebates in ascending order of the rolling volume of the trader.
+    pub tiers: [VolumeRebateTier; MAX_VOLUME_REBATE_TIERS],
+}
+
+impl VolumeRebateConfig {
+    /// Returns the trade fee rebate of a trader with `volume` of rolling volume,
+    /// which is that of the largest tier the volume reaches, thousands of bps.
+    pub fn trade_fee_rebate_kbps(&self, volume: u64) -> u64 {
+        self.tiers
+            .iter()
+            .rev()
+            .find(|tier| tier.min_volume != 0 && volume >= tier.min_volume)
+            .map_or(0, |tier| tier.trade_fee_rebate_kbps)
+    }
+}
+
+/// Treasury of a [Factory], which receives the admin fees of its swaps
+/// collected by [crate::cpamm::collect_protocol_fees].
+#[account]
+#[derive(Copy, Debug, Default, PartialEq, Eq)]
+pub struct FactoryTreasury {
+    /// The [Factory].
+    pub factory: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+    /// Owner of the token accounts which receive the collected fees.
+    pub owner: Pubkey,
+}
+
+/// A registered referrer. Referral fees of swaps referencing the [Referrer] are sent to
+/// token accounts owned by the [Referrer], from which its owner claims them with
+/// [crate::cpamm::claim_referral_fees].
+#[account]
+#[derive(Copy, Debug, Default, PartialEq, Eq)]
+pub struct Referrer {
+    /// Owner of the [Referrer], who claims the referral fees.
+    pub owner: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+    /// Number of swaps which paid a referral fee to the [Referrer].
+    pub num_referrals: u64,
+}
+
+/// A mint on the allowlist or denylist of a [Factory], depending on its
+/// [Factory::mint_list_mode]. Managed by the admin of the [Factory].
+#[account]
+#[derive(Copy, Debug, Default, PartialEq, Eq)]
+pub struct MintListEntry {
+    /// The [Factory].
+    pub factory: Pubkey,
+    /// The listed mint.
+    pub mint: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+}
+
+/// Transfers the input tokens of swaps signed off-chain by its owner.
+/// The owner approves it as the delegate of their input token accounts.
+/// For more information, view [crate::cpamm::swap_with_signature].
+#[account]
+#[derive(Copy, Debug, Default, PartialEq, Eq)]
+pub struct SignedSwapAuthority {
+    /// The owner, which signs the [SwapIntent]s.
+    pub owner: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+    /// Nonce of the next [SwapIntent]. Incremented by every signed swap.
+    pub nonce: u64,
+}
+
+/// A swap authorized by the owner of a [SignedSwapAuthority].
+/// The owner signs its Borsh serialization with ed25519.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapIntent {
+    /// The [SwapInfo].
+    pub swap: Pubkey,
+    /// The owner's token account of the input token.
+    pub input: Pubkey,
+    /// The owner's token account of the output token.
+    pub output: Pubkey,
+    /// Amount of the input token to swap.
+    pub amount_in: u64,
+    /// Minimum amount of the output token to receive.
+    pub minimum_amount_out: u64,
+    /// Must be the nonce of the [SignedSwapAuthority].
+    pub nonce: u64,
+    /// Unix timestamp after which the intent can no longer be executed.
+    pub expiry: i64,
+}
+
+/// LP tokens of a swap held in escrow until an unlock timestamp.
+/// For more information, view [crate::cpamm::deposit_locked].
+#[account]
+#[derive(Copy, Debug, Default, PartialEq, Eq)]
+pub struct LockedPosition {
+    /// Base key used to derive the [LockedPosition].
+    pub base: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+    /// The [SwapInfo].
+    pub swap: Pubkey,
+    /// The owner which deposits into and unlocks the position.
+    pub owner: Pubkey,
+    /// Token account of the position holding the locked LP tokens.
+    pub escrow: Pubkey,
+    /// Unix timestamp from which the LP tokens may be unlocked.
+    pub unlock_ts: i64,
+    /// Amount of LP tokens locked.
+    pub pool_token_amount: u64,
+}
+
+/// Slot of the latest deposit minting LP tokens of a swap to an LP token account,
+/// which starts the withdraw cooldown of the LP token account. For more information, view [crate::cpamm::set_withdraw_cooldown].
+#[account]
+#[derive(Copy, Debug, Default, PartialEq, Eq)]
+pub struct DepositCooldown {
+    /// The [SwapInfo].
+    pub swap: Pubkey,
+    /// The LP token account deposited to and withdrawn from.
+    pub lp_account: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+    /// Slot of the latest deposit to the LP token account.
+    pub last_deposit_slot: u64,
+}
+
+/// Volume swapped by a trader in a swap, which earns the trader the rebates of
+/// the [VolumeRebateConfig] of the [Factory]. For more information, view [crate::cpamm::set_volume_rebates].
+#[account]
+#[derive(Copy, Debug, Default, PartialEq, Eq)]
+pub struct TraderStats {
+    /// The [SwapInfo].
+    pub swap: Pubkey,
+    /// The user authority of the swaps.
+    pub owner: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+    /// When the current window started.
+    pub window_start: i64,
+    /// Volume swapped in the current window, in token 0 of the swap.
+    pub window_volume: u64,
+    /// Volume swapped in the previous window, in token 0 of the swap.
+    pub previous_window_volume: u64,
+}
+
+impl TraderStats {
+    /// Returns the rolling volume of the trader at `now`, which is the larger of the volumes
+    /// of the current and the previous windows of `window_seconds`.
+    pub fn rolling_volume(&self, now: i64, window_seconds: i64) -> u64 {
+        let window_end = self.window_start.saturating_add(window_seconds);
+        if now < window_end {
+            self.window_volume.max(self.previous_window_volume)
+        } else if now < window_end.saturating_add(window_seconds) {
+            // the current window has become the previous window
+            self.window_volume
+        } else {
+            0
+        }
+    }
+
+    /// Adds `volume` to the volume of the window of `window_seconds` at `now`,
+    /// starting a new window if the current one has ended.
+    pub fn track_volume(&mut self, now: i64, window_seconds: i64, volume: u64) -> Option<()> {
+        let window_end = self.window_start.saturating_add(window_seconds);
+        if now >= window_end {
+            self.previous_window_volume = if now < window_end.saturating_add(window_seconds) {
+                self.window_volume
+            } else {
+                0
+            };
+            self.window_start = now;
+            self.window_volume = 0;
+        }
+        self.window_volume = self.window_volume.checked_add(volume)?;
+        Some(())
+    }
+}
+
+/// Maximum length of the [SwapMetadata::name], in bytes.
+pub const MAX_SWAP_METADATA_NAME_LEN: usize = 32;
+/// Maximum length of the [SwapMetadata::uri], in bytes.
+pub const MAX_SWAP_METADATA_URI_LEN: usize = 200;
+/// Maximum number of [SwapMetadata::tags].
+pub const MAX_SWAP_METADATA_TAGS: usize = 5;
+/// Maximum length of each of the [SwapMetadata::tags], in bytes.
+pub const MAX_SWAP_METADATA_TAG_LEN: usize = 16;
+
+/// Information about a swap for display by front-ends, written by the creator of the swap.
+#[account]
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SwapMetadata {
+    /// The [SwapInfo].
+    pub swap: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+    /// Short name of the swap.
+    pub name: String,
+    /// URI of the project of the swap.
+    pub uri: String,
+    /// Tags of the swap, such as "stable" or "lst".
+    pub tags: Vec<String>,
+}
+
+impl SwapMetadata {
+    /// Maximum size of a [SwapMetadata], including the account discriminator.
+    pub const LEN: usize = 8
+        + 32
+        + 1
+        + (4 + MAX_SWAP_METADATA_NAME_LEN)
+        + (4 + MAX_SWAP_METADATA_URI_LEN)
+        + (4 + MAX_SWAP_METADATA_TAGS * (4 + MAX_SWAP_METADATA_TAG_LEN));
+}
+
+/// Commitment to a swap which is revealed and executed in a later slot.
+/// For more information, view [crate::cpamm::commit_swap].
+#[account]
+#[derive(Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapCommitment {
+    /// The [SwapInfo].
+    pub swap: Pubkey,
+    /// The user authority which performs the swap.
+    pub user_authority: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+    /// Hash of the parameters of the swap. See [SwapCommitment::hash_swap].
+    pub commitment: [u8; 32],
+    /// Slot in which the swap was committed.
+    pub slot: u64,
+    /// Receives the rent of the [SwapCommitment] once the swap is revealed.
+    pub payer: Pubkey,
+}
+
+impl SwapCommitment {
+    /// Computes the SHA-256 hash committing to a swap from the `input` to the `output`
+    /// token account of the user. The `salt` is kept secret until the swap is revealed.
+    pub fn hash_swap(
+        input: Pubkey,
+        output: Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        salt: [u8; 32],
+    ) -> [u8; 32] {
+        anchor_lang::solana_program::hash::hashv(&[
+            input.as_ref(),
+            output.as_ref(),
+            &amount_in.to_le_bytes(),
+            &minimum_amount_out.to_le_bytes(),
+            &salt,
+        ])
+        .to_bytes()
+    }
 }
 
 /// A swap with an index. Used by the [Factory].
@@ -37,8 +609,19 @@ pub struct SwapMeta {
     pub created_at: i64,
     /// Who created the [SwapInfo].
     pub created_by: Pubkey,
+    /// Version of the layout of the meta. For more information, view [SWAP_META_VERSION].
+    pub version: u8,
 }
 
+/// Version of the layout of new [SwapMeta]s.
+///
+/// Metas created before the version was recorded are version 0. Fields are only ever appended
+/// to [SwapMeta], so [crate::cpamm::migrate_swap_meta] upgrades older metas by growing them
+/// to the current size.
+///
+/// Version 1 added [SwapMeta::version].
+pub const SWAP_META_VERSION: u8 = 1;
+
 /// Stores information about a swap.
 #[account]
 #[derive(Copy, Debug, Default, PartialEq, Eq)]
@@ -58,7 +641,8 @@ pub struct SwapInfo {
     /// Token 1
     pub token_1: SwapTokenInfo,
 
-    /// Paused state
+    /// If true, swaps and deposits are paused. Withdrawals remain allowed,
+    /// so that LPs can always exit the pool.
     pub is_paused: bool,
     /// Pool tokens are issued when A or B tokens are deposited.
     /// Pool tokens can be withdrawn back to the original A or B token.
@@ -72,8 +656,495 @@ pub struct SwapInfo {
     /// For more information, view [crate::price_info].
     pub price_info: SwapPriceInfo,
 
-    /// Data reserved for future program upgrades.
-    pub reserved: [u64; 16],
+    /// Proportion of the admin trade fee sent to the referrer of a swap, thousands of bps.
+    /// Configured by the admin of the [Factory].
+    pub referral_fee_kbps: u64,
+    /// Proportion of the admin trade fee sent to the host of a swap, thousands of bps.
+    /// Configured by the admin of the [SwapInfo].
+    pub host_fee_kbps: u64,
+
+    /// Whether swaps from token 0 to token 1 are paused.
+    pub is_paused_0_to_1: bool,
+    /// Whether swaps from token 1 to token 0 are paused.
+    pub is_paused_1_to_0: bool,
+    /// Maximum input of a swap as a proportion of the input reserve, in bps.
+    /// Zero means there is no limit.
+    pub max_trade_bps_of_reserve: u16,
+    /// Number of swaps executed in [Self::last_swap_slot].
+    pub swaps_in_slot: u16,
+    /// Maximum number of swaps per slot. Zero means there is no limit.
+    pub max_swaps_per_slot: u16,
+    /// Slot of the last swap.
+    pub last_swap_slot: u64,
+    /// Maximum price impact of a swap versus the spot price, in bps.
+    /// Zero means there is no limit. Only applies to constant product swaps.
+    pub max_price_impact_bps: u16,
+    /// Weight of the latest price change in [Self::volatility_bps], in bps.
+    pub volatility_weight_bps: u16,
+    /// Number of slots after a deposit during which the depositor cannot withdraw.
+    /// Zero disables the cooldown. For more information, view [DepositCooldown].
+    pub withdraw_cooldown_slots: u32,
+
+    /// Exponentially weighted moving average of the price changes of swaps, in bps.
+    /// Only tracked while the dynamic trade fee is enabled.
+    pub volatility_bps: u64,
+    /// Trade fee added per bps of [Self::volatility_bps], thousands of bps.
+    /// Zero disables the dynamic trade fee.
+    pub dynamic_fee_kbps_per_volatility_bps: u64,
+    /// Maximum dynamic trade fee, thousands of bps.
+    pub max_dynamic_trade_fee_kbps: u64,
+    /// Trade fees of large trades, in ascending order of trade size.
+    /// Unused tiers have a zero trade size.
+    pub fee_tiers: [SwapFeeTier; MAX_FEE_TIERS],
+    /// Pool fee tier of the [Factory] the swap was created with.
+    /// Each fee tier of a pair of tokens has its own swap.
+    pub pool_fee_tier: u8,
+    /// Padding which keeps the size of the [SwapInfo] unchanged.
+    pub padding_1: [u8; 3],
+
+    /// Amount of token 0 in the pool. Tokens sent to the reserve outside of the
+    /// instructions of the swap are not included until [crate::cpamm::sync] is called.
+    pub reserve_0_amount: u64,
+    /// Amount of token 1 in the pool. Tokens sent to the reserve outside of the
+    /// instructions of the swap are not included until [crate::cpamm::sync] is called.
+    pub reserve_1_amount: u64,
+    /// Maximum amount of token 0 in the pool after a deposit. Zero means there is no cap.
+    pub max_reserve_0: u64,
+    /// Maximum amount of token 1 in the pool after a deposit. Zero means there is no cap.
+    pub max_reserve_1: u64,
+
+    /// Amplification coefficient of the stable swap curve, set when the swap is created.
+    /// Zero means the swap uses the constant product curve.
+    /// For more information, view [xyk::stable].
+    pub amp_factor: u64,
+
+    /// Creator of the swap, who may receive a share of the admin trade fee.
+    /// The default key for swaps created before it was recorded.
+    pub creator: Pubkey,
+    /// Proportion of the admin trade fee sent to the creator of the swap, thousands of bps.
+    /// Configured by the admin of the [SwapInfo].
+    pub creator_fee_kbps: u64,
+
+    /// Version of the layout of the swap. For more information, view [SWAP_INFO_VERSION].
+    pub version: u8,
+
+    /// When the [SwapInfo::fees] were last changed, or when the swap was created.
+    /// Zero for swaps migrated from an older version whose fees have not changed since.
+    pub fees_last_changed_at: i64,
+
+    /// If true, anyone may compound the admin fees of the swap into protocol-owned liquidity
+    /// with [crate::cpamm::compound_admin_fees]. Configured by the admin of the [Factory].
+    pub is_auto_compound_enabled: bool,
+
+    /// When the swap was created. Zero for swaps created before it was recorded.
+    pub created_at: i64,
+    /// Trade fee at the creation of the swap, which decays linearly to the trade fee of the swap
+    /// over [SwapInfo::launch_fee_duration_seconds] to deter sniping at launch, thousands of bps.
+    /// Zero if the swap has no launch fee.
+    pub launch_trade_fee_kbps: u64,
+    /// Duration of the decay of the launch trade fee, in seconds.
+    pub launch_fee_duration_seconds: i64,
+
+    /// Total trade fees of token 0 sent out of the pool, to the admin fee account, hosts,
+    /// creators and referrers. The LPs earned [SwapCumulativeTokenStats::total_trade_fees]
+    /// less these. Counted since the swap was migrated to version 5.
+    pub total_fees_paid_out_0: u128,
+    /// Total trade fees of token 1 sent out of the pool. For more information,
+    /// view [SwapInfo::total_fees_paid_out_0].
+    pub total_fees_paid_out_1: u128,
+
+    /// Trade fee added to swaps of token 0 for token 1, thousands of bps.
+    /// Configured by the admin of the [SwapInfo] to discourage one direction of trading.
+    pub trade_fee_surcharge_0_to_1_kbps: u64,
+    /// Trade fee added to swaps of token 1 for token 0, thousands of bps.
+    pub trade_fee_surcharge_1_to_0_kbps: u64,
+
+    /// Pyth price account guarding the execution prices of swaps. The default key if
+    /// the swap has no oracle band. For more information, view [crate::oracle].
+    pub oracle: Pubkey,
+    /// If true, the oracle prices token 1 in token 0 instead of token 0 in token 1.
+    pub is_oracle_inverted: bool,
+    /// Power of ten converting the price of the oracle to the smallest units of the tokens.
+    pub oracle_decimals_adjustment: i8,
+    /// Maximum deviation of the execution price of a swap from the price of the oracle, in bps.
+    pub oracle_band_bps: u16,
+
+    /// Execution price of the last swap before fees, as the price of token 0 in token 1
+    /// in their smallest units, scaled by [crate::price_info::PRICE_SCALE].
+    pub last_trade_price: u128,
+    /// Slot of the last swap.
+    pub last_trade_slot: u64,
+    /// Size of the last swap, in token 0.
+    pub last_trade_size: u64,
+
+    /// Median-filtered price information, which ignores the extremes of the prices
+    /// within a slot. For more information, view [SwapMedianPriceInfo].
+    pub median_price_info: SwapMedianPriceInfo,
+
+    /// Lowest price of token 0 in token 1 since the last [Observation] of the swap was written,
+    /// scaled by [crate::price_info::PRICE_SCALE]. Zero if the price has not been sampled since.
+    pub price_0_low_since_observation: u128,
+    /// Highest price of token 0 in token 1 since the last [Observation] of the swap was written,
+    /// scaled by [crate::price_info::PRICE_SCALE].
+    pub price_0_high_since_observation: u128,
+
+    /// If true, an instruction is invoking another program on behalf of the swap, such as the
+    /// callback of [crate::cpamm::flash_swap], and the swap rejects any nested instruction.
+    pub is_in_progress: bool,
+
+    /// If true, a mint of the swap had a freeze authority when the swap was created, so the
+    /// freeze authority may freeze the reserves. Only set under [FREEZE_AUTHORITY_POLICY_FLAG].
+    pub has_freezable_mint: bool,
+
+    /// Maximum change of either reserve within a slot, in bps of the reserve at the first swap
+    /// of the slot. Exceeding it trips the circuit breaker. Zero disables the circuit breaker.
+    pub max_reserve_change_bps_per_slot: u16,
+    /// Number of slots after the slot in which the circuit breaker tripped during which
+    /// swaps remain blocked.
+    pub circuit_breaker_cooldown_slots: u32,
+    /// Reserve of token 0 at the first swap of [Self::last_swap_slot].
+    pub slot_start_reserve_0: u64,
+    /// Reserve of token 1 at the first swap of [Self::last_swap_slot].
+    pub slot_start_reserve_1: u64,
+    /// Swaps are blocked by the circuit breaker until this slot. Zero if it has not tripped.
+    pub circuit_breaker_until_slot: u64,
+
+    /// Weight of token 0 in a weighted swap, or zero if the swap is not weighted.
+    pub weight_0: u8,
+    /// Weight of token 1 in a weighted swap, or zero if the swap is not weighted.
+    pub weight_1: u8,
+}
+
+/// Version of the layout of new [SwapInfo]s.
+///
+/// Swaps created by the first release of the program are version 0. They end in 16 reserved
+/// words, which version 1 replaces with the fields appended since, so
+/// [crate::cpamm::migrate_pool] upgrades them by growing them to the current size and setting
+/// [SwapInfo::reserve_0_amount] and [SwapInfo::reserve_1_amount] from the reserve balances.
+///
+/// Version 1 added [SwapInfo::version] and every field after it.
+pub const SWAP_INFO_VERSION: u8 = 1;
+
+/// An account whose layout records its version, so that accounts created by older versions
+/// of the program can be upgraded in place.
+pub trait Versioned {
+    /// Version of the layout of new accounts.
+    const VERSION: u8;
+
+    /// Returns the version of the layout of the account.
+    fn version(&self) -> u8;
+
+    /// Upgrades the account, whose appended fields are zeroed, to [Self::VERSION].
+    fn upgrade(&mut self);
+}
+
+impl Versioned for Factory {
+    const VERSION: u8 = FACTORY_VERSION;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn upgrade(&mut self) {
+        // Upgrades of later versions go here, from the oldest to the newest.
+        self.version = FACTORY_VERSION;
+    }
+}
+
+impl Versioned for SwapMeta {
+    const VERSION: u8 = SWAP_META_VERSION;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn upgrade(&mut self) {
+        // Upgrades of later versions go here, from the oldest to the newest.
+        self.version = SWAP_META_VERSION;
+    }
+}
+
+impl Versioned for SwapInfo {
+    const VERSION: u8 = SWAP_INFO_VERSION;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn upgrade(&mut self) {
+        // Upgrades of later versions go here, from the oldest to the newest.
+        self.version = SWAP_INFO_VERSION;
+    }
+}
+
+impl SwapInfo {
+    /// Returns true if the swap uses the constant product curve.
+    pub fn is_constant_product(&self) -> bool {
+        self.amp_factor == 0 && !self.is_weighted()
+    }
+
+    /// Returns true if the swap uses the weighted constant mean curve.
+    pub fn is_weighted(&self) -> bool {
+        self.weight_0 != 0
+    }
+
+    /// Returns the weights of the token with the given mint and of the other token.
+    fn weights(&self, mint: Pubkey) -> (u8, u8) {
+        if mint == self.token_0.mint {
+            (self.weight_0, self.weight_1)
+        } else {
+            (self.weight_1, self.weight_0)
+        }
+    }
+
+    /// Computes a swap of an exact source amount of the token with the mint `source_mint`
+    /// on the curve of the swap.
+    pub fn curve_swap(
+        &self,
+        source_mint: Pubkey,
+        source_amount: u64,
+        swap_source_amount: u64,
+        swap_destination_amount: u64,
+    ) -> Option<xyk::SwapResult> {
+        if self.is_weighted() {
+            let (source_weight, destination_weight) = self.weights(source_mint);
+            xyk::weighted::swap(
+                source_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                source_weight,
+                destination_weight,
+            )
+        } else if self.is_constant_product() {
+            xyk::swap(source_amount, swap_source_amount, swap_destination_amount)
+        } else {
+            xyk::stable::swap(
+                self.amp_factor,
+                source_amount,
+                swap_source_amount,
+                swap_destination_amount,
+            )
+        }
+    }
+
+    /// Computes a swap of an exact destination amount from the token with the mint `source_mint`
+    /// on the curve of the swap.
+    pub fn curve_swap_exact_out(
+        &self,
+        source_mint: Pubkey,
+        destination_amount: u64,
+        swap_source_amount: u64,
+        swap_destination_amount: u64,
+    ) -> Option<xyk::SwapResult> {
+        if self.is_weighted() {
+            let (source_weight, destination_weight) = self.weights(source_mint);
+            xyk::weighted::swap_exact_out(
+                destination_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                source_weight,
+                destination_weight,
+            )
+        } else if self.is_constant_product() {
+            xyk::swap_exact_out(
+                destination_amount,
+                swap_source_amount,
+                swap_destination_amount,
+            )
+        } else {
+            xyk::stable::swap_exact_out(
+                self.amp_factor,
+                destination_amount,
+                swap_source_amount,
+                swap_destination_amount,
+            )
+        }
+    }
+
+    /// Returns true if the invariant of the curve of the swap did not decrease
+    /// from the old to the new amounts, where the first amount of each pair
+    /// is of the token with the mint `mint_a`.
+    pub fn is_curve_invariant_preserved(
+        &self,
+        mint_a: Pubkey,
+        (old_amount_a, old_amount_b): (u64, u64),
+        (new_amount_a, new_amount_b): (u64, u64),
+    ) -> Option<bool> {
+        if self.is_weighted() {
+            let (weight_a, weight_b) = self.weights(mint_a);
+            xyk::weighted::is_invariant_preserved(
+                weight_a,
+                weight_b,
+                old_amount_a,
+                old_amount_b,
+                new_amount_a,
+                new_amount_b,
+            )
+        } else if self.is_constant_product() {
+            xyk::is_invariant_preserved(old_amount_a, old_amount_b, new_amount_a, new_amount_b)
+        } else {
+            xyk::stable::is_invariant_preserved(
+                self.amp_factor,
+                old_amount_a,
+                old_amount_b,
+                new_amount_a,
+                new_amount_b,
+            )
+        }
+    }
+
+    /// Returns true if swaps from the token with the given mint are paused.
+    pub fn is_direction_paused(&self, input_mint: Pubkey) -> bool {
+        if input_mint == self.token_0.mint {
+            self.is_paused_0_to_1
+        } else {
+            self.is_paused_1_to_0
+        }
+    }
+
+    /// Returns the amounts of tokens 0 and 1 in the pool.
+    pub fn reserve_amounts(&self) -> (u64, u64) {
+        (self.reserve_0_amount, self.reserve_1_amount)
+    }
+
+    /// Returns the amount of the token with the given mint in the pool.
+    pub fn reserve_amount(&self, mint: Pubkey) -> u64 {
+        if mint == self.token_0.mint {
+            self.reserve_0_amount
+        } else {
+            self.reserve_1_amount
+        }
+    }
+
+    /// Adds the change of the balance of the reserve of the token with the given mint
+    /// to the amount of the token in the pool.
+    ///
+    /// Tokens sent to the reserve outside of the instructions of the swap are not part of
+    /// the change, so they stay out of the pool.
+    pub fn track_reserve_balance(
+        &mut self,
+        mint: Pubkey,
+        old_balance: u64,
+        new_balance: u64,
+    ) -> Option<()> {
+        let reserve_amount = if mint == self.token_0.mint {
+            &mut self.reserve_0_amount
+        } else {
+            &mut self.reserve_1_amount
+        };
+        *reserve_amount = reserve_amount
+            .checked_add(new_balance)?
+            .checked_sub(old_balance)?;
+        Some(())
+    }
+
+    /// Adds `amount` of trade fees of the token with the `mint` sent out of the pool
+    /// to its total fees paid out.
+    pub fn track_fees_paid_out(&mut self, mint: Pubkey, amount: u64) -> Option<()> {
+        let total_fees_paid_out = if mint == self.token_0.mint {
+            &mut self.total_fees_paid_out_0
+        } else {
+            &mut self.total_fees_paid_out_1
+        };
+        *total_fees_paid_out = total_fees_paid_out.checked_add(amount.into())?;
+        Some(())
+    }
+
+    /// Records a swap of the token with the `input_mint` at `slot` as the last trade of the swap.
+    pub fn record_last_trade(
+        &mut self,
+        input_mint: Pubkey,
+        swap_result: &xyk::SwapResult,
+        slot: u64,
+    ) -> Option<()> {
+        self.last_trade_price = crate::oracle::execution_price(self, input_mint, swap_result)?;
+        self.last_trade_size = if input_mint == self.token_0.mint {
+            swap_result.source_amount_swapped
+        } else {
+            swap_result.destination_amount_swapped
+        };
+        self.last_trade_slot = slot;
+        Some(())
+    }
+
+    /// Returns the fees charged on swaps, including the dynamic trade fee if it is enabled.
+    /// This does not include the [SwapFeeTier]s, which depend on the trade size.
+    pub fn swap_fees(&self) -> SwapFees {
+        self.with_dynamic_fee(self.with_launch_fee(self.fees))
+    }
+
+    /// Returns the fees charged on a swap of `amount_in` of the `input_mint`
+    /// into the `input_reserve`.
+    ///
+    /// The trade fee of the largest [SwapFeeTier] the trade reaches replaces the base trade fee,
+    /// and the trade fee surcharge of the direction of the swap is added to it.
+    /// It is raised to the launch trade fee while that decays, then the dynamic trade fee
+    /// is added if it is enabled.
+    pub fn swap_fees_for_trade(
+        &self,
+        input_mint: Pubkey,
+        amount_in: u64,
+        input_reserve: u64,
+    ) -> Option<SwapFees> {
+        let mut fees = self.fees;
+        let trade_bps = (amount_in as u128).checked_mul(xyk::BPS_PER_WHOLE.into())?;
+        for tier in self.fee_tiers.iter() {
+            if tier.min_trade_bps_of_reserve != 0
+                && trade_bps
+                    >= (input_reserve as u128).checked_mul(tier.min_trade_bps_of_reserve.into())?
+            {
+                fees.trade_fee_kbps = tier.trade_fee_kbps;
+            }
+        }
+        let surcharge_kbps = if input_mint == self.token_0.mint {
+            self.trade_fee_surcharge_0_to_1_kbps
+        } else {
+            self.trade_fee_surcharge_1_to_0_kbps
+        };
+        fees.trade_fee_kbps = fees
+            .trade_fee_kbps
+            .saturating_add(surcharge_kbps)
+            .min(crate::fees::KBPS_PER_WHOLE);
+        Some(self.with_dynamic_fee(self.with_launch_fee(fees)))
+    }
+
+    /// Raises the trade fee of the `fees` to the launch trade fee, which decays linearly from
+    /// [SwapInfo::launch_trade_fee_kbps] at [SwapInfo::created_at] to the trade fee of the `fees`.
+    fn with_launch_fee(&self, fees: SwapFees) -> SwapFees {
+        if self.launch_trade_fee_kbps <= fees.trade_fee_kbps {
+            return fees;
+        }
+        let now = match Clock::get() {
+            Ok(clock) => clock.unix_timestamp,
+            Err(_) => return fees,
+        };
+        let elapsed = now.saturating_sub(self.created_at);
+        if elapsed < 0 || elapsed >= self.launch_fee_duration_seconds {
+            return fees;
+        }
+
+        // remaining = (launch - base) * (duration - elapsed) / duration
+        let decaying_kbps = self.launch_trade_fee_kbps - fees.trade_fee_kbps;
+        let remaining_kbps = (decaying_kbps as u128)
+            .saturating_mul((self.launch_fee_duration_seconds - elapsed) as u128)
+            / (self.launch_fee_duration_seconds as u128);
+        SwapFees {
+            trade_fee_kbps: fees.trade_fee_kbps + remaining_kbps as u64,
+            ..fees
+        }
+    }
+
+    /// Adds the dynamic trade fee to the `fees` if it is enabled.
+    fn with_dynamic_fee(&self, fees: SwapFees) -> SwapFees {
+        if self.dynamic_fee_kbps_per_volatility_bps == 0 {
+            return fees;
+        }
+        fees.with_volatility_fee(
+            self.volatility_bps,
+            self.dynamic_fee_kbps_per_volatility_bps,
+            self.max_dynamic_trade_fee_kbps,
+        )
+    }
 }
 
 /// Cumulative statistics about the swap.
@@ -112,16 +1183,190 @@ pub struct SwapCumulativeTokenStats {
 
 /// Price information about a [SwapInfo].
 /// Usage: <https://uniswap.org/docs/v2/core-concepts/oracles/>
+///
+/// This is updated before every instruction which changes the reserves of the swap.
+/// The TWAP of token 0 between two observations is the wrapping difference of their
+/// [SwapPriceInfo::price_0_cumulative_last] divided by the seconds elapsed between
+/// their [SwapPriceInfo::last_update_ts], scaled by [crate::price_info::PRICE_SCALE].
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct SwapPriceInfo {
-    /// Last time the state was updated
+    /// Last time the state was updated, the `blockTimestampLast` of Uniswap V2.
     pub last_update_ts: i64,
-    /// Last cumulative price of token 0
+    /// Last cumulative price of token 0, in token 1 per token 0.
     pub price_0_cumulative_last: u128,
-    /// Last cumulative price of token 1
+    /// Last cumulative price of token 1, in token 0 per token 1.
     pub price_1_cumulative_last: u128,
 }
 
+/// Maximum number of prices sampled per slot by a [SwapMedianPriceInfo].
+pub const MAX_MEDIAN_SAMPLES: usize = 8;
+
+/// Cumulative prices of a [SwapInfo] which accumulate the median of the prices seen within
+/// each slot, rather than the last price. The price of the swap is sampled before every
+/// instruction which changes the reserves, and once more when the next slot begins, so a price
+/// which is pushed away and back within a slot does not move the median.
+/// This makes the TWAP much harder to manipulate within a single block.
+///
+/// The median of a slot takes effect at the first sample after the slot is over.
+/// Once a slot has [MAX_MEDIAN_SAMPLES] samples, later samples replace the last one.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapMedianPriceInfo {
+    /// Slot of the samples.
+    pub slot: u64,
+    /// Number of samples in the slot.
+    pub num_samples: u8,
+    /// Prices of token 0 in token 1 sampled in the slot,
+    /// scaled by [crate::price_info::PRICE_SCALE].
+    pub samples: [u128; MAX_MEDIAN_SAMPLES],
+    /// Median price of token 0 in token 1 of the latest slot with samples before [Self::slot],
+    /// scaled by [crate::price_info::PRICE_SCALE]. Zero if there is no such slot.
+    pub median_price_0: u128,
+    /// Last time the cumulative prices were updated.
+    pub last_update_ts: i64,
+    /// Last cumulative median price of token 0, in token 1 per token 0.
+    pub price_0_cumulative_last: u128,
+    /// Last cumulative median price of token 1, in token 0 per token 1.
+    pub price_1_cumulative_last: u128,
+}
+
+/// Ring buffer of [Observation]s of the cumulative prices of a [SwapInfo], which lets programs
+/// read the TWAP of the swap over past windows. The observations are stored after this header.
+/// For more information, view [crate::observations].
+#[account(zero_copy)]
+#[repr(C, packed)]
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Observations {
+    /// The [SwapInfo].
+    pub swap: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+    /// Index of the most recent observation.
+    pub index: u16,
+    /// Number of observations in the ring buffer.
+    pub cardinality: u16,
+    /// Number of observations the account has room for.
+    /// The ring buffer grows to it the next time it wraps around.
+    pub cardinality_next: u16,
+}
+
+/// Cumulative prices of a [SwapInfo] at a point in time.
+#[zero_copy]
+#[repr(C, packed)]
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Observation {
+    /// When the observation was made. Zero if it has not been written yet.
+    pub timestamp: i64,
+    /// Cumulative price of token 0. For more information, view [SwapPriceInfo].
+    pub price_0_cumulative: u128,
+    /// Cumulative price of token 1. For more information, view [SwapPriceInfo].
+    pub price_1_cumulative: u128,
+    /// Cumulative median price of token 0. For more information, view [SwapMedianPriceInfo].
+    pub median_price_0_cumulative: u128,
+    /// Cumulative median price of token 1. For more information, view [SwapMedianPriceInfo].
+    pub median_price_1_cumulative: u128,
+    /// Lowest price of token 0 in token 1 since the previous observation,
+    /// scaled by [crate::price_info::PRICE_SCALE].
+    pub price_0_low: u128,
+    /// Highest price of token 0 in token 1 since the previous observation,
+    /// scaled by [crate::price_info::PRICE_SCALE].
+    pub price_0_high: u128,
+}
+
+// SAFETY: an [Observation] is packed plain old data, so it has no padding nor invalid values.
+unsafe impl bytemuck::Zeroable for Observation {}
+unsafe impl bytemuck::Pod for Observation {}
+
+/// Maximum [Factory::timelock_delay_seconds]. 30 days.
+pub const MAX_TIMELOCK_DELAY_SECONDS: i64 = 30 * 24 * 60 * 60;
+
+/// A change which must wait for the timelock of a [Factory]. For more information,
+/// view [crate::cpamm::queue_change].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimelockedChange {
+    /// Sets the [SwapFees] of the target [SwapInfo], like [crate::cpamm::set_fees].
+    Fees {
+        /// The new fees.
+        new_fees: SwapFees,
+    },
+    /// Sets the amplification coefficient of the target stable [SwapInfo].
+    AmpFactor {
+        /// The new amplification coefficient.
+        amp_factor: u64,
+    },
+    /// Proposes a new admin of the target [Factory], like [crate::cpamm::propose_admin].
+    Admin {
+        /// The proposed admin.
+        new_admin: Pubkey,
+    },
+    /// Sets the [Factory::timelock_delay_seconds] of the target [Factory].
+    TimelockDelay {
+        /// The new delay, in seconds.
+        delay_seconds: i64,
+    },
+}
+
+impl TimelockedChange {
+    /// Returns true if the target of the change is a [SwapInfo] rather than a [Factory].
+    pub fn targets_swap(&self) -> bool {
+        matches!(self, Self::Fees { .. } | Self::AmpFactor { .. })
+    }
+}
+
+/// A [TimelockedChange] of a [SwapInfo] or a [Factory] queued by its admin,
+/// which may be executed once its ETA has passed. Each target has at most one pending change.
+#[account]
+#[derive(Copy, Debug, PartialEq, Eq)]
+pub struct PendingChange {
+    /// The [Factory] whose timelock applies.
+    pub factory: Pubkey,
+    /// The [SwapInfo] or the [Factory] to change.
+    pub target: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+    /// When the change may be executed.
+    pub eta: i64,
+    /// The change.
+    pub change: TimelockedChange,
+}
+
+impl PendingChange {
+    /// Size of a [PendingChange], including the account discriminator.
+    /// The largest [TimelockedChange] is [TimelockedChange::Fees].
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 8 + (1 + 8 * 4);
+}
+
+/// Liquidity-weighted price of a token pair across all of the pool fee tiers of a [Factory],
+/// updated by the permissionless [crate::cpamm::update_pair_price] crank.
+/// The liquidity of a pool is the geometric mean of its reserves.
+#[account]
+#[derive(Copy, Debug, Default, PartialEq, Eq)]
+pub struct PairPrice {
+    /// The [Factory].
+    pub factory: Pubkey,
+    /// Mint of token 0 of the swaps.
+    pub token_0_mint: Pubkey,
+    /// Mint of token 1 of the swaps.
+    pub token_1_mint: Pubkey,
+    /// The bump seed.
+    pub bump: u8,
+    /// Number of swaps of the latest update.
+    pub num_swaps: u8,
+    /// Total liquidity of the swaps of the latest update.
+    pub total_liquidity: u64,
+    /// Liquidity-weighted spot price of token 0 in token 1,
+    /// scaled by [crate::price_info::PRICE_SCALE].
+    pub price_0: u128,
+    /// Liquidity-weighted spot price of token 1 in token 0,
+    /// scaled by [crate::price_info::PRICE_SCALE].
+    pub price_1: u128,
+    /// Cumulative [Self::price_0], which may overflow. For more information, view [SwapPriceInfo].
+    pub price_0_cumulative: u128,
+    /// Cumulative [Self::price_1], which may overflow. For more information, view [SwapPriceInfo].
+    pub price_1_cumulative: u128,
+    /// When the prices were last updated.
+    pub last_update_ts: i64,
+}
+
 /// Information about one of the tokens.
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct SwapTokenInfo {
@@ -143,6 +1388,18 @@ impl<'info> From<&InitSwapToken<'info>> for SwapTokenInfo {
     }
 }
 
+/// Maximum number of [SwapFeeTier]s of a [SwapInfo].
+pub const MAX_FEE_TIERS: usize = 2;
+
+/// Trade fee of trades of at least a proportion of the input reserve.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapFeeTier {
+    /// Minimum input of a trade as a proportion of the input reserve, in bps.
+    pub min_trade_bps_of_reserve: u16,
+    /// Trade fee, thousands of bps
+    pub trade_fee_kbps: u64,
+}
+
 /// Fees associated with a [SwapInfo].
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct SwapFees {
@@ -155,3 +1412,203 @@ pub struct SwapFees {
     /// Proportion of withdraw fee sent to the admin, thousands of bps
     pub admin_withdraw_fee_kbps: u64,
 }
+
+/// Result of a swap, set as the return data of the swap instructions.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapReturnData {
+    /// Amount of the input token taken from the user.
+    pub amount_in: u64,
+    /// Amount of the output token sent to the user.
+    pub amount_out: u64,
+}
+
+/// One of the swaps of a [crate::cpamm::swap_batch].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BatchSwap {
+    /// Amount of the input token to swap.
+    pub amount_in: u64,
+    /// Minimum amount of the output token to receive.
+    pub minimum_amount_out: u64,
+}
+
+/// Result of a [crate::cpamm::deposit], [crate::cpamm::deposit_imbalanced] or
+/// [crate::cpamm::deposit_single], set as the return data of the instruction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DepositReturnData {
+    /// Amount of LP tokens minted to the user.
+    pub pool_token_amount: u64,
+    /// Amount of token 0 taken from the user.
+    pub amount_in_0: u64,
+    /// Amount of token 1 taken from the user.
+    pub amount_in_1: u64,
+}
+
+/// Result of a [crate::cpamm::withdraw] or [crate::cpamm::withdraw_one],
+/// set as the return data of the instruction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WithdrawReturnData {
+    /// Amount of LP tokens burned from the user.
+    pub pool_token_amount: u64,
+    /// Amount of token 0 sent to the user.
+    pub amount_out_0: u64,
+    /// Amount of token 1 sent to the user.
+    pub amount_out_1: u64,
+}
+
+/// Result of a [crate::cpamm::observe], set as the return data of the instruction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapTwap {
+    /// Start of the window.
+    pub start_ts: i64,
+    /// End of the window, which is now.
+    pub end_ts: i64,
+    /// Time-weighted average price of token 0 in token 1 over the window,
+    /// scaled by [crate::price_info::PRICE_SCALE].
+    pub price_0_twap: u128,
+    /// Time-weighted average price of token 1 in token 0 over the window,
+    /// scaled by [crate::price_info::PRICE_SCALE].
+    pub price_1_twap: u128,
+    /// Time-weighted average of the median-filtered price of token 0 in token 1 over the window,
+    /// scaled by [crate::price_info::PRICE_SCALE]. For more information, view [SwapMedianPriceInfo].
+    pub median_price_0_twap: u128,
+    /// Time-weighted average of the median-filtered price of token 1 in token 0 over the window,
+    /// scaled by [crate::price_info::PRICE_SCALE].
+    pub median_price_1_twap: u128,
+    /// Lowest price of token 0 in token 1 over the window,
+    /// scaled by [crate::price_info::PRICE_SCALE].
+    pub price_0_low: u128,
+    /// Highest price of token 0 in token 1 over the window,
+    /// scaled by [crate::price_info::PRICE_SCALE].
+    pub price_0_high: u128,
+    /// Width of the range of the price over the window relative to the TWAP, in bps.
+    /// A wide range means the TWAP is a low-confidence reading of the price.
+    pub price_0_width_bps: u64,
+    /// Number of observations made within the window.
+    pub num_observations: u16,
+    /// When the most recent observation was made. A window much longer ago than this
+    /// is interpolated from few observations.
+    pub latest_observation_ts: i64,
+}
+
+/// Result of a [crate::cpamm::quote_swap], set as the return data of the instruction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapQuote {
+    /// Amount of the input token which would be taken from the user.
+    pub amount_in: u64,
+    /// Amount of the output token which would be sent to the user.
+    pub amount_out: u64,
+    /// Trade fee charged on the output token.
+    /// This includes both the LP fee and the admin fee.
+    pub trade_fee: u64,
+    /// Portion of the trade fee sent to the admin.
+    pub admin_trade_fee: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    fn key(seed: u8) -> Pubkey {
+        Pubkey::new_from_array([seed; 32])
+    }
+
+    /// Serializes a [SwapInfo] field by field in the layout of the first release of the program,
+    /// which ended in 16 reserved words.
+    fn baseline_swap_info() -> Vec<u8> {
+        let mut data = SwapInfo::discriminator().to_vec();
+        data.extend_from_slice(key(1).as_ref()); // factory
+        data.push(254); // bump
+        data.extend_from_slice(&7_u64.to_le_bytes()); // index
+        data.extend_from_slice(key(2).as_ref()); // admin_key
+        for seed in [3, 6] {
+            // token_0 and token_1
+            data.extend_from_slice(key(seed).as_ref()); // reserves
+            data.extend_from_slice(key(seed + 1).as_ref()); // mint
+            data.extend_from_slice(key(seed + 2).as_ref()); // admin_fees
+        }
+        data.push(1); // is_paused
+        data.extend_from_slice(key(9).as_ref()); // pool_mint
+        for fee in [30_u64, 40, 50, 60] {
+            data.extend_from_slice(&fee.to_le_bytes()); // fees
+        }
+        for volume in [11_u128, 12] {
+            // cumulative_stats.token_0 and cumulative_stats.token_1
+            data.extend_from_slice(&volume.to_le_bytes()); // total_input_volume
+            data.extend_from_slice(&(volume + 100).to_le_bytes()); // total_output_volume
+            data.extend_from_slice(&(volume + 200).to_le_bytes()); // total_deposit_volume
+            data.extend_from_slice(&(volume + 300).to_le_bytes()); // total_withdraw_volume
+            data.extend_from_slice(&(volume as u64 + 400).to_le_bytes()); // total_trade_fees
+        }
+        data.extend_from_slice(&13_u128.to_le_bytes()); // total_lp_minted
+        data.extend_from_slice(&14_u128.to_le_bytes()); // total_lp_redeemed
+        data.extend_from_slice(&15_i64.to_le_bytes()); // price_info.last_update_ts
+        data.extend_from_slice(&16_u128.to_le_bytes()); // price_info.price_0_cumulative_last
+        data.extend_from_slice(&17_u128.to_le_bytes()); // price_info.price_1_cumulative_last
+        data.extend_from_slice(&[0; 16 * 8]); // reserved
+        data
+    }
+
+    #[test]
+    fn baseline_swap_info_layout() {
+        let mut data = baseline_swap_info();
+        // migrate_pool grows the account, zeroing the appended fields
+        let new_len = SwapInfo::default().try_to_vec().unwrap().len() + 8;
+        assert!(data.len() < new_len);
+        data.resize(new_len, 0);
+
+        let mut swap_info = SwapInfo::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(swap_info.factory, key(1));
+        assert_eq!(swap_info.bump, 254);
+        assert_eq!(swap_info.index, 7);
+        assert_eq!(swap_info.admin_key, key(2));
+        assert_eq!(
+            swap_info.token_0,
+            SwapTokenInfo {
+                reserves: key(3),
+                mint: key(4),
+                admin_fees: key(5),
+            }
+        );
+        assert_eq!(
+            swap_info.token_1,
+            SwapTokenInfo {
+                reserves: key(6),
+                mint: key(7),
+                admin_fees: key(8),
+            }
+        );
+        assert!(swap_info.is_paused);
+        assert_eq!(swap_info.pool_mint, key(9));
+        assert_eq!(
+            swap_info.fees,
+            SwapFees {
+                trade_fee_kbps: 30,
+                withdraw_fee_kbps: 40,
+                admin_trade_fee_kbps: 50,
+                admin_withdraw_fee_kbps: 60,
+            }
+        );
+        assert_eq!(swap_info.cumulative_stats.token_0.total_input_volume, 11);
+        assert_eq!(swap_info.cumulative_stats.token_1.total_trade_fees, 412);
+        assert_eq!(swap_info.cumulative_stats.total_lp_minted, 13);
+        assert_eq!(swap_info.cumulative_stats.total_lp_redeemed, 14);
+        assert_eq!(
+            swap_info.price_info,
+            SwapPriceInfo {
+                last_update_ts: 15,
+                price_0_cumulative_last: 16,
+                price_1_cumulative_last: 17,
+            }
+        );
+
+        // the fields added since the baseline read as their defaults
+        assert_eq!(swap_info.version, 0);
+        assert_eq!(swap_info.weight_0, 0);
+        assert_eq!(swap_info.weight_1, 0);
+        assert!(swap_info.is_constant_product());
+
+        swap_info.upgrade();
+        assert_eq!(swap_info.version(), SWAP_INFO_VERSION);
+    }
+}
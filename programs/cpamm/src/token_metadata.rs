@@ -0,0 +1,123 @@
+//! Instructions of the Metaplex Token Metadata program.
+//!
+//! Only the instructions used for the metadata of LP mints are encoded. Creators,
+//! collections and uses are never set.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+
+/// Index of the `UpdateMetadataAccountV2` instruction.
+const UPDATE_METADATA_ACCOUNT_V2: u8 = 15;
+/// Index of the `CreateMetadataAccountV2` instruction.
+const CREATE_METADATA_ACCOUNT_V2: u8 = 16;
+
+/// Seed prefix of metadata accounts.
+const METADATA_PREFIX: &[u8] = b"metadata";
+
+/// The `DataV2` of a metadata account, without creators, collection or uses.
+#[derive(AnchorSerialize)]
+pub(crate) struct MetadataData {
+    /// The name of the token.
+    pub(crate) name: String,
+    /// The symbol of the token.
+    pub(crate) symbol: String,
+    /// URI of the JSON metadata of the token.
+    pub(crate) uri: String,
+}
+
+impl MetadataData {
+    /// Serializes the data as a `DataV2`.
+    fn serialize_v2(&self, data: &mut Vec<u8>) -> Result<()> {
+        self.serialize(data)?;
+        // seller fee basis points
+        0_u16.serialize(data)?;
+        // no creators, collection or uses
+        data.extend_from_slice(&[0, 0, 0]);
+        Ok(())
+    }
+}
+
+/// Accounts of the metadata instructions.
+pub(crate) struct MetadataAccounts<'info> {
+    /// The metadata account of the mint.
+    pub(crate) metadata: AccountInfo<'info>,
+    /// The mint.
+    pub(crate) mint: AccountInfo<'info>,
+    /// The mint authority, which is also the update authority of the metadata.
+    pub(crate) authority: AccountInfo<'info>,
+    /// Pays for the metadata account.
+    pub(crate) payer: AccountInfo<'info>,
+    /// The Token Metadata program.
+    pub(crate) token_metadata_program: AccountInfo<'info>,
+    /// The System program.
+    pub(crate) system_program: AccountInfo<'info>,
+    /// The Rent sysvar.
+    pub(crate) rent: AccountInfo<'info>,
+}
+
+/// Finds the address of the metadata account of the `mint`.
+pub(crate) fn find_metadata_address(mint: &Pubkey) -> Pubkey {
+    let program_id = crate::addresses::TOKEN_METADATA_PROGRAM;
+    Pubkey::find_program_address(
+        &[METADATA_PREFIX, program_id.as_ref(), mint.as_ref()],
+        &program_id,
+    )
+    .0
+}
+
+/// Creates the metadata account of the mint, or updates it if it already exists.
+pub(crate) fn set_metadata(
+    accounts: &MetadataAccounts,
+    metadata_data: &MetadataData,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let mut data = vec![];
+    let account_metas = if accounts.metadata.data_is_empty() {
+        data.push(CREATE_METADATA_ACCOUNT_V2);
+        metadata_data.serialize_v2(&mut data)?;
+        // is mutable
+        true.serialize(&mut data)?;
+        vec![
+            AccountMeta::new(accounts.metadata.key(), false),
+            AccountMeta::new_readonly(accounts.mint.key(), false),
+            AccountMeta::new_readonly(accounts.authority.key(), true),
+            AccountMeta::new(accounts.payer.key(), true),
+            AccountMeta::new_readonly(accounts.authority.key(), true),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+            AccountMeta::new_readonly(accounts.rent.key(), false),
+        ]
+    } else {
+        data.push(UPDATE_METADATA_ACCOUNT_V2);
+        // new data
+        data.push(1);
+        metadata_data.serialize_v2(&mut data)?;
+        // unchanged update authority, primary sale and mutability
+        data.extend_from_slice(&[0, 0, 0]);
+        vec![
+            AccountMeta::new(accounts.metadata.key(), false),
+            AccountMeta::new_readonly(accounts.authority.key(), true),
+        ]
+    };
+
+    invoke_signed(
+        &Instruction {
+            program_id: accounts.token_metadata_program.key(),
+            accounts: account_metas,
+            data,
+        },
+        &[
+            accounts.metadata.clone(),
+            accounts.mint.clone(),
+            accounts.authority.clone(),
+            accounts.payer.clone(),
+            accounts.system_program.clone(),
+            accounts.rent.clone(),
+            accounts.token_metadata_program.clone(),
+        ],
+        signer_seeds,
+    )?;
+    Ok(())
+}
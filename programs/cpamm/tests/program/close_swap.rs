@@ -0,0 +1,148 @@
+use crate::harness::{program_error, Bank};
+use crate::setup::{admin, new_swap, withdraw_accounts, TestSwap};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use cpamm::ErrorCode;
+
+const RESERVE_AMOUNT: u64 = 1_000_000;
+
+fn withdraw_all(bank: &mut Bank, test: &TestSwap) {
+    let output_0 = bank.new_token_account(test.mint_0, admin(), 0);
+    let output_1 = bank.new_token_account(test.mint_1, admin(), 0);
+    let accounts = withdraw_accounts(test, admin(), test.creator_lp, output_0, output_1);
+    bank.execute(
+        accounts,
+        cpamm::instruction::WithdrawAll {
+            minimum_amount_out_0: 0,
+            minimum_amount_out_1: 0,
+            deadline: None,
+        },
+    )
+    .unwrap();
+}
+
+fn close_swap(
+    bank: &mut Bank,
+    test: &TestSwap,
+    authority: Pubkey,
+    recipient: Pubkey,
+    destination_0: Pubkey,
+    destination_1: Pubkey,
+) -> ProgramResult {
+    bank.execute(
+        cpamm::accounts::CloseSwap {
+            swap: test.swap,
+            swap_meta: test.swap_meta,
+            pool_mint: test.pool_mint,
+            reserve_0: test.reserve_0,
+            reserve_1: test.reserve_1,
+            locked_lp: test.locked_lp,
+            destination_0,
+            destination_1,
+            authority,
+            recipient,
+            token_program: spl_token::ID,
+        },
+        cpamm::instruction::CloseSwap {},
+    )
+}
+
+#[test]
+fn close_swap_with_locked_liquidity() {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, RESERVE_AMOUNT, RESERVE_AMOUNT);
+    withdraw_all(&mut bank, &test);
+    let pool_supply = bank.mint(test.pool_mint).supply;
+    assert_eq!(pool_supply, bank.balance(test.locked_lp));
+    assert!(pool_supply > 0);
+    let dust_0 = bank.balance(test.reserve_0);
+    let dust_1 = bank.balance(test.reserve_1);
+    assert!(dust_0 > 0 && dust_1 > 0);
+
+    let fees_0 = bank.balance(test.fees_0);
+    let fees_1 = bank.balance(test.fees_1);
+
+    let recipient = bank.new_wallet(0);
+    let rent: u64 = [
+        test.swap,
+        test.swap_meta,
+        test.reserve_0,
+        test.reserve_1,
+        test.locked_lp,
+    ]
+    .iter()
+    .map(|key| bank.lamports(*key))
+    .sum();
+
+    close_swap(
+        &mut bank,
+        &test,
+        admin(),
+        recipient,
+        test.fees_0,
+        test.fees_1,
+    )
+    .unwrap();
+
+    for key in [
+        test.swap,
+        test.swap_meta,
+        test.reserve_0,
+        test.reserve_1,
+        test.locked_lp,
+    ] {
+        assert_eq!(bank.lamports(key), 0);
+    }
+    assert_eq!(bank.lamports(recipient), rent);
+    assert_eq!(bank.balance(test.fees_0), fees_0 + dust_0);
+    assert_eq!(bank.balance(test.fees_1), fees_1 + dust_1);
+    assert_eq!(bank.mint(test.pool_mint).supply, 0);
+}
+
+#[test]
+fn close_swap_requires_admin_with_locked_liquidity() {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, RESERVE_AMOUNT, RESERVE_AMOUNT);
+    withdraw_all(&mut bank, &test);
+    let closer = bank.new_wallet(0);
+    assert_eq!(
+        close_swap(&mut bank, &test, closer, closer, test.fees_0, test.fees_1),
+        Err(program_error(vipers::VipersError::KeyMismatch))
+    );
+    assert!(bank.lamports(test.swap) > 0);
+}
+
+#[test]
+fn close_swap_requires_admin_fees_destinations() {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, RESERVE_AMOUNT, RESERVE_AMOUNT);
+    withdraw_all(&mut bank, &test);
+    let destination_0 = bank.new_token_account(test.mint_0, admin(), 0);
+    let destination_1 = bank.new_token_account(test.mint_1, admin(), 0);
+    for (destination_0, destination_1) in
+        [(destination_0, test.fees_1), (test.fees_0, destination_1)]
+    {
+        assert_eq!(
+            close_swap(
+                &mut bank,
+                &test,
+                admin(),
+                admin(),
+                destination_0,
+                destination_1
+            ),
+            Err(program_error(vipers::VipersError::KeyMismatch))
+        );
+    }
+    assert!(bank.lamports(test.swap) > 0);
+}
+
+#[test]
+fn close_swap_with_outstanding_lp() {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, RESERVE_AMOUNT, RESERVE_AMOUNT);
+    assert_eq!(
+        close_swap(&mut bank, &test, admin(), admin(), test.fees_0, test.fees_1),
+        Err(program_error(ErrorCode::SwapNotEmpty))
+    );
+}
@@ -0,0 +1,221 @@
+use crate::harness::{program_error, Accounts, Bank};
+use crate::setup::{admin, new_swap, withdraw_accounts, TestSwap};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{entrypoint::ProgramResult, system_program};
+use cpamm::ErrorCode;
+use spl_token::error::TokenError;
+
+const COOLDOWN_SLOTS: u32 = 10;
+const POOL_TOKEN_AMOUNT: u64 = 10_000;
+
+/// A depositor of a swap with a withdraw cooldown.
+struct Depositor {
+    wallet: Pubkey,
+    input_0: Pubkey,
+    input_1: Pubkey,
+    lp: Pubkey,
+}
+
+fn find_cooldown_address(test: &TestSwap, lp_account: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"DepositCooldown", test.swap.as_ref(), lp_account.as_ref()],
+        &cpamm::ID,
+    )
+    .0
+}
+
+fn new_cooldown(bank: &mut Bank, test: &TestSwap, lp_account: Pubkey) -> Pubkey {
+    let cooldown = find_cooldown_address(test, lp_account);
+    bank.execute(
+        cpamm::accounts::NewDepositCooldown {
+            swap: test.swap,
+            lp_account,
+            cooldown,
+            payer: admin(),
+            system_program: system_program::ID,
+        },
+        cpamm::instruction::NewDepositCooldown {},
+    )
+    .unwrap();
+    cooldown
+}
+
+fn new_swap_with_cooldown(bank: &mut Bank) -> (TestSwap, Depositor) {
+    let test = new_swap(bank, 1_000_000, 1_000_000);
+    bank.execute(
+        cpamm::accounts::SwapAdminContext {
+            admin: admin(),
+            swap: test.swap,
+        },
+        cpamm::instruction::SetWithdrawCooldown {
+            withdraw_cooldown_slots: COOLDOWN_SLOTS,
+        },
+    )
+    .unwrap();
+
+    let wallet = bank.new_wallet(0);
+    let depositor = Depositor {
+        wallet,
+        input_0: bank.new_token_account(test.mint_0, wallet, 100_000),
+        input_1: bank.new_token_account(test.mint_1, wallet, 100_000),
+        lp: bank.new_token_account(test.pool_mint, wallet, 0),
+    };
+    new_cooldown(bank, &test, depositor.lp);
+    (test, depositor)
+}
+
+fn deposit(bank: &mut Bank, test: &TestSwap, depositor: &Depositor) -> ProgramResult {
+    let accounts = Accounts(vec![
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new(test.swap, false),
+        AccountMeta::new_readonly(test.factory, false),
+        AccountMeta::new_readonly(depositor.wallet, true),
+        AccountMeta::new(depositor.input_0, false),
+        AccountMeta::new(test.reserve_0, false),
+        AccountMeta::new(depositor.input_1, false),
+        AccountMeta::new(test.reserve_1, false),
+        AccountMeta::new(test.pool_mint, false),
+        AccountMeta::new(depositor.lp, false),
+    ]);
+    bank.execute_with_remaining(
+        accounts,
+        cpamm::instruction::Deposit {
+            pool_token_amount: POOL_TOKEN_AMOUNT,
+            maximum_amount_in_0: u64::MAX,
+            maximum_amount_in_1: u64::MAX,
+            deadline: None,
+        },
+        vec![AccountMeta::new(
+            find_cooldown_address(test, depositor.lp),
+            false,
+        )],
+    )
+}
+
+fn withdraw(
+    bank: &mut Bank,
+    test: &TestSwap,
+    depositor: &Depositor,
+    input_lp: Pubkey,
+) -> ProgramResult {
+    bank.execute_with_remaining(
+        withdraw_accounts(
+            test,
+            depositor.wallet,
+            input_lp,
+            depositor.input_0,
+            depositor.input_1,
+        ),
+        cpamm::instruction::WithdrawAll {
+            minimum_amount_out_0: 0,
+            minimum_amount_out_1: 0,
+            deadline: None,
+        },
+        vec![AccountMeta::new(
+            find_cooldown_address(test, input_lp),
+            false,
+        )],
+    )
+}
+
+fn thaw(bank: &mut Bank, test: &TestSwap, lp_account: Pubkey) -> ProgramResult {
+    bank.execute(
+        cpamm::accounts::ThawLpAccount {
+            swap: test.swap,
+            pool_mint: test.pool_mint,
+            lp_account,
+            cooldown: find_cooldown_address(test, lp_account),
+            token_program: spl_token::ID,
+        },
+        cpamm::instruction::ThawLpAccount {},
+    )
+}
+
+fn transfer_lp(bank: &mut Bank, depositor: &Depositor, destination: Pubkey) -> ProgramResult {
+    bank.process(
+        spl_token::instruction::transfer(
+            &spl_token::ID,
+            &depositor.lp,
+            &destination,
+            &depositor.wallet,
+            &[],
+            POOL_TOKEN_AMOUNT,
+        )
+        .unwrap(),
+    )
+}
+
+#[test]
+fn withdraw_after_cooldown() {
+    let mut bank = Bank::new();
+    let (test, depositor) = new_swap_with_cooldown(&mut bank);
+    deposit(&mut bank, &test, &depositor).unwrap();
+    assert_eq!(bank.balance(depositor.lp), POOL_TOKEN_AMOUNT);
+
+    assert_eq!(
+        withdraw(&mut bank, &test, &depositor, depositor.lp),
+        Err(program_error(ErrorCode::WithdrawCooldownActive))
+    );
+    bank.advance(0, COOLDOWN_SLOTS.into());
+    withdraw(&mut bank, &test, &depositor, depositor.lp).unwrap();
+    assert_eq!(bank.balance(depositor.lp), 0);
+    assert!(!bank.token_account(depositor.lp).is_frozen());
+}
+
+#[test]
+fn deposit_freezes_lp_during_cooldown() {
+    let mut bank = Bank::new();
+    let (test, depositor) = new_swap_with_cooldown(&mut bank);
+    deposit(&mut bank, &test, &depositor).unwrap();
+    // depositing again thaws the LP token account to mint to it
+    deposit(&mut bank, &test, &depositor).unwrap();
+    assert!(bank.token_account(depositor.lp).is_frozen());
+
+    // the LP tokens cannot be moved to a token account without a recent deposit
+    let other_lp = bank.new_token_account(test.pool_mint, depositor.wallet, 0);
+    new_cooldown(&mut bank, &test, other_lp);
+    assert_eq!(
+        transfer_lp(&mut bank, &depositor, other_lp),
+        Err(TokenError::AccountFrozen.into())
+    );
+    assert_eq!(
+        thaw(&mut bank, &test, depositor.lp),
+        Err(program_error(ErrorCode::WithdrawCooldownActive))
+    );
+
+    bank.advance(0, COOLDOWN_SLOTS.into());
+    thaw(&mut bank, &test, depositor.lp).unwrap();
+    transfer_lp(&mut bank, &depositor, other_lp).unwrap();
+    withdraw(&mut bank, &test, &depositor, other_lp).unwrap();
+    assert_eq!(bank.balance(other_lp), 0);
+}
+
+#[test]
+fn cooldown_of_another_lp_account() {
+    let mut bank = Bank::new();
+    let (test, depositor) = new_swap_with_cooldown(&mut bank);
+    let other_lp = bank.new_token_account(test.pool_mint, depositor.wallet, 0);
+    new_cooldown(&mut bank, &test, other_lp);
+    let accounts = withdraw_accounts(
+        &test,
+        depositor.wallet,
+        depositor.lp,
+        depositor.input_0,
+        depositor.input_1,
+    );
+    assert_eq!(
+        bank.execute_with_remaining(
+            accounts,
+            cpamm::instruction::WithdrawAll {
+                minimum_amount_out_0: 0,
+                minimum_amount_out_1: 0,
+                deadline: None,
+            },
+            vec![AccountMeta::new(
+                find_cooldown_address(&test, other_lp),
+                false
+            )],
+        ),
+        Err(program_error(vipers::VipersError::KeyMismatch))
+    );
+}
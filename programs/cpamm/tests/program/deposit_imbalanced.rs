@@ -0,0 +1,108 @@
+use crate::harness::{program_error, Accounts, Bank};
+use crate::setup::{admin, find_roles_address, new_swap, TestSwap};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use cpamm::ErrorCode;
+
+const RESERVE_AMOUNT: u64 = 1_000_000;
+
+struct Depositor {
+    wallet: Pubkey,
+    token_0: Pubkey,
+    token_1: Pubkey,
+    lp: Pubkey,
+}
+
+fn new_depositor(bank: &mut Bank, test: &TestSwap) -> Depositor {
+    let wallet = bank.new_wallet(0);
+    Depositor {
+        wallet,
+        token_0: bank.new_token_account(test.mint_0, wallet, RESERVE_AMOUNT),
+        token_1: bank.new_token_account(test.mint_1, wallet, RESERVE_AMOUNT),
+        lp: bank.new_token_account(test.pool_mint, wallet, 0),
+    }
+}
+
+fn deposit_imbalanced(
+    bank: &mut Bank,
+    test: &TestSwap,
+    depositor: &Depositor,
+    amount_in_0: u64,
+    amount_in_1: u64,
+) -> ProgramResult {
+    // the client struct of the nested token accounts cannot be named outside of the program
+    let accounts = Accounts(vec![
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new(test.swap, false),
+        AccountMeta::new_readonly(test.factory, false),
+        AccountMeta::new_readonly(depositor.wallet, true),
+        AccountMeta::new(depositor.token_0, false),
+        AccountMeta::new(test.reserve_0, false),
+        AccountMeta::new(depositor.token_1, false),
+        AccountMeta::new(test.reserve_1, false),
+        AccountMeta::new(test.pool_mint, false),
+        AccountMeta::new(depositor.lp, false),
+    ]);
+    bank.execute(
+        accounts,
+        cpamm::instruction::DepositImbalanced {
+            amount_in_0,
+            amount_in_1,
+            min_lp_out: 0,
+            deadline: None,
+        },
+    )
+}
+
+#[test]
+fn imbalanced_deposit_trade_size() {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, RESERVE_AMOUNT, RESERVE_AMOUNT);
+    let depositor = new_depositor(&mut bank, &test);
+    bank.execute(
+        cpamm::accounts::SwapAdminContext {
+            admin: admin(),
+            swap: test.swap,
+        },
+        cpamm::instruction::SetMaxTradeSize {
+            max_trade_bps_of_reserve: 100,
+        },
+    )
+    .unwrap();
+
+    // the excess of token 0 is swapped for about 5% of the reserve of token 1
+    assert_eq!(
+        deposit_imbalanced(&mut bank, &test, &depositor, 100_000, 0),
+        Err(program_error(ErrorCode::TradeTooLarge))
+    );
+    // a proportional deposit swaps nothing
+    deposit_imbalanced(&mut bank, &test, &depositor, 100_000, 100_000).unwrap();
+    assert!(bank.balance(depositor.lp) > 0);
+}
+
+#[test]
+fn imbalanced_deposit_directional_fee() {
+    let deposit_with_surcharge = |trade_fee_surcharge_0_to_1_kbps: u64| {
+        let mut bank = Bank::new();
+        let test = new_swap(&mut bank, RESERVE_AMOUNT, RESERVE_AMOUNT);
+        let depositor = new_depositor(&mut bank, &test);
+        bank.execute(
+            cpamm::accounts::SwapRoleContext {
+                authority: admin(),
+                swap: test.swap,
+                factory: test.factory,
+                roles: find_roles_address(test.factory),
+            },
+            cpamm::instruction::SetDirectionalFees {
+                trade_fee_surcharge_0_to_1_kbps,
+                trade_fee_surcharge_1_to_0_kbps: 0,
+            },
+        )
+        .unwrap();
+        deposit_imbalanced(&mut bank, &test, &depositor, 100_000, 0).unwrap();
+        bank.balance(depositor.lp)
+    };
+
+    // the swap of the excess of token 0 is charged the surcharge of swaps of token 0
+    assert!(deposit_with_surcharge(cpamm::fees::MAX_TRADE_FEE_KBPS) < deposit_with_surcharge(0));
+}
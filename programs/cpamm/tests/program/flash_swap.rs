@@ -0,0 +1,281 @@
+use crate::harness::{program_error, Accounts, Bank};
+use crate::setup::{admin, find_roles_address, new_swap, TestSwap};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    entrypoint::ProgramResult, instruction::Instruction, program::invoke,
+};
+use anchor_lang::InstructionData;
+use cpamm::ErrorCode;
+
+const RESERVE_AMOUNT: u64 = 1_000_000;
+const AMOUNT_OUT: u64 = 1_000;
+
+/// A flash swap callback which invokes the program whose ID is the first 32 bytes of the
+/// data with the rest of the data, passing through all of its accounts.
+fn callback(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let instruction = Instruction {
+        program_id: Pubkey::new(&data[..32]),
+        accounts: accounts
+            .iter()
+            .map(|info| AccountMeta {
+                pubkey: *info.key,
+                is_signer: info.is_signer,
+                is_writable: info.is_writable,
+            })
+            .collect(),
+        data: data[32..].to_vec(),
+    };
+    invoke(&instruction, accounts)
+}
+
+struct Borrower {
+    wallet: Pubkey,
+    token_0: Pubkey,
+    token_1: Pubkey,
+}
+
+fn new_borrower(bank: &mut Bank, test: &TestSwap, amount_0: u64, amount_1: u64) -> Borrower {
+    let wallet = bank.new_wallet(0);
+    Borrower {
+        wallet,
+        token_0: bank.new_token_account(test.mint_0, wallet, amount_0),
+        token_1: bank.new_token_account(test.mint_1, wallet, amount_1),
+    }
+}
+
+/// Borrows [AMOUNT_OUT] of token 0, then invokes `instruction` from the callback.
+fn flash_swap(
+    bank: &mut Bank,
+    test: &TestSwap,
+    borrower: &Borrower,
+    instruction: Instruction,
+) -> ProgramResult {
+    borrow(bank, test, borrower, AMOUNT_OUT, instruction)
+}
+
+/// Borrows `amount_out_0` of token 0, then invokes `instruction` from the callback.
+fn borrow(
+    bank: &mut Bank,
+    test: &TestSwap,
+    borrower: &Borrower,
+    amount_out_0: u64,
+    instruction: Instruction,
+) -> ProgramResult {
+    let callback_program = Pubkey::new_unique();
+    bank.add_program(callback_program, callback);
+
+    let accounts = Accounts(vec![
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new(test.swap, false),
+        AccountMeta::new_readonly(test.factory, false),
+        AccountMeta::new_readonly(borrower.wallet, true),
+        AccountMeta::new(borrower.token_0, false),
+        AccountMeta::new(test.reserve_0, false),
+        AccountMeta::new(test.fees_0, false),
+        AccountMeta::new(borrower.token_1, false),
+        AccountMeta::new(test.reserve_1, false),
+        AccountMeta::new(test.fees_1, false),
+        AccountMeta::new_readonly(callback_program, false),
+    ]);
+    let mut data = instruction.program_id.to_bytes().to_vec();
+    data.extend(instruction.data);
+    bank.execute_with_remaining(
+        accounts,
+        cpamm::instruction::FlashSwap {
+            amount_out_0,
+            amount_out_1: 0,
+            data,
+        },
+        instruction.accounts,
+    )
+}
+
+/// Transfers `amount` of token 0 from the borrower to the reserve.
+fn repay(test: &TestSwap, borrower: &Borrower, amount: u64) -> Instruction {
+    spl_token::instruction::transfer(
+        &spl_token::ID,
+        &borrower.token_0,
+        &test.reserve_0,
+        &borrower.wallet,
+        &[],
+        amount,
+    )
+    .unwrap()
+}
+
+/// Transfers `amount` of token 1 from the borrower to the reserve.
+fn repay_in_token_1(test: &TestSwap, borrower: &Borrower, amount: u64) -> Instruction {
+    spl_token::instruction::transfer(
+        &spl_token::ID,
+        &borrower.token_1,
+        &test.reserve_1,
+        &borrower.wallet,
+        &[],
+        amount,
+    )
+    .unwrap()
+}
+
+#[test]
+fn repaid_flash_swap() {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, RESERVE_AMOUNT, RESERVE_AMOUNT);
+    let borrower = new_borrower(&mut bank, &test, 100, 0);
+    let instruction = repay(&test, &borrower, AMOUNT_OUT + 100);
+    flash_swap(&mut bank, &test, &borrower, instruction).unwrap();
+
+    assert_eq!(bank.balance(borrower.token_0), 0);
+    assert_eq!(
+        bank.balance(test.reserve_0) + bank.balance(test.fees_0),
+        RESERVE_AMOUNT + 100
+    );
+    assert!(!bank.get::<cpamm::SwapInfo>(test.swap).is_in_progress);
+}
+
+#[test]
+fn unpaid_flash_swap() {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, RESERVE_AMOUNT, RESERVE_AMOUNT);
+    let borrower = new_borrower(&mut bank, &test, 0, 0);
+    let instruction = repay(&test, &borrower, AMOUNT_OUT);
+    assert_eq!(
+        flash_swap(&mut bank, &test, &borrower, instruction),
+        Err(program_error(ErrorCode::InsufficientFlashSwapRepayment))
+    );
+    assert_eq!(bank.balance(test.reserve_0), RESERVE_AMOUNT);
+}
+
+#[test]
+fn flash_swap_directional_fee() {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, RESERVE_AMOUNT, RESERVE_AMOUNT);
+    let borrower = new_borrower(&mut bank, &test, 100, 0);
+    let instruction = repay(&test, &borrower, AMOUNT_OUT + 20);
+    bank.execute(
+        cpamm::accounts::SwapRoleContext {
+            authority: admin(),
+            swap: test.swap,
+            factory: test.factory,
+            roles: find_roles_address(test.factory),
+        },
+        cpamm::instruction::SetDirectionalFees {
+            trade_fee_surcharge_0_to_1_kbps: cpamm::fees::MAX_TRADE_FEE_KBPS,
+            trade_fee_surcharge_1_to_0_kbps: 0,
+        },
+    )
+    .unwrap();
+
+    // the repaid token 0 is charged the surcharge of swaps of token 0
+    assert_eq!(
+        flash_swap(&mut bank, &test, &borrower, instruction.clone()),
+        Err(program_error(ErrorCode::InsufficientFlashSwapRepayment))
+    );
+    bank.execute(
+        cpamm::accounts::SwapRoleContext {
+            authority: admin(),
+            swap: test.swap,
+            factory: test.factory,
+            roles: find_roles_address(test.factory),
+        },
+        cpamm::instruction::SetDirectionalFees {
+            trade_fee_surcharge_0_to_1_kbps: 0,
+            trade_fee_surcharge_1_to_0_kbps: cpamm::fees::MAX_TRADE_FEE_KBPS,
+        },
+    )
+    .unwrap();
+    flash_swap(&mut bank, &test, &borrower, instruction).unwrap();
+}
+
+#[test]
+fn flash_swap_trade_size() {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, RESERVE_AMOUNT, RESERVE_AMOUNT);
+    let borrower = new_borrower(&mut bank, &test, 0, 3 * AMOUNT_OUT);
+    let instruction = repay_in_token_1(&test, &borrower, AMOUNT_OUT + 100);
+    flash_swap(&mut bank, &test, &borrower, instruction.clone()).unwrap();
+    assert_eq!(bank.balance(borrower.token_0), AMOUNT_OUT);
+
+    // repaying the borrowed token 0 in token 1 is a trade of token 1 for token 0
+    bank.execute(
+        cpamm::accounts::SwapAdminContext {
+            admin: admin(),
+            swap: test.swap,
+        },
+        cpamm::instruction::SetMaxTradeSize {
+            max_trade_bps_of_reserve: 5,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        flash_swap(&mut bank, &test, &borrower, instruction),
+        Err(program_error(ErrorCode::TradeTooLarge))
+    );
+    assert_eq!(bank.balance(borrower.token_0), AMOUNT_OUT);
+}
+
+#[test]
+fn empty_flash_swaps_are_not_rate_limited() {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, RESERVE_AMOUNT, RESERVE_AMOUNT);
+    let borrower = new_borrower(&mut bank, &test, 200, 0);
+    bank.execute(
+        cpamm::accounts::SwapAdminContext {
+            admin: admin(),
+            swap: test.swap,
+        },
+        cpamm::instruction::SetMaxSwapsPerSlot {
+            max_swaps_per_slot: 1,
+        },
+    )
+    .unwrap();
+
+    // borrowing nothing does not count against the limit
+    for _ in 0..3 {
+        borrow(&mut bank, &test, &borrower, 0, repay(&test, &borrower, 0)).unwrap();
+    }
+    let instruction = repay(&test, &borrower, AMOUNT_OUT + 100);
+    flash_swap(&mut bank, &test, &borrower, instruction.clone()).unwrap();
+    assert_eq!(
+        flash_swap(&mut bank, &test, &borrower, instruction),
+        Err(program_error(ErrorCode::SwapRateLimited))
+    );
+}
+
+// The runtime also rejects the callback invoking the program again while the flash swap is
+// being executed. The harness does not, so this exercises the lock of the swap itself.
+#[test]
+fn flash_swap_reentrancy() {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, RESERVE_AMOUNT, RESERVE_AMOUNT);
+    let borrower = new_borrower(&mut bank, &test, 0, 0);
+
+    // swap the borrowed tokens back into the swap from within the callback
+    let swap = Instruction {
+        program_id: cpamm::ID,
+        accounts: vec![
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new(test.swap, false),
+            AccountMeta::new_readonly(test.factory, false),
+            AccountMeta::new_readonly(borrower.wallet, true),
+            AccountMeta::new(borrower.token_0, false),
+            AccountMeta::new(test.reserve_0, false),
+            AccountMeta::new(test.fees_0, false),
+            AccountMeta::new(borrower.token_1, false),
+            AccountMeta::new(test.reserve_1, false),
+            AccountMeta::new(test.fees_1, false),
+        ],
+        data: cpamm::instruction::Swap {
+            amount_in: AMOUNT_OUT,
+            minimum_amount_out: 0,
+            deadline: None,
+            allow_partial_fill: false,
+        }
+        .data(),
+    };
+    assert_eq!(
+        flash_swap(&mut bank, &test, &borrower, swap),
+        Err(program_error(ErrorCode::Reentrancy))
+    );
+    assert_eq!(bank.balance(test.reserve_0), RESERVE_AMOUNT);
+    assert!(!bank.get::<cpamm::SwapInfo>(test.swap).is_in_progress);
+}
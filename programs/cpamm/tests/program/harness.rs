@@ -0,0 +1,465 @@
+//! Executes instructions of the program in-process against accounts held in memory.
+//!
+//! Cross-program invocations are dispatched to the SPL Token processor, to a minimal
+//! system program, to the program itself, or to the test programs registered with
+//! [Bank::add_program]. A failed instruction leaves the accounts untouched, like a failed
+//! transaction.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    bpf_loader, bpf_loader_upgradeable,
+    entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE, SUCCESS},
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    program_pack::Pack,
+    program_stubs::{self, SyscallStubs},
+    system_program, sysvar,
+};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use std::{cell::RefCell, collections::HashMap, rc::Rc, sync::Once};
+
+/// Processor of a test program.
+pub type Processor = fn(&Pubkey, &[AccountInfo], &[u8]) -> ProgramResult;
+
+thread_local! {
+    static CLOCK: RefCell<Clock> = RefCell::new(Clock {
+        slot: 1_000,
+        unix_timestamp: 1_650_000_000,
+        ..Clock::default()
+    });
+    static PROGRAMS: RefCell<HashMap<Pubkey, Processor>> = RefCell::new(HashMap::new());
+    static CALL_STACK: RefCell<Vec<Pubkey>> = RefCell::new(Vec::new());
+}
+
+/// An account whose data may grow by [MAX_PERMITTED_DATA_INCREASE] per instruction,
+/// laid out like the input of a program so that [AccountInfo::realloc] works.
+struct StoredAccount {
+    key: &'static Pubkey,
+    lamports: Rc<RefCell<&'static mut u64>>,
+    data: Rc<RefCell<&'static mut [u8]>>,
+    owner: &'static Pubkey,
+    executable: bool,
+}
+
+impl StoredAccount {
+    fn new(key: Pubkey, lamports: u64, data: &[u8], owner: Pubkey, executable: bool) -> Self {
+        let buffer: &'static mut [u8] =
+            Box::leak(vec![0; 8 + data.len() + MAX_PERMITTED_DATA_INCREASE].into_boxed_slice());
+        buffer[..8].copy_from_slice(&(data.len() as u64).to_le_bytes());
+        buffer[8..8 + data.len()].copy_from_slice(data);
+        Self {
+            key: Box::leak(Box::new(key)),
+            lamports: Rc::new(RefCell::new(Box::leak(Box::new(lamports)))),
+            data: Rc::new(RefCell::new(&mut buffer[8..8 + data.len()])),
+            owner: Box::leak(Box::new(owner)),
+            executable,
+        }
+    }
+
+    fn info(&self, is_signer: bool, is_writable: bool) -> AccountInfo<'static> {
+        AccountInfo {
+            key: self.key,
+            is_signer,
+            is_writable,
+            lamports: self.lamports.clone(),
+            data: self.data.clone(),
+            owner: self.owner,
+            executable: self.executable,
+            rent_epoch: 0,
+        }
+    }
+
+    fn snapshot(&self) -> (u64, Vec<u8>, Pubkey) {
+        (
+            **self.lamports.borrow(),
+            self.data.borrow().to_vec(),
+            *self.owner,
+        )
+    }
+
+    fn restore(&self, (lamports, data, owner): &(u64, Vec<u8>, Pubkey)) {
+        let info = self.info(false, true);
+        **info.lamports.borrow_mut() = *lamports;
+        info.realloc(data.len(), false).unwrap();
+        info.data.borrow_mut().copy_from_slice(data);
+        info.assign(owner);
+    }
+}
+
+/// Accounts of a test, which instructions are executed against.
+pub struct Bank {
+    accounts: HashMap<Pubkey, StoredAccount>,
+}
+
+impl Default for Bank {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bank {
+    /// Creates a bank holding the programs and the sysvars used by the program.
+    pub fn new() -> Self {
+        static STUBS: Once = Once::new();
+        STUBS.call_once(|| {
+            program_stubs::set_syscall_stubs(Box::new(Stubs));
+        });
+
+        let mut bank = Self {
+            accounts: HashMap::new(),
+        };
+        for program in [system_program::ID, spl_token::ID, cpamm::ID] {
+            bank.accounts.insert(
+                program,
+                StoredAccount::new(program, 1, &[], bpf_loader::ID, true),
+            );
+        }
+        let rent = Rent::default();
+        let mut rent_data = rent.lamports_per_byte_year.to_le_bytes().to_vec();
+        rent_data.extend_from_slice(&rent.exemption_threshold.to_le_bytes());
+        rent_data.push(rent.burn_percent);
+        bank.set_account(sysvar::rent::ID, 1, &rent_data, sysvar::ID);
+        bank
+    }
+
+    /// Registers a test program, which the program may invoke.
+    pub fn add_program(&mut self, program_id: Pubkey, processor: Processor) {
+        PROGRAMS.with(|programs| programs.borrow_mut().insert(program_id, processor));
+        self.accounts.insert(
+            program_id,
+            StoredAccount::new(program_id, 1, &[], bpf_loader_upgradeable::ID, true),
+        );
+    }
+
+    /// Creates or replaces an account.
+    pub fn set_account(&mut self, key: Pubkey, lamports: u64, data: &[u8], owner: Pubkey) {
+        self.accounts
+            .insert(key, StoredAccount::new(key, lamports, data, owner, false));
+    }
+
+    /// Creates a wallet holding `lamports`.
+    pub fn new_wallet(&mut self, lamports: u64) -> Pubkey {
+        let wallet = Pubkey::new_unique();
+        self.set_account(wallet, lamports, &[], system_program::ID);
+        wallet
+    }
+
+    /// Funds an existing or new wallet with 100 SOL.
+    pub fn fund(&mut self, wallet: Pubkey) {
+        match self.accounts.get(&wallet) {
+            Some(account) => **account.lamports.borrow_mut() += 100 * LAMPORTS_PER_SOL,
+            None => self.set_account(wallet, 100 * LAMPORTS_PER_SOL, &[], system_program::ID),
+        }
+    }
+
+    /// Creates a mint without a freeze authority.
+    pub fn new_mint(&mut self, mint_authority: Pubkey, decimals: u8) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        self.set_mint(
+            mint,
+            spl_token::state::Mint {
+                mint_authority: Some(mint_authority).into(),
+                decimals,
+                is_initialized: true,
+                ..Default::default()
+            },
+        );
+        mint
+    }
+
+    /// Creates or replaces a mint.
+    pub fn set_mint(&mut self, key: Pubkey, mint: spl_token::state::Mint) {
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        mint.pack_into_slice(&mut data);
+        let lamports = Rent::default().minimum_balance(data.len());
+        self.set_account(key, lamports, &data, spl_token::ID);
+    }
+
+    /// Creates a token account holding `amount`, minting it out of thin air.
+    pub fn new_token_account(&mut self, mint: Pubkey, owner: Pubkey, amount: u64) -> Pubkey {
+        let key = Pubkey::new_unique();
+        self.set_token_account(
+            key,
+            spl_token::state::Account {
+                mint,
+                owner,
+                amount,
+                state: spl_token::state::AccountState::Initialized,
+                ..Default::default()
+            },
+        );
+        let mut mint_state = self.mint(mint);
+        mint_state.supply += amount;
+        self.set_mint(mint, mint_state);
+        key
+    }
+
+    /// Creates or replaces a token account.
+    pub fn set_token_account(&mut self, key: Pubkey, account: spl_token::state::Account) {
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        account.pack_into_slice(&mut data);
+        let lamports = Rent::default().minimum_balance(data.len());
+        self.set_account(key, lamports, &data, spl_token::ID);
+    }
+
+    /// Returns the mint at `key`.
+    pub fn mint(&self, key: Pubkey) -> spl_token::state::Mint {
+        spl_token::state::Mint::unpack(&self.data(key)).unwrap()
+    }
+
+    /// Returns the token account at `key`.
+    pub fn token_account(&self, key: Pubkey) -> spl_token::state::Account {
+        spl_token::state::Account::unpack(&self.data(key)).unwrap()
+    }
+
+    /// Returns the amount of the token account at `key`.
+    pub fn balance(&self, key: Pubkey) -> u64 {
+        self.token_account(key).amount
+    }
+
+    /// Returns the account of the program at `key`.
+    pub fn get<T: AccountDeserialize>(&self, key: Pubkey) -> T {
+        T::try_deserialize(&mut &self.data(key)[..]).unwrap()
+    }
+
+    /// Overwrites the account of the program at `key`, keeping its size.
+    pub fn put<T: AccountSerialize>(&mut self, key: Pubkey, account: &T) {
+        let stored = &self.accounts[&key];
+        account
+            .try_serialize(&mut &mut stored.data.borrow_mut()[..])
+            .unwrap();
+    }
+
+    /// Returns the data of the account at `key`, which is empty if the account does not exist.
+    pub fn data(&self, key: Pubkey) -> Vec<u8> {
+        self.accounts
+            .get(&key)
+            .map(|account| account.data.borrow().to_vec())
+            .unwrap_or_default()
+    }
+
+    /// Returns the lamports of the account at `key`.
+    pub fn lamports(&self, key: Pubkey) -> u64 {
+        self.accounts
+            .get(&key)
+            .map(|account| **account.lamports.borrow())
+            .unwrap_or_default()
+    }
+
+    /// Returns the owner of the account at `key`.
+    pub fn owner(&self, key: Pubkey) -> Pubkey {
+        self.accounts
+            .get(&key)
+            .map(|account| *account.owner)
+            .unwrap_or(system_program::ID)
+    }
+
+    /// Returns the current clock.
+    pub fn clock(&self) -> Clock {
+        CLOCK.with(|clock| clock.borrow().clone())
+    }
+
+    /// Advances the clock by `seconds` and `slots`.
+    pub fn advance(&mut self, seconds: i64, slots: u64) {
+        CLOCK.with(|clock| {
+            let mut clock = clock.borrow_mut();
+            clock.unix_timestamp += seconds;
+            clock.slot += slots;
+        });
+    }
+
+    /// Executes an instruction of the program.
+    pub fn execute(
+        &mut self,
+        accounts: impl ToAccountMetas,
+        instruction: impl InstructionData,
+    ) -> ProgramResult {
+        self.execute_with_remaining(accounts, instruction, vec![])
+    }
+
+    /// Executes an instruction of the program with `remaining_accounts`.
+    pub fn execute_with_remaining(
+        &mut self,
+        accounts: impl ToAccountMetas,
+        instruction: impl InstructionData,
+        remaining_accounts: Vec<AccountMeta>,
+    ) -> ProgramResult {
+        let mut metas = accounts.to_account_metas(None);
+        metas.extend(remaining_accounts);
+        self.process(Instruction {
+            program_id: cpamm::ID,
+            accounts: metas,
+            data: instruction.data(),
+        })
+    }
+
+    /// Executes an instruction, reverting all accounts if it fails.
+    /// Accounts which do not exist yet are created empty, owned by the system program.
+    pub fn process(&mut self, instruction: Instruction) -> ProgramResult {
+        for meta in instruction.accounts.iter() {
+            self.accounts.entry(meta.pubkey).or_insert_with(|| {
+                StoredAccount::new(meta.pubkey, 0, &[], system_program::ID, false)
+            });
+        }
+        let infos: Vec<AccountInfo> = instruction
+            .accounts
+            .iter()
+            .map(|meta| self.accounts[&meta.pubkey].info(meta.is_signer, meta.is_writable))
+            .collect();
+        let snapshots: Vec<_> = self
+            .accounts
+            .values()
+            .map(|account| (account, account.snapshot()))
+            .collect();
+
+        let result = invoke_program(&instruction.program_id, &infos, &instruction.data);
+        if result.is_err() {
+            for (account, snapshot) in snapshots {
+                account.restore(&snapshot);
+            }
+        }
+        result
+    }
+}
+
+/// Runs the processor of `program_id`, tracking the programs being executed.
+fn invoke_program(program_id: &Pubkey, infos: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    CALL_STACK.with(|stack| stack.borrow_mut().push(*program_id));
+    let result = if *program_id == cpamm::ID {
+        cpamm::entry(program_id, infos, data)
+    } else if *program_id == spl_token::ID {
+        spl_token::processor::Processor::process(program_id, infos, data)
+    } else if *program_id == system_program::ID {
+        process_system_instruction(infos, data)
+    } else {
+        let processor = PROGRAMS.with(|programs| programs.borrow().get(program_id).copied());
+        match processor {
+            Some(processor) => processor(program_id, infos, data),
+            None => Err(ProgramError::IncorrectProgramId),
+        }
+    };
+    CALL_STACK.with(|stack| stack.borrow_mut().pop());
+    result
+}
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_log(&self, _message: &str) {}
+
+    fn sol_log_data(&self, _fields: &[&[u8]]) {}
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let caller = CALL_STACK.with(|stack| *stack.borrow().last().unwrap());
+        let signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &caller))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| ProgramError::InvalidSeeds)?;
+
+        let mut infos = vec![];
+        for meta in instruction.accounts.iter() {
+            let info = account_infos
+                .iter()
+                .find(|info| *info.key == meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            if meta.is_signer && !info.is_signer && !signers.contains(&meta.pubkey) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if meta.is_writable && !info.is_writable {
+                return Err(ProgramError::InvalidArgument);
+            }
+            let mut info = info.clone();
+            info.is_signer = meta.is_signer;
+            info.is_writable = meta.is_writable;
+            infos.push(info);
+        }
+        invoke_program(&instruction.program_id, &infos, &instruction.data)
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = CLOCK.with(|clock| clock.borrow().clone());
+        unsafe { *(var_addr as *mut Clock) = clock };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+}
+
+/// The subset of the system program used by the program: creating, allocating and
+/// assigning accounts, and transferring lamports.
+fn process_system_instruction(infos: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let read_u64 = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+    let read_key = |offset: usize| Pubkey::new(&data[offset..offset + 32]);
+    match u32::from_le_bytes(data[..4].try_into().unwrap()) {
+        // CreateAccount
+        0 => {
+            let (from, to) = (&infos[0], &infos[1]);
+            if !to.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if to.lamports() != 0 || !to.data_is_empty() || *to.owner != system_program::ID {
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+            transfer_lamports(from, to, read_u64(4))?;
+            to.realloc(read_u64(12) as usize, true)?;
+            to.assign(&read_key(20));
+        }
+        // Assign
+        1 => {
+            if !infos[0].is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            infos[0].assign(&read_key(4));
+        }
+        // Transfer
+        2 => transfer_lamports(&infos[0], &infos[1], read_u64(4))?,
+        // Allocate
+        8 => {
+            if !infos[0].is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            infos[0].realloc(read_u64(4) as usize, true)?;
+        }
+        _ => return Err(ProgramError::InvalidInstructionData),
+    }
+    Ok(())
+}
+
+fn transfer_lamports(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    if !from.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *from.owner != system_program::ID || !from.data_is_empty() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let remaining = from
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **from.lamports.borrow_mut() = remaining;
+    **to.lamports.borrow_mut() += lamports;
+    Ok(())
+}
+
+/// Accounts of an instruction listed by hand, in the order of its accounts struct.
+pub struct Accounts(pub Vec<AccountMeta>);
+
+impl ToAccountMetas for Accounts {
+    fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+        self.0.clone()
+    }
+}
+
+/// Converts an error of the program to the [ProgramError] it fails with.
+pub fn program_error(error: impl Into<anchor_lang::error::Error>) -> ProgramError {
+    error.into().into()
+}
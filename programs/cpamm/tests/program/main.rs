@@ -0,0 +1,15 @@
+//! Tests of the instructions of the program, executed in-process by the [harness].
+
+mod close_swap;
+mod cooldown;
+mod deposit_imbalanced;
+mod flash_swap;
+mod harness;
+mod migration;
+mod programs;
+mod referral;
+mod roles;
+mod setup;
+mod skim;
+mod swap_batch;
+mod timelock;
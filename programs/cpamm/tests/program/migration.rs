@@ -0,0 +1,140 @@
+//! Migrations of accounts in the layout of the first release of the program,
+//! which ended in 16 reserved words that later versions use for new fields.
+
+use crate::harness::{program_error, Accounts, Bank};
+use crate::setup::{admin, new_factory, new_swap, new_swap_on, TestSwap};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{entrypoint::ProgramResult, system_program};
+use cpamm::{Factory, SwapInfo, FACTORY_VERSION, SWAP_INFO_VERSION};
+
+/// Length of the [SwapInfo] fields of the first release, including the discriminator.
+const BASELINE_SWAP_INFO_FIELDS_LEN: usize = 586;
+/// Length of the reserved words of the first release.
+const BASELINE_RESERVED_LEN: usize = 16 * 8;
+
+/// Rewrites an account in the baseline layout: its fields of the first release followed by
+/// zeroed reserved words, with the rent of that length.
+fn set_baseline_account(bank: &mut Bank, key: Pubkey, fields_len: usize) {
+    let mut data = bank.data(key)[..fields_len].to_vec();
+    data.resize(fields_len + BASELINE_RESERVED_LEN, 0);
+    let lamports = Rent::default().minimum_balance(data.len());
+    bank.set_account(key, lamports, &data, cpamm::ID);
+}
+
+fn migrate_pool(bank: &mut Bank, test: &TestSwap) -> ProgramResult {
+    bank.execute(
+        cpamm::accounts::MigratePool {
+            swap: test.swap,
+            reserve_0: test.reserve_0,
+            reserve_1: test.reserve_1,
+            payer: admin(),
+            system_program: system_program::ID,
+        },
+        cpamm::instruction::MigratePool {},
+    )
+}
+
+fn swap(bank: &mut Bank, test: &TestSwap, amount_in: u64) -> ProgramResult {
+    let trader = bank.new_wallet(0);
+    let input = bank.new_token_account(test.mint_0, trader, amount_in);
+    let output = bank.new_token_account(test.mint_1, trader, 0);
+    let accounts = Accounts(vec![
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new(test.swap, false),
+        AccountMeta::new_readonly(test.factory, false),
+        AccountMeta::new_readonly(trader, true),
+        AccountMeta::new(input, false),
+        AccountMeta::new(test.reserve_0, false),
+        AccountMeta::new(test.fees_0, false),
+        AccountMeta::new(output, false),
+        AccountMeta::new(test.reserve_1, false),
+        AccountMeta::new(test.fees_1, false),
+    ]);
+    bank.execute(
+        accounts,
+        cpamm::instruction::Swap {
+            amount_in,
+            minimum_amount_out: 1,
+            deadline: None,
+            allow_partial_fill: false,
+        },
+    )
+}
+
+#[test]
+fn migrate_baseline_swap_info() {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, 1_000_000, 1_000_000);
+    let swap_info = bank.get::<SwapInfo>(test.swap);
+    set_baseline_account(&mut bank, test.swap, BASELINE_SWAP_INFO_FIELDS_LEN);
+    let baseline_lamports = bank.lamports(test.swap);
+
+    // the account cannot be used until it is migrated
+    assert!(swap(&mut bank, &test, 1_000).is_err());
+
+    let mut swapped_reserves = test.clone();
+    swapped_reserves.reserve_0 = test.reserve_1;
+    swapped_reserves.reserve_1 = test.reserve_0;
+    assert_eq!(
+        migrate_pool(&mut bank, &swapped_reserves),
+        Err(program_error(vipers::VipersError::KeyMismatch))
+    );
+
+    migrate_pool(&mut bank, &test).unwrap();
+    let new_len = bank.get::<SwapInfo>(test.swap).try_to_vec().unwrap().len() + 8;
+    assert_eq!(bank.data(test.swap).len(), new_len);
+    assert_eq!(
+        bank.lamports(test.swap),
+        Rent::default().minimum_balance(new_len)
+    );
+    assert!(bank.lamports(test.swap) > baseline_lamports);
+
+    let migrated = bank.get::<SwapInfo>(test.swap);
+    assert_eq!(migrated.version, SWAP_INFO_VERSION);
+    assert_eq!(migrated.factory, swap_info.factory);
+    assert_eq!(migrated.admin_key, swap_info.admin_key);
+    assert_eq!(migrated.token_0, swap_info.token_0);
+    assert_eq!(migrated.token_1, swap_info.token_1);
+    assert_eq!(migrated.pool_mint, swap_info.pool_mint);
+    assert_eq!(migrated.fees, swap_info.fees);
+    assert!(migrated.is_constant_product());
+    // the reserves of the first release were not tracked
+    assert_eq!(migrated.reserve_amounts(), swap_info.reserve_amounts());
+
+    swap(&mut bank, &test, 1_000).unwrap();
+    assert_eq!(
+        migrate_pool(&mut bank, &test),
+        Err(program_error(vipers::VipersError::InvariantFailed))
+    );
+}
+
+#[test]
+fn migrate_baseline_factory() {
+    let mut bank = Bank::new();
+    let factory = new_factory(&mut bank);
+    let before = bank.get::<Factory>(factory);
+    let len = bank.data(factory).len();
+    // the fields added since the first release take up exactly its reserved words
+    set_baseline_account(&mut bank, factory, len - BASELINE_RESERVED_LEN);
+    assert_eq!(bank.data(factory).len(), len);
+
+    bank.execute(
+        cpamm::accounts::MigrateFactory {
+            factory,
+            payer: admin(),
+            system_program: system_program::ID,
+        },
+        cpamm::instruction::MigrateFactory {},
+    )
+    .unwrap();
+    let migrated = bank.get::<Factory>(factory);
+    assert_eq!(migrated.version, FACTORY_VERSION);
+    assert_eq!(migrated.admin, before.admin);
+    assert_eq!(migrated.num_swaps, before.num_swaps);
+    assert!(!migrated.is_permissioned);
+
+    // swaps can be created on the migrated factory without permission
+    let test = new_swap_on(&mut bank, factory, 1_000_000, 1_000_000);
+    assert_eq!(bank.get::<Factory>(factory).num_swaps, 1);
+    swap(&mut bank, &test, 1_000).unwrap();
+}
@@ -0,0 +1,71 @@
+use crate::harness::{program_error, Accounts, Bank};
+use crate::setup::{admin, find_factory_address, new_swap, withdraw_accounts};
+use anchor_lang::error::ErrorCode as AnchorErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+/// A program which accepts every instruction, passed in place of the token or system program.
+fn spoofed_program(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    Ok(())
+}
+
+fn new_spoofed_program(bank: &mut Bank) -> Pubkey {
+    let program_id = Pubkey::new_unique();
+    bank.add_program(program_id, spoofed_program);
+    program_id
+}
+
+/// Replaces the account at `index` of the `accounts` with `key`.
+fn replace_account(accounts: Accounts, index: usize, key: Pubkey) -> Accounts {
+    let mut metas = accounts.0;
+    metas[index].pubkey = key;
+    Accounts(metas)
+}
+
+#[test]
+fn spoofed_system_program() {
+    let mut bank = Bank::new();
+    let spoofed = new_spoofed_program(&mut bank);
+    bank.fund(admin());
+    let base = bank.new_wallet(0);
+    assert_eq!(
+        bank.execute(
+            cpamm::accounts::NewFactory {
+                base,
+                factory: find_factory_address(base),
+                payer: admin(),
+                system_program: spoofed,
+            },
+            cpamm::instruction::NewFactory { _bump: 0 },
+        ),
+        Err(program_error(AnchorErrorCode::InvalidProgramId))
+    );
+}
+
+#[test]
+fn spoofed_token_program() {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, 1_000_000, 1_000_000);
+    let spoofed = new_spoofed_program(&mut bank);
+    let output_0 = bank.new_token_account(test.mint_0, admin(), 0);
+    let output_1 = bank.new_token_account(test.mint_1, admin(), 0);
+
+    // the token program is the first account of the user context of the withdrawal
+    let accounts = withdraw_accounts(&test, admin(), test.creator_lp, output_0, output_1);
+    assert_eq!(
+        bank.execute(
+            replace_account(accounts, 0, spoofed),
+            cpamm::instruction::WithdrawAll {
+                minimum_amount_out_0: 0,
+                minimum_amount_out_1: 0,
+                deadline: None,
+            },
+        ),
+        Err(program_error(AnchorErrorCode::InvalidProgramId))
+    );
+    assert_eq!(bank.balance(output_0), 0);
+}
@@ -0,0 +1,72 @@
+use crate::harness::{Accounts, Bank};
+use crate::setup::{admin, find_roles_address, new_swap};
+use anchor_lang::prelude::*;
+
+const RESERVE_AMOUNT: u64 = 1_000_000;
+const AMOUNT_IN: u64 = 100_000;
+
+/// Balances of token 1 in the reserve, the fees account and the referrer
+/// after swapping [AMOUNT_IN] of token 0.
+fn swap_with_referrer(has_referrer: bool) -> (u64, u64, u64) {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, RESERVE_AMOUNT, RESERVE_AMOUNT);
+    bank.execute(
+        cpamm::accounts::SetReferralFee {
+            factory: test.factory,
+            admin: admin(),
+            roles: find_roles_address(test.factory),
+            swap: test.swap,
+        },
+        cpamm::instruction::SetReferralFee {
+            referral_fee_kbps: 5_000_000,
+        },
+    )
+    .unwrap();
+
+    let wallet = bank.new_wallet(0);
+    let user_0 = bank.new_token_account(test.mint_0, wallet, AMOUNT_IN);
+    let user_1 = bank.new_token_account(test.mint_1, wallet, 0);
+    let referrer = bank.new_token_account(test.mint_1, Pubkey::new_unique(), 0);
+    let remaining_accounts = if has_referrer {
+        vec![AccountMeta::new(referrer, false)]
+    } else {
+        vec![]
+    };
+    bank.execute_with_remaining(
+        Accounts(vec![
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new(test.swap, false),
+            AccountMeta::new_readonly(test.factory, false),
+            AccountMeta::new_readonly(wallet, true),
+            AccountMeta::new(user_0, false),
+            AccountMeta::new(test.reserve_0, false),
+            AccountMeta::new(test.fees_0, false),
+            AccountMeta::new(user_1, false),
+            AccountMeta::new(test.reserve_1, false),
+            AccountMeta::new(test.fees_1, false),
+        ]),
+        cpamm::instruction::Swap {
+            amount_in: AMOUNT_IN,
+            minimum_amount_out: 0,
+            deadline: None,
+            allow_partial_fill: false,
+        },
+        remaining_accounts,
+    )
+    .unwrap();
+    (
+        bank.balance(test.reserve_1),
+        bank.balance(test.fees_1),
+        bank.balance(referrer),
+    )
+}
+
+#[test]
+fn referral_fee_from_admin_fee() {
+    let (reserve, fees, _) = swap_with_referrer(false);
+    let (referred_reserve, referred_fees, referral_fee) = swap_with_referrer(true);
+    assert!(referral_fee > 0);
+    // the LP portion of the trade fee stays in the reserve
+    assert_eq!(referred_reserve, reserve);
+    assert_eq!(referred_fees + referral_fee, fees);
+}
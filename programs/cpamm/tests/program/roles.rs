@@ -0,0 +1,219 @@
+use crate::harness::{program_error, Bank};
+use crate::setup::{admin, find_roles_address, new_swap, TestSwap};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{entrypoint::ProgramResult, system_program};
+use cpamm::{ErrorCode, FeeRecipient, Role, SwapFeeTier, SwapInfo};
+
+fn grant_role(bank: &mut Bank, test: &TestSwap, role: Role) -> Pubkey {
+    let holder = bank.new_wallet(0);
+    bank.execute(
+        set_role_accounts(test),
+        cpamm::instruction::GrantRole { role, holder },
+    )
+    .unwrap();
+    holder
+}
+
+fn set_role_accounts(test: &TestSwap) -> cpamm::accounts::SetRole {
+    cpamm::accounts::SetRole {
+        factory: test.factory,
+        admin: admin(),
+        roles: find_roles_address(test.factory),
+        payer: admin(),
+        system_program: system_program::ID,
+    }
+}
+
+fn swap_role_context(test: &TestSwap, authority: Pubkey) -> cpamm::accounts::SwapRoleContext {
+    cpamm::accounts::SwapRoleContext {
+        authority,
+        swap: test.swap,
+        factory: test.factory,
+        roles: find_roles_address(test.factory),
+    }
+}
+
+fn pause_swap(bank: &mut Bank, test: &TestSwap, authority: Pubkey) -> ProgramResult {
+    bank.execute(
+        swap_role_context(test, authority),
+        cpamm::instruction::PauseSwap {},
+    )
+}
+
+fn set_directional_pause(
+    bank: &mut Bank,
+    test: &TestSwap,
+    authority: Pubkey,
+    is_paused_0_to_1: bool,
+    is_paused_1_to_0: bool,
+) -> ProgramResult {
+    bank.execute(
+        swap_role_context(test, authority),
+        cpamm::instruction::SetDirectionalPause {
+            is_paused_0_to_1,
+            is_paused_1_to_0,
+        },
+    )
+}
+
+fn set_fee_tiers(bank: &mut Bank, test: &TestSwap, authority: Pubkey) -> ProgramResult {
+    bank.execute(
+        swap_role_context(test, authority),
+        cpamm::instruction::SetFeeTiers {
+            fee_tiers: vec![SwapFeeTier {
+                min_trade_bps_of_reserve: 100,
+                trade_fee_kbps: 100_000,
+            }],
+        },
+    )
+}
+
+fn set_dynamic_fee(bank: &mut Bank, test: &TestSwap, authority: Pubkey) -> ProgramResult {
+    bank.execute(
+        swap_role_context(test, authority),
+        cpamm::instruction::SetDynamicFee {
+            fee_kbps_per_volatility_bps: 1_000,
+            max_trade_fee_kbps: 100_000,
+            volatility_weight_bps: 1_000,
+        },
+    )
+}
+
+fn set_referral_fee(bank: &mut Bank, test: &TestSwap, authority: Pubkey) -> ProgramResult {
+    bank.execute(
+        cpamm::accounts::SetReferralFee {
+            factory: test.factory,
+            admin: authority,
+            roles: find_roles_address(test.factory),
+            swap: test.swap,
+        },
+        cpamm::instruction::SetReferralFee {
+            referral_fee_kbps: 1_000_000,
+        },
+    )
+}
+
+fn set_fee_distribution(bank: &mut Bank, test: &TestSwap, authority: Pubkey) -> ProgramResult {
+    bank.fund(authority);
+    bank.execute(
+        cpamm::accounts::SetFeeDistribution {
+            factory: test.factory,
+            admin: authority,
+            roles: find_roles_address(test.factory),
+            fee_distribution: Pubkey::find_program_address(
+                &[b"FeeDistribution", test.factory.as_ref()],
+                &cpamm::ID,
+            )
+            .0,
+            payer: authority,
+            system_program: system_program::ID,
+        },
+        cpamm::instruction::SetFeeDistribution {
+            recipients: vec![FeeRecipient {
+                owner: Pubkey::new_unique(),
+                share_bps: 5_000,
+            }],
+        },
+    )
+}
+
+fn missing_role() -> ProgramResult {
+    Err(program_error(ErrorCode::MissingRole))
+}
+
+#[test]
+fn pauser() {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, 1_000_000, 1_000_000);
+    let stranger = bank.new_wallet(0);
+    assert_eq!(pause_swap(&mut bank, &test, stranger), missing_role());
+    assert_eq!(
+        set_directional_pause(&mut bank, &test, stranger, true, false),
+        missing_role()
+    );
+
+    let pauser = grant_role(&mut bank, &test, Role::Pauser);
+    set_directional_pause(&mut bank, &test, pauser, true, false).unwrap();
+    let swap_info: SwapInfo = bank.get(test.swap);
+    assert!(swap_info.is_paused_0_to_1 && !swap_info.is_paused_1_to_0);
+    // only the admin may unpause
+    assert_eq!(
+        set_directional_pause(&mut bank, &test, pauser, false, false),
+        missing_role()
+    );
+    set_directional_pause(&mut bank, &test, admin(), false, false).unwrap();
+
+    pause_swap(&mut bank, &test, pauser).unwrap();
+    assert!(bank.get::<SwapInfo>(test.swap).is_paused);
+
+    // a pauser may not set fees
+    assert_eq!(set_dynamic_fee(&mut bank, &test, pauser), missing_role());
+}
+
+#[test]
+fn fee_setter() {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, 1_000_000, 1_000_000);
+    let stranger = bank.new_wallet(0);
+    assert_eq!(set_fee_tiers(&mut bank, &test, stranger), missing_role());
+    assert_eq!(set_dynamic_fee(&mut bank, &test, stranger), missing_role());
+    assert_eq!(set_referral_fee(&mut bank, &test, stranger), missing_role());
+
+    let fee_setter = grant_role(&mut bank, &test, Role::FeeSetter);
+    set_fee_tiers(&mut bank, &test, fee_setter).unwrap();
+    set_dynamic_fee(&mut bank, &test, fee_setter).unwrap();
+    set_referral_fee(&mut bank, &test, fee_setter).unwrap();
+    let swap_info: SwapInfo = bank.get(test.swap);
+    assert_eq!(swap_info.fee_tiers[0].trade_fee_kbps, 100_000);
+    assert_eq!(swap_info.max_dynamic_trade_fee_kbps, 100_000);
+    assert_eq!(swap_info.referral_fee_kbps, 1_000_000);
+
+    // a fee setter may not pause
+    assert_eq!(pause_swap(&mut bank, &test, fee_setter), missing_role());
+
+    bank.execute(
+        set_role_accounts(&test),
+        cpamm::instruction::RevokeRole {
+            role: Role::FeeSetter,
+        },
+    )
+    .unwrap();
+    assert_eq!(set_fee_tiers(&mut bank, &test, fee_setter), missing_role());
+}
+
+#[test]
+fn treasurer() {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, 1_000_000, 1_000_000);
+    let stranger = bank.new_wallet(0);
+    assert_eq!(
+        set_fee_distribution(&mut bank, &test, stranger),
+        missing_role()
+    );
+
+    let treasurer = grant_role(&mut bank, &test, Role::Treasurer);
+    set_fee_distribution(&mut bank, &test, treasurer).unwrap();
+    assert_eq!(
+        set_referral_fee(&mut bank, &test, treasurer),
+        missing_role()
+    );
+}
+
+#[test]
+fn roles_of_another_factory() {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, 1_000_000, 1_000_000);
+    let other = new_swap(&mut bank, 1_000_000, 1_000_000);
+    let pauser = grant_role(&mut bank, &other, Role::Pauser);
+    let result = bank.execute(
+        cpamm::accounts::SwapRoleContext {
+            authority: pauser,
+            swap: test.swap,
+            factory: test.factory,
+            roles: find_roles_address(other.factory),
+        },
+        cpamm::instruction::PauseSwap {},
+    );
+    assert!(result.is_err());
+    assert!(!bank.get::<SwapInfo>(test.swap).is_paused);
+}
@@ -0,0 +1,184 @@
+//! Creates factories and swaps through the instructions of the program.
+
+use crate::harness::{Accounts, Bank};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+use std::str::FromStr;
+
+/// Decimals of the mints of test swaps.
+pub const DECIMALS: u8 = 6;
+
+/// A swap of a new [cpamm::Factory] and the accounts around it.
+#[derive(Clone)]
+pub struct TestSwap {
+    pub factory: Pubkey,
+    pub swap: Pubkey,
+    pub swap_meta: Pubkey,
+    pub pool_mint: Pubkey,
+    pub mint_0: Pubkey,
+    pub mint_1: Pubkey,
+    pub reserve_0: Pubkey,
+    pub reserve_1: Pubkey,
+    pub fees_0: Pubkey,
+    pub fees_1: Pubkey,
+    pub locked_lp: Pubkey,
+    /// LP token account of the creator of the swap, owned by [admin].
+    pub creator_lp: Pubkey,
+}
+
+/// Admin of new factories, which the program sets to a fixed address.
+pub fn admin() -> Pubkey {
+    Pubkey::from_str("2rgQZrNmFkn7TvKbZuuMXjtDZ5YVagx9oqyTi5H3Dr2a").unwrap()
+}
+
+/// Finds the address of the [cpamm::Factory] created with `base`.
+pub fn find_factory_address(base: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"Factory", base.as_ref()], &cpamm::ID).0
+}
+
+/// Finds the address of the [cpamm::FactoryRoles] of the `factory`.
+pub fn find_roles_address(factory: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"FactoryRoles", factory.as_ref()], &cpamm::ID).0
+}
+
+/// Creates a [cpamm::Factory] administered by [admin].
+pub fn new_factory(bank: &mut Bank) -> Pubkey {
+    bank.fund(admin());
+    let base = bank.new_wallet(0);
+    let factory = find_factory_address(base);
+    bank.execute(
+        cpamm::accounts::NewFactory {
+            base,
+            factory,
+            payer: admin(),
+            system_program: system_program::ID,
+        },
+        cpamm::instruction::NewFactory { _bump: 0 },
+    )
+    .unwrap();
+    factory
+}
+
+/// Creates a constant product swap on a new [cpamm::Factory] with the given reserves.
+pub fn new_swap(bank: &mut Bank, reserve_0_amount: u64, reserve_1_amount: u64) -> TestSwap {
+    let factory = new_factory(bank);
+    new_swap_on(bank, factory, reserve_0_amount, reserve_1_amount)
+}
+
+/// Creates a constant product swap on the `factory` with the given reserves.
+pub fn new_swap_on(
+    bank: &mut Bank,
+    factory: Pubkey,
+    reserve_0_amount: u64,
+    reserve_1_amount: u64,
+) -> TestSwap {
+    let mint_authority = Pubkey::new_unique();
+    let (mint_0, mint_1) = {
+        let a = bank.new_mint(mint_authority, DECIMALS);
+        let b = bank.new_mint(mint_authority, DECIMALS);
+        (a.min(b), a.max(b))
+    };
+    let (swap, _) = cpamm::find_swap_address(factory, mint_0, mint_1, 0);
+    let num_swaps = bank.get::<cpamm::Factory>(factory).num_swaps;
+    let (swap_meta, _) = cpamm::find_swap_meta_address(factory, num_swaps);
+
+    let pool_mint = Pubkey::new_unique();
+    bank.set_mint(
+        pool_mint,
+        spl_token::state::Mint {
+            mint_authority: Some(swap).into(),
+            freeze_authority: Some(swap).into(),
+            decimals: DECIMALS,
+            is_initialized: true,
+            ..Default::default()
+        },
+    );
+    let reserve_0 = bank.new_token_account(mint_0, swap, reserve_0_amount);
+    let reserve_1 = bank.new_token_account(mint_1, swap, reserve_1_amount);
+    let fees_0 = bank.new_token_account(mint_0, swap, 0);
+    let fees_1 = bank.new_token_account(mint_1, swap, 0);
+    let locked_lp = bank.new_token_account(pool_mint, swap, 0);
+    let creator_lp = bank.new_token_account(pool_mint, admin(), 0);
+    let (creation_fee_vault, _) = cpamm::find_creation_fee_vault_address(factory);
+    let mint_list_entry = |mint: Pubkey| {
+        Pubkey::find_program_address(
+            &[b"MintListEntry", factory.as_ref(), mint.as_ref()],
+            &cpamm::ID,
+        )
+        .0
+    };
+
+    // the client struct of the nested token accounts cannot be named outside of the program
+    let accounts = Accounts(vec![
+        AccountMeta::new(factory, false),
+        AccountMeta::new(swap, false),
+        AccountMeta::new(swap_meta, false),
+        AccountMeta::new(pool_mint, false),
+        AccountMeta::new_readonly(mint_0, false),
+        AccountMeta::new_readonly(reserve_0, false),
+        AccountMeta::new_readonly(fees_0, false),
+        AccountMeta::new_readonly(mint_1, false),
+        AccountMeta::new_readonly(reserve_1, false),
+        AccountMeta::new_readonly(fees_1, false),
+        AccountMeta::new(creator_lp, false),
+        AccountMeta::new(locked_lp, false),
+        AccountMeta::new(admin(), true),
+        AccountMeta::new(creation_fee_vault, false),
+        AccountMeta::new_readonly(mint_list_entry(mint_0), false),
+        AccountMeta::new_readonly(mint_list_entry(mint_1), false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ]);
+    bank.execute(
+        accounts,
+        cpamm::instruction::NewSwap {
+            _bump: 0,
+            pool_fee_tier: 0,
+            amp_factor: 0,
+            weight_0: 0,
+            weight_1: 0,
+            initial_amount_0: 0,
+            initial_amount_1: 0,
+        },
+    )
+    .unwrap();
+
+    TestSwap {
+        factory,
+        swap,
+        swap_meta,
+        pool_mint,
+        mint_0,
+        mint_1,
+        reserve_0,
+        reserve_1,
+        fees_0,
+        fees_1,
+        locked_lp,
+        creator_lp,
+    }
+}
+
+/// Accounts of a [cpamm::withdraw] of the LP tokens of `input_lp`, owned by `user`.
+pub fn withdraw_accounts(
+    test: &TestSwap,
+    user: Pubkey,
+    input_lp: Pubkey,
+    output_0: Pubkey,
+    output_1: Pubkey,
+) -> Accounts {
+    Accounts(vec![
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new(test.swap, false),
+        AccountMeta::new_readonly(test.factory, false),
+        AccountMeta::new_readonly(user, true),
+        AccountMeta::new(test.pool_mint, false),
+        AccountMeta::new(input_lp, false),
+        AccountMeta::new(output_0, false),
+        AccountMeta::new(test.reserve_0, false),
+        AccountMeta::new(test.fees_0, false),
+        AccountMeta::new(output_1, false),
+        AccountMeta::new(test.reserve_1, false),
+        AccountMeta::new(test.fees_1, false),
+    ])
+}
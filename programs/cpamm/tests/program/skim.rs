@@ -0,0 +1,64 @@
+use crate::harness::{program_error, Bank};
+use crate::setup::{new_swap, TestSwap};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use cpamm::{ErrorCode, SwapInfo};
+
+const RESERVE_AMOUNT: u64 = 1_000_000;
+
+fn skim(bank: &mut Bank, test: &TestSwap, output_0: Pubkey, output_1: Pubkey) -> ProgramResult {
+    bank.execute(
+        cpamm::accounts::Skim {
+            swap: test.swap,
+            pool_mint: test.pool_mint,
+            reserve_0: test.reserve_0,
+            reserve_1: test.reserve_1,
+            output_0,
+            output_1,
+            token_program: spl_token::ID,
+        },
+        cpamm::instruction::Skim {},
+    )
+}
+
+/// Sends `amount` of token 0 directly to the reserve, outside of the instructions of the swap.
+fn donate(bank: &mut Bank, test: &TestSwap, amount: u64) {
+    let mut reserve = bank.token_account(test.reserve_0);
+    reserve.amount += amount;
+    bank.set_token_account(test.reserve_0, reserve);
+}
+
+#[test]
+fn skim_surplus() {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, RESERVE_AMOUNT, RESERVE_AMOUNT);
+    donate(&mut bank, &test, 500);
+    let skimmer = bank.new_wallet(0);
+    let output_0 = bank.new_token_account(test.mint_0, skimmer, 0);
+    let output_1 = bank.new_token_account(test.mint_1, skimmer, 0);
+
+    skim(&mut bank, &test, output_0, output_1).unwrap();
+    assert_eq!(bank.balance(output_0), 500);
+    assert_eq!(bank.balance(output_1), 0);
+    assert_eq!(bank.balance(test.reserve_0), RESERVE_AMOUNT);
+}
+
+#[test]
+fn skim_untracked_reserves() {
+    let mut bank = Bank::new();
+    let test = new_swap(&mut bank, RESERVE_AMOUNT, RESERVE_AMOUNT);
+    // a swap created before the reserves were tracked
+    let mut swap_info = bank.get::<SwapInfo>(test.swap);
+    swap_info.reserve_0_amount = 0;
+    swap_info.reserve_1_amount = 0;
+    bank.put(test.swap, &swap_info);
+
+    let skimmer = bank.new_wallet(0);
+    let output_0 = bank.new_token_account(test.mint_0, skimmer, 0);
+    let output_1 = bank.new_token_account(test.mint_1, skimmer, 0);
+    assert_eq!(
+        skim(&mut bank, &test, output_0, output_1),
+        Err(program_error(ErrorCode::ReservesNotTracked))
+    );
+    assert_eq!(bank.balance(test.reserve_0), RESERVE_AMOUNT);
+}
@@ -0,0 +1,107 @@
+use crate::harness::{program_error, Accounts, Bank};
+use crate::setup::{admin, new_swap, TestSwap};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use cpamm::{BatchSwap, ErrorCode};
+
+const RESERVE_AMOUNT: u64 = 1_000_000_000;
+const AMOUNT_IN: u64 = 1_000;
+
+/// Data of a trading Pyth price account with an exponent of zero.
+fn pyth_price_data(price: i64) -> Vec<u8> {
+    let mut data = vec![0; 240];
+    data[0..4].copy_from_slice(&0xa1b2c3d4_u32.to_le_bytes());
+    data[8..12].copy_from_slice(&3_u32.to_le_bytes());
+    data[208..216].copy_from_slice(&price.to_le_bytes());
+    data[224..228].copy_from_slice(&1_u32.to_le_bytes());
+    data
+}
+
+fn new_swap_with_oracle_band(bank: &mut Bank) -> (TestSwap, Pubkey) {
+    let test = new_swap(bank, RESERVE_AMOUNT, RESERVE_AMOUNT);
+    let oracle = Pubkey::new_unique();
+    bank.set_account(oracle, 1_000_000, &pyth_price_data(1), Pubkey::new_unique());
+    bank.execute(
+        cpamm::accounts::SetOracleBand {
+            admin: admin(),
+            swap: test.swap,
+            token_0_mint: test.mint_0,
+            token_1_mint: test.mint_1,
+            oracle,
+        },
+        cpamm::instruction::SetOracleBand {
+            is_oracle_inverted: false,
+            oracle_band_bps: 100,
+        },
+    )
+    .unwrap();
+    (test, oracle)
+}
+
+fn swap_batch(bank: &mut Bank, test: &TestSwap, oracle: Option<Pubkey>) -> ProgramResult {
+    let trader = bank.new_wallet(0);
+    let mut remaining_accounts = vec![];
+    for _ in 0..2 {
+        remaining_accounts.push(AccountMeta::new(
+            bank.new_token_account(test.mint_0, trader, AMOUNT_IN),
+            false,
+        ));
+        remaining_accounts.push(AccountMeta::new(
+            bank.new_token_account(test.mint_1, trader, 0),
+            false,
+        ));
+    }
+    remaining_accounts.extend(oracle.map(|oracle| AccountMeta::new_readonly(oracle, false)));
+
+    let accounts = Accounts(vec![
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new(test.swap, false),
+        AccountMeta::new_readonly(test.factory, false),
+        AccountMeta::new_readonly(trader, true),
+        AccountMeta::new(test.reserve_0, false),
+        AccountMeta::new(test.fees_0, false),
+        AccountMeta::new(test.reserve_1, false),
+        AccountMeta::new(test.fees_1, false),
+    ]);
+    let swap = BatchSwap {
+        amount_in: AMOUNT_IN,
+        minimum_amount_out: 0,
+    };
+    bank.execute_with_remaining(
+        accounts,
+        cpamm::instruction::SwapBatch {
+            swaps: vec![swap, swap],
+            deadline: None,
+        },
+        remaining_accounts,
+    )
+}
+
+#[test]
+fn swap_batch_with_oracle_band() {
+    let mut bank = Bank::new();
+    let (test, oracle) = new_swap_with_oracle_band(&mut bank);
+    swap_batch(&mut bank, &test, Some(oracle)).unwrap();
+    assert_eq!(bank.balance(test.reserve_0), RESERVE_AMOUNT + 2 * AMOUNT_IN);
+}
+
+#[test]
+fn swap_batch_without_oracle() {
+    let mut bank = Bank::new();
+    let (test, _) = new_swap_with_oracle_band(&mut bank);
+    assert_eq!(
+        swap_batch(&mut bank, &test, None),
+        Err(program_error(vipers::VipersError::OptionUnwrapFailed))
+    );
+}
+
+#[test]
+fn swap_batch_outside_oracle_band() {
+    let mut bank = Bank::new();
+    let (test, oracle) = new_swap_with_oracle_band(&mut bank);
+    bank.set_account(oracle, 1_000_000, &pyth_price_data(2), Pubkey::new_unique());
+    assert_eq!(
+        swap_batch(&mut bank, &test, Some(oracle)),
+        Err(program_error(ErrorCode::OraclePriceDeviation))
+    );
+}
@@ -0,0 +1,316 @@
+use crate::harness::{program_error, Bank};
+use crate::setup::{admin, find_roles_address, new_swap, TestSwap};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{entrypoint::ProgramResult, system_program};
+use cpamm::{ErrorCode, SwapFees, TimelockedChange};
+
+const DELAY_SECONDS: i64 = 24 * 60 * 60;
+
+type FeeSetter = fn(&mut Bank, &TestSwap) -> ProgramResult;
+
+fn fee_bounds_address(factory: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"FeeBounds", factory.as_ref()], &cpamm::ID).0
+}
+
+/// Finds the address of a fee configuration of the `factory`, seeded with its `name`.
+fn config_address(name: &[u8], factory: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[name, factory.as_ref()], &cpamm::ID).0
+}
+
+fn factory_role_context(test: &TestSwap) -> cpamm::accounts::FactoryRoleContext {
+    cpamm::accounts::FactoryRoleContext {
+        factory: test.factory,
+        roles: find_roles_address(test.factory),
+        authority: admin(),
+    }
+}
+
+fn swap_role_context(test: &TestSwap) -> cpamm::accounts::SwapRoleContext {
+    cpamm::accounts::SwapRoleContext {
+        authority: admin(),
+        swap: test.swap,
+        factory: test.factory,
+        roles: find_roles_address(test.factory),
+    }
+}
+
+/// Each fee setter which bypasses the timelock, with settable arguments.
+const FEE_SETTERS: [(&str, FeeSetter); 14] = [
+    ("set_pool_fees", |bank, test| {
+        bank.execute(
+            cpamm::accounts::SetPoolFees {
+                fee_bounds: fee_bounds_address(test.factory),
+                authority: admin(),
+                swap: test.swap,
+                factory: test.factory,
+            },
+            cpamm::instruction::SetPoolFees {
+                trade_fee_kbps: 50_000,
+                withdraw_fee_kbps: 0,
+            },
+        )
+    }),
+    ("set_protocol_fee_share", |bank, test| {
+        bank.execute(
+            cpamm::accounts::SetProtocolFeeShare {
+                factory: test.factory,
+                admin: admin(),
+                roles: find_roles_address(test.factory),
+                swap: test.swap,
+            },
+            cpamm::instruction::SetProtocolFeeShare {
+                protocol_fee_share_kbps: 1_000_000,
+            },
+        )
+    }),
+    ("set_fee_tiers", |bank, test| {
+        bank.execute(
+            swap_role_context(test),
+            cpamm::instruction::SetFeeTiers {
+                fee_tiers: vec![cpamm::SwapFeeTier {
+                    min_trade_bps_of_reserve: 100,
+                    trade_fee_kbps: 100_000,
+                }],
+            },
+        )
+    }),
+    ("set_launch_fee", |bank, test| {
+        bank.execute(
+            swap_role_context(test),
+            cpamm::instruction::SetLaunchFee {
+                launch_trade_fee_kbps: 500_000,
+                launch_fee_duration_seconds: 60,
+            },
+        )
+    }),
+    ("set_dynamic_fee", |bank, test| {
+        bank.execute(
+            swap_role_context(test),
+            cpamm::instruction::SetDynamicFee {
+                fee_kbps_per_volatility_bps: 1_000,
+                max_trade_fee_kbps: 100_000,
+                volatility_weight_bps: 1_000,
+            },
+        )
+    }),
+    ("set_directional_fees", |bank, test| {
+        bank.execute(
+            swap_role_context(test),
+            cpamm::instruction::SetDirectionalFees {
+                trade_fee_surcharge_0_to_1_kbps: 10_000,
+                trade_fee_surcharge_1_to_0_kbps: 0,
+            },
+        )
+    }),
+    ("set_default_fees", |bank, test| {
+        bank.execute(
+            factory_role_context(test),
+            cpamm::instruction::SetDefaultFees {
+                default_fees: cpamm::fees::INITIAL,
+            },
+        )
+    }),
+    ("set_pool_fee_tiers", |bank, test| {
+        bank.execute(
+            factory_role_context(test),
+            cpamm::instruction::SetPoolFeeTiers {
+                pool_fee_tiers_kbps: vec![50_000, 300_000],
+            },
+        )
+    }),
+    ("set_creation_fee", |bank, test| {
+        bank.execute(
+            factory_role_context(test),
+            cpamm::instruction::SetCreationFee {
+                creation_fee_lamports: 1_000_000_000,
+            },
+        )
+    }),
+    ("set_referral_fee", |bank, test| {
+        bank.execute(
+            cpamm::accounts::SetReferralFee {
+                factory: test.factory,
+                admin: admin(),
+                roles: find_roles_address(test.factory),
+                swap: test.swap,
+            },
+            cpamm::instruction::SetReferralFee {
+                referral_fee_kbps: 1_000_000,
+            },
+        )
+    }),
+    ("set_host_fee", |bank, test| {
+        bank.execute(
+            swap_role_context(test),
+            cpamm::instruction::SetHostFee {
+                host_fee_kbps: 1_000_000,
+            },
+        )
+    }),
+    ("set_creator_fee", |bank, test| {
+        bank.execute(
+            swap_role_context(test),
+            cpamm::instruction::SetCreatorFee {
+                creator_fee_kbps: 1_000_000,
+            },
+        )
+    }),
+    ("set_holder_discount", |bank, test| {
+        bank.execute(
+            cpamm::accounts::SetHolderDiscount {
+                factory: test.factory,
+                admin: admin(),
+                roles: find_roles_address(test.factory),
+                config: config_address(b"HolderDiscountConfig", test.factory),
+                payer: admin(),
+                system_program: system_program::ID,
+            },
+            cpamm::instruction::SetHolderDiscount {
+                discount_mint: test.mint_0,
+                tiers: vec![cpamm::HolderDiscountTier {
+                    min_balance: 1,
+                    trade_fee_discount_kbps: 1_000_000,
+                }],
+            },
+        )
+    }),
+    ("set_volume_rebates", |bank, test| {
+        bank.execute(
+            cpamm::accounts::SetVolumeRebates {
+                factory: test.factory,
+                admin: admin(),
+                roles: find_roles_address(test.factory),
+                config: config_address(b"VolumeRebateConfig", test.factory),
+                payer: admin(),
+                system_program: system_program::ID,
+            },
+            cpamm::instruction::SetVolumeRebates {
+                window_seconds: 60,
+                tiers: vec![cpamm::VolumeRebateTier {
+                    min_volume: 1,
+                    trade_fee_rebate_kbps: 1_000_000,
+                }],
+            },
+        )
+    }),
+];
+
+fn new_swap_with_fee_bounds(bank: &mut Bank) -> TestSwap {
+    let test = new_swap(bank, 1_000_000, 1_000_000);
+    bank.execute(
+        cpamm::accounts::SetFeeBounds {
+            factory: test.factory,
+            admin: admin(),
+            fee_bounds: fee_bounds_address(test.factory),
+            payer: admin(),
+            system_program: system_program::ID,
+        },
+        cpamm::instruction::SetFeeBounds {
+            min_trade_fee_kbps: 0,
+            max_trade_fee_kbps: 100_000,
+            min_withdraw_fee_kbps: 0,
+            max_withdraw_fee_kbps: 100_000,
+        },
+    )
+    .unwrap();
+    test
+}
+
+fn set_timelock_delay(bank: &mut Bank, test: &TestSwap, delay_seconds: i64) -> ProgramResult {
+    bank.execute(
+        cpamm::accounts::FactoryAdminContext {
+            factory: test.factory,
+            admin: admin(),
+        },
+        cpamm::instruction::SetTimelockDelay { delay_seconds },
+    )
+}
+
+#[test]
+fn fee_setters_without_timelock() {
+    let mut bank = Bank::new();
+    let test = new_swap_with_fee_bounds(&mut bank);
+    for (name, set_fees) in FEE_SETTERS {
+        assert_eq!(set_fees(&mut bank, &test), Ok(()), "{}", name);
+    }
+}
+
+#[test]
+fn fee_setters_with_timelock() {
+    let mut bank = Bank::new();
+    let test = new_swap_with_fee_bounds(&mut bank);
+    set_timelock_delay(&mut bank, &test, DELAY_SECONDS).unwrap();
+    for (name, set_fees) in FEE_SETTERS {
+        assert_eq!(
+            set_fees(&mut bank, &test),
+            Err(program_error(ErrorCode::TimelockRequired)),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn timelocked_fees() {
+    let mut bank = Bank::new();
+    let test = new_swap_with_fee_bounds(&mut bank);
+    set_timelock_delay(&mut bank, &test, DELAY_SECONDS).unwrap();
+
+    let new_fees = SwapFees {
+        trade_fee_kbps: 100_000,
+        ..bank.get::<cpamm::SwapInfo>(test.swap).fees
+    };
+    let set_fees = |bank: &mut Bank| {
+        bank.execute(
+            cpamm::accounts::SetFees {
+                admin: admin(),
+                swap: test.swap,
+                factory: test.factory,
+                roles: find_roles_address(test.factory),
+            },
+            cpamm::instruction::SetFees { new_fees },
+        )
+    };
+    assert_eq!(
+        set_fees(&mut bank),
+        Err(program_error(ErrorCode::TimelockRequired))
+    );
+
+    let pending_change =
+        Pubkey::find_program_address(&[b"PendingChange", test.swap.as_ref()], &cpamm::ID).0;
+    bank.execute(
+        cpamm::accounts::QueueChange {
+            factory: test.factory,
+            target: test.swap,
+            pending_change,
+            admin: admin(),
+            payer: admin(),
+            system_program: system_program::ID,
+        },
+        cpamm::instruction::QueueChange {
+            change: TimelockedChange::Fees { new_fees },
+        },
+    )
+    .unwrap();
+    let execute_change = |bank: &mut Bank| {
+        bank.execute(
+            cpamm::accounts::PendingChangeContext {
+                factory: test.factory,
+                target: test.swap,
+                pending_change,
+                admin: admin(),
+                recipient: admin(),
+            },
+            cpamm::instruction::ExecuteChange {},
+        )
+    };
+    assert_eq!(
+        execute_change(&mut bank),
+        Err(program_error(ErrorCode::TimelockNotReady))
+    );
+
+    bank.advance(DELAY_SECONDS, 1);
+    execute_change(&mut bank).unwrap();
+    assert_eq!(bank.get::<cpamm::SwapInfo>(test.swap).fees, new_fees);
+    assert_eq!(bank.lamports(pending_change), 0);
+}
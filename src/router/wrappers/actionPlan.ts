@@ -167,6 +167,7 @@ const makeSwapInstruction = async ({
       user: {
         tokenProgram: TOKEN_PROGRAM_ID,
         swap: swap.key,
+        factory: swap.state.factory,
         userAuthority: user,
       },
       input: {
@@ -186,9 +187,14 @@ const makeSwapInstruction = async ({
     ? cpAmm.instruction.swap(
         inputAmount.toU64(),
         minimumAmountOut?.toU64() ?? new u64(0),
+        null,
         ctx
       )
-    : cpAmm.instruction.swapMax(minimumAmountOut?.toU64() ?? new u64(0), ctx);
+    : cpAmm.instruction.swapMax(
+        minimumAmountOut?.toU64() ?? new u64(0),
+        null,
+        ctx
+      );
   return {
     accounts: [input, output],
     mints: [swap.state[inputToken].mint, swap.state[outputToken].mint],
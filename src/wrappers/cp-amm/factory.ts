@@ -7,6 +7,7 @@ import {
   getOrCreateATAs,
   SPLToken,
   TOKEN_PROGRAM_ID,
+  u64,
 } from "@saberhq/token-utils";
 import type { AccountInfo, PublicKey } from "@solana/web3.js";
 import { Keypair, SystemProgram } from "@solana/web3.js";
@@ -22,7 +23,12 @@ import type {
 import type { SenchaSDK } from "../../sdk";
 import { comparePubkeys } from "../../utils/comparePubkeys";
 import { parseSwapMetaData } from "./parsers";
-import { findSwapAddress, findSwapMetaAddress } from "./pda";
+import {
+  findCreationFeeVaultAddress,
+  findMintListEntryAddress,
+  findSwapAddress,
+  findSwapMetaAddress,
+} from "./pda";
 import type { PendingSwap } from "./types";
 
 /**
@@ -173,6 +179,10 @@ export class SenchaFactory {
     poolMintKP = Keypair.generate(),
     payer = this.provider.wallet.publicKey,
     initialLP = this.provider.wallet.publicKey,
+    poolFeeTier = 0,
+    ampFactor = new u64(0),
+    weight0 = 0,
+    weight1 = 0,
   }: {
     poolMintKP?: Keypair;
     token0Amount: TokenAmount;
@@ -182,6 +192,20 @@ export class SenchaFactory {
      */
     initialLP?: PublicKey;
     payer?: PublicKey;
+    /**
+     * Pool fee tier of the factory, which determines the trade fee.
+     */
+    poolFeeTier?: number;
+    /**
+     * Amplification coefficient of a stable swap. Zero creates a constant product swap.
+     */
+    ampFactor?: u64;
+    /**
+     * Weights of the tokens of a weighted swap, e.g. 4 and 1 for an 80/20 swap.
+     * Zero weights create an unweighted swap.
+     */
+    weight0?: number;
+    weight1?: number;
   }): Promise<PendingSwap> {
     const token0 = token0Amount.token;
     const token1 = token1Amount.token;
@@ -198,13 +222,25 @@ export class SenchaFactory {
       factory: this.factory,
       mintA: token0.mintAccount,
       mintB: token1.mintAccount,
+      poolFeeTier,
     });
     // TODO: this can cause a race condition if two people call this at the same time
     // we should make this step optional if users complain
-    const [swapMeta, metaBump] = await findSwapMetaAddress({
+    const [swapMeta] = await findSwapMetaAddress({
       factory: this.factory,
       index: factoryData.numSwaps.toNumber(),
     });
+    const [creationFeeVault] = await findCreationFeeVaultAddress({
+      factory: this.factory,
+    });
+    const [mintListEntry0] = await findMintListEntryAddress({
+      factory: this.factory,
+      mint: token0.mintAccount,
+    });
+    const [mintListEntry1] = await findMintListEntryAddress({
+      factory: this.factory,
+      mint: token1.mintAccount,
+    });
 
     const decimals = Math.max(token0.decimals, token1.decimals);
 
@@ -278,35 +314,42 @@ export class SenchaFactory {
         [],
         token1Amount.toU64()
       ),
-      this.program.instruction.newSwap(swapBump, {
-        accounts: {
-          factory: this.factory,
-          swap,
-          poolMint: poolMintKP.publicKey,
-          token0: {
-            mint: token0.mintAccount,
-            reserve: poolATAs.accounts.token0,
-            fees: feeAccountA.key,
+      this.program.instruction.newSwap(
+        swapBump,
+        poolFeeTier,
+        ampFactor,
+        weight0,
+        weight1,
+        // the reserves are seeded above
+        new u64(0),
+        new u64(0),
+        {
+          accounts: {
+            factory: this.factory,
+            swap,
+            swapMeta,
+            poolMint: poolMintKP.publicKey,
+            token0: {
+              mint: token0.mintAccount,
+              reserve: poolATAs.accounts.token0,
+              fees: feeAccountA.key,
+            },
+            token1: {
+              mint: token1.mintAccount,
+              reserve: poolATAs.accounts.token1,
+              fees: feeAccountB.key,
+            },
+            payer,
+            creationFeeVault,
+            mintListEntry0,
+            mintListEntry1,
+            outputLp: initialLPATAs.accounts.lp,
+            lockedLp: poolATAs.accounts.lp,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
           },
-          token1: {
-            mint: token1.mintAccount,
-            reserve: poolATAs.accounts.token1,
-            fees: feeAccountB.key,
-          },
-          payer,
-          outputLp: initialLPATAs.accounts.lp,
-          tokenProgram: TOKEN_PROGRAM_ID,
-          systemProgram: SystemProgram.programId,
-        },
-      }),
-      this.program.instruction.newSwapMeta(metaBump, {
-        accounts: {
-          swap,
-          swapMeta,
-          payer,
-          systemProgram: SystemProgram.programId,
-        },
-      }),
+        }
+      ),
     ]);
 
     return {
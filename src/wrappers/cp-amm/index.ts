@@ -1,12 +1,13 @@
 import type { Provider } from "@saberhq/solana-contrib";
 import { TransactionEnvelope } from "@saberhq/solana-contrib";
-import type { TokenAmount, u64 } from "@saberhq/token-utils";
+import type { TokenAmount } from "@saberhq/token-utils";
 import {
   createInitMintInstructions,
   createTokenAccount,
   getMintInfo,
   getOrCreateATAs,
   TOKEN_PROGRAM_ID,
+  u64,
 } from "@saberhq/token-utils";
 import type {
   KeyedAccountInfo,
@@ -15,6 +16,7 @@ import type {
   TransactionInstruction,
 } from "@solana/web3.js";
 import { Keypair, SystemProgram } from "@solana/web3.js";
+import type BN from "bn.js";
 
 import type { SenchaSDK } from "../..";
 import { DEFAULT_FACTORY } from "../../constants";
@@ -23,7 +25,9 @@ import { comparePubkeys } from "../../utils/comparePubkeys";
 import { decodeFees } from "./fee";
 import { PARSE_SWAP_INFO } from "./parsers";
 import {
+  findCreationFeeVaultAddress,
   findFactoryAddress,
+  findMintListEntryAddress,
   findSwapAddress,
   findSwapMetaAddress,
 } from "./pda";
@@ -186,11 +190,25 @@ export class CpAmmWrapper {
     if (!factoryData) {
       throw new Error("Factory does not exist on network");
     }
-    const [swapMeta, metaBump] = await findSwapMetaAddress({
+    const [swapMeta] = await findSwapMetaAddress({
       factory,
       index: factoryData.numSwaps.toNumber(),
       programId: program.programId,
     });
+    const [creationFeeVault] = await findCreationFeeVaultAddress({
+      factory,
+      programId: program.programId,
+    });
+    const [mintListEntry0] = await findMintListEntryAddress({
+      factory,
+      mint: tokenAMint,
+      programId: program.programId,
+    });
+    const [mintListEntry1] = await findMintListEntryAddress({
+      factory,
+      mint: tokenBMint,
+      programId: program.programId,
+    });
 
     const realDecimals =
       decimals === undefined
@@ -267,35 +285,42 @@ export class CpAmmWrapper {
       sdk.provider,
       [
         ...seedPoolAccountsResult.instructions,
-        program.instruction.newSwap(swapBump, {
-          accounts: {
-            factory,
-            swap,
-            poolMint: poolMintKP.publicKey,
-            token0: {
-              mint: tokenAMint,
-              reserve: reserves.tokenA,
-              fees: feeAccountA.key,
-            },
-            token1: {
-              mint: tokenBMint,
-              reserve: reserves.tokenB,
-              fees: feeAccountB.key,
+        program.instruction.newSwap(
+          swapBump,
+          0,
+          new u64(0),
+          0,
+          0,
+          // the reserves are seeded above
+          new u64(0),
+          new u64(0),
+          {
+            accounts: {
+              factory,
+              swap,
+              swapMeta,
+              poolMint: poolMintKP.publicKey,
+              token0: {
+                mint: tokenAMint,
+                reserve: reserves.tokenA,
+                fees: feeAccountA.key,
+              },
+              token1: {
+                mint: tokenBMint,
+                reserve: reserves.tokenB,
+                fees: feeAccountB.key,
+              },
+              payer,
+              creationFeeVault,
+              mintListEntry0,
+              mintListEntry1,
+              outputLp: initLp.outputLp,
+              lockedLp: reserves.lpReserve,
+              tokenProgram,
+              systemProgram: SystemProgram.programId,
             },
-            payer,
-            outputLp: initLp.outputLp,
-            tokenProgram,
-            systemProgram: SystemProgram.programId,
-          },
-        }),
-        program.instruction.newSwapMeta(metaBump, {
-          accounts: {
-            swap,
-            swapMeta,
-            payer,
-            systemProgram: SystemProgram.programId,
-          },
-        }),
+          }
+        ),
       ],
       [...seedPoolAccountsResult.signers]
     );
@@ -333,6 +358,7 @@ export class CpAmmWrapper {
       user: {
         tokenProgram: TOKEN_PROGRAM_ID,
         swap: this.key,
+        factory: this.state.factory,
         userAuthority,
       },
     };
@@ -346,11 +372,16 @@ export class CpAmmWrapper {
     poolTokenAmount,
     maximumAmountIn0,
     maximumAmountIn1,
+    deadline,
     userAuthority = this.provider.wallet.publicKey,
   }: {
     poolTokenAmount: u64;
     maximumAmountIn0: u64;
     maximumAmountIn1: u64;
+    /**
+     * Unix timestamp after which the deposit should fail.
+     */
+    deadline?: BN;
     userAuthority?: PublicKey;
   }): Promise<TransactionEnvelope> {
     const instructions: TransactionInstruction[] = [];
@@ -364,6 +395,7 @@ export class CpAmmWrapper {
         poolTokenAmount,
         maximumAmountIn0,
         maximumAmountIn1,
+        deadline ?? null,
         {
           accounts: {
             ...this._getCommonAccounts(userAuthority),
@@ -388,11 +420,16 @@ export class CpAmmWrapper {
     poolTokenAmount,
     minimumAmountOut0,
     minimumAmountOut1,
+    deadline,
     userAuthority = this.provider.wallet.publicKey,
   }: {
     poolTokenAmount: u64;
     minimumAmountOut0: u64;
     minimumAmountOut1: u64;
+    /**
+     * Unix timestamp after which the withdraw should fail.
+     */
+    deadline?: BN;
     userAuthority?: PublicKey;
   }): Promise<TransactionEnvelope> {
     const instructions: TransactionInstruction[] = [];
@@ -406,6 +443,7 @@ export class CpAmmWrapper {
         poolTokenAmount,
         minimumAmountOut0,
         minimumAmountOut1,
+        deadline ?? null,
         {
           accounts: {
             ...this._getCommonAccounts(userAuthority),
@@ -431,11 +469,37 @@ export class CpAmmWrapper {
   async swap({
     amountIn,
     minAmountOut,
+    deadline,
+    referrer,
+    hostFeeAccount,
+    feeExemption,
+    allowPartialFill = false,
     userAuthority = this.provider.wallet.publicKey,
   }: {
     userAuthority?: PublicKey;
     amountIn: TokenAmount;
     minAmountOut: TokenAmount;
+    /**
+     * Unix timestamp after which the swap should fail.
+     */
+    deadline?: BN;
+    /**
+     * Token account of the output token which receives the referral fee.
+     */
+    referrer?: PublicKey;
+    /**
+     * Token account of the output token which receives the host fee.
+     */
+    hostFeeAccount?: PublicKey;
+    /**
+     * Fee exemption of the user authority, which discounts the trade fee.
+     */
+    feeExemption?: PublicKey;
+    /**
+     * If true, only the part of the input which meets the price of
+     * `minAmountOut` per `amountIn` is swapped, instead of failing.
+     */
+    allowPartialFill?: boolean;
   }): Promise<TransactionEnvelope> {
     const instructions: TransactionInstruction[] = [];
 
@@ -457,21 +521,36 @@ export class CpAmmWrapper {
       : (["token1", "token0"] as const);
 
     instructions.push(
-      this.program.instruction.swap(amountIn.toU64(), minAmountOut.toU64(), {
-        accounts: {
-          ...this._getCommonAccounts(userAuthority),
-          input: {
-            user: accounts[inputToken],
-            reserve: this.state[inputToken].reserves,
-            fees: this.state[inputToken].adminFees,
-          },
-          output: {
-            user: accounts[outputToken],
-            reserve: this.state[outputToken].reserves,
-            fees: this.state[outputToken].adminFees,
+      this.program.instruction.swap(
+        amountIn.toU64(),
+        minAmountOut.toU64(),
+        deadline ?? null,
+        allowPartialFill,
+        {
+          accounts: {
+            ...this._getCommonAccounts(userAuthority),
+            input: {
+              user: accounts[inputToken],
+              reserve: this.state[inputToken].reserves,
+              fees: this.state[inputToken].adminFees,
+            },
+            output: {
+              user: accounts[outputToken],
+              reserve: this.state[outputToken].reserves,
+              fees: this.state[outputToken].adminFees,
+            },
           },
-        },
-      })
+          remainingAccounts: [referrer, hostFeeAccount, feeExemption]
+            .slice(0, feeExemption ? 3 : hostFeeAccount ? 2 : referrer ? 1 : 0)
+            .map((pubkey, index) => ({
+              // the program ID is passed in place of a skipped optional account
+              pubkey: pubkey ?? this.program.programId,
+              isSigner: false,
+              // the fee exemption is read-only
+              isWritable: index !== 2,
+            })),
+        }
+      )
     );
 
     return new TransactionEnvelope(this.provider, instructions);
@@ -21,11 +21,16 @@ export const findSwapAddress = async ({
   factory,
   mintA,
   mintB,
+  poolFeeTier = 0,
   programId = PROGRAM_ADDRESSES.CpAmm,
 }: {
   factory: PublicKey;
   mintA: PublicKey;
   mintB: PublicKey;
+  /**
+   * Pool fee tier of the factory. The default tier 0 has no seed.
+   */
+  poolFeeTier?: number;
   programId?: PublicKey;
 }): Promise<[PublicKey, number]> => {
   const [token0Mint, token1Mint] =
@@ -37,6 +42,7 @@ export const findSwapAddress = async ({
       factory.toBuffer(),
       token0Mint.toBuffer(),
       token1Mint.toBuffer(),
+      ...(poolFeeTier === 0 ? [] : [Buffer.from([poolFeeTier])]),
     ],
     programId
   );
@@ -60,3 +66,334 @@ export const findSwapMetaAddress = async ({
     programId
   );
 };
+
+export const findCreationFeeVaultAddress = async ({
+  factory,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  factory: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("CreationFeeVault"), factory.toBuffer()],
+    programId
+  );
+};
+
+export const findSwapMetadataAddress = async ({
+  swap,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  swap: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("SwapMetadata"), swap.toBuffer()],
+    programId
+  );
+};
+
+export const findReferrerAddress = async ({
+  owner,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  owner: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("Referrer"), owner.toBuffer()],
+    programId
+  );
+};
+
+export const findFactoryTreasuryAddress = async ({
+  factory,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  factory: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("FactoryTreasury"), factory.toBuffer()],
+    programId
+  );
+};
+
+export const findMintListEntryAddress = async ({
+  factory,
+  mint,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  factory: PublicKey;
+  mint: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode("MintListEntry"),
+      factory.toBuffer(),
+      mint.toBuffer(),
+    ],
+    programId
+  );
+};
+
+export const findFeeExemptionAddress = async ({
+  factory,
+  address,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  factory: PublicKey;
+  address: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode("FeeExemption"),
+      factory.toBuffer(),
+      address.toBuffer(),
+    ],
+    programId
+  );
+};
+
+export const findSignedSwapAuthorityAddress = async ({
+  owner,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  owner: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("SignedSwapAuthority"), owner.toBuffer()],
+    programId
+  );
+};
+
+export const findSwapCommitmentAddress = async ({
+  swap,
+  userAuthority,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  swap: PublicKey;
+  userAuthority: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode("SwapCommitment"),
+      swap.toBuffer(),
+      userAuthority.toBuffer(),
+    ],
+    programId
+  );
+};
+
+export const findLockedPositionAddress = async ({
+  base,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  base: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("LockedPosition"), base.toBuffer()],
+    programId
+  );
+};
+
+export const findLockedPositionEscrowAddress = async ({
+  position,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  position: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("LockedPositionEscrow"), position.toBuffer()],
+    programId
+  );
+};
+
+export const findDepositCooldownAddress = async ({
+  swap,
+  lpAccount,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  swap: PublicKey;
+  lpAccount: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode("DepositCooldown"),
+      swap.toBuffer(),
+      lpAccount.toBuffer(),
+    ],
+    programId
+  );
+};
+
+export const findHolderDiscountConfigAddress = async ({
+  factory,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  factory: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("HolderDiscountConfig"), factory.toBuffer()],
+    programId
+  );
+};
+
+export const findBuybackConfigAddress = async ({
+  factory,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  factory: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("BuybackConfig"), factory.toBuffer()],
+    programId
+  );
+};
+
+export const findFeeBoundsAddress = async ({
+  factory,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  factory: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("FeeBounds"), factory.toBuffer()],
+    programId
+  );
+};
+
+export const findFeeDistributionAddress = async ({
+  factory,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  factory: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("FeeDistribution"), factory.toBuffer()],
+    programId
+  );
+};
+
+export const findVolumeRebateConfigAddress = async ({
+  factory,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  factory: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("VolumeRebateConfig"), factory.toBuffer()],
+    programId
+  );
+};
+
+export const findTraderStatsAddress = async ({
+  swap,
+  owner,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  swap: PublicKey;
+  owner: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode("TraderStats"),
+      swap.toBuffer(),
+      owner.toBuffer(),
+    ],
+    programId
+  );
+};
+
+export const findObservationsAddress = async ({
+  swap,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  swap: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("Observations"), swap.toBuffer()],
+    programId
+  );
+};
+
+export const findPairPriceAddress = async ({
+  factory,
+  mintA,
+  mintB,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  factory: PublicKey;
+  mintA: PublicKey;
+  mintB: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  const [token0Mint, token1Mint] =
+    comparePubkeys(mintA, mintB) !== -1 ? [mintB, mintA] : [mintA, mintB];
+
+  return await PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode("PairPrice"),
+      factory.toBuffer(),
+      token0Mint.toBuffer(),
+      token1Mint.toBuffer(),
+    ],
+    programId
+  );
+};
+
+export const findPendingChangeAddress = async ({
+  target,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  /**
+   * The swap or the factory to change.
+   */
+  target: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("PendingChange"), target.toBuffer()],
+    programId
+  );
+};
+
+export const findFactoryGuardianAddress = async ({
+  factory,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  factory: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("FactoryGuardian"), factory.toBuffer()],
+    programId
+  );
+};
+
+export const findFactoryRolesAddress = async ({
+  factory,
+  programId = PROGRAM_ADDRESSES.CpAmm,
+}: {
+  factory: PublicKey;
+  programId?: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("FactoryRoles"), factory.toBuffer()],
+    programId
+  );
+};